@@ -0,0 +1,632 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::computer_state::CpuVariant;
+use crate::computer_state::operations::{self, AddressingMode};
+
+use expression::{EvalError, Expr};
+
+mod expression;
+
+// Two pass assembler for standard 6502 syntax
+// Each line is an optional label ending in a colon, followed by an instruction, a directive, or a
+// constant assignment like "name = expr". Comments start with a semicolon. Local labels start with @
+// and belong to the label before them, so "@loop" can be reused after each label
+// The first pass assigns an address to every label. Operands which are known to fit in the zero page
+// by then use zero page addressing, and forward references use absolute addressing. The second pass
+// evaluates every operand and emits the bytes, keeping the encodings chosen by the first pass
+// Directives are .org, .byte, .word, .include and .incbin. Included files are relative to the file
+// including them
+
+/// Deepest nesting of included files, which stops a file from including itself forever
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// Reason a program could not be assembled, with where it was found
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AssemblyError {
+    /// File the error is in, or None for source which was not read from a file
+    pub file: Option<PathBuf>,
+    /// Line the error is on, or None for errors such as a missing file
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for AssemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), Some(line)) => write!(f, "{}:{line}: {}", file.display(), self.message),
+            (Some(file), None) => write!(f, "{}: {}", file.display(), self.message),
+            (None, Some(line)) => write!(f, "line {line}: {}", self.message),
+            (None, None) => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for AssemblyError {}
+
+/// A line of the listing, with the address it was assembled at and the bytes it produced
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ListingLine {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    pub source: String,
+}
+
+/// Writes the address, up to three bytes and the source, with any more bytes on the lines after
+impl fmt::Display for ListingLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const BYTES_PER_LINE: usize = 3;
+
+        let mut rows = self.bytes.chunks(BYTES_PER_LINE);
+        let hex = |row: &[u8]| row.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<_>>().join(" ");
+        write!(f, "{:5}  {:04X}  {:<8}  {}", self.line, self.addr, rows.next().map(hex).unwrap_or_default(), self.source)?;
+        for (index, row) in rows.enumerate() {
+            let addr = self.addr.wrapping_add(((index + 1) * BYTES_PER_LINE) as u16);
+            write!(f, "\n{:5}  {addr:04X}  {}", "", hex(row))?;
+        }
+        Ok(())
+    }
+}
+
+/// An assembled program
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Assembly {
+    /// Address of the first byte of the binary
+    pub origin: u16,
+    /// Bytes from the lowest address written to the highest, with any gaps between them filled with zeros
+    pub binary: Vec<u8>,
+    pub listing: Vec<ListingLine>,
+    /// Value of every label and constant, with local labels named after the label they belong to
+    pub symbols: BTreeMap<String, i64>,
+}
+
+/// Assembles the source, reading included files relative to the current directory
+pub fn assemble(source: &str, variant: CpuVariant) -> Result<Assembly, AssemblyError> {
+    let mut assembler = Assembler::new(variant);
+    assembler.read_source(source, None, 0)?;
+    assembler.assemble()
+}
+
+/// Assembles the file
+pub fn assemble_file(path: &Path, variant: CpuVariant) -> Result<Assembly, AssemblyError> {
+    let source = std::fs::read_to_string(path).map_err(|error| AssemblyError {
+        file: Some(path.to_path_buf()),
+        line: None,
+        message: error.to_string(),
+    })?;
+    let mut assembler = Assembler::new(variant);
+    assembler.read_source(&source, Some(path), 0)?;
+    assembler.assemble()
+}
+
+/// Item of a .byte directive
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Data {
+    Value(Expr),
+    Text(Vec<u8>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Index {
+    X,
+    Y,
+}
+
+/// Operand of an instruction, as it was written
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Operand {
+    None,
+    Accumulator,
+    Immediate(Expr),
+    /// (expr,X)
+    IndirectX(Expr),
+    /// (expr),Y
+    IndirectY(Expr),
+    /// (expr)
+    Indirect(Expr),
+    Indexed(Expr, Index),
+    Direct(Expr),
+    /// expr,expr, which is only used by BBR and BBS
+    Pair(Expr, Expr),
+}
+
+/// What a line does
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Statement {
+    Empty,
+    Constant(String, Expr),
+    Org(Expr),
+    Bytes(Vec<Data>),
+    Words(Vec<Expr>),
+    Binary(Vec<u8>),
+    Instruction { mnemonic: String, operand: Operand },
+}
+
+/// A line of source, once it has been parsed
+struct Line {
+    file: Option<PathBuf>,
+    number: usize,
+    source: String,
+    label: Option<String>,
+    statement: Statement,
+}
+
+impl Line {
+    fn error(&self, message: impl Into<String>) -> AssemblyError {
+        AssemblyError { file: self.file.clone(), line: Some(self.number), message: message.into() }
+    }
+}
+
+/// Opcode and addressing mode chosen for an instruction in the first pass
+#[derive(Clone, Copy)]
+struct Encoding {
+    opcode: u8,
+    mode: AddressingMode,
+}
+
+/// Value of every symbol, and the encoding of every line which is an instruction
+type FirstPass = (HashMap<String, i64>, Vec<Option<Encoding>>);
+
+struct Assembler {
+    variant: CpuVariant,
+    /// Opcode of each addressing mode of each mnemonic, preferring documented opcodes
+    opcodes: HashMap<&'static str, HashMap<AddressingMode, u8>>,
+    lines: Vec<Line>,
+    /// Last label which was not local, which local labels belong to
+    scope: String,
+}
+
+impl Assembler {
+    fn new(variant: CpuVariant) -> Assembler {
+        let mut opcodes: HashMap<_, HashMap<_, _>> = HashMap::new();
+        // Undocumented opcodes are only used where no documented opcode does the same thing
+        for illegal in [false, true] {
+            for opcode in 0..=u8::MAX {
+                if operations::is_illegal(variant, opcode) == illegal {
+                    opcodes.entry(operations::decode_mnemonic(variant, opcode))
+                        .or_default()
+                        .entry(operations::decode_addressing_mode(variant, opcode))
+                        .or_insert(opcode);
+                }
+            }
+        }
+        Assembler { variant, opcodes, lines: Vec::new(), scope: String::new() }
+    }
+
+    /// Parses every line of the source, reading included files into it
+    fn read_source(&mut self, source: &str, file: Option<&Path>, depth: usize) -> Result<(), AssemblyError> {
+        for (index, text) in source.lines().enumerate() {
+            let mut line = Line {
+                file: file.map(Path::to_path_buf),
+                number: index + 1,
+                source: text.to_string(),
+                label: None,
+                statement: Statement::Empty,
+            };
+            let included = self.parse_line(&mut line, text).map_err(|message| line.error(message))?;
+            if included.is_some() && depth >= MAX_INCLUDE_DEPTH {
+                return Err(line.error("files are included too deeply"));
+            }
+            self.lines.push(line);
+
+            if let Some(path) = included {
+                let path = resolve_path(file, &path);
+                let included_source = std::fs::read_to_string(&path).map_err(|error| AssemblyError {
+                    file: Some(path.clone()),
+                    line: None,
+                    message: error.to_string(),
+                })?;
+                self.read_source(&included_source, Some(&path), depth + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the line into its label and statement
+    /// Returns the path of the file to include, if the line is an .include directive
+    fn parse_line(&mut self, line: &mut Line, text: &str) -> Result<Option<String>, String> {
+        let mut rest = strip_comment(text).trim();
+
+        // Label
+        let name_len = rest.find(|c| !(expression::is_symbol_char(c) || c == '@')).unwrap_or(rest.len());
+        if name_len > 0 && rest[name_len..].starts_with(':') {
+            let name = &rest[..name_len];
+            if !name.starts_with('@') {
+                self.scope = name.to_string();
+            }
+            line.label = Some(expression::qualify(name, &self.scope));
+            rest = rest[name_len + 1..].trim();
+        }
+        if rest.is_empty() {
+            return Ok(None);
+        }
+
+        // Constant
+        if let Some((name, value)) = rest.split_once('=') {
+            let name = name.trim();
+            if !name.is_empty() && name.chars().all(expression::is_symbol_char) {
+                line.statement = Statement::Constant(name.to_string(), Expr::parse(value, &self.scope)?);
+                return Ok(None);
+            }
+        }
+
+        let (word, operand) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let operand = operand.trim();
+        let word = word.to_ascii_lowercase();
+        line.statement = match word.as_str() {
+            ".org" => Statement::Org(Expr::parse(operand, &self.scope)?),
+            ".byte" => Statement::Bytes(
+                split_operands(operand).into_iter().map(|item| self.parse_data(item)).collect::<Result<_, _>>()?,
+            ),
+            ".word" => Statement::Words(
+                split_operands(operand).into_iter().map(|item| Expr::parse(item, &self.scope)).collect::<Result<_, _>>()?,
+            ),
+            ".include" => return parse_string(operand).map(|path| Some(String::from_utf8_lossy(&path).into_owned())),
+            ".incbin" => {
+                let path = resolve_path(line.file.as_deref(), &String::from_utf8_lossy(&parse_string(operand)?));
+                let bytes = std::fs::read(&path).map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+                Statement::Binary(bytes)
+            }
+            _ if word.starts_with('.') => return Err(format!("unknown directive {word}")),
+            _ => Statement::Instruction { operand: self.parse_operand(operand)?, mnemonic: word },
+        };
+        Ok(None)
+    }
+
+    fn parse_data(&self, item: &str) -> Result<Data, String> {
+        if item.starts_with('"') {
+            parse_string(item).map(Data::Text)
+        } else {
+            Expr::parse(item, &self.scope).map(Data::Value)
+        }
+    }
+
+    fn parse_operand(&self, text: &str) -> Result<Operand, String> {
+        let parse = |text: &str| Expr::parse(text, &self.scope);
+        if text.is_empty() {
+            return Ok(Operand::None);
+        }
+        if text.eq_ignore_ascii_case("a") {
+            return Ok(Operand::Accumulator);
+        }
+        if let Some(value) = text.strip_prefix('#') {
+            return parse(value).map(Operand::Immediate);
+        }
+
+        // An operand in brackets is indirect, unless the brackets are only part of an expression
+        if let Some(inner) = text.strip_prefix('(').and_then(|rest| rest.get(..matching_bracket(rest)?)) {
+            let after = text[inner.len() + 2..].trim();
+            let parts = split_operands(inner);
+            if after.is_empty() {
+                match parts[..] {
+                    [ptr, index] if index.eq_ignore_ascii_case("x") => return parse(ptr).map(Operand::IndirectX),
+                    [_] => return parse(inner).map(Operand::Indirect),
+                    _ => {}
+                }
+            } else if after.strip_prefix(',').is_some_and(|index| index.trim().eq_ignore_ascii_case("y")) {
+                return parse(inner).map(Operand::IndirectY);
+            }
+        }
+
+        match split_operands(text)[..] {
+            [value] => parse(value).map(Operand::Direct),
+            [value, index] if index.eq_ignore_ascii_case("x") => Ok(Operand::Indexed(parse(value)?, Index::X)),
+            [value, index] if index.eq_ignore_ascii_case("y") => Ok(Operand::Indexed(parse(value)?, Index::Y)),
+            [first, second] => Ok(Operand::Pair(parse(first)?, parse(second)?)),
+            _ => Err("too many operands".to_string()),
+        }
+    }
+
+    /// Runs both passes over the parsed lines
+    fn assemble(&self) -> Result<Assembly, AssemblyError> {
+        let (symbols, encodings) = self.assign_addresses()?;
+        self.emit(symbols, &encodings)
+    }
+
+    /// First pass, which finds the address of every label and the encoding of every instruction
+    fn assign_addresses(&self) -> Result<FirstPass, AssemblyError> {
+        let mut symbols = HashMap::new();
+        let mut encodings = Vec::with_capacity(self.lines.len());
+        // Constants which refer to later labels, evaluated once every label is known
+        let mut deferred = Vec::new();
+        let mut pc: i64 = 0;
+
+        for line in &self.lines {
+            if let Some(label) = &line.label {
+                define(&mut symbols, line, label, pc)?;
+            }
+            let mut encoding = None;
+            match &line.statement {
+                Statement::Empty => {}
+                Statement::Constant(name, value) => match value.eval(&symbols, pc) {
+                    Ok(value) => define(&mut symbols, line, name, value)?,
+                    Err(EvalError::Undefined(_)) => deferred.push((line, name, value, pc)),
+                    Err(error) => return Err(line.error(eval_message(error))),
+                },
+                Statement::Org(addr) => pc = self.org(line, addr, &symbols, pc)?,
+                Statement::Bytes(data) => pc += data.iter().map(|item| match item {
+                    Data::Value(_) => 1,
+                    Data::Text(text) => text.len() as i64,
+                }).sum::<i64>(),
+                Statement::Words(values) => pc += 2 * values.len() as i64,
+                Statement::Binary(bytes) => pc += bytes.len() as i64,
+                Statement::Instruction { mnemonic, operand } => {
+                    let chosen = self.choose_encoding(line, mnemonic, operand, |value| value.eval(&symbols, pc).ok())?;
+                    pc += 1 + chosen.mode.operand_len() as i64;
+                    encoding = Some(chosen);
+                }
+            }
+            if pc > 0x10000 {
+                return Err(line.error("program runs past $FFFF"));
+            }
+            encodings.push(encoding);
+        }
+
+        // Constants can refer to each other in any order, so keep going while any are resolved
+        while !deferred.is_empty() {
+            let count = deferred.len();
+            let mut unresolved = Vec::new();
+            for (line, name, value, pc) in deferred {
+                match value.eval(&symbols, pc) {
+                    Ok(value) => define(&mut symbols, line, name, value)?,
+                    Err(EvalError::Undefined(_)) => unresolved.push((line, name, value, pc)),
+                    Err(error) => return Err(line.error(eval_message(error))),
+                }
+            }
+            if let Some(&(line, _, value, pc)) = unresolved.first().filter(|_| unresolved.len() == count) {
+                return Err(line.error(eval_message(value.eval(&symbols, pc).unwrap_err())));
+            }
+            deferred = unresolved;
+        }
+        Ok((symbols, encodings))
+    }
+
+    /// Second pass, which emits the bytes of every line
+    fn emit(&self, symbols: HashMap<String, i64>, encodings: &[Option<Encoding>]) -> Result<Assembly, AssemblyError> {
+        let mut memory = vec![None; 0x10000];
+        let mut listing = Vec::with_capacity(self.lines.len());
+        let mut pc: i64 = 0;
+
+        for (line, encoding) in self.lines.iter().zip(encodings) {
+            let eval = |value: &Expr| value.eval(&symbols, pc).map_err(|error| line.error(eval_message(error)));
+            let bytes = match &line.statement {
+                Statement::Empty | Statement::Constant(..) => Vec::new(),
+                Statement::Org(addr) => {
+                    pc = self.org(line, addr, &symbols, pc)?;
+                    Vec::new()
+                }
+                Statement::Bytes(data) => {
+                    let mut bytes = Vec::new();
+                    for item in data {
+                        match item {
+                            Data::Value(value) => bytes.push(byte(line, eval(value)?)?),
+                            Data::Text(text) => bytes.extend_from_slice(text),
+                        }
+                    }
+                    bytes
+                }
+                Statement::Words(values) => {
+                    let mut bytes = Vec::new();
+                    for value in values {
+                        bytes.extend_from_slice(&word(line, eval(value)?)?.to_le_bytes());
+                    }
+                    bytes
+                }
+                Statement::Binary(bytes) => bytes.clone(),
+                Statement::Instruction { operand, .. } => {
+                    let encoding = encoding.unwrap_or_else(|| unreachable!("instructions are encoded in the first pass"));
+                    let mut bytes = vec![encoding.opcode];
+                    bytes.extend(encode_operand(line, encoding.mode, operand, pc, eval)?);
+                    bytes
+                }
+            };
+
+            let addr = pc as u16;
+            for (offset, byte) in bytes.iter().enumerate() {
+                memory[pc as usize + offset] = Some(*byte);
+            }
+            pc += bytes.len() as i64;
+            listing.push(ListingLine {
+                file: line.file.clone(),
+                line: line.number,
+                addr,
+                bytes,
+                source: line.source.clone(),
+            });
+        }
+
+        let first = memory.iter().position(Option::is_some);
+        let last = memory.iter().rposition(Option::is_some);
+        let binary = match (first, last) {
+            (Some(first), Some(last)) => memory[first..=last].iter().map(|byte| byte.unwrap_or_default()).collect(),
+            _ => Vec::new(),
+        };
+        Ok(Assembly {
+            origin: first.unwrap_or_default() as u16,
+            binary,
+            listing,
+            symbols: symbols.into_iter().collect(),
+        })
+    }
+
+    /// Evaluates the address of an .org directive, which cannot refer to later labels
+    fn org(&self, line: &Line, addr: &Expr, symbols: &HashMap<String, i64>, pc: i64) -> Result<i64, AssemblyError> {
+        let addr = addr.eval(symbols, pc).map_err(|error| line.error(eval_message(error)))?;
+        word(line, addr).map(i64::from)
+    }
+
+    /// Chooses the opcode for the instruction, given the value of its operand if it is already known
+    fn choose_encoding(&self, line: &Line, mnemonic: &str, operand: &Operand, known: impl Fn(&Expr) -> Option<i64>)
+        -> Result<Encoding, AssemblyError> {
+        let Some(modes) = self.opcodes.get(mnemonic) else {
+            let variant = match self.variant {
+                CpuVariant::Nmos6502 => "6502",
+                CpuVariant::Wdc65C02 => "65C02",
+            };
+            return Err(line.error(format!("unknown instruction {} for the {variant}", mnemonic.to_uppercase())));
+        };
+        let opcode = |mode: AddressingMode| modes.get(&mode).map(|&opcode| Encoding { opcode, mode });
+        // Zero page addressing is only used when the operand is known to fit
+        let zero_page_or_absolute = |value: &Expr, zero_page, absolute| {
+            let fits = known(value).is_some_and(|value| (0..=0xFF).contains(&value));
+            match (opcode(zero_page), opcode(absolute)) {
+                (Some(zero_page), Some(_)) if fits => Some(zero_page),
+                (_, Some(absolute)) => Some(absolute),
+                (zero_page, None) => zero_page,
+            }
+        };
+        let first = |modes: &[AddressingMode]| modes.iter().find_map(|&mode| opcode(mode));
+
+        let encoding = match operand {
+            Operand::None => first(&[AddressingMode::Implied, AddressingMode::Accumulator]),
+            Operand::Accumulator => first(&[AddressingMode::Accumulator]),
+            Operand::Immediate(_) => first(&[AddressingMode::Immediate]),
+            Operand::IndirectX(_) => first(&[AddressingMode::IndirectX, AddressingMode::AbsoluteIndirectX]),
+            Operand::IndirectY(_) => first(&[AddressingMode::IndirectY]),
+            Operand::Indirect(_) => first(&[AddressingMode::ZeroPageIndirect, AddressingMode::Indirect]),
+            Operand::Indexed(value, Index::X) =>
+                zero_page_or_absolute(value, AddressingMode::ZeroPageX, AddressingMode::AbsoluteX),
+            Operand::Indexed(value, Index::Y) =>
+                zero_page_or_absolute(value, AddressingMode::ZeroPageY, AddressingMode::AbsoluteY),
+            Operand::Direct(value) => first(&[AddressingMode::Relative])
+                .or_else(|| zero_page_or_absolute(value, AddressingMode::ZeroPage, AddressingMode::Absolute)),
+            Operand::Pair(..) => first(&[AddressingMode::ZeroPageRelative]),
+        };
+        encoding.ok_or_else(|| line.error(format!("invalid addressing mode for {}", mnemonic.to_uppercase())))
+    }
+}
+
+/// Returns the bytes of the operand in the chosen addressing mode
+fn encode_operand(line: &Line, mode: AddressingMode, operand: &Operand, pc: i64,
+                  eval: impl Fn(&Expr) -> Result<i64, AssemblyError>) -> Result<Vec<u8>, AssemblyError> {
+    let value = match operand {
+        Operand::None | Operand::Accumulator => return Ok(Vec::new()),
+        Operand::Pair(zero_page, target) => {
+            let offset = branch_offset(line, eval(target)?, pc + 3)?;
+            return Ok(vec![zero_page_address(line, eval(zero_page)?)?, offset]);
+        }
+        Operand::Immediate(value) | Operand::IndirectX(value) | Operand::IndirectY(value) |
+        Operand::Indirect(value) | Operand::Indexed(value, _) | Operand::Direct(value) => eval(value)?,
+    };
+    Ok(match mode {
+        AddressingMode::Immediate => vec![byte(line, value)?],
+        AddressingMode::Relative => vec![branch_offset(line, value, pc + 2)?],
+        _ if mode.operand_len() == 1 => vec![zero_page_address(line, value)?],
+        _ => word(line, value)?.to_le_bytes().to_vec(),
+    })
+}
+
+fn define(symbols: &mut HashMap<String, i64>, line: &Line, name: &str, value: i64) -> Result<(), AssemblyError> {
+    if symbols.insert(name.to_string(), value).is_some() {
+        return Err(line.error(format!("{name} is already defined")));
+    }
+    Ok(())
+}
+
+fn eval_message(error: EvalError) -> String {
+    match error {
+        EvalError::Undefined(name) => format!("{name} is not defined"),
+        EvalError::DivisionByZero => "division by zero".to_string(),
+    }
+}
+
+/// Converts a value to a byte, allowing negative values down to -128
+fn byte(line: &Line, value: i64) -> Result<u8, AssemblyError> {
+    if (-0x80..=0xFF).contains(&value) {
+        Ok(value as u8)
+    } else {
+        Err(line.error(format!("value {value} does not fit in a byte")))
+    }
+}
+
+/// Converts a value to a word, allowing negative values down to -32768
+fn word(line: &Line, value: i64) -> Result<u16, AssemblyError> {
+    if (-0x8000..=0xFFFF).contains(&value) {
+        Ok(value as u16)
+    } else {
+        Err(line.error(format!("value {value} does not fit in a word")))
+    }
+}
+
+fn zero_page_address(line: &Line, value: i64) -> Result<u8, AssemblyError> {
+    u8::try_from(value).map_err(|_| line.error(format!("address {value} is not in the zero page")))
+}
+
+/// Returns the offset of a branch to the target, from the address after the branch
+fn branch_offset(line: &Line, target: i64, next: i64) -> Result<u8, AssemblyError> {
+    let offset = target - next;
+    i8::try_from(offset)
+        .map(|offset| offset as u8)
+        .map_err(|_| line.error(format!("branch target is {offset} bytes away, which is out of range")))
+}
+
+/// Resolves a path relative to the file which refers to it
+fn resolve_path(file: Option<&Path>, path: &str) -> PathBuf {
+    match file.and_then(Path::parent) {
+        Some(dir) => dir.join(path),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Returns the line without its comment, ignoring semicolons in strings and character constants
+fn strip_comment(text: &str) -> &str {
+    let mut quote = None;
+    for (index, c) in text.char_indices() {
+        match (c, quote) {
+            (';', None) => return &text[..index],
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Splits the text at commas which are not in brackets or quotes
+fn split_operands(text: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut quote = None;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match (c, quote) {
+            ('"' | '\'', None) => quote = Some(c),
+            (c, Some(open)) if c == open => quote = None,
+            ('(', None) => depth += 1,
+            (')', None) => depth -= 1,
+            (',', None) if depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(text[start..].trim());
+    parts
+}
+
+/// Returns the index of the bracket closing one which has already been opened
+fn matching_bracket(text: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(index),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Parses a string in double quotes, which may not contain escapes
+fn parse_string(text: &str) -> Result<Vec<u8>, String> {
+    text.strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .filter(|inner| !inner.contains('"'))
+        .map(|inner| inner.as_bytes().to_vec())
+        .ok_or_else(|| format!("expected a string in quotes, found {text}"))
+}
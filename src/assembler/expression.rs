@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use crate::expression::{Grammar, Parser};
+
+// Expressions in operands and directives
+// Operators follow C precedence, from lowest to highest: | ^ & << >> + - * / %
+// Unary operators are - ~ and the byte selectors < and >, which take the low and high byte
+
+/// An expression, with local labels already qualified by the label they belong to
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Expr {
+    Number(i64),
+    Symbol(String),
+    /// The address of the current line, written as *
+    Current,
+    Negate(Box<Expr>),
+    Not(Box<Expr>),
+    LowByte(Box<Expr>),
+    HighByte(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BinaryOp {
+    Or,
+    Xor,
+    And,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// Binary operators at each level of precedence, from lowest to highest
+const PRECEDENCE: [&[(&str, BinaryOp)]; 6] = [
+    &[("|", BinaryOp::Or)],
+    &[("^", BinaryOp::Xor)],
+    &[("&", BinaryOp::And)],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+    &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide), ("%", BinaryOp::Remainder)],
+];
+
+/// Reason an expression has no value
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EvalError {
+    /// The symbol has not been defined, which is expected for forward references in the first pass
+    Undefined(String),
+    DivisionByZero,
+}
+
+impl Expr {
+    /// Parses the expression, qualifying local labels with the scope
+    pub fn parse(text: &str, scope: &str) -> Result<Expr, String> {
+        Parser::parse(text, AssemblyGrammar { scope })
+    }
+
+    /// Evaluates the expression, with * as the given address
+    pub fn eval(&self, symbols: &HashMap<String, i64>, current: i64) -> Result<i64, EvalError> {
+        let eval = |expr: &Expr| expr.eval(symbols, current);
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Symbol(name) => *symbols.get(name).ok_or_else(|| EvalError::Undefined(name.clone()))?,
+            Expr::Current => current,
+            Expr::Negate(expr) => eval(expr)?.wrapping_neg(),
+            Expr::Not(expr) => !eval(expr)?,
+            Expr::LowByte(expr) => eval(expr)? & 0xFF,
+            Expr::HighByte(expr) => (eval(expr)? >> 8) & 0xFF,
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (eval(lhs)?, eval(rhs)?);
+                match op {
+                    BinaryOp::Or => lhs | rhs,
+                    BinaryOp::Xor => lhs ^ rhs,
+                    BinaryOp::And => lhs & rhs,
+                    BinaryOp::ShiftLeft => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)).unwrap_or(0),
+                    BinaryOp::ShiftRight => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)).unwrap_or(0),
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Subtract => lhs.wrapping_sub(rhs),
+                    BinaryOp::Multiply => lhs.wrapping_mul(rhs),
+                    BinaryOp::Divide => lhs.checked_div(rhs).ok_or(EvalError::DivisionByZero)?,
+                    BinaryOp::Remainder => lhs.checked_rem(rhs).ok_or(EvalError::DivisionByZero)?,
+                }
+            }
+        })
+    }
+}
+
+/// Returns whether the character can be part of a symbol name
+pub fn is_symbol_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Qualifies a local label, which starts with @, with the label it belongs to
+pub fn qualify(name: &str, scope: &str) -> String {
+    if name.starts_with('@') { format!("{scope}{name}") } else { name.to_string() }
+}
+
+/// Syntax of operands and directives, with local labels qualified by the scope they are in
+struct AssemblyGrammar<'a> {
+    scope: &'a str,
+}
+
+impl Grammar for AssemblyGrammar<'_> {
+    type Expr = Expr;
+    type UnaryOp = fn(Box<Expr>) -> Expr;
+    type BinaryOp = BinaryOp;
+
+    const PRECEDENCE: &'static [&'static [(&'static str, BinaryOp)]] = &PRECEDENCE;
+    const UNARY: &'static [(&'static str, fn(Box<Expr>) -> Expr)] = &[
+        ("-", Expr::Negate),
+        ("~", Expr::Not),
+        ("<", Expr::LowByte),
+        (">", Expr::HighByte),
+    ];
+
+    fn unary(op: Self::UnaryOp, expr: Expr) -> Expr {
+        op(Box::new(expr))
+    }
+
+    fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    fn primary(parser: &mut Parser<'_, Self>, first: char) -> Result<Expr, String> {
+        match first {
+            '(' => {
+                parser.skip(1);
+                parser.bracketed(")")
+            }
+            '*' => {
+                parser.skip(1);
+                Ok(Expr::Current)
+            }
+            '$' => parser.number(1, 16).map(Expr::Number),
+            '%' => parser.number(1, 2).map(Expr::Number),
+            '0'..='9' => parser.number(0, 10).map(Expr::Number),
+            '\'' => {
+                let mut chars = parser.rest().chars();
+                match (chars.nth(1), chars.next()) {
+                    (Some(c), Some('\'')) if c.is_ascii() => {
+                        parser.skip(3);
+                        Ok(Expr::Number(c as i64))
+                    }
+                    _ => Err("invalid character constant".to_string()),
+                }
+            }
+            '@' | '_' | 'a'..='z' | 'A'..='Z' => {
+                // A local label's @ is only allowed at the start of its name
+                let start = parser.rest();
+                parser.skip(first.len_utf8());
+                let len = first.len_utf8() + parser.take_while(is_symbol_char).len();
+                Ok(Expr::Symbol(qualify(&start[..len], parser.grammar.scope)))
+            }
+            _ => Err(format!("unexpected '{first}' in expression")),
+        }
+    }
+}
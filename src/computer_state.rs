@@ -1,41 +1,155 @@
-use crate::computer_state::memory::Memory;
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::execution::*;
+use crate::computer_state::operations::interrupt;
 use crate::computer_state::registers::*;
+use crate::computer_state::rewind::RewindBuffer;
 use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::watch::Watchpoints;
 
-pub(crate) mod status_register;
-pub(crate) mod operations;
-mod registers;
+pub mod status_register;
+pub mod operations;
+pub mod execution;
+pub mod bus;
+pub mod registers;
+pub mod rewind;
+pub mod snapshot;
+pub mod watch;
 mod memory;
 
-pub struct ComputerState {
+/// The processor being emulated
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CpuVariant {
+    /// The original NMOS 6502, including its undocumented opcodes
+    Nmos6502,
+    /// The WDC 65C02S, with its additional instructions and bug fixes
+    Wdc65C02,
+}
+
+pub struct ComputerState<B: Bus = FlatRam> {
     // MEMORY
     // Each page is 256 bytes
     // First page is reserved for the Zero-Page ($0000-$00FF)
     // Second page is reserved for system stack ($0100-$01FF)
     // Last 6 bytes are reserved for interrupts ($FFFA-$FFFF)
-    pub mem: Memory,
+    pub bus: B,
 
+    // REGISTERS
+    pub pc: ProgramCounter,
     pub acc: Accumulator,
     pub sta: StatusRegister,
+    x: usize,
+    y: usize,
+    /// Stack pointer, which is an offset into the stack on page 1
+    stk: u8,
+
+    /// Total number of cycles executed since the computer was created
+    pub cycles: u64,
+    /// Extra cycles incurred by the current instruction, such as a branch being taken
+    pub(crate) extra_cycles: u8,
+    /// Set when an indexed read crosses a page boundary, costing the instruction an extra cycle
+    page_crossed: bool,
+
+    // INTERRUPT LINES
+    /// Level of the IRQ line; serviced between instructions while the I flag is clear
+    irq_line: bool,
+    /// Level of the NMI line
+    nmi_line: bool,
+    /// Level of the NMI line combined with the bus, when it was last checked for an edge
+    nmi_level: bool,
+    /// Set when the NMI line is asserted, and cleared once the NMI has been serviced
+    nmi_pending: bool,
+
+    // PROCESSOR
+    variant: CpuVariant,
+    /// Set by WAI; no instructions are executed until an interrupt is signalled
+    pub(crate) waiting: bool,
+    /// Set by STP; no instructions are executed until the processor is reset
+    pub(crate) stopped: bool,
+    /// Set by JAM; reported once as an error, after which the processor is stopped
+    pub(crate) jammed: bool,
+
+    // ILLEGAL OPCODES
+    illegal_opcode_policy: IllegalOpcodePolicy,
+    illegal_opcode_handler: Option<IllegalOpcodeHandler<B>>,
+
+    // DEBUGGING
+    /// History of recent steps, which is only recorded while rewinding is enabled
+    rewind: Option<RewindBuffer>,
+    /// Memory watchpoints, and the accesses which triggered them
+    watch: Watchpoints,
 }
 
 impl ComputerState {
+    /// Constructs an NMOS 6502 with 64KB of RAM
     pub fn new() -> ComputerState {
+        ComputerState::with_variant(CpuVariant::Nmos6502)
+    }
+
+    /// Constructs the given variant of the processor with 64KB of RAM
+    pub fn with_variant(variant: CpuVariant) -> ComputerState {
+        ComputerState::with_bus(variant, FlatRam::new())
+    }
+}
+
+impl Default for ComputerState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<B: Bus> ComputerState<B> {
+    /// Constructs the given variant of the processor, connected to the bus
+    pub fn with_bus(variant: CpuVariant, bus: B) -> ComputerState<B> {
         ComputerState {
-            mem: Memory::new(),
+            bus,
+            pc: ProgramCounter::new(0),
             acc: Accumulator::new(0),
             sta: StatusRegister::new(),
+            x: 0,
+            y: 0,
+            stk: 0xFF,
+            cycles: 0,
+            extra_cycles: 0,
+            page_crossed: false,
+            irq_line: false,
+            nmi_line: false,
+            nmi_level: false,
+            nmi_pending: false,
+            variant,
+            waiting: false,
+            stopped: false,
+            jammed: false,
+            illegal_opcode_policy: IllegalOpcodePolicy::Emulate,
+            illegal_opcode_handler: None,
+            rewind: None,
+            watch: Watchpoints::default(),
         }
     }
 
+    pub const fn variant(&self) -> CpuVariant {
+        self.variant
+    }
+
+    /// Sets what happens when an illegal opcode is fetched
+    /// By default, illegal opcodes are emulated
+    pub fn set_illegal_opcode_policy(&mut self, policy: IllegalOpcodePolicy) {
+        self.illegal_opcode_policy = policy
+    }
+
+    /// Sets the handler called for illegal opcodes when they are trapped
+    pub fn set_illegal_opcode_handler(&mut self, handler: IllegalOpcodeHandler<B>) {
+        self.illegal_opcode_handler = Some(handler)
+    }
+
     // DISPLAY FUNCTIONS
     pub fn get_state_str(&self) -> String {
         // Registers
-        let program_counter = self.mem.pc.get();
-        //let stack_pointer = stringify!(self.mem.pc.get());
+        let program_counter = self.pc.get();
+        //let stack_pointer = stringify!(self.pc.get());
         let accumulator = self.acc.get();
-        let index_x = self.mem.x;
-        let index_y = self.mem.y;
+        let index_x = self.x;
+        let index_y = self.y;
+        let cycles = self.cycles;
 
         // Processor Status
         let carry_flag =     self.sta.flag_value(StatusRegister::C);
@@ -46,9 +160,9 @@ impl ComputerState {
         let overflow_flag =  self.sta.flag_value(StatusRegister::V);
         let negative_flag =  self.sta.flag_value(StatusRegister::N);
 
-        let pc = self.mem.pc.get();
-        let opcode = self.mem.fetch_byte_from_addr(pc);
-        let instruction = operations::decode_operation_name(opcode);
+        let pc = self.pc.get();
+        let opcode = self.peek_byte_from_addr(pc);
+        let instruction = operations::decode_operation_name(self.variant, opcode);
 
         format!(
             "CURRENT INSTRUCTION:
@@ -59,6 +173,7 @@ impl ComputerState {
             Accumulator:     {accumulator}
             Index X:         {index_x}
             Index Y:         {index_y}
+            Cycles:          {cycles}
             Status Flags:
                 Carry:       {carry_flag}
                 Zero:        {zero_flag}
@@ -71,48 +186,200 @@ impl ComputerState {
 
     // REGISTERS
     pub const fn get_x(&self) -> usize {
-        self.mem.x
+        self.x
     }
 
     pub const fn get_y(&self) -> usize {
-        self.mem.y
+        self.y
     }
 
     pub const fn get_stk(&self) -> usize {
-        self.mem.stk
+        self.stk as usize
     }
 
     pub fn set_x(&mut self, value: usize) {
-        self.mem.x = value
+        self.x = value
     }
 
     pub fn set_y(&mut self, value: usize) {
-        self.mem.y = value
+        self.y = value
     }
 
     pub fn set_stk(&mut self, value: usize) {
-        self.mem.stk = value
+        self.stk = value as u8
+    }
+
+
+    // INTERRUPTS
+    /// Sets the level of the IRQ line
+    /// While asserted, an interrupt is serviced before each instruction unless the I flag is set
+    pub fn set_irq(&mut self, asserted: bool) {
+        self.irq_line = asserted
+    }
+
+    /// Sets the level of the NMI line
+    /// An interrupt is only serviced when the line becomes asserted, regardless of the I flag
+    pub fn set_nmi(&mut self, asserted: bool) {
+        self.nmi_line = asserted;
+        self.poll_nmi();
+    }
+
+    /// Returns whether the IRQ line is asserted, either by the processor's own line or by the bus
+    fn irq_asserted(&self) -> bool {
+        self.irq_line || self.bus.irq()
+    }
+
+    /// Makes an NMI pending if the NMI line, combined with the bus, has become asserted
+    fn poll_nmi(&mut self) {
+        let level = self.nmi_line || self.bus.nmi();
+        if level && !self.nmi_level {
+            self.nmi_pending = true;
+        }
+        self.nmi_level = level;
+    }
+
+    /// Resets the processor, loading the PC from the reset vector
+    /// Like the real chip, the stack pointer is moved down by three without writing to the stack,
+    /// and interrupts are disabled. The 65C02 also clears decimal mode
+    pub fn reset(&mut self) {
+        self.stk = self.stk.wrapping_sub(3);
+        self.sta |= StatusRegister::I;
+        if self.variant == CpuVariant::Wdc65C02 {
+            self.sta = self.sta.difference(StatusRegister::D);
+        }
+        self.nmi_pending = false;
+        self.waiting = false;
+        self.stopped = false;
+        self.jammed = false;
+
+        let reset_vector = self.fetch_nibble_from_addr(interrupt::RESET_VECTOR);
+        self.pc.set(usize::from(reset_vector));
+        self.cycles += u64::from(interrupt::INTERRUPT_CYCLES);
+    }
+
+    /// Services a pending NMI, or IRQ if interrupts are enabled
+    /// Returns the number of cycles taken, or None if no interrupt was serviced
+    fn service_interrupts(&mut self) -> Option<u8> {
+        let vector = if self.nmi_pending {
+            self.nmi_pending = false;
+            interrupt::NMI_VECTOR
+        } else if self.irq_asserted() && !self.sta.contains(StatusRegister::I) {
+            interrupt::IRQ_VECTOR
+        } else {
+            return None;
+        };
+
+        interrupt::enter_interrupt(self, vector, false);
+        self.cycles += u64::from(interrupt::INTERRUPT_CYCLES);
+        Some(interrupt::INTERRUPT_CYCLES)
     }
 
 
     // EXECUTION
     /// Executes the instruction at the program counter
-    pub fn execute_next(&mut self) {
+    /// If an interrupt is waiting, it is serviced instead of executing an instruction
+    /// Returns what the step did and how many cycles it took, or why it could not be executed
+    pub fn execute_next(&mut self) -> Result<StepOutcome, ExecutionError> {
+        self.begin_watch_step();
+        if self.rewind.is_none() {
+            return self.step();
+        }
+        // Failed steps are recorded too, as they can still change the state, such as by jamming
+        self.begin_rewind_step();
+        let result = self.step();
+        self.finish_rewind_step();
+        result
+    }
+
+    fn step(&mut self) -> Result<StepOutcome, ExecutionError> {
+        if self.stopped {
+            return Err(ExecutionError::Halted);
+        }
+        // Devices on the bus may have changed the NMI line since the last step
+        self.poll_nmi();
+        // A waiting processor idles for a cycle
+        if self.waiting {
+            // WAI finishes on any interrupt, even if IRQs are disabled
+            if !(self.nmi_pending || self.irq_asserted()) {
+                self.cycles += 1;
+                return Ok(StepOutcome::Waiting);
+            }
+            self.waiting = false;
+        }
+
+        if let Some(cycles) = self.service_interrupts() {
+            return Ok(StepOutcome::Interrupted { cycles });
+        }
+
         // Fetch
-        let opcode = self.mem.fetch_next_byte();
+        let addr = self.pc.get() as u16;
+        let opcode = self.fetch_next_byte();
+        if operations::is_illegal(self.variant, opcode) {
+            if let Some(outcome) = self.handle_illegal_opcode(opcode, addr)? {
+                return Ok(outcome);
+            }
+        }
         // Decode
-        let operation = operations::decode(opcode);
+        let operation = operations::decode::<B>(self.variant, opcode);
         // Execute instruction
+        self.extra_cycles = 0;
         operation(self);
+
+        let page_penalty = u8::from(self.take_page_crossed());
+        let cycles = operations::decode_cycles(self.variant, opcode) + page_penalty + self.extra_cycles;
+        self.cycles += u64::from(cycles);
+
+        if std::mem::take(&mut self.jammed) {
+            return Err(ExecutionError::Jammed { opcode, addr });
+        }
+        Ok(StepOutcome::Executed { cycles })
+    }
+
+    /// Applies the illegal opcode policy to an illegal opcode which has just been fetched
+    /// Returns None if the opcode should be emulated as normal
+    fn handle_illegal_opcode(&mut self, opcode: u8, addr: u16)
+        -> Result<Option<StepOutcome>, ExecutionError> {
+        match self.illegal_opcode_policy {
+            IllegalOpcodePolicy::Emulate => Ok(None),
+            IllegalOpcodePolicy::Halt => {
+                self.pc.set(usize::from(addr));
+                Err(ExecutionError::IllegalOpcode { opcode, addr })
+            }
+            IllegalOpcodePolicy::Trap => {
+                let Some(handler) = self.illegal_opcode_handler else {
+                    self.pc.set(usize::from(addr));
+                    return Err(ExecutionError::Configuration(ConfigurationError::MissingTrapHandler));
+                };
+                let cycles = handler(self, opcode);
+                self.cycles += u64::from(cycles);
+                Ok(Some(StepOutcome::Trapped { opcode, cycles }))
+            }
+            IllegalOpcodePolicy::Nop => {
+                // Skips the operand as well, so the next instruction is not taken from the middle
+                let len = operations::instruction_len(self.variant, opcode) as u16;
+                self.pc.set(usize::from(addr.wrapping_add(len)));
+                let cycles = operations::decode_cycles(self.variant, opcode);
+                self.cycles += u64::from(cycles);
+                Ok(Some(StepOutcome::Executed { cycles }))
+            }
+        }
     }
 
     // MEMORY ACCESS
     pub fn set_up_state(&mut self, bytes: &[u8]) {
-        let old_pc = self.mem.pc.get();
+        let old_pc = self.pc.get();
         for byte in bytes {
-            self.mem.insert_at_pc(*byte);
+            self.insert_at_pc(*byte);
+        }
+        self.pc.set(old_pc);
+    }
+
+    /// Loads the bytes into memory starting at the address, without moving the PC
+    /// Goes through the bus as a load, so that images can be placed in ROM
+    pub fn load_at(&mut self, addr: u16, bytes: &[u8]) {
+        for (offset, byte) in bytes.iter().enumerate() {
+            self.bus.load(addr.wrapping_add(offset as u16), *byte);
         }
-        self.mem.pc.set(old_pc);
     }
 
 
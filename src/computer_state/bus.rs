@@ -0,0 +1,102 @@
+pub mod address_decoder;
+pub mod mapper;
+
+use crate::computer_state::snapshot::{SnapshotError, StateReader, StateWriter};
+
+/// Size of the address space of the processor
+pub const ADDRESS_SPACE_SIZE: usize = 0x10000;
+
+/// Whether an access read from or wrote to the bus
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AccessKind {
+    Read,
+    Write,
+}
+
+/// Everything the processor reads from and writes to, such as RAM, ROM and memory-mapped devices
+/// Every access made by an instruction goes through the bus, so devices can respond to them
+pub trait Bus {
+    /// Reads the byte at the address, as the processor would
+    /// Devices may have side effects on a read, such as clearing a status register
+    fn read(&mut self, addr: u16) -> u8 {
+        self.peek(addr)
+    }
+
+    /// Writes the byte to the address
+    fn write(&mut self, addr: u16, value: u8);
+
+    /// Writes the byte to the address while loading a program, before execution begins
+    /// Unlike a write, this also fills read-only memory such as ROM
+    fn load(&mut self, addr: u16, value: u8) {
+        self.write(addr, value)
+    }
+
+    /// Returns the byte at the address without any side effects
+    /// Used by debugging tools, which must not disturb the emulated machine
+    fn peek(&self, addr: u16) -> u8;
+
+    /// Returns whether a write to the address only stores the byte, so loading the byte peeked
+    /// before it undoes it
+    /// Buses with devices, bank registers or read-only memory return false for those addresses, and
+    /// rewinding saves the whole state of the bus before writing to them instead
+    fn is_plain_memory(&self, _addr: u16) -> bool {
+        true
+    }
+
+    /// Returns whether a device on the bus is asserting the IRQ line
+    /// The line is shared with the processor's own, so either can assert it
+    fn irq(&self) -> bool {
+        false
+    }
+
+    /// Returns whether a device on the bus is asserting the NMI line
+    /// The line is shared with the processor's own, so either can assert it
+    fn nmi(&self) -> bool {
+        false
+    }
+
+    /// Writes the contents of the memory and devices to a snapshot
+    /// Buses with nothing to restore write nothing
+    fn save_state(&self, _snapshot: &mut StateWriter) {}
+
+    /// Restores the contents of the memory and devices from a snapshot written by `save_state`
+    fn load_state(&mut self, _snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        Ok(())
+    }
+}
+
+/// 64KB of RAM covering the whole address space, with no devices
+pub struct FlatRam {
+    mem: [u8; ADDRESS_SPACE_SIZE],
+}
+
+impl FlatRam {
+    /// Constructs RAM with every byte set to 0
+    pub fn new() -> Self {
+        Self { mem: [0; ADDRESS_SPACE_SIZE] }
+    }
+}
+
+impl Default for FlatRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for FlatRam {
+    fn write(&mut self, addr: u16, value: u8) {
+        self.mem[usize::from(addr)] = value;
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.mem[usize::from(addr)]
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_bytes(&self.mem);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        snapshot.read_bytes_into(&mut self.mem)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::ConfigurationError;
+use crate::computer_state::snapshot::{SnapshotError, StateReader, StateWriter};
+
+/// What responds to accesses within a region
+enum Backing {
+    /// RAM or ROM, indexed by the offset into the region
+    Memory(Vec<u8>),
+    /// A device, which is given the offset into the region as its address
+    Device(Box<dyn Bus>),
+}
+
+/// A range of the address space which is connected to memory or a device
+pub struct Region {
+    start: u16,
+    end: u16,
+    /// Mask applied to the offset into the region, so the region repeats every mask + 1 bytes
+    mirror_mask: u16,
+    /// Writes to read-only regions are ignored
+    read_only: bool,
+    backing: Backing,
+}
+
+impl Region {
+    /// RAM of the given size covering the range
+    /// If the range is larger than the RAM, the RAM is mirrored across the range, so the size must be
+    /// a power of two
+    pub fn ram(start: u16, end: u16, size: usize) -> Region {
+        Region::memory(start, end, vec![0; size], false)
+    }
+
+    /// ROM holding the image, which ignores writes
+    /// If the range is larger than the image, the image is mirrored across the range, so its length
+    /// must be a power of two
+    pub fn rom(start: u16, end: u16, image: Vec<u8>) -> Region {
+        Region::memory(start, end, image, true)
+    }
+
+    /// Device covering the range, which is accessed with offsets from the start of the range
+    pub fn device(start: u16, end: u16, device: Box<dyn Bus>) -> Region {
+        Region { start, end, mirror_mask: u16::MAX, read_only: false, backing: Backing::Device(device) }
+    }
+
+    fn memory(start: u16, end: u16, mem: Vec<u8>, read_only: bool) -> Region {
+        // Memory smaller than the range repeats across it
+        let mirror_mask = if mem.len() < Region::span(start, end) {
+            mem.len().saturating_sub(1) as u16
+        } else {
+            u16::MAX
+        };
+        Region { start, end, mirror_mask, read_only, backing: Backing::Memory(mem) }
+    }
+
+    /// Sets the mask applied to offsets into the region, which repeats every mask + 1 bytes
+    pub fn with_mirror_mask(mut self, mask: u16) -> Region {
+        self.mirror_mask = mask;
+        self
+    }
+
+    /// Makes the region ignore writes
+    pub fn read_only(mut self) -> Region {
+        self.read_only = true;
+        self
+    }
+
+    /// Returns the number of addresses in the range
+    const fn span(start: u16, end: u16) -> usize {
+        (end as usize + 1).saturating_sub(start as usize)
+    }
+
+    const fn contains(&self, addr: u16) -> bool {
+        self.start <= addr && addr <= self.end
+    }
+
+    /// Returns the offset into the backing of the address, after mirroring
+    const fn offset(&self, addr: u16) -> u16 {
+        (addr - self.start) & self.mirror_mask
+    }
+
+    /// Checks the range is valid, and that every address in it is backed after mirroring
+    /// The mask must be one less than a power of two, otherwise the mirrors would be scrambled
+    fn validate(&self) -> Result<(), ConfigurationError> {
+        let (start, end) = (self.start, self.end);
+        if start > end {
+            return Err(ConfigurationError::InvalidRegion { start, end });
+        }
+        if let Backing::Memory(mem) = &self.backing {
+            let highest_offset = (Region::span(start, end) - 1).min(usize::from(self.mirror_mask));
+            let contiguous = (u32::from(self.mirror_mask) + 1).is_power_of_two();
+            if highest_offset >= mem.len() || !contiguous {
+                return Err(ConfigurationError::InvalidMirrorMask { start, end, mask: self.mirror_mask });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Bus made from regions of RAM, ROM and devices, like the address decoding logic of a real machine
+/// Reading an address outside every region returns the last value on the data bus
+pub struct AddressDecoder {
+    /// Regions sorted by their start address
+    regions: Vec<Region>,
+    /// Last value read or written, which is what an unconnected address reads as
+    open_bus: u8,
+}
+
+impl AddressDecoder {
+    /// Constructs a decoder from the regions
+    /// Fails if any region is invalid, or if any two regions overlap
+    pub fn new(mut regions: Vec<Region>) -> Result<AddressDecoder, ConfigurationError> {
+        for region in &regions {
+            region.validate()?;
+        }
+
+        regions.sort_by_key(|region| region.start);
+        for pair in regions.windows(2) {
+            if pair[1].start <= pair[0].end {
+                return Err(ConfigurationError::OverlappingRegions {
+                    first: (pair[0].start, pair[0].end),
+                    second: (pair[1].start, pair[1].end),
+                });
+            }
+        }
+
+        Ok(AddressDecoder { regions, open_bus: 0 })
+    }
+
+    fn region(&self, addr: u16) -> Option<&Region> {
+        self.regions.iter().find(|region| region.contains(addr))
+    }
+
+    fn region_mut(&mut self, addr: u16) -> Option<&mut Region> {
+        self.regions.iter_mut().find(|region| region.contains(addr))
+    }
+
+    fn devices(&self) -> impl Iterator<Item = &dyn Bus> {
+        self.regions.iter().filter_map(|region| match &region.backing {
+            Backing::Device(device) => Some(device.as_ref()),
+            Backing::Memory(_) => None,
+        })
+    }
+
+    /// Writes the byte to the region containing the address, even if it is read-only
+    fn write_region(&mut self, addr: u16, value: u8, ignore_read_only: bool) {
+        let Some(region) = self.region_mut(addr) else {
+            return;
+        };
+        if region.read_only && !ignore_read_only {
+            return;
+        }
+
+        let offset = region.offset(addr);
+        match &mut region.backing {
+            Backing::Memory(mem) => mem[usize::from(offset)] = value,
+            Backing::Device(device) if ignore_read_only => device.load(offset, value),
+            Backing::Device(device) => device.write(offset, value),
+        }
+    }
+}
+
+impl Bus for AddressDecoder {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = match self.region_mut(addr) {
+            Some(region) => {
+                let offset = region.offset(addr);
+                match &mut region.backing {
+                    Backing::Memory(mem) => mem[usize::from(offset)],
+                    Backing::Device(device) => device.read(offset),
+                }
+            }
+            None => self.open_bus,
+        };
+        self.open_bus = value;
+        value
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.open_bus = value;
+        self.write_region(addr, value, false);
+    }
+
+    fn load(&mut self, addr: u16, value: u8) {
+        self.write_region(addr, value, true);
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.region(addr) {
+            Some(region) => {
+                let offset = region.offset(addr);
+                match &region.backing {
+                    Backing::Memory(mem) => mem[usize::from(offset)],
+                    Backing::Device(device) => device.peek(offset),
+                }
+            }
+            None => self.open_bus,
+        }
+    }
+
+    fn is_plain_memory(&self, addr: u16) -> bool {
+        // Unmapped writes still change the open bus value
+        self.region(addr).is_some_and(|region| !region.read_only && matches!(region.backing, Backing::Memory(_)))
+    }
+
+    fn irq(&self) -> bool {
+        self.devices().any(|device| device.irq())
+    }
+
+    fn nmi(&self) -> bool {
+        self.devices().any(|device| device.nmi())
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_u8(self.open_bus);
+        for region in &self.regions {
+            match &region.backing {
+                Backing::Memory(mem) => snapshot.write_bytes(mem),
+                Backing::Device(device) => snapshot.write_nested(|nested| device.save_state(nested)),
+            }
+        }
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        self.open_bus = snapshot.read_u8()?;
+        for region in &mut self.regions {
+            match &mut region.backing {
+                Backing::Memory(mem) => snapshot.read_bytes_into(mem)?,
+                Backing::Device(device) => device.load_state(&mut snapshot.read_nested()?)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,494 @@
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::snapshot::{SnapshotError, StateReader, StateWriter};
+
+/// Size of the banks switched by the generic latch, UxROM and MMC1 mappers
+pub const BANK_SIZE_16K: usize = 0x4000;
+/// Size of the PRG RAM of an MMC1 cartridge
+pub const PRG_RAM_SIZE: usize = 0x2000;
+
+/// Bank controller which watches writes to its registers and maps windows of the address space onto
+/// a backing store larger than the window
+/// Addresses are the ones seen by the processor, and a mapper only responds to some of them
+pub trait Mapper {
+    /// Reads the byte at the address, or returns None if the mapper does not respond to it
+    fn read(&mut self, addr: u16) -> Option<u8> {
+        self.peek(addr)
+    }
+
+    /// Returns the byte at the address without any side effects, or None if the mapper does not
+    /// respond to it
+    fn peek(&self, addr: u16) -> Option<u8>;
+
+    /// Writes the byte to the address, which may change the bank registers
+    /// Returns false if the mapper does not respond to the address
+    fn write(&mut self, addr: u16, value: u8) -> bool;
+
+    /// Writes the byte into the bank currently mapped at the address, even if it is ROM
+    /// Returns false if the mapper does not respond to the address
+    fn load(&mut self, addr: u16, value: u8) -> bool;
+
+    /// Returns whether the mapper responds to writes to the address, rather than the bus behind it
+    fn responds_to_write(&self, addr: u16) -> bool;
+
+    /// Writes the banks and registers of the mapper to a snapshot
+    fn save_state(&self, snapshot: &mut StateWriter);
+
+    /// Restores the banks and registers of the mapper from a snapshot written by `save_state`
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError>;
+}
+
+/// Bus with a mapper in front of another bus
+/// Accesses the mapper does not respond to are passed on to the bus behind it
+pub struct Cartridge<M: Mapper, B: Bus = FlatRam> {
+    pub mapper: M,
+    pub bus: B,
+}
+
+impl<M: Mapper, B: Bus> Cartridge<M, B> {
+    /// Constructs a bus with the mapper in front of the bus
+    pub fn new(mapper: M, bus: B) -> Self {
+        Self { mapper, bus }
+    }
+}
+
+impl<M: Mapper, B: Bus> Bus for Cartridge<M, B> {
+    fn read(&mut self, addr: u16) -> u8 {
+        match self.mapper.read(addr) {
+            Some(value) => value,
+            None => self.bus.read(addr),
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        if !self.mapper.write(addr, value) {
+            self.bus.write(addr, value);
+        }
+    }
+
+    fn load(&mut self, addr: u16, value: u8) {
+        if !self.mapper.load(addr, value) {
+            self.bus.load(addr, value);
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match self.mapper.peek(addr) {
+            Some(value) => value,
+            None => self.bus.peek(addr),
+        }
+    }
+
+    fn is_plain_memory(&self, addr: u16) -> bool {
+        !self.mapper.responds_to_write(addr) && self.bus.is_plain_memory(addr)
+    }
+
+    fn irq(&self) -> bool {
+        self.bus.irq()
+    }
+
+    fn nmi(&self) -> bool {
+        self.bus.nmi()
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_nested(|mapper| self.mapper.save_state(mapper));
+        snapshot.write_nested(|bus| self.bus.save_state(bus));
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        self.mapper.load_state(&mut snapshot.read_nested()?)?;
+        self.bus.load_state(&mut snapshot.read_nested()?)
+    }
+}
+
+/// Backing store divided into banks of equal size
+struct Banks {
+    data: Vec<u8>,
+    size: usize,
+}
+
+impl Banks {
+    /// Divides the data into banks, padding the last bank with zeros if it is incomplete
+    fn new(mut data: Vec<u8>, size: usize) -> Banks {
+        let count = data.len().div_ceil(size).max(1);
+        data.resize(count * size, 0);
+        Banks { data, size }
+    }
+
+    fn count(&self) -> usize {
+        self.data.len() / self.size
+    }
+
+    /// Returns the index into the data of the offset into the bank
+    /// Banks past the end of the data wrap around, as the unused high bits of a bank register would
+    fn index(&self, bank: usize, offset: usize) -> usize {
+        (bank % self.count()) * self.size + offset % self.size
+    }
+
+    fn byte(&self, bank: usize, offset: usize) -> u8 {
+        self.data[self.index(bank, offset)]
+    }
+
+    fn set_byte(&mut self, bank: usize, offset: usize, value: u8) {
+        let index = self.index(bank, offset);
+        self.data[index] = value;
+    }
+}
+
+/// Generic mapper where a latch register selects the 16K bank of ROM seen through a 16K window
+pub struct LatchMapper {
+    rom: Banks,
+    /// First address of the window
+    window: u16,
+    /// Address of the latch register
+    latch: u16,
+    bank: usize,
+}
+
+impl LatchMapper {
+    /// Constructs a mapper for the ROM image, with bank 0 selected
+    /// Writing a bank number to the latch address maps that bank into the window
+    pub fn new(image: Vec<u8>, window: u16, latch: u16) -> LatchMapper {
+        LatchMapper { rom: Banks::new(image, BANK_SIZE_16K), window, latch, bank: 0 }
+    }
+
+    /// Returns the bank currently selected by the latch
+    pub const fn bank(&self) -> usize {
+        self.bank
+    }
+
+    /// Returns the offset into the window of the address, or None if it is outside the window
+    fn window_offset(&self, addr: u16) -> Option<usize> {
+        let offset = usize::from(addr.wrapping_sub(self.window));
+        (offset < BANK_SIZE_16K).then_some(offset)
+    }
+}
+
+impl Mapper for LatchMapper {
+    fn peek(&self, addr: u16) -> Option<u8> {
+        self.window_offset(addr).map(|offset| self.rom.byte(self.bank, offset))
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        if addr == self.latch {
+            self.bank = usize::from(value) % self.rom.count();
+            true
+        } else {
+            // The window is ROM, so writes to it are ignored
+            self.window_offset(addr).is_some()
+        }
+    }
+
+    fn responds_to_write(&self, addr: u16) -> bool {
+        addr == self.latch || self.window_offset(addr).is_some()
+    }
+
+    fn load(&mut self, addr: u16, value: u8) -> bool {
+        match self.window_offset(addr) {
+            Some(offset) => {
+                self.rom.set_byte(self.bank, offset, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_bytes(&self.rom.data);
+        snapshot.write_u32(self.bank as u32);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        snapshot.read_bytes_into(&mut self.rom.data)?;
+        self.bank = snapshot.read_u32()? as usize % self.rom.count();
+        Ok(())
+    }
+}
+
+/// NES mapper 0, with 16K or 32K of PRG ROM at $8000-$FFFF and no bank switching
+/// 16K of ROM is mirrored into $C000-$FFFF
+pub struct Nrom {
+    prg_rom: Vec<u8>,
+}
+
+impl Nrom {
+    /// Constructs the mapper for the PRG ROM image
+    /// Images which are not 16K or 32K are padded with zeros to the next of those sizes
+    pub fn new(mut prg_rom: Vec<u8>) -> Nrom {
+        let size = if prg_rom.len() <= BANK_SIZE_16K { BANK_SIZE_16K } else { 2 * BANK_SIZE_16K };
+        prg_rom.resize(size, 0);
+        Nrom { prg_rom }
+    }
+
+    fn index(&self, addr: u16) -> Option<usize> {
+        (addr >= 0x8000).then(|| usize::from(addr - 0x8000) % self.prg_rom.len())
+    }
+}
+
+impl Mapper for Nrom {
+    fn peek(&self, addr: u16) -> Option<u8> {
+        self.index(addr).map(|index| self.prg_rom[index])
+    }
+
+    fn write(&mut self, addr: u16, _: u8) -> bool {
+        self.responds_to_write(addr)
+    }
+
+    fn responds_to_write(&self, addr: u16) -> bool {
+        self.index(addr).is_some()
+    }
+
+    fn load(&mut self, addr: u16, value: u8) -> bool {
+        match self.index(addr) {
+            Some(index) => {
+                self.prg_rom[index] = value;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_bytes(&self.prg_rom);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        snapshot.read_bytes_into(&mut self.prg_rom)
+    }
+}
+
+/// NES mapper 2, where any write to $8000-$FFFF selects the 16K bank of PRG ROM at $8000-$BFFF
+/// The last bank is fixed at $C000-$FFFF
+pub struct UxRom {
+    prg_rom: Banks,
+    bank: usize,
+}
+
+impl UxRom {
+    /// Constructs the mapper for the PRG ROM image, with bank 0 selected
+    pub fn new(prg_rom: Vec<u8>) -> UxRom {
+        UxRom { prg_rom: Banks::new(prg_rom, BANK_SIZE_16K), bank: 0 }
+    }
+
+    /// Returns the bank currently mapped at $8000-$BFFF
+    pub const fn bank(&self) -> usize {
+        self.bank
+    }
+
+    /// Returns the bank and offset into it mapped at the address, if it is in the cartridge space
+    fn locate(&self, addr: u16) -> Option<(usize, usize)> {
+        match addr {
+            0x8000..=0xBFFF => Some((self.bank, usize::from(addr - 0x8000))),
+            0xC000..=0xFFFF => Some((self.prg_rom.count() - 1, usize::from(addr - 0xC000))),
+            _ => None,
+        }
+    }
+}
+
+impl Mapper for UxRom {
+    fn peek(&self, addr: u16) -> Option<u8> {
+        self.locate(addr).map(|(bank, offset)| self.prg_rom.byte(bank, offset))
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        if !self.responds_to_write(addr) {
+            return false;
+        }
+        self.bank = usize::from(value) % self.prg_rom.count();
+        true
+    }
+
+    fn responds_to_write(&self, addr: u16) -> bool {
+        addr >= 0x8000
+    }
+
+    fn load(&mut self, addr: u16, value: u8) -> bool {
+        match self.locate(addr) {
+            Some((bank, offset)) => {
+                self.prg_rom.set_byte(bank, offset, value);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_bytes(&self.prg_rom.data);
+        snapshot.write_u32(self.bank as u32);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        snapshot.read_bytes_into(&mut self.prg_rom.data)?;
+        self.bank = snapshot.read_u32()? as usize % self.prg_rom.count();
+        Ok(())
+    }
+}
+
+/// NES mapper 1, where bank registers are written one bit at a time through a serial port at
+/// $8000-$FFFF, with 8K of PRG RAM at $6000-$7FFF
+/// Only the PRG side is emulated, the CHR bank registers are kept for a PPU to read
+pub struct Mmc1 {
+    prg_rom: Banks,
+    prg_ram: Vec<u8>,
+    /// Bits written so far, shifted in from bit 4 towards bit 0
+    shift: u8,
+    /// Number of bits written to the shift register
+    shift_count: u8,
+    /// Mirroring in bits 0-1, PRG ROM bank mode in bits 2-3 and CHR ROM bank mode in bit 4
+    control: u8,
+    chr_banks: [u8; 2],
+    /// Bank number in bits 0-3, and bit 4 disables the PRG RAM
+    prg_bank: u8,
+}
+
+impl Mmc1 {
+    /// Constructs the mapper for the PRG ROM image, in its power-on state with the last bank
+    /// fixed at $C000-$FFFF
+    pub fn new(prg_rom: Vec<u8>) -> Mmc1 {
+        Mmc1 {
+            prg_rom: Banks::new(prg_rom, BANK_SIZE_16K),
+            prg_ram: vec![0; PRG_RAM_SIZE],
+            shift: 0,
+            shift_count: 0,
+            control: 0x0C,
+            chr_banks: [0; 2],
+            prg_bank: 0,
+        }
+    }
+
+    /// Returns the control register
+    pub const fn control(&self) -> u8 {
+        self.control
+    }
+
+    /// Returns the two CHR bank registers
+    pub const fn chr_banks(&self) -> [u8; 2] {
+        self.chr_banks
+    }
+
+    /// Returns the PRG bank register
+    pub const fn prg_bank(&self) -> u8 {
+        self.prg_bank
+    }
+
+    const fn prg_ram_enabled(&self) -> bool {
+        self.prg_bank & 0x10 == 0
+    }
+
+    /// Returns the bank and offset into it mapped at an address in $8000-$FFFF
+    fn locate_rom(&self, addr: u16) -> (usize, usize) {
+        let bank = usize::from(self.prg_bank & 0x0F);
+        let last = self.prg_rom.count() - 1;
+        let upper = addr >= 0xC000;
+        let offset = usize::from(addr) % BANK_SIZE_16K;
+        match (self.control >> 2) & 0b11 {
+            // 32K mode ignores the lowest bit of the bank number
+            0 | 1 => ((bank & !1) + usize::from(upper), offset),
+            // First bank fixed at $8000, switchable bank at $C000
+            2 => (if upper { bank } else { 0 }, offset),
+            // Switchable bank at $8000, last bank fixed at $C000
+            _ => (if upper { last } else { bank }, offset),
+        }
+    }
+
+    /// Shifts a bit into the shift register, and writes the register selected by the address on
+    /// the fifth write
+    fn write_serial(&mut self, addr: u16, value: u8) {
+        // Writing a byte with bit 7 set resets the shift register and fixes the last bank
+        if value & 0x80 != 0 {
+            self.shift = 0;
+            self.shift_count = 0;
+            self.control |= 0x0C;
+            return;
+        }
+
+        self.shift = (self.shift >> 1) | ((value & 1) << 4);
+        self.shift_count += 1;
+        if self.shift_count < 5 {
+            return;
+        }
+
+        let register = self.shift;
+        self.shift = 0;
+        self.shift_count = 0;
+        match addr {
+            0x8000..=0x9FFF => self.control = register,
+            0xA000..=0xBFFF => self.chr_banks[0] = register,
+            0xC000..=0xDFFF => self.chr_banks[1] = register,
+            _ => self.prg_bank = register,
+        }
+    }
+}
+
+impl Mapper for Mmc1 {
+    fn peek(&self, addr: u16) -> Option<u8> {
+        match addr {
+            // Disabled PRG RAM is left to the bus behind the mapper, which is usually open bus
+            0x6000..=0x7FFF if self.prg_ram_enabled() => Some(self.prg_ram[usize::from(addr - 0x6000)]),
+            0x8000..=0xFFFF => {
+                let (bank, offset) = self.locate_rom(addr);
+                Some(self.prg_rom.byte(bank, offset))
+            }
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, addr: u16, value: u8) -> bool {
+        match addr {
+            0x6000..=0x7FFF if self.prg_ram_enabled() => {
+                self.prg_ram[usize::from(addr - 0x6000)] = value;
+                true
+            }
+            0x8000..=0xFFFF => {
+                self.write_serial(addr, value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn responds_to_write(&self, addr: u16) -> bool {
+        match addr {
+            0x6000..=0x7FFF => self.prg_ram_enabled(),
+            0x8000..=0xFFFF => true,
+            _ => false,
+        }
+    }
+
+    fn load(&mut self, addr: u16, value: u8) -> bool {
+        match addr {
+            0x6000..=0x7FFF => {
+                self.prg_ram[usize::from(addr - 0x6000)] = value;
+                true
+            }
+            0x8000..=0xFFFF => {
+                let (bank, offset) = self.locate_rom(addr);
+                self.prg_rom.set_byte(bank, offset, value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_bytes(&self.prg_rom.data);
+        snapshot.write_bytes(&self.prg_ram);
+        snapshot.write_u8(self.shift);
+        snapshot.write_u8(self.shift_count);
+        snapshot.write_u8(self.control);
+        snapshot.write_u8(self.chr_banks[0]);
+        snapshot.write_u8(self.chr_banks[1]);
+        snapshot.write_u8(self.prg_bank);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        snapshot.read_bytes_into(&mut self.prg_rom.data)?;
+        snapshot.read_bytes_into(&mut self.prg_ram)?;
+        self.shift = snapshot.read_u8()?;
+        self.shift_count = snapshot.read_u8()? % 5;
+        self.control = snapshot.read_u8()?;
+        self.chr_banks = [snapshot.read_u8()?, snapshot.read_u8()?];
+        self.prg_bank = snapshot.read_u8()?;
+        Ok(())
+    }
+}
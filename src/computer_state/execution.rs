@@ -0,0 +1,108 @@
+use std::fmt;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::FlatRam;
+
+/// Handler called when an illegal opcode is trapped
+/// Given the opcode, with the PC pointing at the byte after it, and returns the cycles it took
+pub type IllegalOpcodeHandler<B = FlatRam> = fn(&mut ComputerState<B>, u8) -> u8;
+
+/// What the processor does when it fetches an illegal opcode
+/// Illegal opcodes are the undocumented opcodes of the NMOS 6502, and the undefined opcodes of the
+/// 65C02
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum IllegalOpcodePolicy {
+    /// Execute the opcode as the real chip would
+    Emulate,
+    /// Stop before executing the opcode and return an error
+    Halt,
+    /// Pass the opcode to the illegal opcode handler
+    Trap,
+    /// Skip the instruction as a NOP of the same length, taking the same number of cycles
+    Nop,
+}
+
+/// Result of successfully executing a single step of the processor
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StepOutcome {
+    /// An instruction was executed
+    Executed { cycles: u8 },
+    /// An interrupt was serviced instead of executing an instruction
+    Interrupted { cycles: u8 },
+    /// The processor idled while waiting for an interrupt after WAI
+    Waiting,
+    /// An illegal opcode was passed to the illegal opcode handler
+    Trapped { opcode: u8, cycles: u8 },
+}
+
+impl StepOutcome {
+    /// Returns the number of cycles the step took
+    pub const fn cycles(&self) -> u8 {
+        match *self {
+            StepOutcome::Executed { cycles } |
+            StepOutcome::Interrupted { cycles } |
+            StepOutcome::Trapped { cycles, .. } => cycles,
+            StepOutcome::Waiting => 1,
+        }
+    }
+}
+
+/// Reason the processor could not execute a step
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExecutionError {
+    /// An illegal opcode was fetched while the policy is to halt
+    /// The PC is left pointing at the opcode
+    IllegalOpcode { opcode: u8, addr: u16 },
+    /// A JAM opcode locked up the processor, which must be reset
+    Jammed { opcode: u8, addr: u16 },
+    /// The processor is stopped by STP or JAM, and must be reset
+    Halted,
+    /// The emulator was configured in a way that prevents execution
+    Configuration(ConfigurationError),
+}
+
+/// Problems with how the emulator was set up
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ConfigurationError {
+    /// Illegal opcodes are trapped, but no handler has been given
+    MissingTrapHandler,
+    /// A region of the address decoder ends before it starts
+    InvalidRegion { start: u16, end: u16 },
+    /// Part of a region of the address decoder is not backed by memory after mirroring
+    InvalidMirrorMask { start: u16, end: u16, mask: u16 },
+    /// Two regions of the address decoder cover the same address
+    OverlappingRegions { first: (u16, u16), second: (u16, u16) },
+}
+
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::IllegalOpcode { opcode, addr } =>
+                write!(f, "illegal opcode ${opcode:02X} at ${addr:04X}"),
+            ExecutionError::Jammed { opcode, addr } =>
+                write!(f, "processor jammed by opcode ${opcode:02X} at ${addr:04X}"),
+            ExecutionError::Halted =>
+                write!(f, "processor is halted until it is reset"),
+            ExecutionError::Configuration(error) =>
+                write!(f, "configuration error: {error}"),
+        }
+    }
+}
+
+impl fmt::Display for ConfigurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigurationError::MissingTrapHandler =>
+                write!(f, "illegal opcodes are trapped, but no handler was set"),
+            ConfigurationError::InvalidRegion { start, end } =>
+                write!(f, "region ${start:04X}-${end:04X} ends before it starts"),
+            ConfigurationError::InvalidMirrorMask { start, end, mask } =>
+                write!(f, "mirror mask ${mask:04X} of region ${start:04X}-${end:04X} reaches past its memory or is not one less than a power of two"),
+            ConfigurationError::OverlappingRegions { first: (first_start, first_end), second: (second_start, second_end) } =>
+                write!(f, "region ${first_start:04X}-${first_end:04X} overlaps region ${second_start:04X}-${second_end:04X}"),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+impl std::error::Error for ConfigurationError {}
@@ -1,28 +1,20 @@
-use crate::computer_state::registers::ProgramCounter;
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::{Bus, ADDRESS_SPACE_SIZE};
 
-const MEMORY_SIZE: usize = 0x10000;
-pub struct Memory {
-    pub pc: ProgramCounter,
-    pub x: usize,
-    pub y: usize,
-    pub stk: usize,
+// Memory accesses of the processor
+// Every access goes through the bus, including fetching opcodes and operands, and is checked against
+// the watchpoints
 
-    mem: [u8; MEMORY_SIZE],
-}
-impl Memory {
-    pub(crate) fn new() -> Memory {
-        Memory {
-            x: 0,
-            y: 0,
-            stk: 0xFF,
-            pc: ProgramCounter::new(0),
-            mem: [0; MEMORY_SIZE]
-        }
-    }
+/// Addresses wrap around at the end of memory, as the address bus is 16 bits wide
+const ADDRESS_MASK: usize = ADDRESS_SPACE_SIZE - 1;
 
+impl<B: Bus> ComputerState<B> {
     // SET INSTRUCTIONS
     pub fn set_byte_at_addr(&mut self, addr: usize, value: u8) {
-        self.mem[addr] = value;
+        let addr = (addr & ADDRESS_MASK) as u16;
+        self.record_rewind_write(addr);
+        self.check_watch_write(addr, value);
+        self.bus.write(addr, value);
     }
 
     pub fn set_nibble_at_addr(&mut self, addr: usize, value: u16) {
@@ -33,21 +25,52 @@ impl Memory {
     }
 
     /// Inserts the given value at the position pointed to by the PC; increments the PC
+    /// Goes through the bus as a load, so that programs can be placed in ROM
     pub fn insert_at_pc(&mut self, value: u8) {
-        self.set_byte_at_addr(self.pc.get(), value);
+        self.bus.load(self.pc.get() as u16, value);
         self.pc.add_unsigned(1);
     }
 
 
+    // CYCLE PENALTIES
+    /// Records a page crossing if the indexed address is on a different page to its base address
+    fn check_page_cross(&mut self, base_addr: usize, addr: usize) {
+        self.page_crossed |= (base_addr & 0xFF00) != (addr & 0xFF00);
+    }
+
+    /// Returns whether the last instruction's read crossed a page boundary, and resets it
+    pub fn take_page_crossed(&mut self) -> bool {
+        std::mem::take(&mut self.page_crossed)
+    }
+
+
+    // PEEK INSTRUCTIONS
+    // These read memory without any side effects on the bus, for displaying and debugging
+    /// Returns the byte of data at the given address without side effects
+    pub fn peek_byte_from_addr(&self, addr: usize) -> u8 {
+        self.bus.peek((addr & ADDRESS_MASK) as u16)
+    }
+
+    /// Returns 16-bits of data at the given address in little endian byte-order without side effects
+    pub fn peek_nibble_from_addr(&self, addr: usize) -> u16 {
+        let lo_byte = u16::from(self.peek_byte_from_addr(addr));
+        let hi_byte = u16::from(self.peek_byte_from_addr(addr + 1));
+        (hi_byte << 8) + lo_byte
+    }
+
+
     // FETCH INSTRUCTIONS
     // These instructions help the emulator fetch memory according to addressing modes
     /// Returns the byte of data at the given address
-    pub fn fetch_byte_from_addr(&self, addr: usize) -> u8 {
-        self.mem[addr]
+    pub fn fetch_byte_from_addr(&mut self, addr: usize) -> u8 {
+        let addr = (addr & ADDRESS_MASK) as u16;
+        let value = self.bus.read(addr);
+        self.check_watch_read(addr, value);
+        value
     }
 
     /// Returns 16-bits of data at the given address in little endian byte-order
-    pub fn fetch_nibble_from_addr(&self, addr: usize) -> u16 {
+    pub fn fetch_nibble_from_addr(&mut self, addr: usize) -> u16 {
         let lo_byte = u16::from(self.fetch_byte_from_addr(addr));
         let hi_byte = u16::from(self.fetch_byte_from_addr(addr + 1));
         (hi_byte << 8) + lo_byte
@@ -60,12 +83,12 @@ impl Memory {
     }
 
     /// Returns the 8-bit address at the given address
-    pub fn fetch_zp_addr_from_addr(&self, addr: usize) -> usize {
+    pub fn fetch_zp_addr_from_addr(&mut self, addr: usize) -> usize {
         usize::from(self.fetch_byte_from_addr(addr))
     }
 
     /// Returns the 16-bit address the given address in little endian byte-order
-    pub fn fetch_ab_addr_from_addr(&self, addr: usize) -> usize {
+    pub fn fetch_ab_addr_from_addr(&mut self, addr: usize) -> usize {
         usize::from(self.fetch_nibble_from_addr(addr))
     }
 
@@ -115,26 +138,48 @@ impl Memory {
     /// Fetches the operand as an absolute address and adds the X index to that address
     /// If this addition overflows, it will wrap around
     pub fn fetch_absolute_x_address(&mut self) -> usize {
-        self.fetch_absolute_address() + self.x
+        (self.fetch_absolute_address() + self.x) & ADDRESS_MASK
+    }
+
+    /// Fetches the operand as an absolute address and adds the X index to that address
+    /// Costs an extra cycle if the offset crosses a page boundary
+    pub fn fetch_absolute_x_address_with_penalty(&mut self) -> usize {
+        let base_addr = self.fetch_absolute_address();
+        let addr = (base_addr + self.x) & ADDRESS_MASK;
+        self.check_page_cross(base_addr, addr);
+        addr
     }
 
     /// Fetches the operand as an absolute address and adds the Y index to that address
     /// If this addition overflows, it will wrap around
     pub fn fetch_absolute_y_address(&mut self) -> usize {
-        self.fetch_absolute_address() + self.y
+        (self.fetch_absolute_address() + self.y) & ADDRESS_MASK
     }
 
-    /// TODO finish this documentation when I have more sleep
+    /// Returns the 16-bit address held at the zero-page address
+    /// The high byte of the address is read from the start of the zero-page if the pointer is at $FF
+    pub fn fetch_zp_pointer(&mut self, zp_addr: usize) -> usize {
+        let lo_byte = usize::from(self.fetch_byte_from_addr(zp_addr & 0xFF));
+        let hi_byte = usize::from(self.fetch_byte_from_addr((zp_addr + 1) & 0xFF));
+        (hi_byte << 8) + lo_byte
+    }
+
+    /// Fetches the 16-bit address held at the zero-page address in the operand plus the X index
     pub fn fetch_indirect_x_address(&mut self) -> usize {
         let indirect_addr = self.fetch_zero_page_x_address();
-        self.fetch_ab_addr_from_addr(indirect_addr)
+        self.fetch_zp_pointer(indirect_addr)
     }
 
+    /// Fetches the 16-bit address held at the zero-page address in the operand, then adds the Y index
     pub fn fetch_indirect_y_address(&mut self) -> usize {
         let indirect_addr = self.fetch_next_zp_addr();
-        let y = self.y;
+        (self.fetch_zp_pointer(indirect_addr) + self.y) & ADDRESS_MASK
+    }
 
-        self.fetch_ab_addr_from_addr(indirect_addr) + y
+    /// Fetches the 16-bit address held at the zero-page address in the operand
+    pub fn fetch_indirect_zp_address(&mut self) -> usize {
+        let indirect_addr = self.fetch_next_zp_addr();
+        self.fetch_zp_pointer(indirect_addr)
     }
 
     /// Moves the PC up by one and fetches that constant from memory
@@ -145,20 +190,23 @@ impl Memory {
 
     /// Fetches the byte of memory located at the zero-page address
     pub fn fetch_zero_page(&mut self) -> u8 {
-        self.mem[self.fetch_zero_page_address()]
+        let addr = self.fetch_zero_page_address();
+        self.fetch_byte_from_addr(addr)
     }
 
     /// Fetches the byte of memory located at the zero-page address and adds the X index register to it
     /// The result of this addition wraps
     pub fn fetch_zero_page_x(&mut self) -> u8 {
-        self.mem[self.fetch_zero_page_x_address()]
+        let addr = self.fetch_zero_page_x_address();
+        self.fetch_byte_from_addr(addr)
     }
 
     /// Fetches the byte of memory located at the zero-page address and adds the Y index register to it
     /// The result of this addition wraps
     /// Exactly the same as fetch_zero_page_x(), but for the Y index register. Used by fewer operations
     pub fn fetch_zero_page_y(&mut self) -> u8 {
-        self.mem[self.fetch_zero_page_y_address()]
+        let addr = self.fetch_zero_page_y_address();
+        self.fetch_byte_from_addr(addr)
     }
 
     pub fn fetch_relative(&mut self) -> i8 {
@@ -175,52 +223,83 @@ impl Memory {
     }
 
     /// Fetches the memory at the target location of an absolute address mode instruction
-    pub fn fetch_absolute(&mut self) -> u8 { self.mem[self.fetch_absolute_address()] }
+    pub fn fetch_absolute(&mut self) -> u8 {
+        let addr = self.fetch_absolute_address();
+        self.fetch_byte_from_addr(addr)
+    }
 
     /// Fetches the X index register to the absolute address, then fetches the memory from that
     /// address with the offset
-    pub fn fetch_absolute_x(&mut self) -> u8 { self.mem[self.fetch_absolute_x_address()] }
+    /// Costs an extra cycle if the offset crosses a page boundary
+    pub fn fetch_absolute_x(&mut self) -> u8 {
+        let base_addr = self.fetch_absolute_address();
+        let addr = (base_addr + self.x) & ADDRESS_MASK;
+        self.check_page_cross(base_addr, addr);
+        self.fetch_byte_from_addr(addr)
+    }
 
     /// Fetches the Y index register to the absolute address, then fetches the memory from that
     /// address with the offset
+    /// Costs an extra cycle if the offset crosses a page boundary
     pub fn fetch_absolute_y(&mut self) -> u8 {
-        self.mem[self.fetch_absolute_y_address()]
+        let base_addr = self.fetch_absolute_address();
+        let addr = (base_addr + self.y) & ADDRESS_MASK;
+        self.check_page_cross(base_addr, addr);
+        self.fetch_byte_from_addr(addr)
     }
 
-    pub fn fetch_indirect(&mut self) -> u16 {
-        let addr = self.fetch_next_nibble();
-        self.fetch_nibble_from_addr(usize::from(addr))
+    /// Fetches the memory held by the address given by the absolute address plus the X index
+    pub fn fetch_indirect_x(&mut self) -> u8 {
+        let addr = self.fetch_indirect_x_address();
+        self.fetch_byte_from_addr(addr)
     }
 
-    /// Fetches the memory held by the address given by the absolute address plus the X index
-    pub fn fetch_indirect_x(&mut self) -> u8 { self.mem[self.fetch_indirect_x_address()] }
+    /// Fetches the memory held at the address pointed to by the zero-page address
+    pub fn fetch_indirect_zp(&mut self) -> u8 {
+        let addr = self.fetch_indirect_zp_address();
+        self.fetch_byte_from_addr(addr)
+    }
 
     /// Fetches the memory held at the address pointed to by the given address plus the Y index
-    pub fn fetch_indirect_y(&mut self) -> u8 { self.mem[self.fetch_indirect_y_address()] }
+    /// Costs an extra cycle if the offset crosses a page boundary
+    pub fn fetch_indirect_y(&mut self) -> u8 {
+        let indirect_addr = self.fetch_next_zp_addr();
+        let base_addr = self.fetch_zp_pointer(indirect_addr);
+        let addr = (base_addr + self.y) & ADDRESS_MASK;
+        self.check_page_cross(base_addr, addr);
+        self.fetch_byte_from_addr(addr)
+    }
 
 
     // STACK INSTRUCTION
+    // The stack grows down from $01FF, and the stack pointer wraps around within page 1
     fn get_real_stack_addr(&self) -> usize {
-        self.stk + 0x0100
+        usize::from(self.stk) + 0x0100
     }
 
+    /// Writes the value at the stack pointer, then decrements the stack pointer
     pub fn push_on_stack(&mut self, value: u8) {
         self.set_byte_at_addr(self.get_real_stack_addr(), value);
-        self.stk -= 1;
+        self.stk = self.stk.wrapping_sub(1);
     }
 
+    /// Pushes the high byte then the low byte, so the value is little endian in memory
     pub fn push_nibble_on_stack(&mut self, value: u16) {
-        self.set_nibble_at_addr(self.get_real_stack_addr(), value);
-        self.stk -= 2;
+        let [lo_byte, hi_byte] = value.to_le_bytes();
+        self.push_on_stack(hi_byte);
+        self.push_on_stack(lo_byte);
     }
 
+    /// Increments the stack pointer, then reads the value at the stack pointer
     pub fn pop_from_stack(&mut self) -> u8 {
-        self.stk += 1;
+        self.stk = self.stk.wrapping_add(1);
         self.fetch_byte_from_addr(self.get_real_stack_addr())
     }
 
+    /// Pops the low byte then the high byte
     pub fn pop_nibble_from_stack(&mut self) -> u16 {
-        self.stk += 2;
-        self.fetch_nibble_from_addr(self.get_real_stack_addr())
+        let lo_byte = self.pop_from_stack();
+        let hi_byte = self.pop_from_stack();
+        u16::from_le_bytes([lo_byte, hi_byte])
     }
 }
@@ -1,4 +1,7 @@
-use super::ComputerState;
+use std::marker::PhantomData;
+
+use super::{ComputerState, CpuVariant};
+use super::bus::{Bus, FlatRam};
 
 use arithmetic::*;
 use bitwise::*;
@@ -10,6 +13,7 @@ use register_transfers::*;
 use stack::*;
 use jumps_calls::*;
 use comparison::*;
+use illegal::*;
 
 pub mod arithmetic;
 pub mod interrupt;
@@ -21,334 +25,841 @@ pub mod register_transfers;
 pub mod stack;
 pub mod jumps_calls;
 pub mod comparison;
+pub mod illegal;
 
 macro_rules! add_op {
-    ($fn_ptr:expr, $address:expr) => {{
-        ($fn_ptr, stringify!($fn_ptr), $address)
+    ($fn_ptr:expr, $address:expr, $cycles:expr) => {{
+        ($fn_ptr, stringify!($fn_ptr), $address, $cycles)
     }};
 }
 
-type MosOp = fn (&mut ComputerState);
+pub type MosOp<B = FlatRam> = fn (&mut ComputerState<B>);
 
-/// Invalid instruction
-fn inv(_: &mut ComputerState) {
-    panic!("Invalid Instruction!");
-}
+/// Placeholder for opcodes without an instruction
+/// Never executed, as every decode table is checked to be complete when it is built
+fn inv<B: Bus>(_: &mut ComputerState<B>) {}
 
 const INSTRUCTION_COUNT: usize = 256;
-const INSTRUCTION_LIST: [(MosOp, &str, usize); 151] = [
-    // LOAD / STORE OPERATIONS
-    // load accumulator
-    add_op!(lda_im, 0xA9),
-    add_op!(lda_zp, 0xA5),
-    add_op!(lda_zpx, 0xB5),
-    add_op!(lda_ab, 0xAD),
-    add_op!(lda_abx, 0xBD),
-    add_op!(lda_aby, 0xB9),
-    add_op!(lda_inx, 0xA1),
-    add_op!(lda_iny, 0xB1),
-
-    // load x register
-    add_op!(ldx_im, 0xA2),
-    add_op!(ldx_zp, 0xA6),
-    add_op!(ldx_zpy, 0xB6),
-    add_op!(ldx_ab, 0xAE),
-    add_op!(ldx_aby, 0xBE),
-
-    // load y register
-    add_op!(ldy_im, 0xA0),
-    add_op!(ldy_zp, 0xA4),
-    add_op!(ldy_zpx, 0xB4),
-    add_op!(ldy_ab, 0xAC),
-    add_op!(ldy_abx, 0xBC),
-
-    // store accumulator
-    add_op!(sta_zp, 0x85),
-    add_op!(sta_zpx, 0x95),
-    add_op!(sta_ab, 0x8D),
-    add_op!(sta_abx, 0x9D),
-    add_op!(sta_aby, 0x99),
-    add_op!(sta_inx, 0x81),
-    add_op!(sta_iny, 0x91),
-
-    // store x register
-    add_op!(stx_zp, 0x86),
-    add_op!(stx_zpy, 0x96),
-    add_op!(stx_ab, 0x8E),
-
-    // store y register
-    add_op!(sty_zp, 0x84),
-    add_op!(sty_zpx, 0x94),
-    add_op!(sty_ab, 0x8C),
-
-
-    // REGISTER TRANSFERS
-    // transfer accumulator to x register
-    add_op!(tax, 0xAA),
-
-    // transfer accumulator to y register
-    add_op!(tay, 0xA8),
-
-    // transfer x register to accumulator
-    add_op!(txa, 0x8A),
-
-    // transfer y register to accumulator
-    add_op!(tya, 0x98),
-
-
-    // STACK OPERATIONS
-    // transfer stack pointer to x
-    add_op!(tsx, 0xBA),
-
-    // transfer x to stack pointer
-    add_op!(txs, 0x9A),
-
-    // push accumulator onto stack
-    add_op!(pha, 0x48),
-
-    // push processor status onto stack
-    add_op!(php, 0x08),
-
-    // pull accumulator from stack
-    add_op!(pla, 0x68),
-
-    // pull processor status from stack
-    add_op!(plp, 0x28),
-
-
-    // LOGICAL
-    // logical and
-    add_op!(and_im, 0x29),
-    add_op!(and_zp, 0x25),
-    add_op!(and_zpx, 0x35),
-    add_op!(and_ab, 0x2D),
-    add_op!(and_abx, 0x3D),
-    add_op!(and_aby, 0x39),
-    add_op!(and_inx, 0x21),
-    add_op!(and_iny, 0x31),
-
-    // exclusive or
-    add_op!(eor_im, 0x49),
-    add_op!(eor_zp, 0x45),
-    add_op!(eor_zpx, 0x55),
-    add_op!(eor_ab, 0x4D),
-    add_op!(eor_abx, 0x5D),
-    add_op!(eor_aby, 0x59),
-    add_op!(eor_inx, 0x41),
-    add_op!(eor_iny, 0x51),
-
-    // logical inclusive or
-    add_op!(or_im, 0x09),
-    add_op!(or_zp, 0x05),
-    add_op!(or_zpx, 0x15),
-    add_op!(or_ab, 0x0D),
-    add_op!(or_abx, 0x1D),
-    add_op!(or_aby, 0x19),
-    add_op!(or_inx, 0x01),
-    add_op!(or_iny, 0x11),
-
-    // bit test
-    add_op!(bit_zp, 0x24),
-    add_op!(bit_ab, 0x2C),
-
-
-    // ARITHMETIC
-    // add with carry
-    add_op!(adc_im, 0x69),
-    add_op!(adc_zp, 0x65),
-    add_op!(adc_zpx, 0x75),
-    add_op!(adc_ab, 0x6D),
-    add_op!(adc_abx, 0x7D),
-    add_op!(adc_aby, 0x79),
-    add_op!(adc_inx, 0x61),
-    add_op!(adc_iny, 0x71),
-
-    // subtract with carry
-    add_op!(sbc_im, 0xE9),
-    add_op!(sbc_zp, 0xE5),
-    add_op!(sbc_zpx, 0xF5),
-    add_op!(sbc_ab, 0xED),
-    add_op!(sbc_abx, 0xFD),
-    add_op!(sbc_aby, 0xF9),
-    add_op!(sbc_inx, 0xE1),
-    add_op!(sbc_iny, 0xF1),
-
-    // compare accumulator
-    add_op!(cmp_im, 0xC9),
-    add_op!(cmp_zp, 0xC5),
-    add_op!(cmp_zpx, 0xD5),
-    add_op!(cmp_ab, 0xCD),
-    add_op!(cmp_abx, 0xDD),
-    add_op!(cmp_aby, 0xD9),
-    add_op!(cmp_inx, 0xC1),
-    add_op!(cmp_iny, 0xD1),
-
-    // compare x register
-    add_op!(cpx_im, 0xE0),
-    add_op!(cpx_zp, 0xE4),
-    add_op!(cpx_ab, 0xEC),
-
-    // compare y register
-    add_op!(cpy_im, 0xC0),
-    add_op!(cpy_zp, 0xC4),
-    add_op!(cpy_ab, 0xCC),
-
-
-    // INCREMENTS & DECREMENTS
-    // increment a memory location
-    add_op!(inc_zp, 0xE6),
-    add_op!(inc_zpx, 0xF6),
-    add_op!(inc_ab, 0xEE),
-    add_op!(inc_abx, 0xFE),
-
-    // increment x register
-    add_op!(inx, 0xE8),
-
-    // increment y register
-    add_op!(iny, 0xC8),
-
-    // decrement a memory location
-    add_op!(dec_zp, 0xC6),
-    add_op!(dec_zpx, 0xD6),
-    add_op!(dec_ab, 0xCE),
-    add_op!(dec_abx, 0xDE),
-
-    // decrement x register
-    add_op!(dex, 0xCA),
-
-    // decrement y register
-    add_op!(dey, 0x88),
-
-
-    // SHIFTS
-    // arithmetic shift left
-    add_op!(asl_acc, 0x0A),
-    add_op!(asl_zp, 0x06),
-    add_op!(asl_zpx, 0x16),
-    add_op!(asl_ab, 0x0E),
-    add_op!(asl_abx, 0x1A),
-
-    // logical shift right
-    add_op!(lsr_acc, 0x4A),
-    add_op!(lsr_zp, 0x46),
-    add_op!(lsr_zpx, 0x56),
-    add_op!(lsr_ab, 0x4E),
-    add_op!(lsr_abx, 0x5A),
-
-    // rotate left
-    add_op!(rol_acc, 0x2A),
-    add_op!(rol_zp, 0x26),
-    add_op!(rol_zpx, 0x36),
-    add_op!(rol_ab, 0x2E),
-    add_op!(rol_abx, 0x3E),
-
-    // rotate right
-    add_op!(ror_acc, 0x6A),
-    add_op!(ror_zp, 0x66),
-    add_op!(ror_zpx, 0x76),
-    add_op!(ror_ab, 0x6E),
-    add_op!(ror_abx, 0x7E),
-
-
-    // JUMPS & CALLS
-    // jump to another location
-    add_op!(jmp_ab, 0x4C),
-    add_op!(jmp_in, 0x6C),
-
-    // jump to a subroutine
-    add_op!(jsr, 0x20),
-
-    // return from subroutine
-    add_op!(rts, 0x60),
-
-    // BRANCHES
-    // branch if carry flag clear
-    add_op!(bcc, 0x90),
-
-    // branch if carry flag set
-    add_op!(bcs, 0xB0),
-
-    // branch if zero flag set
-    add_op!(beq, 0xF0),
-
-    // branch if negative flag set
-    add_op!(bmi, 0x30),
-
-    // branch if zero flag clear
-    add_op!(bne, 0xD0),
-
-    // branch if negative flag clear
-    add_op!(bpl, 0x10),
-
-    // branch if overflow flag clear
-    add_op!(bvs, 0x70),
-
-    // branch if overflow flag set
-    add_op!(bvc, 0x50),
-
-
-    // STATUS FLAG CHANGES
-    // clear carry flag
-    add_op!(clc, 0x18),
-
-    // clear decimal mode flag
-    add_op!(cld, 0xD8),
-
-    // clear interrupt disable flag
-    add_op!(cli, 0x58),
+/// Each entry holds the operation, its name, its opcode and its base cycle count
+type InstructionEntry<B> = (MosOp<B>, &'static str, usize, u8);
+/// Each entry holds the operation, its name and its base cycle count, indexed by opcode
+type InstructionTable<B> = [(MosOp<B>, &'static str, u8); INSTRUCTION_COUNT];
+
+/// Instruction lists and decode tables for a processor connected to the given bus
+/// Every operation is generic over the bus, so each bus gets its own tables
+struct Instructions<B: Bus>(PhantomData<B>);
+
+impl<B: Bus> Instructions<B> {
+    /// Documented instructions shared by every variant
+    const INSTRUCTION_LIST: [InstructionEntry<B>; 151] = [
+        // LOAD / STORE OPERATIONS
+        // load accumulator
+        add_op!(lda_im, 0xA9, 2),
+        add_op!(lda_zp, 0xA5, 3),
+        add_op!(lda_zpx, 0xB5, 4),
+        add_op!(lda_ab, 0xAD, 4),
+        add_op!(lda_abx, 0xBD, 4),
+        add_op!(lda_aby, 0xB9, 4),
+        add_op!(lda_inx, 0xA1, 6),
+        add_op!(lda_iny, 0xB1, 5),
+
+        // load x register
+        add_op!(ldx_im, 0xA2, 2),
+        add_op!(ldx_zp, 0xA6, 3),
+        add_op!(ldx_zpy, 0xB6, 4),
+        add_op!(ldx_ab, 0xAE, 4),
+        add_op!(ldx_aby, 0xBE, 4),
+
+        // load y register
+        add_op!(ldy_im, 0xA0, 2),
+        add_op!(ldy_zp, 0xA4, 3),
+        add_op!(ldy_zpx, 0xB4, 4),
+        add_op!(ldy_ab, 0xAC, 4),
+        add_op!(ldy_abx, 0xBC, 4),
+
+        // store accumulator
+        add_op!(sta_zp, 0x85, 3),
+        add_op!(sta_zpx, 0x95, 4),
+        add_op!(sta_ab, 0x8D, 4),
+        add_op!(sta_abx, 0x9D, 5),
+        add_op!(sta_aby, 0x99, 5),
+        add_op!(sta_inx, 0x81, 6),
+        add_op!(sta_iny, 0x91, 6),
+
+        // store x register
+        add_op!(stx_zp, 0x86, 3),
+        add_op!(stx_zpy, 0x96, 4),
+        add_op!(stx_ab, 0x8E, 4),
+
+        // store y register
+        add_op!(sty_zp, 0x84, 3),
+        add_op!(sty_zpx, 0x94, 4),
+        add_op!(sty_ab, 0x8C, 4),
+
+
+        // REGISTER TRANSFERS
+        // transfer accumulator to x register
+        add_op!(tax, 0xAA, 2),
+
+        // transfer accumulator to y register
+        add_op!(tay, 0xA8, 2),
+
+        // transfer x register to accumulator
+        add_op!(txa, 0x8A, 2),
+
+        // transfer y register to accumulator
+        add_op!(tya, 0x98, 2),
+
+
+        // STACK OPERATIONS
+        // transfer stack pointer to x
+        add_op!(tsx, 0xBA, 2),
+
+        // transfer x to stack pointer
+        add_op!(txs, 0x9A, 2),
+
+        // push accumulator onto stack
+        add_op!(pha, 0x48, 3),
+
+        // push processor status onto stack
+        add_op!(php, 0x08, 3),
+
+        // pull accumulator from stack
+        add_op!(pla, 0x68, 4),
+
+        // pull processor status from stack
+        add_op!(plp, 0x28, 4),
+
+
+        // LOGICAL
+        // logical and
+        add_op!(and_im, 0x29, 2),
+        add_op!(and_zp, 0x25, 3),
+        add_op!(and_zpx, 0x35, 4),
+        add_op!(and_ab, 0x2D, 4),
+        add_op!(and_abx, 0x3D, 4),
+        add_op!(and_aby, 0x39, 4),
+        add_op!(and_inx, 0x21, 6),
+        add_op!(and_iny, 0x31, 5),
+
+        // exclusive or
+        add_op!(eor_im, 0x49, 2),
+        add_op!(eor_zp, 0x45, 3),
+        add_op!(eor_zpx, 0x55, 4),
+        add_op!(eor_ab, 0x4D, 4),
+        add_op!(eor_abx, 0x5D, 4),
+        add_op!(eor_aby, 0x59, 4),
+        add_op!(eor_inx, 0x41, 6),
+        add_op!(eor_iny, 0x51, 5),
+
+        // logical inclusive or
+        add_op!(or_im, 0x09, 2),
+        add_op!(or_zp, 0x05, 3),
+        add_op!(or_zpx, 0x15, 4),
+        add_op!(or_ab, 0x0D, 4),
+        add_op!(or_abx, 0x1D, 4),
+        add_op!(or_aby, 0x19, 4),
+        add_op!(or_inx, 0x01, 6),
+        add_op!(or_iny, 0x11, 5),
+
+        // bit test
+        add_op!(bit_zp, 0x24, 3),
+        add_op!(bit_ab, 0x2C, 4),
+
+
+        // ARITHMETIC
+        // add with carry
+        add_op!(adc_im, 0x69, 2),
+        add_op!(adc_zp, 0x65, 3),
+        add_op!(adc_zpx, 0x75, 4),
+        add_op!(adc_ab, 0x6D, 4),
+        add_op!(adc_abx, 0x7D, 4),
+        add_op!(adc_aby, 0x79, 4),
+        add_op!(adc_inx, 0x61, 6),
+        add_op!(adc_iny, 0x71, 5),
+
+        // subtract with carry
+        add_op!(sbc_im, 0xE9, 2),
+        add_op!(sbc_zp, 0xE5, 3),
+        add_op!(sbc_zpx, 0xF5, 4),
+        add_op!(sbc_ab, 0xED, 4),
+        add_op!(sbc_abx, 0xFD, 4),
+        add_op!(sbc_aby, 0xF9, 4),
+        add_op!(sbc_inx, 0xE1, 6),
+        add_op!(sbc_iny, 0xF1, 5),
+
+        // compare accumulator
+        add_op!(cmp_im, 0xC9, 2),
+        add_op!(cmp_zp, 0xC5, 3),
+        add_op!(cmp_zpx, 0xD5, 4),
+        add_op!(cmp_ab, 0xCD, 4),
+        add_op!(cmp_abx, 0xDD, 4),
+        add_op!(cmp_aby, 0xD9, 4),
+        add_op!(cmp_inx, 0xC1, 6),
+        add_op!(cmp_iny, 0xD1, 5),
+
+        // compare x register
+        add_op!(cpx_im, 0xE0, 2),
+        add_op!(cpx_zp, 0xE4, 3),
+        add_op!(cpx_ab, 0xEC, 4),
+
+        // compare y register
+        add_op!(cpy_im, 0xC0, 2),
+        add_op!(cpy_zp, 0xC4, 3),
+        add_op!(cpy_ab, 0xCC, 4),
+
+
+        // INCREMENTS & DECREMENTS
+        // increment a memory location
+        add_op!(inc_zp, 0xE6, 5),
+        add_op!(inc_zpx, 0xF6, 6),
+        add_op!(inc_ab, 0xEE, 6),
+        add_op!(inc_abx, 0xFE, 7),
+
+        // increment x register
+        add_op!(inx, 0xE8, 2),
+
+        // increment y register
+        add_op!(iny, 0xC8, 2),
+
+        // decrement a memory location
+        add_op!(dec_zp, 0xC6, 5),
+        add_op!(dec_zpx, 0xD6, 6),
+        add_op!(dec_ab, 0xCE, 6),
+        add_op!(dec_abx, 0xDE, 7),
+
+        // decrement x register
+        add_op!(dex, 0xCA, 2),
+
+        // decrement y register
+        add_op!(dey, 0x88, 2),
+
+
+        // SHIFTS
+        // arithmetic shift left
+        add_op!(asl_acc, 0x0A, 2),
+        add_op!(asl_zp, 0x06, 5),
+        add_op!(asl_zpx, 0x16, 6),
+        add_op!(asl_ab, 0x0E, 6),
+        add_op!(asl_abx, 0x1E, 7),
+
+        // logical shift right
+        add_op!(lsr_acc, 0x4A, 2),
+        add_op!(lsr_zp, 0x46, 5),
+        add_op!(lsr_zpx, 0x56, 6),
+        add_op!(lsr_ab, 0x4E, 6),
+        add_op!(lsr_abx, 0x5E, 7),
+
+        // rotate left
+        add_op!(rol_acc, 0x2A, 2),
+        add_op!(rol_zp, 0x26, 5),
+        add_op!(rol_zpx, 0x36, 6),
+        add_op!(rol_ab, 0x2E, 6),
+        add_op!(rol_abx, 0x3E, 7),
+
+        // rotate right
+        add_op!(ror_acc, 0x6A, 2),
+        add_op!(ror_zp, 0x66, 5),
+        add_op!(ror_zpx, 0x76, 6),
+        add_op!(ror_ab, 0x6E, 6),
+        add_op!(ror_abx, 0x7E, 7),
+
+
+        // JUMPS & CALLS
+        // jump to another location
+        add_op!(jmp_ab, 0x4C, 3),
+        add_op!(jmp_in, 0x6C, 5),
+
+        // jump to a subroutine
+        add_op!(jsr, 0x20, 6),
+
+        // return from subroutine
+        add_op!(rts, 0x60, 6),
+
+        // BRANCHES
+        // branch if carry flag clear
+        add_op!(bcc, 0x90, 2),
+
+        // branch if carry flag set
+        add_op!(bcs, 0xB0, 2),
+
+        // branch if zero flag set
+        add_op!(beq, 0xF0, 2),
+
+        // branch if negative flag set
+        add_op!(bmi, 0x30, 2),
+
+        // branch if zero flag clear
+        add_op!(bne, 0xD0, 2),
+
+        // branch if negative flag clear
+        add_op!(bpl, 0x10, 2),
+
+        // branch if overflow flag clear
+        add_op!(bvs, 0x70, 2),
+
+        // branch if overflow flag set
+        add_op!(bvc, 0x50, 2),
+
+
+        // STATUS FLAG CHANGES
+        // clear carry flag
+        add_op!(clc, 0x18, 2),
+
+        // clear decimal mode flag
+        add_op!(cld, 0xD8, 2),
+
+        // clear interrupt disable flag
+        add_op!(cli, 0x58, 2),
+
+        // clear overflow flag
+        add_op!(clv, 0xB8, 2),
+
+        // set carry flag
+        add_op!(sec, 0x38, 2),
+
+        // set decimal mode flag
+        add_op!(sed, 0xF8, 2),
+
+        // set interrupt disable flag
+        add_op!(sei, 0x78, 2),
+
+
+        // SYSTEM FUNCTIONS
+        // force an interrupt
+        add_op!(brk, 0x00, 7),
+
+        // no operation
+        add_op!(nop, 0xEA, 2),
+
+        // return from interrupt
+        add_op!(rti, 0x40, 6),
+
+
+    ];
+
+    /// Undocumented instructions of the NMOS 6502
+    const ILLEGAL_INSTRUCTION_LIST: [InstructionEntry<B>; 105] = [
+        // shift left then or with accumulator
+        add_op!(slo_zp, 0x07, 5),
+        add_op!(slo_zpx, 0x17, 6),
+        add_op!(slo_ab, 0x0F, 6),
+        add_op!(slo_abx, 0x1F, 7),
+        add_op!(slo_aby, 0x1B, 7),
+        add_op!(slo_inx, 0x03, 8),
+        add_op!(slo_iny, 0x13, 8),
+
+        // rotate left then and with accumulator
+        add_op!(rla_zp, 0x27, 5),
+        add_op!(rla_zpx, 0x37, 6),
+        add_op!(rla_ab, 0x2F, 6),
+        add_op!(rla_abx, 0x3F, 7),
+        add_op!(rla_aby, 0x3B, 7),
+        add_op!(rla_inx, 0x23, 8),
+        add_op!(rla_iny, 0x33, 8),
+
+        // shift right then exclusive or with accumulator
+        add_op!(sre_zp, 0x47, 5),
+        add_op!(sre_zpx, 0x57, 6),
+        add_op!(sre_ab, 0x4F, 6),
+        add_op!(sre_abx, 0x5F, 7),
+        add_op!(sre_aby, 0x5B, 7),
+        add_op!(sre_inx, 0x43, 8),
+        add_op!(sre_iny, 0x53, 8),
+
+        // rotate right then add to accumulator
+        add_op!(rra_zp, 0x67, 5),
+        add_op!(rra_zpx, 0x77, 6),
+        add_op!(rra_ab, 0x6F, 6),
+        add_op!(rra_abx, 0x7F, 7),
+        add_op!(rra_aby, 0x7B, 7),
+        add_op!(rra_inx, 0x63, 8),
+        add_op!(rra_iny, 0x73, 8),
+
+        // decrement then compare with accumulator
+        add_op!(dcp_zp, 0xC7, 5),
+        add_op!(dcp_zpx, 0xD7, 6),
+        add_op!(dcp_ab, 0xCF, 6),
+        add_op!(dcp_abx, 0xDF, 7),
+        add_op!(dcp_aby, 0xDB, 7),
+        add_op!(dcp_inx, 0xC3, 8),
+        add_op!(dcp_iny, 0xD3, 8),
+
+        // increment then subtract from accumulator
+        add_op!(isc_zp, 0xE7, 5),
+        add_op!(isc_zpx, 0xF7, 6),
+        add_op!(isc_ab, 0xEF, 6),
+        add_op!(isc_abx, 0xFF, 7),
+        add_op!(isc_aby, 0xFB, 7),
+        add_op!(isc_inx, 0xE3, 8),
+        add_op!(isc_iny, 0xF3, 8),
+
+        // load accumulator and x register
+        add_op!(lax_zp, 0xA7, 3),
+        add_op!(lax_zpy, 0xB7, 4),
+        add_op!(lax_ab, 0xAF, 4),
+        add_op!(lax_aby, 0xBF, 4),
+        add_op!(lax_inx, 0xA3, 6),
+        add_op!(lax_iny, 0xB3, 5),
+
+        // store accumulator and x register
+        add_op!(sax_zp, 0x87, 3),
+        add_op!(sax_zpy, 0x97, 4),
+        add_op!(sax_ab, 0x8F, 4),
+        add_op!(sax_inx, 0x83, 6),
+
+        // and then copy negative flag into carry
+        add_op!(anc_im, 0x0B, 2),
+        add_op!(anc_im, 0x2B, 2),
+
+        // and then shift right
+        add_op!(alr_im, 0x4B, 2),
+
+        // and then rotate right
+        add_op!(arr_im, 0x6B, 2),
+
+        // and x register with accumulator then subtract
+        add_op!(sbx_im, 0xCB, 2),
+
+        // subtract with carry
+        add_op!(usbc_im, 0xEB, 2),
+
+        // and memory with stack pointer
+        add_op!(las_aby, 0xBB, 4),
+
+        // unstable operations
+        add_op!(ane_im, 0x8B, 2),
+        add_op!(lxa_im, 0xAB, 2),
+        add_op!(sha_aby, 0x9F, 5),
+        add_op!(sha_iny, 0x93, 6),
+        add_op!(shx_aby, 0x9E, 5),
+        add_op!(shy_abx, 0x9C, 5),
+        add_op!(tas_aby, 0x9B, 5),
+
+        // no operation
+        add_op!(nop_imp, 0x1A, 2),
+        add_op!(nop_imp, 0x3A, 2),
+        add_op!(nop_imp, 0x5A, 2),
+        add_op!(nop_imp, 0x7A, 2),
+        add_op!(nop_imp, 0xDA, 2),
+        add_op!(nop_imp, 0xFA, 2),
+        add_op!(nop_im, 0x80, 2),
+        add_op!(nop_im, 0x82, 2),
+        add_op!(nop_im, 0x89, 2),
+        add_op!(nop_im, 0xC2, 2),
+        add_op!(nop_im, 0xE2, 2),
+        add_op!(nop_zp, 0x04, 3),
+        add_op!(nop_zp, 0x44, 3),
+        add_op!(nop_zp, 0x64, 3),
+        add_op!(nop_zpx, 0x14, 4),
+        add_op!(nop_zpx, 0x34, 4),
+        add_op!(nop_zpx, 0x54, 4),
+        add_op!(nop_zpx, 0x74, 4),
+        add_op!(nop_zpx, 0xD4, 4),
+        add_op!(nop_zpx, 0xF4, 4),
+        add_op!(nop_ab, 0x0C, 4),
+        add_op!(nop_abx, 0x1C, 4),
+        add_op!(nop_abx, 0x3C, 4),
+        add_op!(nop_abx, 0x5C, 4),
+        add_op!(nop_abx, 0x7C, 4),
+        add_op!(nop_abx, 0xDC, 4),
+        add_op!(nop_abx, 0xFC, 4),
+
+        // halt the processor
+        add_op!(jam, 0x02, 2),
+        add_op!(jam, 0x12, 2),
+        add_op!(jam, 0x22, 2),
+        add_op!(jam, 0x32, 2),
+        add_op!(jam, 0x42, 2),
+        add_op!(jam, 0x52, 2),
+        add_op!(jam, 0x62, 2),
+        add_op!(jam, 0x72, 2),
+        add_op!(jam, 0x92, 2),
+        add_op!(jam, 0xB2, 2),
+        add_op!(jam, 0xD2, 2),
+        add_op!(jam, 0xF2, 2),
+
+    ];
+
+    /// Instructions added or changed by the WDC 65C02
+    const CMOS_INSTRUCTION_LIST: [InstructionEntry<B>; 66] = [
+        // LOAD / STORE OPERATIONS
+        // load accumulator
+        add_op!(lda_izp, 0xB2, 5),
+
+        // store accumulator
+        add_op!(sta_izp, 0x92, 5),
+
+        // store zero
+        add_op!(stz_zp, 0x64, 3),
+        add_op!(stz_zpx, 0x74, 4),
+        add_op!(stz_ab, 0x9C, 4),
+        add_op!(stz_abx, 0x9E, 5),
+
+
+        // STACK OPERATIONS
+        // push x register onto stack
+        add_op!(phx, 0xDA, 3),
+
+        // push y register onto stack
+        add_op!(phy, 0x5A, 3),
+
+        // pull x register from stack
+        add_op!(plx, 0xFA, 4),
+
+        // pull y register from stack
+        add_op!(ply, 0x7A, 4),
+
+
+        // LOGICAL
+        // logical and
+        add_op!(and_izp, 0x32, 5),
+
+        // exclusive or
+        add_op!(eor_izp, 0x52, 5),
+
+        // logical inclusive or
+        add_op!(or_izp, 0x12, 5),
+
+        // bit test
+        add_op!(bit_im, 0x89, 2),
+        add_op!(bit_zpx, 0x34, 4),
+        add_op!(bit_abx, 0x3C, 4),
+
+        // test and reset bits
+        add_op!(trb_zp, 0x14, 5),
+        add_op!(trb_ab, 0x1C, 6),
+
+        // test and set bits
+        add_op!(tsb_zp, 0x04, 5),
+        add_op!(tsb_ab, 0x0C, 6),
+
+        // reset memory bit
+        add_op!(rmb0, 0x07, 5),
+        add_op!(rmb1, 0x17, 5),
+        add_op!(rmb2, 0x27, 5),
+        add_op!(rmb3, 0x37, 5),
+        add_op!(rmb4, 0x47, 5),
+        add_op!(rmb5, 0x57, 5),
+        add_op!(rmb6, 0x67, 5),
+        add_op!(rmb7, 0x77, 5),
+
+        // set memory bit
+        add_op!(smb0, 0x87, 5),
+        add_op!(smb1, 0x97, 5),
+        add_op!(smb2, 0xA7, 5),
+        add_op!(smb3, 0xB7, 5),
+        add_op!(smb4, 0xC7, 5),
+        add_op!(smb5, 0xD7, 5),
+        add_op!(smb6, 0xE7, 5),
+        add_op!(smb7, 0xF7, 5),
+
+
+        // ARITHMETIC
+        // add with carry
+        add_op!(adc_izp, 0x72, 5),
+
+        // subtract with carry
+        add_op!(sbc_izp, 0xF2, 5),
+
+        // compare accumulator
+        add_op!(cmp_izp, 0xD2, 5),
+
+
+        // INCREMENTS & DECREMENTS
+        // increment accumulator
+        add_op!(inc_acc, 0x1A, 2),
+
+        // decrement accumulator
+        add_op!(dec_acc, 0x3A, 2),
+
+
+        // SHIFTS
+        // arithmetic shift left
+        add_op!(asl_abx, 0x1E, 6),
+
+        // logical shift right
+        add_op!(lsr_abx, 0x5E, 6),
+
+        // rotate left
+        add_op!(rol_abx, 0x3E, 6),
+
+        // rotate right
+        add_op!(ror_abx, 0x7E, 6),
+
+
+        // JUMPS & CALLS
+        // jump to another location
+        add_op!(jmp_in, 0x6C, 6),
+        add_op!(jmp_inx, 0x7C, 6),
+
+
+        // BRANCHES
+        // branch always
+        add_op!(bra, 0x80, 2),
+
+        // branch if memory bit reset
+        add_op!(bbr0, 0x0F, 5),
+        add_op!(bbr1, 0x1F, 5),
+        add_op!(bbr2, 0x2F, 5),
+        add_op!(bbr3, 0x3F, 5),
+        add_op!(bbr4, 0x4F, 5),
+        add_op!(bbr5, 0x5F, 5),
+        add_op!(bbr6, 0x6F, 5),
+        add_op!(bbr7, 0x7F, 5),
+
+        // branch if memory bit set
+        add_op!(bbs0, 0x8F, 5),
+        add_op!(bbs1, 0x9F, 5),
+        add_op!(bbs2, 0xAF, 5),
+        add_op!(bbs3, 0xBF, 5),
+        add_op!(bbs4, 0xCF, 5),
+        add_op!(bbs5, 0xDF, 5),
+        add_op!(bbs6, 0xEF, 5),
+        add_op!(bbs7, 0xFF, 5),
+
+
+        // SYSTEM FUNCTIONS
+        // wait for interrupt
+        add_op!(wai, 0xCB, 3),
+
+        // stop the processor
+        add_op!(stp, 0xDB, 3),
+    ];
+
+    /// Undefined opcodes of the WDC 65C02, which are NOPs of various lengths and timings
+    const CMOS_UNDEFINED_INSTRUCTION_LIST: [InstructionEntry<B>; 44] = [
+        add_op!(nop_imp, 0x03, 1),
+        add_op!(nop_imp, 0x13, 1),
+        add_op!(nop_imp, 0x23, 1),
+        add_op!(nop_imp, 0x33, 1),
+        add_op!(nop_imp, 0x43, 1),
+        add_op!(nop_imp, 0x53, 1),
+        add_op!(nop_imp, 0x63, 1),
+        add_op!(nop_imp, 0x73, 1),
+        add_op!(nop_imp, 0x83, 1),
+        add_op!(nop_imp, 0x93, 1),
+        add_op!(nop_imp, 0xA3, 1),
+        add_op!(nop_imp, 0xB3, 1),
+        add_op!(nop_imp, 0xC3, 1),
+        add_op!(nop_imp, 0xD3, 1),
+        add_op!(nop_imp, 0xE3, 1),
+        add_op!(nop_imp, 0xF3, 1),
+        add_op!(nop_imp, 0x0B, 1),
+        add_op!(nop_imp, 0x1B, 1),
+        add_op!(nop_imp, 0x2B, 1),
+        add_op!(nop_imp, 0x3B, 1),
+        add_op!(nop_imp, 0x4B, 1),
+        add_op!(nop_imp, 0x5B, 1),
+        add_op!(nop_imp, 0x6B, 1),
+        add_op!(nop_imp, 0x7B, 1),
+        add_op!(nop_imp, 0x8B, 1),
+        add_op!(nop_imp, 0x9B, 1),
+        add_op!(nop_imp, 0xAB, 1),
+        add_op!(nop_imp, 0xBB, 1),
+        add_op!(nop_imp, 0xEB, 1),
+        add_op!(nop_imp, 0xFB, 1),
+        add_op!(nop_im, 0x02, 2),
+        add_op!(nop_im, 0x22, 2),
+        add_op!(nop_im, 0x42, 2),
+        add_op!(nop_im, 0x62, 2),
+        add_op!(nop_im, 0x82, 2),
+        add_op!(nop_im, 0xC2, 2),
+        add_op!(nop_im, 0xE2, 2),
+        add_op!(nop_zp, 0x44, 3),
+        add_op!(nop_zpx, 0x54, 4),
+        add_op!(nop_zpx, 0xD4, 4),
+        add_op!(nop_zpx, 0xF4, 4),
+        add_op!(nop_ab, 0x5C, 8),
+        add_op!(nop_ab, 0xDC, 4),
+        add_op!(nop_ab, 0xFC, 4),
+    ];
+
+    const INSTRUCTION_DATA_TABLE: InstructionTable<B> =
+        build_instruction_table(&[&Self::INSTRUCTION_LIST, &Self::ILLEGAL_INSTRUCTION_LIST]);
+    const CMOS_INSTRUCTION_DATA_TABLE: InstructionTable<B> = build_instruction_table(&[
+        &Self::INSTRUCTION_LIST,
+        &Self::CMOS_INSTRUCTION_LIST,
+        &Self::CMOS_UNDEFINED_INSTRUCTION_LIST
+    ]);
+}
 
-    // clear overflow flag
-    add_op!(clv, 0xB8),
+/// Builds a decode table from lists of instructions
+/// Entries in later lists replace entries with the same opcode in earlier lists
+/// Fails to compile if any opcode is left without an instruction
+const fn build_instruction_table<B: Bus>(lists: &[&[InstructionEntry<B>]]) -> InstructionTable<B> {
+    let mut tmp_data_table: InstructionTable<B> = [(inv, "INVALID", 0); INSTRUCTION_COUNT];
+    let mut filled = [false; INSTRUCTION_COUNT];
+
+    let mut list = 0;
+    while list < lists.len() {
+        let mut i = 0;
+        while i < lists[list].len() {
+            let func= lists[list][i].0;
+            let name= lists[list][i].1;
+            let opcode= lists[list][i].2;
+            let cycles= lists[list][i].3;
+            tmp_data_table[opcode] = (func, name, cycles);
+            filled[opcode] = true;
+            i += 1;
+        }
+        list += 1;
+    }
 
-    // set carry flag
-    add_op!(sec, 0x38),
+    let mut opcode = 0;
+    while opcode < INSTRUCTION_COUNT {
+        assert!(filled[opcode], "Every opcode must decode to an instruction");
+        opcode += 1;
+    }
 
-    // set decimal mode flag
-    add_op!(sed, 0xF8),
+    tmp_data_table
+}
 
-    // set interrupt disable flag
-    add_op!(sei, 0x78),
+/// Builds a table marking which opcodes are in the list of illegal instructions
+const fn build_illegal_table<B: Bus>(illegal_list: &[InstructionEntry<B>]) -> [bool; INSTRUCTION_COUNT] {
+    let mut tmp_illegal_table = [false; INSTRUCTION_COUNT];
+    let mut i = 0;
+    while i < illegal_list.len() {
+        tmp_illegal_table[illegal_list[i].2] = true;
+        i += 1;
+    }
+    tmp_illegal_table
+}
 
+// Whether an opcode is illegal does not depend on the bus
+const ILLEGAL_TABLE: [bool; INSTRUCTION_COUNT] =
+    build_illegal_table(&Instructions::<FlatRam>::ILLEGAL_INSTRUCTION_LIST);
+const CMOS_ILLEGAL_TABLE: [bool; INSTRUCTION_COUNT] =
+    build_illegal_table(&Instructions::<FlatRam>::CMOS_UNDEFINED_INSTRUCTION_LIST);
+
+/// Returns the decode table for the given variant
+const fn instruction_table<'a, B: Bus>(variant: CpuVariant) -> &'a InstructionTable<B> {
+    match variant {
+        CpuVariant::Nmos6502 => &Instructions::<B>::INSTRUCTION_DATA_TABLE,
+        CpuVariant::Wdc65C02 => &Instructions::<B>::CMOS_INSTRUCTION_DATA_TABLE,
+    }
+}
 
-    // SYSTEM FUNCTIONS
-    // force an interrupt
-    add_op!(brk, 0x00),
 
-    // no operation
-    add_op!(nop, 0xEA),
+pub const fn decode<B: Bus>(variant: CpuVariant, opcode: u8) -> MosOp<B> {
+    instruction_table::<B>(variant)[opcode as usize].0
+}
 
-    // return from interrupt
-    add_op!(rti, 0x40),
+// Names and cycle counts do not depend on the bus, so they are read from the default tables
+pub const fn decode_operation_name(variant: CpuVariant, opcode: u8) -> &'static str {
+    instruction_table::<FlatRam>(variant)[opcode as usize].1
+}
 
-];
+/// Returns the number of cycles the operation takes before any page crossing or branch penalties
+pub const fn decode_cycles(variant: CpuVariant, opcode: u8) -> u8 {
+    instruction_table::<FlatRam>(variant)[opcode as usize].2
+}
 
-const INSTRUCTION_DATA_TABLE: [(MosOp, &str); INSTRUCTION_COUNT] = {
-    let mut tmp_data_table: [(MosOp, &str); INSTRUCTION_COUNT] =
-        [(inv, "INVALID"); INSTRUCTION_COUNT];
+/// How an instruction finds its operand, which determines its length and how it is written
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AddressingMode {
+    Implied,
+    Accumulator,
+    Immediate,
+    ZeroPage,
+    ZeroPageX,
+    ZeroPageY,
+    Absolute,
+    AbsoluteX,
+    AbsoluteY,
+    /// JMP ($XXXX)
+    Indirect,
+    /// ($XX,X)
+    IndirectX,
+    /// ($XX),Y
+    IndirectY,
+    /// ($XX), 65C02 only
+    ZeroPageIndirect,
+    /// JMP ($XXXX,X), 65C02 only
+    AbsoluteIndirectX,
+    /// Branches, where the operand is an offset from the next instruction
+    Relative,
+    /// BBR and BBS, which test a zero page address and then branch, 65C02 only
+    ZeroPageRelative,
+}
 
-    let mut i = 0;
-    while i < INSTRUCTION_LIST.len() {
-        let func= INSTRUCTION_LIST[i].0;
-        let name= INSTRUCTION_LIST[i].1;
-        let opcode= INSTRUCTION_LIST[i].2;
-        tmp_data_table[opcode] = (func, name);
-        i += 1;
+impl AddressingMode {
+    /// Returns the number of bytes after the opcode
+    pub const fn operand_len(self) -> usize {
+        match self {
+            AddressingMode::Implied | AddressingMode::Accumulator => 0,
+            AddressingMode::Immediate | AddressingMode::ZeroPage | AddressingMode::ZeroPageX |
+            AddressingMode::ZeroPageY | AddressingMode::IndirectX | AddressingMode::IndirectY |
+            AddressingMode::ZeroPageIndirect | AddressingMode::Relative => 1,
+            AddressingMode::Absolute | AddressingMode::AbsoluteX | AddressingMode::AbsoluteY |
+            AddressingMode::Indirect | AddressingMode::AbsoluteIndirectX |
+            AddressingMode::ZeroPageRelative => 2,
+        }
     }
+}
 
-    tmp_data_table
-};
+/// Returns the mnemonic of the opcode in lower case, such as "lda"
+/// Operation names are the mnemonic followed by the addressing mode, so the mnemonic is read from them
+pub fn decode_mnemonic(variant: CpuVariant, opcode: u8) -> &'static str {
+    let name = decode_operation_name(variant, opcode);
+    match name.split_once('_').map_or(name, |(mnemonic, _)| mnemonic) {
+        "or" => "ora",
+        "usbc" => "sbc",
+        mnemonic => mnemonic,
+    }
+}
 
+/// Returns the addressing mode of the opcode
+/// Read from the suffix of the operation name, or from the mnemonic for operations without one
+pub fn decode_addressing_mode(variant: CpuVariant, opcode: u8) -> AddressingMode {
+    let name = decode_operation_name(variant, opcode);
+    let (mnemonic, suffix) = name.split_once('_').unwrap_or((name, ""));
+    match suffix {
+        "im" => AddressingMode::Immediate,
+        "zp" => AddressingMode::ZeroPage,
+        "zpx" => AddressingMode::ZeroPageX,
+        "zpy" => AddressingMode::ZeroPageY,
+        "ab" => AddressingMode::Absolute,
+        "abx" => AddressingMode::AbsoluteX,
+        "aby" => AddressingMode::AbsoluteY,
+        "in" => AddressingMode::Indirect,
+        // The indexed indirect JMP indexes an absolute address rather than a zero page one
+        "inx" if mnemonic == "jmp" => AddressingMode::AbsoluteIndirectX,
+        "inx" => AddressingMode::IndirectX,
+        "iny" => AddressingMode::IndirectY,
+        "izp" => AddressingMode::ZeroPageIndirect,
+        "acc" => AddressingMode::Accumulator,
+        _ => match mnemonic {
+            "jsr" => AddressingMode::Absolute,
+            "bcc" | "bcs" | "beq" | "bmi" | "bne" | "bpl" | "bvc" | "bvs" | "bra" => AddressingMode::Relative,
+            _ if mnemonic.starts_with("rmb") || mnemonic.starts_with("smb") => AddressingMode::ZeroPage,
+            _ if mnemonic.starts_with("bbr") || mnemonic.starts_with("bbs") => AddressingMode::ZeroPageRelative,
+            _ => AddressingMode::Implied,
+        },
+    }
+}
 
-pub const fn decode(opcode: u8) -> MosOp {
-    INSTRUCTION_DATA_TABLE[opcode as usize].0
+/// Returns the number of bytes taken by the instruction, including the opcode
+pub fn instruction_len(variant: CpuVariant, opcode: u8) -> usize {
+    1 + decode_addressing_mode(variant, opcode).operand_len()
 }
 
-pub const fn decode_operation_name(opcode: u8) -> &'static str {
-    INSTRUCTION_DATA_TABLE[opcode as usize].1
+/// True if the opcode is undocumented or undefined on the given variant
+pub const fn is_illegal(variant: CpuVariant, opcode: u8) -> bool {
+    match variant {
+        CpuVariant::Nmos6502 => ILLEGAL_TABLE[opcode as usize],
+        CpuVariant::Wdc65C02 => CMOS_ILLEGAL_TABLE[opcode as usize],
+    }
 }
 
+/// Returns the opcode of the operation on the NMOS 6502
 pub fn opcode_from_operation(op: MosOp) -> u8 {
-    let op_index = INSTRUCTION_DATA_TABLE.iter().position(|&f| f.0 == op);
+    variant_opcode_from_operation(CpuVariant::Nmos6502, op)
+}
+
+/// Returns the opcode of the operation on the given variant
+pub fn variant_opcode_from_operation<B: Bus>(variant: CpuVariant, op: MosOp<B>) -> u8 {
+    let op_index = instruction_table::<B>(variant).iter().position(|&f| std::ptr::fn_addr_eq(f.0, op));
     // If given a correct function, this will always give a result,
     // and the index will fit inside an u8
     op_index.unwrap() as u8
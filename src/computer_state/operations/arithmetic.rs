@@ -1,6 +1,12 @@
-use crate::computer_state::{ComputerState, StatusRegister};
-use crate::computer_state::memory::Memory;
-use crate::computer_state::status_register::{get_zero_neg_flags};
+use crate::computer_state::{ComputerState, CpuVariant, StatusRegister};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, ZERO_NEG_FLAGS};
+
+/// Flags which are set by the result of an addition or subtraction
+const ARITHMETIC_FLAGS: StatusRegister = StatusRegister::N
+    .union(StatusRegister::V)
+    .union(StatusRegister::Z)
+    .union(StatusRegister::C);
 
 // ADDITION
 /// ADC (addition with carry)
@@ -15,53 +21,109 @@ const fn adc(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
         StatusRegister::C.get_cond(overflowed).union(
             // Overflowed from negative to positive or vice versa
         StatusRegister::V.get_cond(
-            (n < 0x7F && acc < 0x7F && result > 0x7F) ||
-            (n > 0x7F && acc > 0x7F && result < 0x7F))));
+            (acc ^ result) & (n ^ result) & 0x80 != 0)));
 
     (result, flags)
 }
 
+/// ADC (addition with carry) in decimal mode
+/// Behaves like the NMOS 6502: Z is taken from the binary sum, while N and V are taken from
+/// the sum before the high digit is adjusted
+const fn adc_decimal(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
+    let (binary_result, _) = adc(acc, n, carry);
+
+    // Add the low digits, adjusting them back into decimal if they overflowed
+    let mut lo_digit = (acc & 0x0F) as i16 + (n & 0x0F) as i16 + carry as i16;
+    if lo_digit >= 0x0A {
+        lo_digit = ((lo_digit + 0x06) & 0x0F) + 0x10;
+    }
+
+    // N and V see the high digits as signed numbers before the high digit is adjusted
+    let signed_sum = (acc & 0xF0) as i8 as i16 + (n & 0xF0) as i8 as i16 + lo_digit;
+
+    let mut sum = (acc & 0xF0) as i16 + (n & 0xF0) as i16 + lo_digit;
+    if sum >= 0xA0 {
+        sum += 0x60;
+    }
+
+    let flags =
+        StatusRegister::Z.get_cond(binary_result == 0).union(
+        StatusRegister::N.get_cond(signed_sum & 0x80 != 0).union(
+        StatusRegister::V.get_cond(signed_sum < -128 || signed_sum > 127).union(
+        StatusRegister::C.get_cond(sum >= 0x100))));
+
+    ((sum & 0xFF) as u8, flags)
+}
+
 /// Mutates the state of the computer according to the result of addition
 /// Acts as an adapter between the implementation of add and the computer
-fn adc_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let (result, flags) = adc(state.acc.get(), addr_fn(&mut state.mem), state.get_carry());
+fn adc_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let value = addr_fn(state);
+    add_to_acc(state, value);
+}
+
+/// ADC (addition with carry) in decimal mode, as on the 65C02
+/// The 65C02 fixes N and Z to reflect the decimal result, at the cost of an extra cycle
+const fn adc_decimal_cmos(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
+    let (result, flags) = adc_decimal(acc, n, carry);
+    let flags = flags.difference(StatusRegister::N.union(StatusRegister::Z))
+        .union(get_zero_neg_flags(result));
+    (result, flags)
+}
+
+/// Adds the value and carry to the accumulator, using decimal mode if the D flag is set
+pub(super) fn add_to_acc<B: Bus>(state: &mut ComputerState<B>, value: u8) {
+    let decimal = state.sta.contains(StatusRegister::D);
+    let add = match (decimal, state.variant()) {
+        (false, _) => adc,
+        (true, CpuVariant::Nmos6502) => adc_decimal,
+        (true, CpuVariant::Wdc65C02) => {
+            state.extra_cycles += 1;
+            adc_decimal_cmos
+        }
+    };
+    let (result, flags) = add(state.acc.get(), value, state.get_carry());
 
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ARITHMETIC_FLAGS, flags);
 }
 
 /// ADC (intermediate addressing mode)
 /// Opcode: 69
-pub fn adc_im(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_immediate); }
+pub fn adc_im<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_immediate); }
 /// ADC (zero-page addressing mode)
 /// Opcode: 65
-pub fn adc_zp(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_zero_page); }
+pub fn adc_zp<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_zero_page); }
 /// ADC (zero-page X addressing mode)
 /// Opcode: 75
-pub fn adc_zpx(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_zero_page_x); }
+pub fn adc_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_zero_page_x); }
 /// ADC (absolute addressing mode)
 /// Opcode: 6D
-pub fn adc_ab(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_absolute); }
+pub fn adc_ab<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_absolute); }
 /// ADC (absolute X addressing mode)
 /// Opcode: 7D
-pub fn adc_abx(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_absolute_x); }
+pub fn adc_abx<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_absolute_x); }
 /// ADC (absolute Y addressing mode)
 /// Opcode: 79
-pub fn adc_aby(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_absolute_y); }
+pub fn adc_aby<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_absolute_y); }
 /// ADC (indirect X addressing mode)
 /// Opcode: 61
-pub fn adc_inx(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_indirect_x);}
+pub fn adc_inx<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_indirect_x);}
 /// ADC (indirect Y addressing mode)
 /// Opcode: 71
-pub fn adc_iny(state: &mut ComputerState)
-{ adc_adapter(state, Memory::fetch_indirect_y); }
+pub fn adc_iny<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_indirect_y); }
+/// ADC (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: 72
+pub fn adc_izp<B: Bus>(state: &mut ComputerState<B>)
+{ adc_adapter(state, ComputerState::fetch_indirect_zp); }
 
 
 // SUBTRACTION
@@ -69,149 +131,221 @@ pub fn adc_iny(state: &mut ComputerState)
 const fn sbc(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
     // Use a - b is equivalent to a + (-b)
     let negative_n = 0xFF ^ n;
-    // Inversion of carry is used to indicate a borrow
-    let negative_carry = if carry == 1 { 0 } else { 1 };
-    adc(acc, negative_n, negative_carry)
+    // A clear carry indicates a borrow, which the ones' complement already accounts for
+    adc(acc, negative_n, carry)
+}
+
+/// SBC (subtraction with carry) in decimal mode
+/// Behaves like the NMOS 6502: every flag is set as though the subtraction was binary
+const fn sbc_decimal(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
+    let (_, flags) = sbc(acc, n, carry);
+
+    // Subtract the low digits, adjusting them back into decimal if they borrowed
+    let mut lo_digit = (acc & 0x0F) as i16 - (n & 0x0F) as i16 + carry as i16 - 1;
+    if lo_digit < 0 {
+        lo_digit = ((lo_digit - 0x06) & 0x0F) - 0x10;
+    }
+
+    let mut difference = (acc & 0xF0) as i16 - (n & 0xF0) as i16 + lo_digit;
+    if difference < 0 {
+        difference -= 0x60;
+    }
+
+    ((difference & 0xFF) as u8, flags)
 }
 
 /// Mutates the state of the computer according to the result of subtraction
 /// Acts as an adapter between the implementation of sub and the computer
-fn sbc_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let (result, flags) = sbc(state.acc.get(), addr_fn(&mut state.mem), state.get_carry());
+fn sbc_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let value = addr_fn(state);
+    sub_from_acc(state, value);
+}
+
+/// SBC (subtraction with carry) in decimal mode, as on the 65C02
+/// The 65C02 adjusts the whole result at once and sets N and Z from it, at the cost of an extra
+/// cycle. V and C are the same as in binary mode
+const fn sbc_decimal_cmos(acc: u8, n: u8, carry: u8) -> (u8, StatusRegister) {
+    let (_, flags) = sbc(acc, n, carry);
+
+    let lo_digit = (acc & 0x0F) as i16 - (n & 0x0F) as i16 + carry as i16 - 1;
+    let mut difference = acc as i16 - n as i16 + carry as i16 - 1;
+    if difference < 0 {
+        difference -= 0x60;
+    }
+    if lo_digit < 0 {
+        difference -= 0x06;
+    }
+
+    let result = (difference & 0xFF) as u8;
+    let flags = flags.difference(StatusRegister::N.union(StatusRegister::Z))
+        .union(get_zero_neg_flags(result));
+    (result, flags)
+}
+
+/// Subtracts the value and borrow from the accumulator, using decimal mode if the D flag is set
+pub(super) fn sub_from_acc<B: Bus>(state: &mut ComputerState<B>, value: u8) {
+    let decimal = state.sta.contains(StatusRegister::D);
+    let sub = match (decimal, state.variant()) {
+        (false, _) => sbc,
+        (true, CpuVariant::Nmos6502) => sbc_decimal,
+        (true, CpuVariant::Wdc65C02) => {
+            state.extra_cycles += 1;
+            sbc_decimal_cmos
+        }
+    };
+    let (result, flags) = sub(state.acc.get(), value, state.get_carry());
 
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ARITHMETIC_FLAGS, flags);
 }
 
 /// SBC (intermediate addressing mode)
 /// Opcode: E9
-pub fn sbc_im(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_immediate); }
+pub fn sbc_im<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_immediate); }
 /// SBC (zero-page addressing mode)
 /// Opcode: E5
-pub fn sbc_zp(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_zero_page); }
+pub fn sbc_zp<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_zero_page); }
 /// SBC (zero-page X addressing mode)
 /// Opcode:F5
-pub fn sbc_zpx(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_zero_page_x); }
+pub fn sbc_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_zero_page_x); }
 /// SBC (absolute addressing mode)
 /// Opcode: ED
-pub fn sbc_ab(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_absolute); }
+pub fn sbc_ab<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_absolute); }
 /// SBC (absolute X addressing mode)
 /// Opcode: FD
-pub fn sbc_abx(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_absolute_x); }
+pub fn sbc_abx<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_absolute_x); }
 /// SBC (absolute Y addressing mode)
 /// Opcode: F9
-pub fn sbc_aby(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_absolute_y); }
+pub fn sbc_aby<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_absolute_y); }
 /// SBC (indirect X addressing mode)
 /// Opcode: E1
-pub fn sbc_inx(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_indirect_x); }
+pub fn sbc_inx<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_indirect_x); }
 /// SBC (indirect Y addressing mode)
 /// Opcode: F1
-pub fn sbc_iny(state: &mut ComputerState)
-{ sbc_adapter(state, Memory::fetch_indirect_y); }
+pub fn sbc_iny<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_indirect_y); }
+/// SBC (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: F2
+pub fn sbc_izp<B: Bus>(state: &mut ComputerState<B>)
+{ sbc_adapter(state, ComputerState::fetch_indirect_zp); }
 
 
 /// DEC (Decrement memory by one)
 /// Returns tuple containing the new value to place in memory,
 /// and the status flags after the operation has completed
-const fn dec(val: u8) -> (u8, StatusRegister) {
+pub(super) const fn dec(val: u8) -> (u8, StatusRegister) {
     let result = val.wrapping_sub(1);
     (result, get_zero_neg_flags(result))
 }
 
 /// Mutates the state of the computer according to the result of taking the decrement
 /// Acts as an adapter between the implementation of dec and the computer
-fn dec_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
-    let addr = addr_fn(&mut state.mem);
-    let (result, flags) = dec(state.mem.fetch_byte_from_addr(addr));
-    state.mem.set_byte_at_addr(addr, result);
-    state.sta |= flags;
+fn dec_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let addr = addr_fn(state);
+    let (result, flags) = dec(state.fetch_byte_from_addr(addr));
+    state.set_byte_at_addr(addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 /// DEC (zero-page addressing mode)
 /// Opcode: C6
-pub fn dec_zp(state: &mut ComputerState)
-{ dec_adapter(state, Memory::fetch_zero_page_address) }
+pub fn dec_zp<B: Bus>(state: &mut ComputerState<B>)
+{ dec_adapter(state, ComputerState::fetch_zero_page_address) }
 /// DEC (zero-page X addressing mode)
 /// Opcode: D6
-pub fn dec_zpx(state: &mut ComputerState)
-{ dec_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn dec_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ dec_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// DEC (absolute addressing mode)
 /// Opcode: CE
-pub fn dec_ab(state: &mut ComputerState)
-{ dec_adapter(state, Memory::fetch_absolute_address) }
+pub fn dec_ab<B: Bus>(state: &mut ComputerState<B>)
+{ dec_adapter(state, ComputerState::fetch_absolute_address) }
 /// DEC (absolute X addressing mode)
 /// Opcode: DE
-pub fn dec_abx(state: &mut ComputerState)
-{ dec_adapter(state, Memory::fetch_absolute_x_address) }
+pub fn dec_abx<B: Bus>(state: &mut ComputerState<B>)
+{ dec_adapter(state, ComputerState::fetch_absolute_x_address) }
 
 /// DEX (implied addressing mode)
 /// Opcode: CA
-pub fn dex(state: &mut ComputerState) {
+pub fn dex<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = dec(state.get_x() as u8);
     state.set_x(usize::from(result));
-    state.sta |= flags
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 /// DEY (implied addressing mode)
 /// Opcode: 88
-pub fn dey(state: &mut ComputerState) {
+pub fn dey<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = dec(state.get_y() as u8);
     state.set_y(usize::from(result));
-    state.sta |= flags
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
+}
+/// DEC (accumulator addressing mode, 65C02 only)
+/// Opcode: 3A
+pub fn dec_acc<B: Bus>(state: &mut ComputerState<B>) {
+    let (result, flags) = dec(state.acc.get());
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 
 /// INC (Increment memory by one)
 /// Returns tuple containing the new value to place in memory,
 /// and the status flags after the operation has completed
-const fn inc(val: u8) -> (u8, StatusRegister) {
+pub(super) const fn inc(val: u8) -> (u8, StatusRegister) {
     let result = val.wrapping_add(1);
     (result, get_zero_neg_flags(result))
 }
 
 /// Mutates the state of the computer according to the result of taking the increment
 /// Acts as an adapter between the implementation of inc and the computer
-fn inc_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
-    let addr = addr_fn(&mut state.mem);
-    let (result, flags) = inc(state.mem.fetch_byte_from_addr(addr));
-    state.mem.set_byte_at_addr(addr, result);
-    state.sta |= flags;
+fn inc_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let addr = addr_fn(state);
+    let (result, flags) = inc(state.fetch_byte_from_addr(addr));
+    state.set_byte_at_addr(addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 /// INC (zero-page addressing mode)
 /// Opcode: E6
-pub fn inc_zp(state: &mut ComputerState)
-{ inc_adapter(state, Memory::fetch_zero_page_address) }
+pub fn inc_zp<B: Bus>(state: &mut ComputerState<B>)
+{ inc_adapter(state, ComputerState::fetch_zero_page_address) }
 /// INC (zero-page X addressing mode)
 /// Opcode: F6
-pub fn inc_zpx(state: &mut ComputerState)
-{ inc_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn inc_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ inc_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// INC (absolute addressing mode)
 /// Opcode: EE
-pub fn inc_ab(state: &mut ComputerState)
-{ inc_adapter(state, Memory::fetch_absolute_address) }
+pub fn inc_ab<B: Bus>(state: &mut ComputerState<B>)
+{ inc_adapter(state, ComputerState::fetch_absolute_address) }
 /// INC (absolute X addressing mode)
 /// Opcode: FE
-pub fn inc_abx(state: &mut ComputerState)
-{ inc_adapter(state, Memory::fetch_absolute_x_address) }
+pub fn inc_abx<B: Bus>(state: &mut ComputerState<B>)
+{ inc_adapter(state, ComputerState::fetch_absolute_x_address) }
 
 /// INX (implied addressing mode)
 /// Opcode: E8
-pub fn inx(state: &mut ComputerState) {
+pub fn inx<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = inc(state.get_x() as u8);
     state.set_x(usize::from(result));
-    state.sta |= flags
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 /// INY (implied addressing mode)
 /// Opcode: C8
-pub fn iny(state: &mut ComputerState) {
+pub fn iny<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = inc(state.get_y() as u8);
     state.set_y(usize::from(result));
-    state.sta |= flags
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
+}
+/// INC (accumulator addressing mode, 65C02 only)
+/// Opcode: 1A
+pub fn inc_acc<B: Bus>(state: &mut ComputerState<B>) {
+    let (result, flags) = inc(state.acc.get());
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
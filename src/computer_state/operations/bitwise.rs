@@ -1,152 +1,169 @@
-use crate::computer_state::{ComputerState, StatusRegister};
-use crate::computer_state::memory::Memory;
-use crate::computer_state::status_register::{get_zero_neg_flags};
+use crate::computer_state::{ComputerState, CpuVariant, StatusRegister};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, ZERO_NEG_CARRY_FLAGS, ZERO_NEG_FLAGS};
 
 // AND
 /// AND (bitwise and)
-const fn and(acc: u8, value: u8) -> (u8, StatusRegister) {
+pub(super) const fn and(acc: u8, value: u8) -> (u8, StatusRegister) {
     let result = acc & value;
     (result, get_zero_neg_flags(result))
 }
 
 /// Mutates the state of the computer according to the result of logical and
 /// Acts as an adapter between the implementation of 'AND' and the computer
-fn and_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let (result, flags) = and(state.acc.get(), addr_fn(&mut state.mem));
+fn and_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let (result, flags) = and(state.acc.get(), addr_fn(state));
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 /// AND (intermediate addressing mode)
 /// Opcode: 29
-pub fn and_im(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_immediate) }
+pub fn and_im<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_immediate) }
 /// AND (zero-page addressing mode)
 /// Opcode: 25
-pub fn and_zp(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_zero_page) }
+pub fn and_zp<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_zero_page) }
 /// AND (zero-page X addressing mode)
 /// Opcode: 35
-pub fn and_zpx(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_zero_page_x) }
+pub fn and_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_zero_page_x) }
 /// AND (absolute addressing mode)
 /// Opcode: 2D
-pub fn and_ab(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_absolute) }
+pub fn and_ab<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_absolute) }
 /// AND (absolute X addressing mode)
 /// Opcode: 3D
-pub fn and_abx(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_absolute_x) }
+pub fn and_abx<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_absolute_x) }
 /// AND (absolute Y addressing mode)
 /// Opcode: 39
-pub fn and_aby(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_absolute_y) }
+pub fn and_aby<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_absolute_y) }
 /// AND (indirect X addressing mode)
 /// Opcode: 21
-pub fn and_inx(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_indirect_x) }
+pub fn and_inx<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_indirect_x) }
 /// AND (indirect Y addressing mode)
 /// Opcode: 31
-pub fn and_iny(state: &mut ComputerState)
-{ and_adapter(state, Memory::fetch_indirect_y) }
+pub fn and_iny<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_indirect_y) }
+/// AND (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: 32
+pub fn and_izp<B: Bus>(state: &mut ComputerState<B>)
+{ and_adapter(state, ComputerState::fetch_indirect_zp) }
 
 
 /// OR (logical bitwise inclusive or)
-const fn or(acc: u8, value: u8) -> (u8, StatusRegister) {
+pub(super) const fn or(acc: u8, value: u8) -> (u8, StatusRegister) {
     let result = acc | value;
     (result, get_zero_neg_flags(result))
 }
 
 /// Mutates the state of the computer according to the result of logical or
 /// Acts as an adapter between the implementation of 'OR' and the computer
-fn or_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let (result, flags) = or(state.acc.get(), addr_fn(&mut state.mem));
+fn or_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let (result, flags) = or(state.acc.get(), addr_fn(state));
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 /// OR (intermediate addressing mode)
 /// Opcode: 09
-pub fn or_im(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_immediate) }
+pub fn or_im<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_immediate) }
 /// OR (zero-page addressing mode)
 /// Opcode: 05
-pub fn or_zp(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_zero_page) }
+pub fn or_zp<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_zero_page) }
 /// OR (zero-page X addressing mode)
 /// Opcode: 15
-pub fn or_zpx(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_zero_page_x) }
+pub fn or_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_zero_page_x) }
 /// OR (absolute addressing mode)
 /// Opcode: 0D
-pub fn or_ab(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_absolute) }
+pub fn or_ab<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_absolute) }
 /// OR (absolute X addressing mode)
 /// Opcode: 1D
-pub fn or_abx(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_absolute_x) }
+pub fn or_abx<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_absolute_x) }
 /// OR (absolute Y addressing mode)
 /// Opcode: 19
-pub fn or_aby(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_absolute_y) }
+pub fn or_aby<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_absolute_y) }
 /// OR (indirect X addressing mode)
 /// Opcode: 01
-pub fn or_inx(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_indirect_x) }
+pub fn or_inx<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_indirect_x) }
 /// OR (indirect Y addressing mode)
 /// Opcode: 11
-pub fn or_iny(state: &mut ComputerState)
-{ or_adapter(state, Memory::fetch_indirect_y) }
+pub fn or_iny<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_indirect_y) }
+/// OR (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: 12
+pub fn or_izp<B: Bus>(state: &mut ComputerState<B>)
+{ or_adapter(state, ComputerState::fetch_indirect_zp) }
 
 
 /// EOR (logical bitwise exclusive or)
-fn eor(acc: u8, value: u8) -> (u8, StatusRegister) {
+pub(super) const fn eor(acc: u8, value: u8) -> (u8, StatusRegister) {
     let result = acc ^ value;
     (result, get_zero_neg_flags(result))
 }
 
 /// Mutates the state of the computer according to the result of logical exclusive or
 /// Acts as an adapter between the implementation of 'XOR' and the computer
-fn eor_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let (result, flags) = eor(state.acc.get(), addr_fn(&mut state.mem));
+fn eor_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let (result, flags) = eor(state.acc.get(), addr_fn(state));
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
 }
 
 /// EOR (intermediate addressing mode)
 /// Opcode: 49
-pub fn eor_im(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_immediate) }
+pub fn eor_im<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_immediate) }
 /// EOR (zero-page addressing mode)
 /// Opcode: 45
-pub fn eor_zp(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_zero_page) }
+pub fn eor_zp<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_zero_page) }
 /// EOR (zero-page X addressing mode)
 /// Opcode: 55
-pub fn eor_zpx(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_zero_page_x) }
+pub fn eor_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_zero_page_x) }
 /// EOR (absolute addressing mode)
 /// Opcode: 4D
-pub fn eor_ab(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_absolute) }
+pub fn eor_ab<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_absolute) }
 /// EOR (absolute X addressing mode)
 /// Opcode: 5D
-pub fn eor_abx(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_absolute_x) }
+pub fn eor_abx<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_absolute_x) }
 /// EOR (absolute Y addressing mode)
 /// Opcode: 59
-pub fn eor_aby(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_absolute_y) }
+pub fn eor_aby<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_absolute_y) }
 /// EOR (indirect X addressing mode)
 /// Opcode: 41
-pub fn eor_inx(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_indirect_x) }
+pub fn eor_inx<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_indirect_x) }
 /// EOR (indirect Y addressing mode)
 /// Opcode: 51
-pub fn eor_iny(state: &mut ComputerState)
-{ eor_adapter(state, Memory::fetch_indirect_y) }
+pub fn eor_iny<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_indirect_y) }
+/// EOR (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: 52
+pub fn eor_izp<B: Bus>(state: &mut ComputerState<B>)
+{ eor_adapter(state, ComputerState::fetch_indirect_zp) }
 
 
+/// Flags which are set by a bit test
+const BIT_FLAGS: StatusRegister = StatusRegister::Z
+    .union(StatusRegister::N)
+    .union(StatusRegister::V);
+
 /// BIT (Bit test)
 const fn bit(acc: u8, value: u8) -> StatusRegister {
     StatusRegister::Z.get_cond(acc & value == 0).union(
@@ -156,23 +173,80 @@ const fn bit(acc: u8, value: u8) -> StatusRegister {
 
 /// Mutates the state of the computer according to the result of the bit test
 /// Acts as an adapter between the implementation of 'BIT' and the computer
-fn bit_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> u8) {
-    let flags = bit(state.acc.get(), addr_fn(&mut state.mem));
-    state.sta |= flags;
+fn bit_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let flags = bit(state.acc.get(), addr_fn(state));
+    state.sta = state.sta.replace(BIT_FLAGS, flags);
 }
 
 /// BIT (zero-page addressing mode)
 /// Opcode: 24
-pub fn bit_zp(state: &mut ComputerState)
-{ bit_adapter(state, Memory::fetch_zero_page) }
+pub fn bit_zp<B: Bus>(state: &mut ComputerState<B>)
+{ bit_adapter(state, ComputerState::fetch_zero_page) }
 /// BIT (absolute addressing mode)
 /// Opcode: 2C
-pub fn bit_ab(state: &mut ComputerState)
-{ bit_adapter(state, Memory::fetch_absolute) }
+pub fn bit_ab<B: Bus>(state: &mut ComputerState<B>)
+{ bit_adapter(state, ComputerState::fetch_absolute) }
+/// BIT (zero-page X addressing mode, 65C02 only)
+/// Opcode: 34
+pub fn bit_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ bit_adapter(state, ComputerState::fetch_zero_page_x) }
+/// BIT (absolute X addressing mode, 65C02 only)
+/// Opcode: 3C
+pub fn bit_abx<B: Bus>(state: &mut ComputerState<B>)
+{ bit_adapter(state, ComputerState::fetch_absolute_x) }
+/// BIT (immediate addressing mode, 65C02 only)
+/// Only the zero flag is affected, as there is no memory location to test
+/// Opcode: 89
+pub fn bit_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let flags = bit(state.acc.get(), value);
+    state.sta = state.sta.replace(StatusRegister::Z, flags);
+}
+
 
+/// TRB and TSB (test and reset or set bits)
+/// Sets the zero flag from the accumulator and memory in the same way as BIT, then clears or sets
+/// the bits of the accumulator in memory
+fn test_bits_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize, set: bool) {
+    let addr = addr_fn(state);
+    let value = state.fetch_byte_from_addr(addr);
+    let acc = state.acc.get();
+    let result = if set { value | acc } else { value & !acc };
+
+    state.set_byte_at_addr(addr, result);
+    state.sta = state.sta.replace(StatusRegister::Z, StatusRegister::Z.get_cond(acc & value == 0));
+}
+
+/// TRB (zero-page addressing mode, 65C02 only)
+/// Opcode: 14
+pub fn trb_zp<B: Bus>(state: &mut ComputerState<B>)
+{ test_bits_adapter(state, ComputerState::fetch_zero_page_address, false) }
+/// TRB (absolute addressing mode, 65C02 only)
+/// Opcode: 1C
+pub fn trb_ab<B: Bus>(state: &mut ComputerState<B>)
+{ test_bits_adapter(state, ComputerState::fetch_absolute_address, false) }
+/// TSB (zero-page addressing mode, 65C02 only)
+/// Opcode: 04
+pub fn tsb_zp<B: Bus>(state: &mut ComputerState<B>)
+{ test_bits_adapter(state, ComputerState::fetch_zero_page_address, true) }
+/// TSB (absolute addressing mode, 65C02 only)
+/// Opcode: 0C
+pub fn tsb_ab<B: Bus>(state: &mut ComputerState<B>)
+{ test_bits_adapter(state, ComputerState::fetch_absolute_address, true) }
+
+
+/// Address of a shift or rotate in the absolute X addressing mode
+/// The NMOS 6502 always takes the extra cycle, where the 65C02 only takes it if the offset crosses
+/// a page boundary
+fn shift_absolute_x_address<B: Bus>(variant: CpuVariant) -> fn(&mut ComputerState<B>) -> usize {
+    match variant {
+        CpuVariant::Nmos6502 => ComputerState::fetch_absolute_x_address,
+        CpuVariant::Wdc65C02 => ComputerState::fetch_absolute_x_address_with_penalty,
+    }
+}
 
 /// ASL (arithmetic shift left)
-const fn asl(value: u8) -> (u8, StatusRegister) {
+pub(super) const fn asl(value: u8) -> (u8, StatusRegister) {
     let bits = value.count_ones();
     let result = value << 1;
     let overflow = bits != result.count_ones();
@@ -186,41 +260,41 @@ const fn asl(value: u8) -> (u8, StatusRegister) {
 
 /// Mutates the state of the computer according to the result of an arithmetic shift left
 /// Acts as an adapter between the implementation of 'ASL' and the computer
-fn asl_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
-    let zp_addr = addr_fn(&mut state.mem);
-    let zp_val = state.mem.fetch_byte_from_addr(zp_addr);
+fn asl_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let zp_addr = addr_fn(state);
+    let zp_val = state.fetch_byte_from_addr(zp_addr);
     let (result, flags) = asl(zp_val);
-    state.mem.set_byte_at_addr(zp_addr, result);
-    state.sta |= flags;
+    state.set_byte_at_addr(zp_addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 
 /// ASL (accumulator addressing mode)
 /// Opcode: 0A
-pub fn asl_acc(state: &mut ComputerState) {
+pub fn asl_acc<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = asl(state.acc.get());
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 /// ASL (zero_page addressing mode)
 /// Opcode: 06
-pub fn asl_zp(state: &mut ComputerState)
-{ asl_adapter(state, Memory::fetch_zero_page_address) }
+pub fn asl_zp<B: Bus>(state: &mut ComputerState<B>)
+{ asl_adapter(state, ComputerState::fetch_zero_page_address) }
 /// ASL (zero_page X addressing mode)
 /// Opcode: 16
-pub fn asl_zpx(state: &mut ComputerState)
-{ asl_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn asl_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ asl_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// ASL (absolute addressing mode)
 /// Opcode: 0E
-pub fn asl_ab(state: &mut ComputerState)
-{ asl_adapter(state, Memory::fetch_absolute_address) }
+pub fn asl_ab<B: Bus>(state: &mut ComputerState<B>)
+{ asl_adapter(state, ComputerState::fetch_absolute_address) }
 /// ASL (absolute X addressing mode)
 /// Opcode: 1E
-pub fn asl_abx(state: &mut ComputerState)
-{ asl_adapter(state, Memory::fetch_absolute_x_address) }
+pub fn asl_abx<B: Bus>(state: &mut ComputerState<B>)
+{ asl_adapter(state, shift_absolute_x_address(state.variant())) }
 
 
 /// LSR (logical shift right)
-const fn lsr(value: u8) -> (u8, StatusRegister) {
+pub(super) const fn lsr(value: u8) -> (u8, StatusRegister) {
     let bits = value.count_ones();
     let result = value >> 1;
     let overflow = bits != result.count_ones();
@@ -234,41 +308,41 @@ const fn lsr(value: u8) -> (u8, StatusRegister) {
 
 /// Mutates the state of the computer according to the result of a logical shift right
 /// Acts as an adapter between the implementation of 'LSR' and the computer
-fn lsr_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
-    let zp_addr = addr_fn(&mut state.mem);
-    let zp_val = state.mem.fetch_byte_from_addr(zp_addr);
+fn lsr_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let zp_addr = addr_fn(state);
+    let zp_val = state.fetch_byte_from_addr(zp_addr);
     let (result, flags) = lsr(zp_val);
-    state.mem.set_byte_at_addr(zp_addr, result);
-    state.sta |= flags;
+    state.set_byte_at_addr(zp_addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 
 /// LSR (accumulator addressing mode)
 /// Opcode: 4A
-pub fn lsr_acc(state: &mut ComputerState) {
+pub fn lsr_acc<B: Bus>(state: &mut ComputerState<B>) {
     let (result, flags) = lsr(state.acc.get());
     state.acc.set(result);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 /// LSR (zero_page addressing mode)
 /// Opcode: 46
-pub fn lsr_zp(state: &mut ComputerState)
-{ lsr_adapter(state, Memory::fetch_zero_page_address) }
+pub fn lsr_zp<B: Bus>(state: &mut ComputerState<B>)
+{ lsr_adapter(state, ComputerState::fetch_zero_page_address) }
 /// LSR (zero_page X addressing mode)
 /// Opcode: 56
-pub fn lsr_zpx(state: &mut ComputerState)
-{ lsr_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn lsr_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ lsr_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// LSR (absolute addressing mode)
 /// Opcode: 4E
-pub fn lsr_ab(state: &mut ComputerState)
-{ lsr_adapter(state, Memory::fetch_absolute_address) }
+pub fn lsr_ab<B: Bus>(state: &mut ComputerState<B>)
+{ lsr_adapter(state, ComputerState::fetch_absolute_address) }
 /// LSR (absolute X addressing mode)
 /// Opcode: 5E
-pub fn lsr_abx(state: &mut ComputerState)
-{ lsr_adapter(state, Memory::fetch_absolute_x_address) }
+pub fn lsr_abx<B: Bus>(state: &mut ComputerState<B>)
+{ lsr_adapter(state, shift_absolute_x_address(state.variant())) }
 
 
 /// ROL (Rotate left one bit)
-const fn rol(value: u8, carry: u8) -> (u8, StatusRegister) {
+pub(super) const fn rol(value: u8, carry: u8) -> (u8, StatusRegister) {
     let result = (value << 1) + carry;
     let flags = get_zero_neg_flags(result).union(
         StatusRegister::C.get_cond((value & 0x80) != 0));
@@ -278,47 +352,45 @@ const fn rol(value: u8, carry: u8) -> (u8, StatusRegister) {
 
 /// Mutates the state of the computer according to the result of a left rotation
 /// Acts as an adapter between the implementation of 'ROL' and the computer
-fn rol_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
+fn rol_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
     let carry = state.get_carry();
-    let zp_addr = addr_fn(&mut state.mem);
-    let zp_val = state.mem.fetch_byte_from_addr(zp_addr);
+    let zp_addr = addr_fn(state);
+    let zp_val = state.fetch_byte_from_addr(zp_addr);
     let (result, flags) = rol(zp_val, carry);
 
-    state.mem.set_byte_at_addr(zp_addr, result);
-    state.sta = state.sta.difference(StatusRegister::C);
-    state.sta |= flags;
+    state.set_byte_at_addr(zp_addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 
 /// ROL (accumulator addressing mode)
 /// Opcode: 2A
-pub fn rol_acc(state: &mut ComputerState) {
+pub fn rol_acc<B: Bus>(state: &mut ComputerState<B>) {
     let carry = state.get_carry();
     let (result, flags) = rol(state.acc.get(), carry);
 
     state.acc.set(result);
-    state.sta = state.sta.difference(StatusRegister::C);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 /// ROL (zero_page addressing mode)
 /// Opcode: 26
-pub fn rol_zp(state: &mut ComputerState)
-{ rol_adapter(state, Memory::fetch_zero_page_address) }
+pub fn rol_zp<B: Bus>(state: &mut ComputerState<B>)
+{ rol_adapter(state, ComputerState::fetch_zero_page_address) }
 /// ROL (zero_page X addressing mode)
 /// Opcode: 36
-pub fn rol_zpx(state: &mut ComputerState)
-{ rol_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn rol_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ rol_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// ROL (absolute addressing mode)
 /// Opcode: 2E
-pub fn rol_ab(state: &mut ComputerState)
-{ rol_adapter(state, Memory::fetch_absolute_address) }
+pub fn rol_ab<B: Bus>(state: &mut ComputerState<B>)
+{ rol_adapter(state, ComputerState::fetch_absolute_address) }
 /// ROL (absolute X addressing mode)
 /// Opcode: 3E
-pub fn rol_abx(state: &mut ComputerState)
-{ rol_adapter(state, Memory::fetch_absolute_x_address) }
+pub fn rol_abx<B: Bus>(state: &mut ComputerState<B>)
+{ rol_adapter(state, shift_absolute_x_address(state.variant())) }
 
 
 /// ROR (Rotate right one bit)
-const fn ror(value: u8, carry: u8) -> (u8, StatusRegister) {
+pub(super) const fn ror(value: u8, carry: u8) -> (u8, StatusRegister) {
     let result = (value >> 1) + (carry << 7);
     let flags = get_zero_neg_flags(result).union(
         StatusRegister::C.get_cond((value & 0x01) != 0));
@@ -328,40 +400,112 @@ const fn ror(value: u8, carry: u8) -> (u8, StatusRegister) {
 
 /// Mutates the state of the computer according to the result of a right rotation
 /// Acts as an adapter between the implementation of 'ROR' and the computer
-fn ror_adapter(state: &mut ComputerState, addr_fn: fn(&mut Memory) -> usize) {
+fn ror_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
     let carry = state.get_carry();
-    let zp_addr = addr_fn(&mut state.mem);
-    let zp_val = state.mem.fetch_byte_from_addr(zp_addr);
+    let zp_addr = addr_fn(state);
+    let zp_val = state.fetch_byte_from_addr(zp_addr);
     let (result, flags) = ror(zp_val, carry);
 
-    state.mem.set_byte_at_addr(zp_addr, result);
-    state.sta = state.sta.difference(StatusRegister::C);
-    state.sta |= flags;
+    state.set_byte_at_addr(zp_addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 
 /// ROR (accumulator addressing mode)
 /// Opcode: 6A
-pub fn ror_acc(state: &mut ComputerState) {
+pub fn ror_acc<B: Bus>(state: &mut ComputerState<B>) {
     let carry = state.get_carry();
     let (result, flags) = ror(state.acc.get(), carry);
 
     state.acc.set(result);
-    state.sta = state.sta.difference(StatusRegister::C);
-    state.sta |= flags;
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
 }
 /// ROR (zero_page addressing mode)
 /// Opcode: 66
-pub fn ror_zp(state: &mut ComputerState)
-{ ror_adapter(state, Memory::fetch_zero_page_address) }
+pub fn ror_zp<B: Bus>(state: &mut ComputerState<B>)
+{ ror_adapter(state, ComputerState::fetch_zero_page_address) }
 /// ROR (zero_page X addressing mode)
 /// Opcode: 76
-pub fn ror_zpx(state: &mut ComputerState)
-{ ror_adapter(state, Memory::fetch_zero_page_x_address) }
+pub fn ror_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ ror_adapter(state, ComputerState::fetch_zero_page_x_address) }
 /// ROR (absolute addressing mode)
 /// Opcode: 6E
-pub fn ror_ab(state: &mut ComputerState)
-{ ror_adapter(state, Memory::fetch_absolute_address) }
+pub fn ror_ab<B: Bus>(state: &mut ComputerState<B>)
+{ ror_adapter(state, ComputerState::fetch_absolute_address) }
 /// ROR (absolute X addressing mode)
 /// Opcode: 7E
-pub fn ror_abx(state: &mut ComputerState)
-{ ror_adapter(state, Memory::fetch_absolute_x_address) }
\ No newline at end of file
+pub fn ror_abx<B: Bus>(state: &mut ComputerState<B>)
+{ ror_adapter(state, shift_absolute_x_address(state.variant())) }
+
+/// RMB and SMB (reset or set a single bit of a zero-page location)
+fn memory_bit_adapter<B: Bus>(state: &mut ComputerState<B>, bit: u8, set: bool) {
+    let addr = state.fetch_zero_page_address();
+    let value = state.fetch_byte_from_addr(addr);
+    let result = if set { value | (1 << bit) } else { value & !(1 << bit) };
+    state.set_byte_at_addr(addr, result);
+}
+
+/// RMB0 (reset bit 0 of memory, 65C02 only)
+/// Opcode: 07
+pub fn rmb0<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 0, false) }
+/// RMB1 (reset bit 1 of memory, 65C02 only)
+/// Opcode: 17
+pub fn rmb1<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 1, false) }
+/// RMB2 (reset bit 2 of memory, 65C02 only)
+/// Opcode: 27
+pub fn rmb2<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 2, false) }
+/// RMB3 (reset bit 3 of memory, 65C02 only)
+/// Opcode: 37
+pub fn rmb3<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 3, false) }
+/// RMB4 (reset bit 4 of memory, 65C02 only)
+/// Opcode: 47
+pub fn rmb4<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 4, false) }
+/// RMB5 (reset bit 5 of memory, 65C02 only)
+/// Opcode: 57
+pub fn rmb5<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 5, false) }
+/// RMB6 (reset bit 6 of memory, 65C02 only)
+/// Opcode: 67
+pub fn rmb6<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 6, false) }
+/// RMB7 (reset bit 7 of memory, 65C02 only)
+/// Opcode: 77
+pub fn rmb7<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 7, false) }
+
+/// SMB0 (set bit 0 of memory, 65C02 only)
+/// Opcode: 87
+pub fn smb0<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 0, true) }
+/// SMB1 (set bit 1 of memory, 65C02 only)
+/// Opcode: 97
+pub fn smb1<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 1, true) }
+/// SMB2 (set bit 2 of memory, 65C02 only)
+/// Opcode: A7
+pub fn smb2<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 2, true) }
+/// SMB3 (set bit 3 of memory, 65C02 only)
+/// Opcode: B7
+pub fn smb3<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 3, true) }
+/// SMB4 (set bit 4 of memory, 65C02 only)
+/// Opcode: C7
+pub fn smb4<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 4, true) }
+/// SMB5 (set bit 5 of memory, 65C02 only)
+/// Opcode: D7
+pub fn smb5<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 5, true) }
+/// SMB6 (set bit 6 of memory, 65C02 only)
+/// Opcode: E7
+pub fn smb6<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 6, true) }
+/// SMB7 (set bit 7 of memory, 65C02 only)
+/// Opcode: F7
+pub fn smb7<B: Bus>(state: &mut ComputerState<B>)
+{ memory_bit_adapter(state, 7, true) }
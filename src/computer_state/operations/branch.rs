@@ -1,49 +1,146 @@
 use crate::computer_state::ComputerState;
+use crate::computer_state::bus::Bus;
 use crate::computer_state::status_register::StatusRegister;
 
-fn branch(state: &mut ComputerState, condition: bool) {
-    let addr = state.mem.fetch_relative();
+pub(super) fn branch<B: Bus>(state: &mut ComputerState<B>, condition: bool) {
+    let addr = state.fetch_relative();
     if condition {
-        state.mem.pc.add_signed(addr);
+        let old_pc = state.pc.get();
+        state.pc.add_signed(addr);
+        // Taking the branch costs one cycle, and a second if it lands on a different page
+        let crossed_page = (old_pc & 0xFF00) != (state.pc.get() & 0xFF00);
+        state.extra_cycles += if crossed_page { 2 } else { 1 };
     }
 }
 
 /// BCS (Branch if carry set)
 /// Opcode: B0
-pub fn bcs(state: &mut ComputerState)
+pub fn bcs<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, state.sta.contains(StatusRegister::C)); }
 
 /// BCC (Branch if carry clear)
 /// Opcode: 90
-pub fn bcc(state: &mut ComputerState)
+pub fn bcc<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, !state.sta.contains(StatusRegister::C)); }
 
 /// BEQ (Branch if equal)
 /// Opcode: F0
-pub fn beq(state: &mut ComputerState)
+pub fn beq<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, state.sta.contains(StatusRegister::Z)); }
 
 /// BNE (Branch if not equal)
 /// Opcode: D0
-pub fn bne(state: &mut ComputerState)
+pub fn bne<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, !state.sta.contains(StatusRegister::Z)); }
 
 /// BMI (Branch if minus)
 /// Opcode: 30
-pub fn bmi(state: &mut ComputerState)
+pub fn bmi<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, state.sta.contains(StatusRegister::N)); }
 
 /// BPL (Branch if positive)
 /// Opcode: 10
-pub fn bpl(state: &mut ComputerState)
+pub fn bpl<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, !state.sta.contains(StatusRegister::N)); }
 
 /// BVS (Branch if overflow set)
 /// Opcode: 70
-pub fn bvs(state: &mut ComputerState)
+pub fn bvs<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, state.sta.contains(StatusRegister::V)); }
 
 /// BVC (Branch if overflow)
 /// Opcode: 50
-pub fn bvc(state: &mut ComputerState)
+pub fn bvc<B: Bus>(state: &mut ComputerState<B>)
 { branch(state, !state.sta.contains(StatusRegister::V)); }
+
+/// BRA (Branch always, 65C02 only)
+/// Opcode: 80
+pub fn bra<B: Bus>(state: &mut ComputerState<B>)
+{ branch(state, true); }
+
+
+/// BBR and BBS (Branch on a single bit of a zero-page location being reset or set)
+fn branch_on_bit<B: Bus>(state: &mut ComputerState<B>, bit: u8, set: bool) {
+    let value = state.fetch_zero_page();
+    branch(state, (value & (1 << bit) != 0) == set);
+}
+
+/// BBR0 (Branch if bit 0 reset, 65C02 only)
+/// Opcode: 0F
+pub fn bbr0<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 0, false); }
+
+/// BBR1 (Branch if bit 1 reset, 65C02 only)
+/// Opcode: 1F
+pub fn bbr1<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 1, false); }
+
+/// BBR2 (Branch if bit 2 reset, 65C02 only)
+/// Opcode: 2F
+pub fn bbr2<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 2, false); }
+
+/// BBR3 (Branch if bit 3 reset, 65C02 only)
+/// Opcode: 3F
+pub fn bbr3<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 3, false); }
+
+/// BBR4 (Branch if bit 4 reset, 65C02 only)
+/// Opcode: 4F
+pub fn bbr4<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 4, false); }
+
+/// BBR5 (Branch if bit 5 reset, 65C02 only)
+/// Opcode: 5F
+pub fn bbr5<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 5, false); }
+
+/// BBR6 (Branch if bit 6 reset, 65C02 only)
+/// Opcode: 6F
+pub fn bbr6<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 6, false); }
+
+/// BBR7 (Branch if bit 7 reset, 65C02 only)
+/// Opcode: 7F
+pub fn bbr7<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 7, false); }
+
+/// BBS0 (Branch if bit 0 set, 65C02 only)
+/// Opcode: 8F
+pub fn bbs0<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 0, true); }
+
+/// BBS1 (Branch if bit 1 set, 65C02 only)
+/// Opcode: 9F
+pub fn bbs1<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 1, true); }
+
+/// BBS2 (Branch if bit 2 set, 65C02 only)
+/// Opcode: AF
+pub fn bbs2<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 2, true); }
+
+/// BBS3 (Branch if bit 3 set, 65C02 only)
+/// Opcode: BF
+pub fn bbs3<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 3, true); }
+
+/// BBS4 (Branch if bit 4 set, 65C02 only)
+/// Opcode: CF
+pub fn bbs4<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 4, true); }
+
+/// BBS5 (Branch if bit 5 set, 65C02 only)
+/// Opcode: DF
+pub fn bbs5<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 5, true); }
+
+/// BBS6 (Branch if bit 6 set, 65C02 only)
+/// Opcode: EF
+pub fn bbs6<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 6, true); }
+
+/// BBS7 (Branch if bit 7 set, 65C02 only)
+/// Opcode: FF
+pub fn bbs7<B: Bus>(state: &mut ComputerState<B>)
+{ branch_on_bit(state, 7, true); }
@@ -1,81 +1,114 @@
 use crate::computer_state::ComputerState;
-use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, StatusRegister, ZERO_NEG_CARRY_FLAGS};
 
-const fn cmp(val: u8, mem: u8) -> StatusRegister {
+/// Compares a register with memory
+/// The zero and negative flags come from the result of subtracting memory from the register
+pub(super) const fn cmp(val: u8, mem: u8) -> StatusRegister {
     StatusRegister::C.get_cond(val >= mem).union(
-    StatusRegister::Z.get_cond(val == mem).union(
-    StatusRegister::N.get_cond(val <  mem)))
+    get_zero_neg_flags(val.wrapping_sub(mem)))
+}
+
+/// Mutates the state of the computer according to the result of comparing the register with memory
+/// Acts as an adapter between the implementation of the comparisons and the computer
+fn compare_adapter<B: Bus>(state: &mut ComputerState<B>, register: u8,
+                           addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let value = addr_fn(state);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, cmp(register, value));
+}
+
+/// Compares the accumulator with memory
+fn cmp_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let register = state.acc.get();
+    compare_adapter(state, register, addr_fn)
+}
+
+/// Compares the X index with memory
+fn cpx_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let register = state.get_x() as u8;
+    compare_adapter(state, register, addr_fn)
+}
+
+/// Compares the Y index with memory
+fn cpy_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let register = state.get_y() as u8;
+    compare_adapter(state, register, addr_fn)
 }
 
 
 /// CMP (immediate memory addressing mode)
 /// Opcode: C9
-pub fn cmp_im(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_immediate()); }
+pub fn cmp_im<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_immediate) }
 
 /// CMP (zero-page memory addressing mode)
 /// Opcode: C5
-pub fn cmp_zp(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_zero_page()); }
+pub fn cmp_zp<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_zero_page) }
 
 /// CMP (zero-page X memory addressing mode)
 /// Opcode: D5
-pub fn cmp_zpx(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_zero_page_x()); }
+pub fn cmp_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_zero_page_x) }
 
 /// CMP (absolute memory addressing mode)
 /// Opcode: CD
-pub fn cmp_ab(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_absolute()); }
+pub fn cmp_ab<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_absolute) }
 
 /// CMP (absolute X memory addressing mode)
 /// Opcode: DD
-pub fn cmp_abx(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_absolute_x()); }
+pub fn cmp_abx<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_absolute_x) }
 
 /// CMP (absolute Y memory addressing mode)
 /// Opcode: D9
-pub fn cmp_aby(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_absolute_y()); }
+pub fn cmp_aby<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_absolute_y) }
 
 /// CMP (indirect X memory addressing mode)
 /// Opcode: C1
-pub fn cmp_inx(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_indirect_x()); }
+pub fn cmp_inx<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_indirect_x) }
 
 /// CMP (indirect Y memory addressing mode)
 /// Opcode: D1
-pub fn cmp_iny(state: &mut ComputerState)
-{ state.sta |= cmp(state.acc.get(), state.mem.fetch_indirect_y()); }
+pub fn cmp_iny<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_indirect_y) }
+
+/// CMP (zero-page indirect memory addressing mode, 65C02 only)
+/// Opcode: D2
+pub fn cmp_izp<B: Bus>(state: &mut ComputerState<B>)
+{ cmp_adapter(state, ComputerState::fetch_indirect_zp) }
 
 
 /// CPX (immediate memory addressing mode)
 /// Opcode: E0
-pub fn cpx_im(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_x() as u8, state.mem.fetch_immediate()); }
+pub fn cpx_im<B: Bus>(state: &mut ComputerState<B>)
+{ cpx_adapter(state, ComputerState::fetch_immediate) }
 
 /// CPX (zero-page memory addressing mode)
 /// Opcode: E4
-pub fn cpx_zp(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_x() as u8, state.mem.fetch_zero_page()); }
+pub fn cpx_zp<B: Bus>(state: &mut ComputerState<B>)
+{ cpx_adapter(state, ComputerState::fetch_zero_page) }
 
 /// CPX (absolute memory addressing mode)
 /// Opcode: EC
-pub fn cpx_ab(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_x() as u8, state.mem.fetch_absolute()); }
+pub fn cpx_ab<B: Bus>(state: &mut ComputerState<B>)
+{ cpx_adapter(state, ComputerState::fetch_absolute) }
 
 
 /// CPY (immediate memory addressing mode)
 /// Opcode: C0
-pub fn cpy_im(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_y() as u8, state.mem.fetch_immediate()); }
+pub fn cpy_im<B: Bus>(state: &mut ComputerState<B>)
+{ cpy_adapter(state, ComputerState::fetch_immediate) }
 
 /// CPY (zero-page memory addressing mode)
 /// Opcode: C4
-pub fn cpy_zp(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_y() as u8, state.mem.fetch_zero_page()); }
+pub fn cpy_zp<B: Bus>(state: &mut ComputerState<B>)
+{ cpy_adapter(state, ComputerState::fetch_zero_page) }
 
 /// CPY (absolute memory addressing mode)
 /// Opcode: CC
-pub fn cpy_ab(state: &mut ComputerState)
-{ state.sta |= cmp(state.get_y() as u8, state.mem.fetch_absolute()); }
+pub fn cpy_ab<B: Bus>(state: &mut ComputerState<B>)
+{ cpy_adapter(state, ComputerState::fetch_absolute) }
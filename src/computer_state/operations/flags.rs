@@ -1,44 +1,45 @@
 use crate::computer_state::{ComputerState, StatusRegister};
+use crate::computer_state::bus::Bus;
 
 // CLEAR INSTRUCTIONS
 /// CLC (Clear carry flag)
 /// Opcode: 18
-pub fn clc(state: &mut ComputerState) {
+pub fn clc<B: Bus>(state: &mut ComputerState<B>) {
     state.sta = state.sta.difference(StatusRegister::C)
 }
 
 /// CLD (Clear decimal flag)
 /// Opcode: D8
-pub fn cld(state: &mut ComputerState) {
+pub fn cld<B: Bus>(state: &mut ComputerState<B>) {
     state.sta = state.sta.difference(StatusRegister::D)
 }
 
 /// CLI (Clear interrupt disable status)
 /// Opcode: 58
-pub fn cli(state: &mut ComputerState) {
+pub fn cli<B: Bus>(state: &mut ComputerState<B>) {
     state.sta = state.sta.difference(StatusRegister::I)
 }
 
 /// CLV (Clear overflow flag)
 /// Opcode: B8
-pub fn clv(state: &mut ComputerState) {
+pub fn clv<B: Bus>(state: &mut ComputerState<B>) {
     state.sta = state.sta.difference(StatusRegister::V)
 }
 
 /// SEC (Set carry flag)
 /// Opcode: 38
-pub fn sec(state: &mut ComputerState) {
+pub fn sec<B: Bus>(state: &mut ComputerState<B>) {
     state.sta |= StatusRegister::C
 }
 
 /// SED (Set decimal flag)
 /// Opcode: F8
-pub fn sed(state: &mut ComputerState) {
+pub fn sed<B: Bus>(state: &mut ComputerState<B>) {
     state.sta |= StatusRegister::D
 }
 
 /// SEI (Set interrupt disable status)
 /// Opcode: 78
-pub fn sei(state: &mut ComputerState) {
+pub fn sei<B: Bus>(state: &mut ComputerState<B>) {
     state.sta |= StatusRegister::I
 }
\ No newline at end of file
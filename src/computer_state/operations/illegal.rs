@@ -0,0 +1,512 @@
+use crate::computer_state::{ComputerState, StatusRegister};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, ZERO_NEG_CARRY_FLAGS, ZERO_NEG_FLAGS};
+use super::arithmetic::{add_to_acc, dec, inc, sub_from_acc};
+use super::bitwise::{and, asl, eor, lsr, or, rol, ror};
+use super::comparison::cmp;
+
+// Undocumented opcodes of the NMOS 6502
+// Most of these are the result of two documented operations sharing the same decoding logic,
+// such as a read-modify-write instruction followed by an accumulator instruction
+
+/// Magic constant ORed into the accumulator by the unstable ANE and LXA operations
+/// The real value depends on the chip and its temperature, but 0xEE is the most commonly observed
+const UNSTABLE_MAGIC: u8 = 0xEE;
+
+/// Applies the read-modify-write operation to the memory at the address, replacing the
+/// flags it affects, and returns the new value in memory
+fn modify_memory<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize,
+                 op: fn(u8, u8) -> (u8, StatusRegister)) -> u8 {
+    let addr = addr_fn(state);
+    let (result, flags) = op(state.fetch_byte_from_addr(addr), state.get_carry());
+    state.set_byte_at_addr(addr, result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
+    result
+}
+
+/// Applies the logical operation between the accumulator and value, replacing N and Z
+fn logic_on_acc<B: Bus>(state: &mut ComputerState<B>, value: u8, op: fn(u8, u8) -> (u8, StatusRegister)) {
+    let (result, flags) = op(state.acc.get(), value);
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, flags);
+}
+
+
+/// SLO (arithmetic shift left, then inclusive or with accumulator)
+fn slo_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let value = modify_memory(state, addr_fn, |value, _| asl(value));
+    logic_on_acc(state, value, or);
+}
+
+/// SLO (zero-page addressing mode)
+/// Opcode: 07
+pub fn slo_zp<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_zero_page_address) }
+/// SLO (zero-page X addressing mode)
+/// Opcode: 17
+pub fn slo_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// SLO (absolute addressing mode)
+/// Opcode: 0F
+pub fn slo_ab<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_absolute_address) }
+/// SLO (absolute X addressing mode)
+/// Opcode: 1F
+pub fn slo_abx<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// SLO (absolute Y addressing mode)
+/// Opcode: 1B
+pub fn slo_aby<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// SLO (indirect X addressing mode)
+/// Opcode: 03
+pub fn slo_inx<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// SLO (indirect Y addressing mode)
+/// Opcode: 13
+pub fn slo_iny<B: Bus>(state: &mut ComputerState<B>)
+{ slo_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// RLA (rotate left, then and with accumulator)
+fn rla_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let value = modify_memory(state, addr_fn, rol);
+    logic_on_acc(state, value, and);
+}
+
+/// RLA (zero-page addressing mode)
+/// Opcode: 27
+pub fn rla_zp<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_zero_page_address) }
+/// RLA (zero-page X addressing mode)
+/// Opcode: 37
+pub fn rla_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// RLA (absolute addressing mode)
+/// Opcode: 2F
+pub fn rla_ab<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_absolute_address) }
+/// RLA (absolute X addressing mode)
+/// Opcode: 3F
+pub fn rla_abx<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// RLA (absolute Y addressing mode)
+/// Opcode: 3B
+pub fn rla_aby<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// RLA (indirect X addressing mode)
+/// Opcode: 23
+pub fn rla_inx<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// RLA (indirect Y addressing mode)
+/// Opcode: 33
+pub fn rla_iny<B: Bus>(state: &mut ComputerState<B>)
+{ rla_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// SRE (logical shift right, then exclusive or with accumulator)
+fn sre_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let value = modify_memory(state, addr_fn, |value, _| lsr(value));
+    logic_on_acc(state, value, eor);
+}
+
+/// SRE (zero-page addressing mode)
+/// Opcode: 47
+pub fn sre_zp<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_zero_page_address) }
+/// SRE (zero-page X addressing mode)
+/// Opcode: 57
+pub fn sre_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// SRE (absolute addressing mode)
+/// Opcode: 4F
+pub fn sre_ab<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_absolute_address) }
+/// SRE (absolute X addressing mode)
+/// Opcode: 5F
+pub fn sre_abx<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// SRE (absolute Y addressing mode)
+/// Opcode: 5B
+pub fn sre_aby<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// SRE (indirect X addressing mode)
+/// Opcode: 43
+pub fn sre_inx<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// SRE (indirect Y addressing mode)
+/// Opcode: 53
+pub fn sre_iny<B: Bus>(state: &mut ComputerState<B>)
+{ sre_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// RRA (rotate right, then add to accumulator with carry)
+/// The carry out of the rotation is used as the carry into the addition
+fn rra_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let value = modify_memory(state, addr_fn, ror);
+    add_to_acc(state, value);
+}
+
+/// RRA (zero-page addressing mode)
+/// Opcode: 67
+pub fn rra_zp<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_zero_page_address) }
+/// RRA (zero-page X addressing mode)
+/// Opcode: 77
+pub fn rra_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// RRA (absolute addressing mode)
+/// Opcode: 6F
+pub fn rra_ab<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_absolute_address) }
+/// RRA (absolute X addressing mode)
+/// Opcode: 7F
+pub fn rra_abx<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// RRA (absolute Y addressing mode)
+/// Opcode: 7B
+pub fn rra_aby<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// RRA (indirect X addressing mode)
+/// Opcode: 63
+pub fn rra_inx<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// RRA (indirect Y addressing mode)
+/// Opcode: 73
+pub fn rra_iny<B: Bus>(state: &mut ComputerState<B>)
+{ rra_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// DCP (decrement memory, then compare with accumulator)
+fn dcp_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let addr = addr_fn(state);
+    let (result, _) = dec(state.fetch_byte_from_addr(addr));
+    state.set_byte_at_addr(addr, result);
+    let flags = cmp(state.acc.get(), result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
+}
+
+/// DCP (zero-page addressing mode)
+/// Opcode: C7
+pub fn dcp_zp<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_zero_page_address) }
+/// DCP (zero-page X addressing mode)
+/// Opcode: D7
+pub fn dcp_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// DCP (absolute addressing mode)
+/// Opcode: CF
+pub fn dcp_ab<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_absolute_address) }
+/// DCP (absolute X addressing mode)
+/// Opcode: DF
+pub fn dcp_abx<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// DCP (absolute Y addressing mode)
+/// Opcode: DB
+pub fn dcp_aby<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// DCP (indirect X addressing mode)
+/// Opcode: C3
+pub fn dcp_inx<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// DCP (indirect Y addressing mode)
+/// Opcode: D3
+pub fn dcp_iny<B: Bus>(state: &mut ComputerState<B>)
+{ dcp_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// ISC (increment memory, then subtract from accumulator with borrow)
+fn isc_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let addr = addr_fn(state);
+    let (result, _) = inc(state.fetch_byte_from_addr(addr));
+    state.set_byte_at_addr(addr, result);
+    sub_from_acc(state, result);
+}
+
+/// ISC (zero-page addressing mode)
+/// Opcode: E7
+pub fn isc_zp<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_zero_page_address) }
+/// ISC (zero-page X addressing mode)
+/// Opcode: F7
+pub fn isc_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_zero_page_x_address) }
+/// ISC (absolute addressing mode)
+/// Opcode: EF
+pub fn isc_ab<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_absolute_address) }
+/// ISC (absolute X addressing mode)
+/// Opcode: FF
+pub fn isc_abx<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_absolute_x_address) }
+/// ISC (absolute Y addressing mode)
+/// Opcode: FB
+pub fn isc_aby<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_absolute_y_address) }
+/// ISC (indirect X addressing mode)
+/// Opcode: E3
+pub fn isc_inx<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_indirect_x_address) }
+/// ISC (indirect Y addressing mode)
+/// Opcode: F3
+pub fn isc_iny<B: Bus>(state: &mut ComputerState<B>)
+{ isc_adapter(state, ComputerState::fetch_indirect_y_address) }
+
+
+/// LAX (load accumulator and X register with the same value)
+fn lax_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> u8) {
+    let value = addr_fn(state);
+    state.acc.set(value);
+    state.set_x(usize::from(value));
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
+}
+
+/// LAX (zero-page addressing mode)
+/// Opcode: A7
+pub fn lax_zp<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_zero_page) }
+/// LAX (zero-page Y addressing mode)
+/// Opcode: B7
+pub fn lax_zpy<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_zero_page_y) }
+/// LAX (absolute addressing mode)
+/// Opcode: AF
+pub fn lax_ab<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_absolute) }
+/// LAX (absolute Y addressing mode)
+/// Opcode: BF
+pub fn lax_aby<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_absolute_y) }
+/// LAX (indirect X addressing mode)
+/// Opcode: A3
+pub fn lax_inx<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_indirect_x) }
+/// LAX (indirect Y addressing mode)
+/// Opcode: B3
+pub fn lax_iny<B: Bus>(state: &mut ComputerState<B>)
+{ lax_adapter(state, ComputerState::fetch_indirect_y) }
+
+
+/// SAX (store the accumulator and X register)
+fn sax_adapter<B: Bus>(state: &mut ComputerState<B>, addr_fn: fn(&mut ComputerState<B>) -> usize) {
+    let addr = addr_fn(state);
+    state.set_byte_at_addr(addr, state.acc.get() & state.get_x() as u8)
+}
+
+/// SAX (zero-page addressing mode)
+/// Opcode: 87
+pub fn sax_zp<B: Bus>(state: &mut ComputerState<B>)
+{ sax_adapter(state, ComputerState::fetch_zero_page_address) }
+/// SAX (zero-page Y addressing mode)
+/// Opcode: 97
+pub fn sax_zpy<B: Bus>(state: &mut ComputerState<B>)
+{ sax_adapter(state, ComputerState::fetch_zero_page_y_address) }
+/// SAX (absolute addressing mode)
+/// Opcode: 8F
+pub fn sax_ab<B: Bus>(state: &mut ComputerState<B>)
+{ sax_adapter(state, ComputerState::fetch_absolute_address) }
+/// SAX (indirect X addressing mode)
+/// Opcode: 83
+pub fn sax_inx<B: Bus>(state: &mut ComputerState<B>)
+{ sax_adapter(state, ComputerState::fetch_indirect_x_address) }
+
+
+/// ANC (and with accumulator, then copy the negative flag into carry)
+/// Opcodes: 0B, 2B
+pub fn anc_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let (result, flags) = and(state.acc.get(), value);
+    state.acc.set(result);
+    let carry = StatusRegister::C.get_cond(flags.contains(StatusRegister::N));
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags.union(carry));
+}
+
+/// ALR (and with accumulator, then logical shift right the accumulator)
+/// Opcode: 4B
+pub fn alr_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let (result, flags) = lsr(state.acc.get() & value);
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, flags);
+}
+
+/// ARR (and with accumulator, then rotate right the accumulator)
+/// Carry and overflow come from bits 6 and 5 of the result, as the rotate shares the adder
+/// In decimal mode, each digit of the result is adjusted in the same way as ADC
+/// Opcode: 6B
+pub fn arr_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.acc.get() & state.fetch_immediate();
+    let carry = state.get_carry();
+    let mut result = (value >> 1) | (carry << 7);
+
+    let flags = if state.sta.contains(StatusRegister::D) {
+        // N and Z see the result before it has been adjusted
+        let mut flags =
+            get_zero_neg_flags(result).union(
+            StatusRegister::V.get_cond((value ^ result) & 0x40 != 0));
+
+        let (lo_digit, hi_digit) = (value & 0x0F, value >> 4);
+        if lo_digit + (lo_digit & 0x01) > 0x05 {
+            result = (result & 0xF0) | (result.wrapping_add(0x06) & 0x0F);
+        }
+        if hi_digit + (hi_digit & 0x01) > 0x05 {
+            result = result.wrapping_add(0x60);
+            flags |= StatusRegister::C;
+        }
+        flags
+    } else {
+        get_zero_neg_flags(result).union(
+        StatusRegister::C.get_cond(result & 0x40 != 0).union(
+        StatusRegister::V.get_cond(((result >> 6) ^ (result >> 5)) & 0x01 != 0)))
+    };
+
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS.union(StatusRegister::V), flags);
+}
+
+/// SBX (store the accumulator and X register into X, minus a value without borrow)
+/// Sets the flags in the same way as CMP
+/// Opcode: CB
+pub fn sbx_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let masked = state.acc.get() & state.get_x() as u8;
+    state.set_x(usize::from(masked.wrapping_sub(value)));
+    state.sta = state.sta.replace(ZERO_NEG_CARRY_FLAGS, cmp(masked, value));
+}
+
+/// USBC (subtract with carry)
+/// Behaves the same as the documented SBC immediate
+/// Opcode: EB
+pub fn usbc_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    sub_from_acc(state, value);
+}
+
+/// LAS (and memory with the stack pointer, then load the accumulator, X and stack pointer)
+/// Opcode: BB
+pub fn las_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute_y() & state.get_stk() as u8;
+    state.acc.set(value);
+    state.set_x(usize::from(value));
+    state.set_stk(usize::from(value));
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
+}
+
+
+// UNSTABLE OPERATIONS
+/// ANE (or the accumulator with a magic constant, then and with X and a value)
+/// Opcode: 8B
+pub fn ane_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let result = (state.acc.get() | UNSTABLE_MAGIC) & state.get_x() as u8 & value;
+    state.acc.set(result);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(result));
+}
+
+/// LXA (or the accumulator with a magic constant, then and with a value into A and X)
+/// Opcode: AB
+pub fn lxa_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
+    let result = (state.acc.get() | UNSTABLE_MAGIC) & value;
+    state.acc.set(result);
+    state.set_x(usize::from(result));
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(result));
+}
+
+/// Stores the value anded with the high byte of the base address plus one
+/// If indexing crosses a page, the high byte of the target address is replaced by the stored value
+fn store_high_and<B: Bus>(state: &mut ComputerState<B>, base_addr: usize, index: usize, value: u8) {
+    let addr = (base_addr + index) & 0xFFFF;
+    let result = value & ((base_addr >> 8) as u8).wrapping_add(1);
+    let addr = if (base_addr & 0xFF00) != (addr & 0xFF00) {
+        (usize::from(result) << 8) | (addr & 0x00FF)
+    } else {
+        addr
+    };
+    state.set_byte_at_addr(addr, result);
+}
+
+/// SHA (absolute Y addressing mode)
+/// Stores the accumulator anded with X and the high byte of the address plus one
+/// Opcode: 9F
+pub fn sha_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let base_addr = state.fetch_absolute_address();
+    let value = state.acc.get() & state.get_x() as u8;
+    store_high_and(state, base_addr, state.get_y(), value);
+}
+
+/// SHA (indirect Y addressing mode)
+/// Stores the accumulator anded with X and the high byte of the address plus one
+/// Opcode: 93
+pub fn sha_iny<B: Bus>(state: &mut ComputerState<B>) {
+    let indirect_addr = state.fetch_next_zp_addr();
+    let base_addr = state.fetch_zp_pointer(indirect_addr);
+    let value = state.acc.get() & state.get_x() as u8;
+    store_high_and(state, base_addr, state.get_y(), value);
+}
+
+/// SHX (absolute Y addressing mode)
+/// Stores X anded with the high byte of the address plus one
+/// Opcode: 9E
+pub fn shx_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let base_addr = state.fetch_absolute_address();
+    store_high_and(state, base_addr, state.get_y(), state.get_x() as u8);
+}
+
+/// SHY (absolute X addressing mode)
+/// Stores Y anded with the high byte of the address plus one
+/// Opcode: 9C
+pub fn shy_abx<B: Bus>(state: &mut ComputerState<B>) {
+    let base_addr = state.fetch_absolute_address();
+    store_high_and(state, base_addr, state.get_x(), state.get_y() as u8);
+}
+
+/// TAS (absolute Y addressing mode)
+/// Moves the accumulator anded with X into the stack pointer, then stores it in the same way as SHA
+/// Opcode: 9B
+pub fn tas_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let base_addr = state.fetch_absolute_address();
+    let value = state.acc.get() & state.get_x() as u8;
+    state.set_stk(usize::from(value));
+    store_high_and(state, base_addr, state.get_y(), value);
+}
+
+
+// NO OPERATIONS
+// These read their operand, but do nothing with it
+/// NOP (implied addressing mode)
+/// Opcodes: 1A, 3A, 5A, 7A, DA, FA
+pub fn nop_imp<B: Bus>(_: &mut ComputerState<B>) {}
+
+/// NOP (immediate addressing mode)
+/// Opcodes: 80, 82, 89, C2, E2
+pub fn nop_im<B: Bus>(state: &mut ComputerState<B>)
+{ state.fetch_immediate(); }
+/// NOP (zero-page addressing mode)
+/// Opcodes: 04, 44, 64
+pub fn nop_zp<B: Bus>(state: &mut ComputerState<B>)
+{ state.fetch_zero_page(); }
+/// NOP (zero-page X addressing mode)
+/// Opcodes: 14, 34, 54, 74, D4, F4
+pub fn nop_zpx<B: Bus>(state: &mut ComputerState<B>)
+{ state.fetch_zero_page_x(); }
+/// NOP (absolute addressing mode)
+/// Opcode: 0C
+pub fn nop_ab<B: Bus>(state: &mut ComputerState<B>)
+{ state.fetch_absolute(); }
+/// NOP (absolute X addressing mode)
+/// Opcodes: 1C, 3C, 5C, 7C, DC, FC
+pub fn nop_abx<B: Bus>(state: &mut ComputerState<B>)
+{ state.fetch_absolute_x(); }
+
+
+/// JAM (halt the processor)
+/// The processor gets stuck until it is reset, so the PC is left pointing at this instruction
+/// Opcodes: 02, 12, 22, 32, 42, 52, 62, 72, 92, B2, D2, F2
+pub fn jam<B: Bus>(state: &mut ComputerState<B>) {
+    let pc = state.pc.get();
+    state.pc.set(pc.wrapping_sub(1));
+    state.jammed = true;
+    state.stopped = true;
+}
@@ -1,31 +1,61 @@
-
-use crate::computer_state::ComputerState;
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
 use crate::computer_state::status_register::StatusRegister;
 
-pub fn nop(_: &mut ComputerState) {}
+pub fn nop<B: Bus>(_: &mut ComputerState<B>) {}
 
-/// BRK (Force Break)
-/// Opcode: 00
-pub fn brk(state: &mut ComputerState) {
+/// Address of the non-maskable interrupt vector
+pub const NMI_VECTOR: usize = 0xFFFA;
+/// Address of the reset vector
+pub const RESET_VECTOR: usize = 0xFFFC;
+/// Address of the interrupt request vector, which is shared with BRK
+pub const IRQ_VECTOR: usize = 0xFFFE;
+/// Number of cycles taken to enter an interrupt or reset
+pub const INTERRUPT_CYCLES: u8 = 7;
+
+/// Pushes the PC and status onto the stack, disables interrupts and jumps through the vector
+/// The pushed status only has the break flag set if the interrupt was caused by BRK
+pub(crate) fn enter_interrupt<B: Bus>(state: &mut ComputerState<B>, vector: usize, is_break: bool) {
     // Push program counter
-    state.mem.push_nibble_on_stack(state.mem.pc.get() as u16);
-    // Then push status with break
-    state.sta |= StatusRegister::B;
-    state.mem.push_on_stack(state.sta.as_byte());
-    // Set interrupt status
+    state.push_nibble_on_stack(state.pc.get() as u16);
+    // Then push status, with break only if this is a BRK
+    state.push_on_stack(state.sta.to_pushed_byte(is_break));
+    // Set interrupt status, and leave decimal mode on the 65C02
     state.sta |= StatusRegister::I;
+    if state.variant() == CpuVariant::Wdc65C02 {
+        state.sta = state.sta.difference(StatusRegister::D);
+    }
     // Set PC to interrupt vector
-    let interrupt_vector = state.mem.fetch_nibble_from_addr(0xFFFE);
-    state.mem.pc.set(usize::from(interrupt_vector));
+    let interrupt_vector = state.fetch_nibble_from_addr(vector);
+    state.pc.set(usize::from(interrupt_vector));
+}
+
+/// BRK (Force Break)
+/// BRK is followed by a padding byte which is skipped, so the pushed return address is PC+2
+/// Opcode: 00
+pub fn brk<B: Bus>(state: &mut ComputerState<B>) {
+    state.fetch_next_byte();
+    enter_interrupt(state, IRQ_VECTOR, true);
 }
 
 /// RTI (Return from interrupt)
 /// Opcode: 40
-pub fn rti(state: &mut ComputerState) {
-    let status = state.mem.pop_from_stack();
-    let pc = state.mem.pop_nibble_from_stack();
+pub fn rti<B: Bus>(state: &mut ComputerState<B>) {
+    let status = state.pop_from_stack();
+    let pc = state.pop_nibble_from_stack();
 
-    state.sta |= StatusRegister::from_byte(status);
-    state.mem.pc.set(usize::from(pc));
+    state.sta = StatusRegister::from_pulled_byte(status);
+    state.pc.set(usize::from(pc));
 }
 
+/// WAI (Wait for interrupt, 65C02 only)
+/// Opcode: CB
+pub fn wai<B: Bus>(state: &mut ComputerState<B>) {
+    state.waiting = true;
+}
+
+/// STP (Stop the processor until it is reset, 65C02 only)
+/// Opcode: DB
+pub fn stp<B: Bus>(state: &mut ComputerState<B>) {
+    state.stopped = true;
+}
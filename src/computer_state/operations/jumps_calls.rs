@@ -1,32 +1,52 @@
-use crate::computer_state::ComputerState;
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
 
 /// JMP (absolute addressing mode)
 /// Opcode: 4C
-pub fn jmp_ab(state: &mut ComputerState) {
-    let addr = state.mem.fetch_next_nibble();
-    state.mem.pc.set(usize::from(addr))
+pub fn jmp_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_next_nibble();
+    state.pc.set(usize::from(addr))
 }
 
 /// JMP (indirect addressing mode)
+/// The NMOS 6502 does not carry into the high byte of the pointer, so JMP ($xxFF) reads the high
+/// byte of the target from $xx00. The 65C02 fixes this
 /// Opcode: 6C
-pub fn jmp_in(state: &mut ComputerState) {
-    let addr = state.mem.fetch_indirect();
-    state.mem.pc.set(usize::from(addr))
+pub fn jmp_in<B: Bus>(state: &mut ComputerState<B>) {
+    let ptr = state.fetch_absolute_address();
+    let addr = if state.variant() == CpuVariant::Nmos6502 && ptr & 0xFF == 0xFF {
+        let lo_byte = u16::from(state.fetch_byte_from_addr(ptr));
+        let hi_byte = u16::from(state.fetch_byte_from_addr(ptr & 0xFF00));
+        (hi_byte << 8) + lo_byte
+    } else {
+        state.fetch_nibble_from_addr(ptr)
+    };
+    state.pc.set(usize::from(addr))
+}
+
+/// JMP (absolute indexed indirect addressing mode, 65C02 only)
+/// Opcode: 7C
+pub fn jmp_inx<B: Bus>(state: &mut ComputerState<B>) {
+    let ptr = (state.fetch_absolute_address() + state.get_x()) & 0xFFFF;
+    let addr = state.fetch_nibble_from_addr(ptr);
+    state.pc.set(usize::from(addr))
 }
 
 
 /// JSR (jump to subroutine)
+/// Pushes the address of the last byte of the JSR instruction, rather than the next instruction
 /// Opcode: 20
-pub fn jsr(state: &mut ComputerState) {
-    let sub_addr = state.mem.fetch_next_nibble();
-    let ret_addr = state.mem.pc.get();
-    state.mem.push_nibble_on_stack(ret_addr as u16);
-    state.mem.pc.set(sub_addr as usize)
+pub fn jsr<B: Bus>(state: &mut ComputerState<B>) {
+    let sub_addr = state.fetch_next_nibble();
+    let ret_addr = (state.pc.get() as u16).wrapping_sub(1);
+    state.push_nibble_on_stack(ret_addr);
+    state.pc.set(sub_addr as usize)
 }
 
 /// RTS (return from subroutine)
+/// Adds one to the popped address to return to the instruction after the JSR
 /// Opcode: 60
-pub fn rts(state: &mut ComputerState) {
-    let ret_addr = state.mem.pop_nibble_from_stack();
-    state.mem.pc.set(ret_addr as usize);
+pub fn rts<B: Bus>(state: &mut ComputerState<B>) {
+    let ret_addr = state.pop_nibble_from_stack().wrapping_add(1);
+    state.pc.set(ret_addr as usize);
 }
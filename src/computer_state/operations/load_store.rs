@@ -1,239 +1,282 @@
 use crate::computer_state::ComputerState;
-use crate::computer_state::memory::Memory;
-use crate::computer_state::status_register::get_zero_neg_flags;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, ZERO_NEG_FLAGS};
 
-fn lda(computer_state: &mut ComputerState, value: u8) {
+fn lda<B: Bus>(computer_state: &mut ComputerState<B>, value: u8) {
     computer_state.acc.set(value);
-    computer_state.sta |= get_zero_neg_flags(value);
+    computer_state.sta = computer_state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
 }
 
 /// LDA (immediate addressing mode)
 /// Opcode: A9
-pub fn lda_im(state: &mut ComputerState) {
-    let value = Memory::fetch_immediate(&mut state.mem);
+pub fn lda_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
     lda(state, value);
 }
 
 /// LDA (zero-page addressing mode)
 /// Opcode: A5
-pub fn lda_zp(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page(&mut state.mem);
+pub fn lda_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page();
     lda(state, value);
 }
 
 /// LDA (zero-page X addressing mode)
 /// Opcode: B5
-pub fn lda_zpx(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page_x(&mut state.mem);
+pub fn lda_zpx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page_x();
     lda(state, value);
 }
 
 /// LDA (absolute addressing mode)
 /// Opcode: AD
-pub fn lda_ab(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute(&mut state.mem);
+pub fn lda_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute();
     lda(state, value);
 }
 
 /// LDA (absolute X addressing mode)
 /// Opcode: BD
-pub fn lda_abx(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute_x(&mut state.mem);
+pub fn lda_abx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute_x();
     lda(state, value);
 }
 
 /// LDA (absolute Y addressing mode)
 /// Opcode: B9
-pub fn lda_aby(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute_y(&mut state.mem);
+pub fn lda_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute_y();
     lda(state, value);
 }
 
 /// LDA (indirect X addressing mode)
 /// Opcode: A1
-pub fn lda_inx(state: &mut ComputerState) {
-    let value = Memory::fetch_indirect_x(&mut state.mem);
+pub fn lda_inx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_indirect_x();
     lda(state, value);
 }
 
 /// LDA (indirect Y addressing mode)
 /// Opcode: B1
-pub fn lda_iny(state: &mut ComputerState) {
-    let value = Memory::fetch_indirect_y(&mut state.mem);
+pub fn lda_iny<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_indirect_y();
     lda(state, value);
 }
 
+/// LDA (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: B2
+pub fn lda_izp<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_indirect_zp();
+    lda(state, value);
+}
 
-fn ldx(state: &mut ComputerState, value: u8) {
+
+fn ldx<B: Bus>(state: &mut ComputerState<B>, value: u8) {
     state.set_x(usize::from(value));
-    state.sta |= get_zero_neg_flags(value);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
 }
 
 /// LDX (immediate addressing mode)
 /// Opcode: A2
-pub fn ldx_im(state: &mut ComputerState) {
-    let value = Memory::fetch_immediate(&mut state.mem);
+pub fn ldx_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
     ldx(state, value);
 }
 
 /// LDX (zero-page addressing mode)
 /// Opcode: A6
-pub fn ldx_zp(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page(&mut state.mem);
+pub fn ldx_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page();
     ldx(state, value);
 }
 
 /// LDX (zero-page Y addressing mode)
 /// Opcode: B6
-pub fn ldx_zpy(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page_y(&mut state.mem);
+pub fn ldx_zpy<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page_y();
     ldx(state, value);
 }
 
 /// LDX (absolute addressing mode)
 /// Opcode: AE
-pub fn ldx_ab(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute(&mut state.mem);
+pub fn ldx_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute();
     ldx(state, value);
 }
 
 /// LDX (absolute Y addressing mode)
 /// Opcode: BE
-pub fn ldx_aby(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute_y(&mut state.mem);
+pub fn ldx_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute_y();
     ldx(state, value);
 }
 
 
-fn ldy(state: &mut ComputerState, value: u8) {
+fn ldy<B: Bus>(state: &mut ComputerState<B>, value: u8) {
     state.set_y(usize::from(value));
-    state.sta |= get_zero_neg_flags(value);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
 }
 
 /// LDY (immediate addressing mode)
 /// Opcode: A2
-pub fn ldy_im(state: &mut ComputerState) {
-    let value = Memory::fetch_immediate(&mut state.mem);
+pub fn ldy_im<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_immediate();
     ldy(state, value);
 }
 
 /// LDY (zero-page addressing mode)
 /// Opcode: A6
-pub fn ldy_zp(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page(&mut state.mem);
+pub fn ldy_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page();
     ldy(state, value);
 }
 
 /// LDY (zero-page X addressing mode)
 /// Opcode: B6
-pub fn ldy_zpx(state: &mut ComputerState) {
-    let value = Memory::fetch_zero_page_x(&mut state.mem);
+pub fn ldy_zpx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_zero_page_x();
     ldy(state, value);
 }
 
 /// LDY (absolute addressing mode)
 /// Opcode: AE
-pub fn ldy_ab(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute(&mut state.mem);
+pub fn ldy_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute();
     ldy(state, value);
 }
 
 /// LDY (absolute X addressing mode)
 /// Opcode: BE
-pub fn ldy_abx(state: &mut ComputerState) {
-    let value = Memory::fetch_absolute_x(&mut state.mem);
+pub fn ldy_abx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.fetch_absolute_x();
     ldy(state, value);
 }
 
 
 /// STA (zero-page addressing mode)
 /// Opcode: 95
-pub fn sta_zp(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 /// STA (zero-page X addressing mode)
 /// Opcode: 85
-pub fn sta_zpx(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_x_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_zpx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_x_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 /// STA (absolute addressing mode)
 /// Opcode: 8D
-pub fn sta_ab(state: &mut ComputerState) {
-    let addr = Memory::fetch_absolute_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 /// STA (absolute X addressing mode)
 /// Opcode: 9D
-pub fn sta_abx(state: &mut ComputerState) {
-    let addr = Memory::fetch_absolute_x_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_abx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_x_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 /// STA (absolute Y addressing mode)
 /// Opcode: 99
-pub fn sta_aby(state: &mut ComputerState) {
-    let addr = Memory::fetch_absolute_y_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_aby<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_y_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 /// STA (indirect X addressing mode)
 /// Opcode: 81
-pub fn sta_inx(state: &mut ComputerState) {
-    let addr = Memory::fetch_indirect_x_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_inx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_indirect_x_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 /// STA (indirect Y addressing mode)
 /// Opcode: 91
-pub fn sta_iny(state: &mut ComputerState) {
-    let addr = Memory::fetch_indirect_y_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.acc.get())
+pub fn sta_iny<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_indirect_y_address();
+    state.set_byte_at_addr(addr, state.acc.get())
+}
+
+/// STA (zero-page indirect addressing mode, 65C02 only)
+/// Opcode: 92
+pub fn sta_izp<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_indirect_zp_address();
+    state.set_byte_at_addr(addr, state.acc.get())
 }
 
 
 /// STX (zero-page addressing mode)
 /// Opcode: 86
-pub fn stx_zp(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_x() as u8)
+pub fn stx_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_address();
+    state.set_byte_at_addr(addr, state.get_x() as u8)
 }
 
 /// STX (zero-page Y addressing mode)
 /// Opcode: 96
-pub fn stx_zpy(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_y_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_x() as u8)
+pub fn stx_zpy<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_y_address();
+    state.set_byte_at_addr(addr, state.get_x() as u8)
 }
 
 /// STX (absolute addressing mode)
 /// Opcode: 8E
-pub fn stx_ab(state: &mut ComputerState) {
-    let addr = Memory::fetch_absolute_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_x() as u8)
+pub fn stx_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_address();
+    state.set_byte_at_addr(addr, state.get_x() as u8)
 }
 
 
 /// STY (zero-page addressing mode)
 /// Opcode: 84
-pub fn sty_zp(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_y() as u8)
+pub fn sty_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_address();
+    state.set_byte_at_addr(addr, state.get_y() as u8)
 }
 
 /// STY (zero-page X addressing mode)
 /// Opcode: 94
-pub fn sty_zpx(state: &mut ComputerState) {
-    let addr = Memory::fetch_zero_page_x_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_y() as u8)
+pub fn sty_zpx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_x_address();
+    state.set_byte_at_addr(addr, state.get_y() as u8)
 }
 
 /// STY (absolute addressing mode)
 /// Opcode: 8C
-pub fn sty_ab(state: &mut ComputerState) {
-    let addr = Memory::fetch_absolute_address(&mut state.mem);
-    state.mem.set_byte_at_addr(addr, state.get_y() as u8)
+pub fn sty_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_address();
+    state.set_byte_at_addr(addr, state.get_y() as u8)
+}
+
+
+/// STZ (zero-page addressing mode, 65C02 only)
+/// Opcode: 64
+pub fn stz_zp<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_address();
+    state.set_byte_at_addr(addr, 0)
+}
+
+/// STZ (zero-page X addressing mode, 65C02 only)
+/// Opcode: 74
+pub fn stz_zpx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_zero_page_x_address();
+    state.set_byte_at_addr(addr, 0)
+}
+
+/// STZ (absolute addressing mode, 65C02 only)
+/// Opcode: 9C
+pub fn stz_ab<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_address();
+    state.set_byte_at_addr(addr, 0)
+}
+
+/// STZ (absolute X addressing mode, 65C02 only)
+/// Opcode: 9E
+pub fn stz_abx<B: Bus>(state: &mut ComputerState<B>) {
+    let addr = state.fetch_absolute_x_address();
+    state.set_byte_at_addr(addr, 0)
 }
@@ -1,30 +1,31 @@
 use crate::computer_state::ComputerState;
-use crate::computer_state::status_register::get_zero_neg_flags;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, ZERO_NEG_FLAGS};
 
 /// TAX (transfer accumulator to X)
 /// Opcode: AA
-pub fn tax(state: &mut ComputerState) {
+pub fn tax<B: Bus>(state: &mut ComputerState<B>) {
     state.set_x(usize::from(state.acc.get()));
-    state.sta |= get_zero_neg_flags(state.acc.get())
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(state.acc.get()));
 }
 
 /// TAY (transfer accumulator to Y)
 /// Opcode: A8
-pub fn tay(state: &mut ComputerState) {
+pub fn tay<B: Bus>(state: &mut ComputerState<B>) {
     state.set_y(usize::from(state.acc.get()));
-    state.sta |= get_zero_neg_flags(state.acc.get())
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(state.acc.get()));
 }
 
 /// TXA (transfer X to accumulator)
 /// Opcode: 8A
-pub fn txa(state: &mut ComputerState) {
+pub fn txa<B: Bus>(state: &mut ComputerState<B>) {
     state.acc.set(state.get_x() as u8);
-    state.sta |= get_zero_neg_flags(state.acc.get())
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(state.acc.get()));
 }
 
 /// TYA (transfer Y to accumulator)
 /// Opcode: 98
-pub fn tya(state: &mut ComputerState) {
+pub fn tya<B: Bus>(state: &mut ComputerState<B>) {
     state.acc.set(state.get_y() as u8);
-    state.sta |= get_zero_neg_flags(state.acc.get())
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(state.acc.get()));
 }
\ No newline at end of file
@@ -1,40 +1,72 @@
 use crate::computer_state::ComputerState;
-use crate::computer_state::status_register::{get_zero_neg_flags, StatusRegister};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::{get_zero_neg_flags, StatusRegister, ZERO_NEG_FLAGS};
 
 /// TSX (transfer stack pointer to X)
 /// Opcode: BA
-pub fn tsx(state: &mut ComputerState) {
+pub fn tsx<B: Bus>(state: &mut ComputerState<B>) {
     state.set_x(state.get_stk());
-    state.sta |= get_zero_neg_flags(state.get_x() as u8);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(state.get_x() as u8));
 }
 
 /// TXS (transfer X to stack pointer)
 /// Opcode: 9A
-pub fn txs(state: &mut ComputerState) {
+pub fn txs<B: Bus>(state: &mut ComputerState<B>) {
     state.set_stk(state.get_x());
 }
 
 /// PHA (push accumulator)
 /// Opcode: 48
-pub fn pha(state: &mut ComputerState) {
-    state.mem.push_on_stack(state.acc.get())
+pub fn pha<B: Bus>(state: &mut ComputerState<B>) {
+    state.push_on_stack(state.acc.get())
 }
 
 /// PHP (push processor status)
+/// The pushed status always has the break flag and unused bit set
 /// Opcode: 08
-pub fn php(state: &mut ComputerState) {
-    state.mem.push_on_stack(state.sta.as_byte())
+pub fn php<B: Bus>(state: &mut ComputerState<B>) {
+    state.push_on_stack(state.sta.to_pushed_byte(true))
 }
 
 /// PLA (pull accumulator)
 /// Opcode: 68
-pub fn pla(state: &mut ComputerState) {
-    state.acc.set(state.mem.pop_from_stack());
-    state.sta |= get_zero_neg_flags(state.acc.get());
+pub fn pla<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.pop_from_stack();
+    state.acc.set(value);
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
 }
 
 /// PLP (pull processor status)
+/// The break flag and unused bit of the pulled status are ignored
 /// Opcode: 28
-pub fn plp(state: &mut ComputerState) {
-    state.sta = StatusRegister::from_byte(state.mem.pop_from_stack());
+pub fn plp<B: Bus>(state: &mut ComputerState<B>) {
+    state.sta = StatusRegister::from_pulled_byte(state.pop_from_stack());
+}
+
+/// PHX (push X register, 65C02 only)
+/// Opcode: DA
+pub fn phx<B: Bus>(state: &mut ComputerState<B>) {
+    state.push_on_stack(state.get_x() as u8)
+}
+
+/// PHY (push Y register, 65C02 only)
+/// Opcode: 5A
+pub fn phy<B: Bus>(state: &mut ComputerState<B>) {
+    state.push_on_stack(state.get_y() as u8)
+}
+
+/// PLX (pull X register, 65C02 only)
+/// Opcode: FA
+pub fn plx<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.pop_from_stack();
+    state.set_x(usize::from(value));
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
+}
+
+/// PLY (pull Y register, 65C02 only)
+/// Opcode: 7A
+pub fn ply<B: Bus>(state: &mut ComputerState<B>) {
+    let value = state.pop_from_stack();
+    state.set_y(usize::from(value));
+    state.sta = state.sta.replace(ZERO_NEG_FLAGS, get_zero_neg_flags(value));
 }
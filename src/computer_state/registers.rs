@@ -33,21 +33,15 @@ impl ProgramCounter {
     pub fn set(&mut self, new_pc: usize)
     { self.pc = new_pc as u16 }
 
-    /// Adds an unsigned offset to the PC
+    /// Adds an unsigned offset to the PC, wrapping around at the end of memory
     pub fn add_unsigned(&mut self, value: u8)
-    { self.pc += u16::from(value) }
+    { self.pc = self.pc.wrapping_add(u16::from(value)) }
 
     /// Adds the raw signed offset to the PC.
     /// Although this method takes an unsigned value,
     /// this value will be reinterpreted as a signed value
     pub fn add_signed(&mut self, value: i8)
-    {
-        if value > 0 {
-            self.pc = self.pc.wrapping_add(value as u16)
-        } else {
-            self.pc = self.pc.wrapping_sub(value.wrapping_abs() as u16)
-        }
-    }
+    { self.pc = self.pc.wrapping_add_signed(i16::from(value)) }
 }
 
 
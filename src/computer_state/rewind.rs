@@ -0,0 +1,225 @@
+use std::collections::VecDeque;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::snapshot::{StateReader, StateWriter};
+use crate::computer_state::status_register::StatusRegister;
+
+// Reverse execution
+// While rewinding is enabled, each step records the registers before it and the bytes of memory it
+// overwrote, so it can be undone. Only the most recent steps are kept
+// Plain memory is restored through the bus as a load, with the value the bus peeked before the write
+// Writes to anything else, such as bank registers, devices and ROM, cannot be undone that way, so the
+// whole state of the bus is saved before the first of them in a step and loaded again to undo it
+
+/// Registers and execution state of the processor before a step
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+struct Registers {
+    pc: u16,
+    acc: u8,
+    x: usize,
+    y: usize,
+    stk: u8,
+    sta: u8,
+    cycles: u64,
+    irq_line: bool,
+    nmi_line: bool,
+    nmi_level: bool,
+    nmi_pending: bool,
+    waiting: bool,
+    stopped: bool,
+    jammed: bool,
+}
+
+/// How to undo a write
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum WriteUndo {
+    /// Address and previous value of a byte of plain memory
+    Byte(u16, u8),
+    /// State of the whole bus before the write
+    Bus(Vec<u8>),
+}
+
+/// Everything needed to undo a single step
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct StepDelta {
+    registers: Registers,
+    /// How to undo every write the step made, in the order they were written
+    writes: Vec<WriteUndo>,
+}
+
+/// Bounded history of the most recent steps
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RewindBuffer {
+    steps: VecDeque<StepDelta>,
+    capacity: usize,
+    /// Step being executed, which is added to the history once it finishes
+    current: Option<StepDelta>,
+}
+
+impl RewindBuffer {
+    /// Constructs a history which keeps up to the given number of steps
+    pub fn new(capacity: usize) -> RewindBuffer {
+        RewindBuffer { steps: VecDeque::with_capacity(capacity), capacity, current: None }
+    }
+
+    /// Returns the number of steps which are kept
+    pub const fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of steps which can be undone
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Returns the cycle count before the oldest step which can be undone
+    pub fn oldest_cycle(&self) -> Option<u64> {
+        self.steps.front().map(|step| step.registers.cycles)
+    }
+
+    fn begin(&mut self, registers: Registers) {
+        self.current = Some(StepDelta { registers, writes: Vec::new() });
+    }
+
+    fn record_write(&mut self, undo: WriteUndo) {
+        if let Some(step) = &mut self.current {
+            step.writes.push(undo);
+        }
+    }
+
+    /// Returns whether the state of the bus has been saved during the step being executed
+    fn has_bus_state(&self) -> bool {
+        self.current.as_ref().is_some_and(|step| step.writes.iter().any(|undo| matches!(undo, WriteUndo::Bus(_))))
+    }
+
+    fn finish(&mut self) {
+        let Some(step) = self.current.take() else {
+            return;
+        };
+        if self.capacity == 0 {
+            return;
+        }
+        if self.steps.len() == self.capacity {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+    }
+}
+
+impl<B: Bus> ComputerState<B> {
+    /// Starts recording the given number of most recent steps, so that they can be undone
+    /// Any history already recorded is discarded
+    pub fn enable_rewind(&mut self, capacity: usize) {
+        self.rewind = Some(RewindBuffer::new(capacity));
+    }
+
+    /// Stops recording steps, and discards the history
+    pub fn disable_rewind(&mut self) {
+        self.rewind = None;
+    }
+
+    /// Returns the history of steps, if rewinding is enabled
+    pub const fn rewind_history(&self) -> Option<&RewindBuffer> {
+        self.rewind.as_ref()
+    }
+
+    /// Undoes the most recent step
+    /// Returns false if there is no step to undo
+    pub fn step_back(&mut self) -> bool {
+        let Some(step) = self.rewind.as_mut().and_then(|rewind| rewind.steps.pop_back()) else {
+            return false;
+        };
+        for undo in step.writes.iter().rev() {
+            match undo {
+                WriteUndo::Byte(addr, value) => self.bus.load(*addr, *value),
+                // The state was saved by this bus, so it always loads
+                WriteUndo::Bus(state) => self.bus.load_state(&mut StateReader::new(state)).unwrap_or_default(),
+            }
+        }
+        self.restore_registers(step.registers);
+        true
+    }
+
+    /// Undoes steps until the cycle count is at or before the given cycle
+    /// Returns false without undoing anything if the history does not reach back that far
+    pub fn rewind_to_cycle(&mut self, cycle: u64) -> bool {
+        let reachable = self.cycles <= cycle ||
+            self.rewind_history().and_then(RewindBuffer::oldest_cycle).is_some_and(|oldest| oldest <= cycle);
+        if !reachable {
+            return false;
+        }
+        while self.cycles > cycle && self.step_back() {}
+        true
+    }
+
+    /// Records the registers before a step, if rewinding is enabled
+    pub(crate) fn begin_rewind_step(&mut self) {
+        let registers = self.save_registers();
+        if let Some(rewind) = &mut self.rewind {
+            rewind.begin(registers);
+        }
+    }
+
+    /// Adds the step being executed to the history, if rewinding is enabled
+    pub(crate) fn finish_rewind_step(&mut self) {
+        if let Some(rewind) = &mut self.rewind {
+            rewind.finish();
+        }
+    }
+
+    /// Records how to undo a write to the address, if rewinding is enabled
+    /// Saving the state of the bus once covers every later write in the step
+    pub(crate) fn record_rewind_write(&mut self, addr: u16) {
+        let Some(rewind) = &mut self.rewind else {
+            return;
+        };
+        if self.bus.is_plain_memory(addr) {
+            rewind.record_write(WriteUndo::Byte(addr, self.bus.peek(addr)));
+        } else if !rewind.has_bus_state() {
+            let mut state = StateWriter::new();
+            self.bus.save_state(&mut state);
+            rewind.record_write(WriteUndo::Bus(state.into_bytes()));
+        }
+    }
+
+    fn save_registers(&self) -> Registers {
+        Registers {
+            pc: self.pc.get() as u16,
+            acc: self.acc.get(),
+            x: self.x,
+            y: self.y,
+            stk: self.stk,
+            sta: self.sta.as_byte(),
+            cycles: self.cycles,
+            irq_line: self.irq_line,
+            nmi_line: self.nmi_line,
+            nmi_level: self.nmi_level,
+            nmi_pending: self.nmi_pending,
+            waiting: self.waiting,
+            stopped: self.stopped,
+            jammed: self.jammed,
+        }
+    }
+
+    fn restore_registers(&mut self, registers: Registers) {
+        self.pc.set(usize::from(registers.pc));
+        self.acc.set(registers.acc);
+        self.x = registers.x;
+        self.y = registers.y;
+        self.stk = registers.stk;
+        self.sta = StatusRegister::from_byte(registers.sta);
+        self.cycles = registers.cycles;
+        self.irq_line = registers.irq_line;
+        self.nmi_line = registers.nmi_line;
+        self.nmi_level = registers.nmi_level;
+        self.nmi_pending = registers.nmi_pending;
+        self.waiting = registers.waiting;
+        self.stopped = registers.stopped;
+        self.jammed = registers.jammed;
+    }
+}
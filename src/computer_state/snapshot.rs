@@ -0,0 +1,367 @@
+use std::fmt;
+
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::IllegalOpcodePolicy;
+use crate::computer_state::rewind::RewindBuffer;
+use crate::computer_state::status_register::StatusRegister;
+
+// Binary snapshots of the whole machine
+// A snapshot is the magic bytes and the format version, followed by chunks. Each chunk is a four byte
+// tag and the length of its data, so loaders skip chunks added by later versions, and ignore fields
+// appended to the end of a chunk. Fields appended to a chunk must be read with a default, so that
+// older snapshots which end before them still load
+// The format version is only increased for changes which older loaders cannot skip
+
+/// Bytes every snapshot starts with
+pub const MAGIC: [u8; 4] = *b"M65S";
+/// Version of the snapshot format written by this emulator
+pub const FORMAT_VERSION: u16 = 1;
+
+/// Registers, cycle count, interrupt lines and execution state of the processor
+const CPU_CHUNK: [u8; 4] = *b"CPU ";
+/// Memory and devices, written by the bus
+const BUS_CHUNK: [u8; 4] = *b"BUS ";
+
+/// Reason a snapshot could not be loaded
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SnapshotError {
+    /// The data does not start with the snapshot magic bytes
+    NotASnapshot,
+    /// The snapshot was written by a newer, incompatible version of the format
+    UnsupportedVersion(u16),
+    /// The snapshot ends in the middle of a chunk or field
+    Truncated,
+    /// A required chunk is missing
+    MissingChunk([u8; 4]),
+    /// The snapshot holds a different amount of memory than the machine it is loaded into
+    SizeMismatch { expected: usize, found: usize },
+    /// A field holds a value which is out of range
+    InvalidValue { field: &'static str, value: u8 },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::NotASnapshot =>
+                write!(f, "not a snapshot"),
+            SnapshotError::UnsupportedVersion(version) =>
+                write!(f, "snapshot format version {version} is newer than the supported version {FORMAT_VERSION}"),
+            SnapshotError::Truncated =>
+                write!(f, "snapshot is truncated"),
+            SnapshotError::MissingChunk(tag) =>
+                write!(f, "snapshot has no {} chunk", String::from_utf8_lossy(tag).trim_end()),
+            SnapshotError::SizeMismatch { expected, found } =>
+                write!(f, "snapshot holds {found} bytes of memory where the machine has {expected}"),
+            SnapshotError::InvalidValue { field, value } =>
+                write!(f, "snapshot has invalid {field} {value}"),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+/// Builds the data of a snapshot, with every value in little endian
+#[derive(Default)]
+pub struct StateWriter {
+    data: Vec<u8>,
+}
+
+impl StateWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.data.push(value);
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_u8(u8::from(value));
+    }
+
+    pub fn write_u16(&mut self, value: u16) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_u64(&mut self, value: u64) {
+        self.data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes the bytes preceded by their length
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_u32(bytes.len() as u32);
+        self.data.extend_from_slice(bytes);
+    }
+
+    /// Writes whatever the function writes preceded by its length, so that it can be read by itself
+    pub fn write_nested(&mut self, write: impl FnOnce(&mut StateWriter)) {
+        let mut nested = StateWriter::new();
+        write(&mut nested);
+        self.write_bytes(&nested.data);
+    }
+
+    fn write_raw(&mut self, bytes: &[u8]) {
+        self.data.extend_from_slice(bytes);
+    }
+}
+
+/// Reads the data of a snapshot, in the order it was written
+pub struct StateReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> StateReader<'a> {
+    pub const fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Returns whether there is anything left to read, such as fields appended by a later version
+    pub const fn has_more(&self) -> bool {
+        !self.data.is_empty()
+    }
+
+    fn read_raw(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if self.data.len() < len {
+            return Err(SnapshotError::Truncated);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], SnapshotError> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.read_raw(N)?);
+        Ok(array)
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, SnapshotError> {
+        self.read_array::<1>().map(|[value]| value)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, SnapshotError> {
+        self.read_u8().map(|value| value != 0)
+    }
+
+    pub fn read_u16(&mut self) -> Result<u16, SnapshotError> {
+        self.read_array().map(u16::from_le_bytes)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, SnapshotError> {
+        self.read_array().map(u32::from_le_bytes)
+    }
+
+    pub fn read_u64(&mut self) -> Result<u64, SnapshotError> {
+        self.read_array().map(u64::from_le_bytes)
+    }
+
+    /// Reads bytes written with their length
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], SnapshotError> {
+        let len = self.read_u32()? as usize;
+        self.read_raw(len)
+    }
+
+    /// Reads bytes written with their length into the buffer, which must be the same length
+    pub fn read_bytes_into(&mut self, buffer: &mut [u8]) -> Result<(), SnapshotError> {
+        let bytes = self.read_bytes()?;
+        if bytes.len() != buffer.len() {
+            return Err(SnapshotError::SizeMismatch { expected: buffer.len(), found: bytes.len() });
+        }
+        buffer.copy_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Returns a reader for data written with `StateWriter::write_nested`
+    pub fn read_nested(&mut self) -> Result<StateReader<'a>, SnapshotError> {
+        self.read_bytes().map(StateReader::new)
+    }
+}
+
+const fn variant_to_byte(variant: CpuVariant) -> u8 {
+    match variant {
+        CpuVariant::Nmos6502 => 0,
+        CpuVariant::Wdc65C02 => 1,
+    }
+}
+
+const fn variant_from_byte(value: u8) -> Result<CpuVariant, SnapshotError> {
+    match value {
+        0 => Ok(CpuVariant::Nmos6502),
+        1 => Ok(CpuVariant::Wdc65C02),
+        _ => Err(SnapshotError::InvalidValue { field: "processor variant", value }),
+    }
+}
+
+const fn policy_to_byte(policy: IllegalOpcodePolicy) -> u8 {
+    match policy {
+        IllegalOpcodePolicy::Emulate => 0,
+        IllegalOpcodePolicy::Halt => 1,
+        IllegalOpcodePolicy::Trap => 2,
+        IllegalOpcodePolicy::Nop => 3,
+    }
+}
+
+const fn policy_from_byte(value: u8) -> Result<IllegalOpcodePolicy, SnapshotError> {
+    match value {
+        0 => Ok(IllegalOpcodePolicy::Emulate),
+        1 => Ok(IllegalOpcodePolicy::Halt),
+        2 => Ok(IllegalOpcodePolicy::Trap),
+        3 => Ok(IllegalOpcodePolicy::Nop),
+        _ => Err(SnapshotError::InvalidValue { field: "illegal opcode policy", value }),
+    }
+}
+
+impl<B: Bus> ComputerState<B> {
+    /// Returns a snapshot of the processor and everything on its bus
+    /// The illegal opcode handler is a function, so it is not saved
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut snapshot = StateWriter::new();
+        snapshot.write_raw(&MAGIC);
+        snapshot.write_u16(FORMAT_VERSION);
+
+        snapshot.write_raw(&CPU_CHUNK);
+        snapshot.write_nested(|cpu| self.save_cpu(cpu));
+        snapshot.write_raw(&BUS_CHUNK);
+        snapshot.write_nested(|bus| self.bus.save_state(bus));
+        snapshot.into_bytes()
+    }
+
+    /// Restores the processor and its bus from a snapshot
+    /// The bus must be set up like the one which was saved, as only the contents of its memory and
+    /// devices are restored. If loading fails, the machine is left as it was
+    pub fn load_state(&mut self, snapshot: &[u8]) -> Result<(), SnapshotError> {
+        let mut snapshot = StateReader::new(snapshot);
+        if snapshot.read_raw(MAGIC.len()).ok() != Some(&MAGIC[..]) {
+            return Err(SnapshotError::NotASnapshot);
+        }
+        let version = snapshot.read_u16()?;
+        if version > FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        let mut cpu_chunk = None;
+        let mut bus_chunk = None;
+        while snapshot.has_more() {
+            let tag = snapshot.read_array::<4>()?;
+            let chunk = snapshot.read_nested()?;
+            match tag {
+                CPU_CHUNK => cpu_chunk = Some(chunk),
+                BUS_CHUNK => bus_chunk = Some(chunk),
+                // Chunks from later versions are skipped
+                _ => {}
+            }
+        }
+        let mut cpu_chunk = cpu_chunk.ok_or(SnapshotError::MissingChunk(CPU_CHUNK))?;
+        let mut bus_chunk = bus_chunk.ok_or(SnapshotError::MissingChunk(BUS_CHUNK))?;
+        let cpu = CpuState::read(&mut cpu_chunk)?;
+
+        // The bus can only be checked by loading it, so it is put back as it was if that fails
+        let mut backup = StateWriter::new();
+        self.bus.save_state(&mut backup);
+        if let Err(error) = self.bus.load_state(&mut bus_chunk) {
+            // The backup was saved by this bus, so it always loads
+            self.bus.load_state(&mut StateReader::new(&backup.into_bytes())).unwrap_or_default();
+            return Err(error);
+        }
+        self.apply_cpu(cpu);
+
+        // The history does not lead up to the restored state
+        if let Some(rewind) = &mut self.rewind {
+            *rewind = RewindBuffer::new(rewind.capacity());
+        }
+        Ok(())
+    }
+
+    fn save_cpu(&self, cpu: &mut StateWriter) {
+        cpu.write_u16(self.pc.get() as u16);
+        cpu.write_u8(self.acc.get());
+        cpu.write_u8(self.x as u8);
+        cpu.write_u8(self.y as u8);
+        cpu.write_u8(self.stk);
+        cpu.write_u8(self.sta.as_byte());
+        cpu.write_u64(self.cycles);
+        cpu.write_u8(variant_to_byte(self.variant));
+        cpu.write_bool(self.irq_line);
+        cpu.write_bool(self.nmi_line);
+        cpu.write_bool(self.nmi_level);
+        cpu.write_bool(self.nmi_pending);
+        cpu.write_bool(self.waiting);
+        cpu.write_bool(self.stopped);
+        cpu.write_bool(self.jammed);
+        cpu.write_u8(policy_to_byte(self.illegal_opcode_policy));
+    }
+
+    fn apply_cpu(&mut self, cpu: CpuState) {
+        self.pc.set(usize::from(cpu.pc));
+        self.acc.set(cpu.acc);
+        self.x = usize::from(cpu.x);
+        self.y = usize::from(cpu.y);
+        self.stk = cpu.stk;
+        self.sta = StatusRegister::from_byte(cpu.sta);
+        self.cycles = cpu.cycles;
+        self.variant = cpu.variant;
+        self.irq_line = cpu.irq_line;
+        self.nmi_line = cpu.nmi_line;
+        self.nmi_level = cpu.nmi_level;
+        self.nmi_pending = cpu.nmi_pending;
+        self.waiting = cpu.waiting;
+        self.stopped = cpu.stopped;
+        self.jammed = cpu.jammed;
+        self.illegal_opcode_policy = cpu.illegal_opcode_policy;
+        // Snapshots are only taken between instructions
+        self.extra_cycles = 0;
+        self.page_crossed = false;
+    }
+}
+
+/// Contents of the CPU chunk, read in full before any of it is applied
+struct CpuState {
+    pc: u16,
+    acc: u8,
+    x: u8,
+    y: u8,
+    stk: u8,
+    sta: u8,
+    cycles: u64,
+    variant: CpuVariant,
+    irq_line: bool,
+    nmi_line: bool,
+    nmi_level: bool,
+    nmi_pending: bool,
+    waiting: bool,
+    stopped: bool,
+    jammed: bool,
+    illegal_opcode_policy: IllegalOpcodePolicy,
+}
+
+impl CpuState {
+    fn read(cpu: &mut StateReader) -> Result<CpuState, SnapshotError> {
+        Ok(CpuState {
+            pc: cpu.read_u16()?,
+            acc: cpu.read_u8()?,
+            x: cpu.read_u8()?,
+            y: cpu.read_u8()?,
+            stk: cpu.read_u8()?,
+            sta: cpu.read_u8()?,
+            cycles: cpu.read_u64()?,
+            variant: variant_from_byte(cpu.read_u8()?)?,
+            irq_line: cpu.read_bool()?,
+            nmi_line: cpu.read_bool()?,
+            nmi_level: cpu.read_bool()?,
+            nmi_pending: cpu.read_bool()?,
+            waiting: cpu.read_bool()?,
+            stopped: cpu.read_bool()?,
+            jammed: cpu.read_bool()?,
+            illegal_opcode_policy: policy_from_byte(cpu.read_u8()?)?,
+        })
+    }
+}
@@ -1,24 +1,29 @@
 use std::ops::BitOrAssign;
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct StatusRegister {
     data: u8
 }
 
 impl StatusRegister {
-    /// Negative flag
-    pub const N: StatusRegister = StatusRegister {data: 1 << 0};
-    /// Overflow flag
-    pub const V: StatusRegister = StatusRegister {data: 1 << 1};
-    /// Break flag
-    pub const B: StatusRegister = StatusRegister {data: 1 << 2};
+    // Flags are laid out in the same order as the real processor: NV-BDIZC
+    /// Carry flag
+    pub const C: StatusRegister = StatusRegister {data: 1 << 0};
+    /// Zero flag
+    pub const Z: StatusRegister = StatusRegister {data: 1 << 1};
+    /// Interrupt disable
+    pub const I: StatusRegister = StatusRegister {data: 1 << 2};
     /// Decimal flag
     pub const D: StatusRegister = StatusRegister {data: 1 << 3};
-    /// Interrupt disable
-    pub const I: StatusRegister = StatusRegister {data: 1 << 4};
-    /// Zero flag
-    pub const Z: StatusRegister = StatusRegister {data: 1 << 5};
-    /// Carry flag
-    pub const C: StatusRegister = StatusRegister {data: 1 << 6};
+    /// Break flag
+    /// Only exists in copies of the status pushed onto the stack, to tell BRK apart from an IRQ
+    pub const B: StatusRegister = StatusRegister {data: 1 << 4};
+    /// Unused bit, which always reads as 1 when the status is pushed onto the stack
+    pub const U: StatusRegister = StatusRegister {data: 1 << 5};
+    /// Overflow flag
+    pub const V: StatusRegister = StatusRegister {data: 1 << 6};
+    /// Negative flag
+    pub const N: StatusRegister = StatusRegister {data: 1 << 7};
 
     /// Constructs a status register with every flag set to 0
     pub const fn new() -> Self { Self {data: 0} }
@@ -28,6 +33,21 @@ impl StatusRegister {
 
     pub const fn as_byte(&self) -> u8 { self.data }
 
+    /// Returns the byte pushed onto the stack by PHP, BRK or an interrupt
+    /// The unused bit is always set, and the break flag is only set by PHP and BRK
+    pub const fn to_pushed_byte(&self, is_break: bool) -> u8 {
+        self.difference(StatusRegister::B)
+            .union(StatusRegister::U)
+            .union(StatusRegister::B.get_cond(is_break))
+            .as_byte()
+    }
+
+    /// Constructs a status register from a byte pulled from the stack by PLP or RTI
+    /// The break flag and unused bit do not exist in the register, so they are ignored
+    pub const fn from_pulled_byte(flags: u8) -> Self {
+        Self::from_byte(flags).difference(StatusRegister::B.union(StatusRegister::U))
+    }
+
     /// Returns the mathematical union of the internal flags and the given flag
     pub const fn union(&self, flags: StatusRegister) -> Self {
         Self::from_byte(self.data | flags.data)
@@ -43,6 +63,12 @@ impl StatusRegister {
         Self::from_byte(self.data & !flags.data)
     }
 
+    /// Returns the internal flags with the affected flags replaced by the given flags
+    /// Flags outside of the affected flags are left unchanged
+    pub const fn replace(&self, affected: StatusRegister, flags: StatusRegister) -> Self {
+        Self::from_byte((self.data & !affected.data) | (flags.data & affected.data))
+    }
+
     /// True if this status contains no flags
     pub const fn is_empty(&self) -> bool { self.data == 0 }
 
@@ -64,6 +90,12 @@ impl StatusRegister {
     }
 }
 
+impl Default for StatusRegister {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // Operators
 impl std::ops::BitOr for StatusRegister {
     type Output = Self;
@@ -79,6 +111,11 @@ impl BitOrAssign for StatusRegister {
 }
 
 // Utils
+/// Flags affected by the result of a load, transfer or logical operation
+pub const ZERO_NEG_FLAGS: StatusRegister = StatusRegister::Z.union(StatusRegister::N);
+/// Flags affected by the result of a shift, rotate or comparison
+pub const ZERO_NEG_CARRY_FLAGS: StatusRegister = ZERO_NEG_FLAGS.union(StatusRegister::C);
+
 pub const fn get_zero_neg_flags(n: u8) -> StatusRegister {
     const MOST_SIG_BIT: u8 = 0x80;
 
@@ -0,0 +1,153 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::{AccessKind, Bus};
+
+// Memory watchpoints
+// Every read and write the processor makes through the memory functions is checked, which includes
+// fetching opcodes and operands and pushing to and pulling from the stack
+// Peeking and loading memory are not processor accesses, so they never trigger a watchpoint
+
+/// Accesses which trigger a watchpoint
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WatchKind {
+    Read,
+    Write,
+    /// Any read or write
+    Access,
+    /// A write which changes the value in memory
+    Change,
+}
+
+impl WatchKind {
+    const fn matches(self, access: AccessKind, old: u8, new: u8) -> bool {
+        match self {
+            WatchKind::Read => matches!(access, AccessKind::Read),
+            WatchKind::Write => matches!(access, AccessKind::Write),
+            WatchKind::Access => true,
+            WatchKind::Change => matches!(access, AccessKind::Write) && old != new,
+        }
+    }
+}
+
+impl fmt::Display for WatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WatchKind::Read => "read",
+            WatchKind::Write => "write",
+            WatchKind::Access => "access",
+            WatchKind::Change => "change",
+        })
+    }
+}
+
+/// Range of addresses to watch, and the accesses to watch for
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u16>,
+    pub kind: WatchKind,
+}
+
+impl fmt::Display for Watchpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start, end) = (*self.range.start(), *self.range.end());
+        if start == end {
+            write!(f, "{} ${start:04X}", self.kind)
+        } else {
+            write!(f, "{} ${start:04X}-${end:04X}", self.kind)
+        }
+    }
+}
+
+/// An access which triggered a watchpoint
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct WatchHit {
+    /// Address of the instruction which made the access
+    pub pc: u16,
+    pub addr: u16,
+    pub access: AccessKind,
+    /// Value in memory before the access, which is the value read for reads
+    pub old: u8,
+    /// Value in memory after the access
+    pub new: u8,
+}
+
+impl fmt::Display for WatchHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.access {
+            AccessKind::Read => write!(
+                f, "read of ${:04X} by instruction at ${:04X}, value ${:02X}", self.addr, self.pc, self.new,
+            ),
+            AccessKind::Write => write!(
+                f, "write to ${:04X} by instruction at ${:04X}, ${:02X} -> ${:02X}", self.addr, self.pc, self.old, self.new,
+            ),
+        }
+    }
+}
+
+/// Watchpoints, and the hits since they were last taken
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub(crate) struct Watchpoints {
+    points: Vec<Watchpoint>,
+    hits: Vec<WatchHit>,
+    /// Address of the instruction being executed
+    pc: u16,
+}
+
+impl Watchpoints {
+    fn check(&mut self, addr: u16, access: AccessKind, old: u8, new: u8) {
+        let triggered = self.points.iter()
+            .any(|point| point.range.contains(&addr) && point.kind.matches(access, old, new));
+        if triggered {
+            self.hits.push(WatchHit { pc: self.pc, addr, access, old, new });
+        }
+    }
+}
+
+impl<B: Bus> ComputerState<B> {
+    /// Watches the addresses for the given kind of access
+    pub fn add_watchpoint(&mut self, range: RangeInclusive<u16>, kind: WatchKind) {
+        self.watch.points.push(Watchpoint { range, kind });
+    }
+
+    /// Removes the watchpoint at the index in the list of watchpoints
+    pub fn remove_watchpoint(&mut self, index: usize) -> Option<Watchpoint> {
+        (index < self.watch.points.len()).then(|| self.watch.points.remove(index))
+    }
+
+    pub fn clear_watchpoints(&mut self) {
+        self.watch.points.clear();
+    }
+
+    /// Returns the watchpoints, in the order they were added
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        &self.watch.points
+    }
+
+    /// Returns the accesses which triggered a watchpoint since this was last called, in the order
+    /// they were made
+    pub fn take_watch_hits(&mut self) -> Vec<WatchHit> {
+        std::mem::take(&mut self.watch.hits)
+    }
+
+    /// Records the address of the instruction about to be executed, for reporting hits
+    pub(crate) fn begin_watch_step(&mut self) {
+        self.watch.pc = self.pc.get() as u16;
+    }
+
+    /// Checks a read of the value at the address against the watchpoints
+    pub(crate) fn check_watch_read(&mut self, addr: u16, value: u8) {
+        if !self.watch.points.is_empty() {
+            self.watch.check(addr, AccessKind::Read, value, value);
+        }
+    }
+
+    /// Checks a write of the value to the address against the watchpoints, before it is written
+    pub(crate) fn check_watch_write(&mut self, addr: u16, value: u8) {
+        if !self.watch.points.is_empty() {
+            let old = self.bus.peek(addr);
+            self.watch.check(addr, AccessKind::Write, old, value);
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::assembler::{self, ListingLine};
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::status_register::StatusRegister;
+use crate::debugger::{self, Debugger, Stop};
+use crate::debugger::expression::Expression;
+use crate::disassembler;
+use crate::json::Json;
+
+// Debug Adapter Protocol server
+// Serves one editor over a pair of streams, normally stdin and stdout. Each message is JSON, preceded by
+// a Content-Length header. Programs run on the command driven debugger, so they stop at the same
+// breakpoints, traps and run limit
+// Launching loads a binary at an address. When the source of the program is given too, it is assembled
+// for its listing, which maps lines of the source to addresses so breakpoints can be set on lines
+// Memory is read with peeks rather than fetches, so that showing it neither triggers watchpoints nor
+// has side effects on devices
+
+/// The only thread, which is the processor
+const THREAD_ID: u64 = 1;
+/// Reference of the only scope, which holds the registers and flags
+const REGISTERS_REFERENCE: u64 = 1;
+/// Largest number of bytes a memory read returns
+const MAX_READ_LEN: u64 = 0x10000;
+
+/// Builds an object from its members
+fn object(members: Vec<(&str, Json)>) -> Json {
+    Json::Object(members.into_iter().map(|(name, value)| (name.to_string(), value)).collect())
+}
+
+/// Formats an address as a memory or instruction reference
+fn reference(addr: u16) -> Json {
+    format!("0x{addr:04X}").into()
+}
+
+/// Parses an address given as a number, or as a string in hex with 0x or $, or in decimal
+fn parse_address(value: &Json) -> Result<u16, String> {
+    let number = match value {
+        Json::String(text) => match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).map_err(|_| format!("invalid address {text}"))?,
+            None => debugger::parse_number(text)?,
+        },
+        value => value.as_u64().ok_or_else(|| format!("invalid address {value}"))?,
+    };
+    u16::try_from(number).map_err(|_| format!("{number} is not an address"))
+}
+
+/// Encodes the bytes as base64, which is how memory is sent
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| bits | u32::from(byte) << (16 - 8 * index));
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(char::from(ALPHABET[(bits >> (18 - 6 * index)) as usize & 0x3F]));
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Returns whether the paths are to the same file
+fn same_file(a: &Path, b: &Path) -> bool {
+    a == b || a.canonicalize().ok().is_some_and(|a| b.canonicalize().ok() == Some(a))
+}
+
+fn event(name: &str, body: Json) -> Json {
+    object(vec![("type", "event".into()), ("event", name.into()), ("body", body)])
+}
+
+fn stopped_event(reason: &str, description: Option<String>) -> Json {
+    let mut body = vec![
+        ("reason", reason.into()),
+        ("threadId", THREAD_ID.into()),
+        ("allThreadsStopped", true.into()),
+    ];
+    if let Some(description) = description {
+        body.push(("description", description.clone().into()));
+        body.push(("text", description.into()));
+    }
+    event("stopped", object(body))
+}
+
+/// Reads a message, returning None once the stream is closed
+pub fn read_message<R: BufRead>(reader: &mut R) -> io::Result<Option<Json>> {
+    let mut len = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            // Blank lines before the headers are ignored
+            if len.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                len = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0; len.unwrap_or(0)];
+    reader.read_exact(&mut body)?;
+    let body = String::from_utf8(body).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    Json::parse(&body).map(Some).map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Writes the message with its header
+pub fn write_message<W: Write>(writer: &mut W, message: &Json) -> io::Result<()> {
+    let body = message.to_string();
+    write!(writer, "Content-Length: {}\r\n\r\n{body}", body.len())?;
+    writer.flush()
+}
+
+/// The program being debugged
+struct Session {
+    state: ComputerState,
+    /// Listing of the program's source, if it was given
+    listing: Vec<ListingLine>,
+    stop_on_entry: bool,
+}
+
+impl Session {
+    /// Returns the first line at or after the given line of the file which assembled to bytes
+    fn find_line(&self, path: &Path, line: u64) -> Option<&ListingLine> {
+        self.listing.iter()
+            .filter(|listed| !listed.bytes.is_empty() && listed.line as u64 >= line)
+            .filter(|listed| listed.file.as_deref().is_some_and(|file| same_file(file, path)))
+            .min_by_key(|listed| listed.line)
+    }
+}
+
+/// Breakpoint on a line of a source file, as the editor asked for it
+/// The line is found in the listing again whenever a program is launched
+struct SourceBreakpoint {
+    id: u64,
+    line: u64,
+}
+
+/// State of the connection with the editor
+pub struct DapServer {
+    /// Processor which is emulated unless launching asks for another
+    variant: CpuVariant,
+    session: Option<Session>,
+    debugger: Debugger,
+    /// Breakpoints on lines of each source file
+    source_breakpoints: BTreeMap<PathBuf, Vec<SourceBreakpoint>>,
+    /// Breakpoints set on addresses
+    instruction_breakpoints: BTreeSet<u16>,
+    /// Identifier of the next source breakpoint
+    next_breakpoint_id: u64,
+    /// Sequence number of the next message sent
+    seq: u64,
+    /// Set once the editor disconnects
+    finished: bool,
+}
+
+impl DapServer {
+    pub fn new(variant: CpuVariant) -> DapServer {
+        DapServer {
+            variant,
+            session: None,
+            debugger: Debugger::new(),
+            source_breakpoints: BTreeMap::new(),
+            instruction_breakpoints: BTreeSet::new(),
+            next_breakpoint_id: 1,
+            seq: 1,
+            finished: false,
+        }
+    }
+
+    /// Serves requests until the editor disconnects or closes the stream
+    pub fn serve<R: BufRead, W: Write>(&mut self, reader: &mut R, writer: &mut W) -> io::Result<()> {
+        while !self.finished {
+            let Some(request) = read_message(reader)? else {
+                break;
+            };
+            for message in self.handle(&request) {
+                write_message(writer, &message)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles a request, returning the response and any events which follow it
+    pub fn handle(&mut self, request: &Json) -> Vec<Json> {
+        let command = request.get("command").and_then(Json::as_str).unwrap_or_default().to_string();
+        let request_seq = request.get("seq").cloned().unwrap_or(Json::Null);
+        let empty = Json::Object(Vec::new());
+        let args = request.get("arguments").unwrap_or(&empty);
+
+        let mut events = Vec::new();
+        let result = match command.as_str() {
+            "initialize" => {
+                events.push(event("initialized", object(vec![])));
+                Ok(object(vec![
+                    ("supportsConfigurationDoneRequest", true.into()),
+                    ("supportsReadMemoryRequest", true.into()),
+                    ("supportsInstructionBreakpoints", true.into()),
+                    ("supportsEvaluateForHovers", true.into()),
+                ]))
+            }
+            // Breakpoints may have been set before there was a listing to find their lines in
+            "launch" => self.launch(args).inspect(|_| {
+                events.extend(self.source_breakpoints.iter().flat_map(|(path, breakpoints)| {
+                    breakpoints.iter().map(|breakpoint| event("breakpoint", object(vec![
+                        ("reason", "changed".into()),
+                        ("breakpoint", self.source_breakpoint(path, breakpoint)),
+                    ])))
+                }));
+            }),
+            "setBreakpoints" => self.set_breakpoints(args),
+            "setInstructionBreakpoints" => self.set_instruction_breakpoints(args),
+            "configurationDone" => {
+                let stop = match self.session().map(|session| session.stop_on_entry) {
+                    Ok(true) => Ok(stopped_event("entry", None)),
+                    Ok(false) => self.run(|debugger, state| debugger.run_continue(state)),
+                    Err(error) => Err(error),
+                };
+                stop.map(|event| {
+                    events.push(event);
+                    object(vec![])
+                })
+            }
+            "threads" => Ok(object(vec![(
+                "threads",
+                vec![object(vec![("id", THREAD_ID.into()), ("name", "6502".into())])].into(),
+            )])),
+            "stackTrace" => self.stack_trace(),
+            "scopes" => Ok(object(vec![(
+                "scopes",
+                vec![object(vec![
+                    ("name", "Registers".into()),
+                    ("presentationHint", "registers".into()),
+                    ("variablesReference", REGISTERS_REFERENCE.into()),
+                    ("expensive", false.into()),
+                ])].into(),
+            )])),
+            "variables" => self.variables(args),
+            "continue" => self.run(|debugger, state| debugger.run_continue(state)).map(|event| {
+                events.push(event);
+                object(vec![("allThreadsContinued", true.into())])
+            }),
+            "next" => self.run(Debugger::next).map(|event| {
+                events.push(event);
+                object(vec![])
+            }),
+            "stepIn" => self.run(|debugger, state| debugger.step(state, 1)).map(|event| {
+                events.push(event);
+                object(vec![])
+            }),
+            "stepOut" => self.run(Debugger::finish).map(|event| {
+                events.push(event);
+                object(vec![])
+            }),
+            // Requests are only handled while the program is stopped, so it is already paused
+            "pause" => self.session().map(|_| {
+                events.push(stopped_event("pause", None));
+                object(vec![])
+            }),
+            "readMemory" => self.read_memory(args),
+            "evaluate" => self.evaluate(args),
+            "disconnect" | "terminate" => {
+                self.finished = true;
+                Ok(object(vec![]))
+            }
+            _ => Err(format!("{command} is not supported")),
+        };
+
+        let mut response = vec![
+            ("type", "response".into()),
+            ("request_seq", request_seq),
+            ("success", result.is_ok().into()),
+            ("command", command.into()),
+        ];
+        match result {
+            Ok(body) => response.push(("body", body)),
+            Err(message) => response.push(("message", message.into())),
+        }
+        // Messages are numbered in the order they are sent
+        std::iter::once(object(response)).chain(events).map(|message| match message {
+            Json::Object(mut members) => {
+                members.insert(0, ("seq".to_string(), self.seq.into()));
+                self.seq += 1;
+                Json::Object(members)
+            }
+            message => message,
+        }).collect()
+    }
+
+    fn session(&self) -> Result<&Session, String> {
+        self.session.as_ref().ok_or_else(|| "no program has been launched".to_string())
+    }
+
+    /// Loads the program given by the program and source arguments
+    fn launch(&mut self, args: &Json) -> Result<Json, String> {
+        let variant = match args.get("cpu").and_then(Json::as_str) {
+            None => self.variant,
+            Some(cpu) if cpu.eq_ignore_ascii_case("65c02") => CpuVariant::Wdc65C02,
+            Some("6502") => CpuVariant::Nmos6502,
+            Some(cpu) => return Err(format!("unknown cpu {cpu}")),
+        };
+        let assembly = args.get("source").and_then(Json::as_str)
+            .map(|source| assembler::assemble_file(Path::new(source), variant).map_err(|error| error.to_string()))
+            .transpose()?;
+
+        // The binary is the program if it is given, or else the assembled source
+        let (binary, default_addr) = match (args.get("program").and_then(Json::as_str), &assembly) {
+            (Some(program), _) => {
+                let binary = std::fs::read(program).map_err(|error| format!("cannot read {program}: {error}"))?;
+                (binary, assembly.as_ref().map_or(0, |assembly| assembly.origin))
+            }
+            (None, Some(assembly)) => (assembly.binary.clone(), assembly.origin),
+            (None, None) => return Err("launch needs a program or a source".to_string()),
+        };
+        let load_addr = args.get("loadAddress").map(parse_address).transpose()?.unwrap_or(default_addr);
+        let start_addr = args.get("startAddress").map(parse_address).transpose()?.unwrap_or(load_addr);
+
+        let mut state = ComputerState::with_variant(variant);
+        state.load_at(load_addr, &binary);
+        state.pc.set(usize::from(start_addr));
+        if let Some(assembly) = &assembly {
+            self.debugger.set_symbols(assembly.symbols.iter().map(|(name, &value)| (name.clone(), value)).collect());
+        }
+        self.session = Some(Session {
+            state,
+            listing: assembly.map(|assembly| assembly.listing).unwrap_or_default(),
+            stop_on_entry: args.get("stopOnEntry").and_then(Json::as_bool).unwrap_or(false),
+        });
+        self.update_breakpoints();
+        Ok(object(vec![]))
+    }
+
+    /// Replaces the breakpoints in a source file, moving each to the first line at or after it which
+    /// assembled to bytes
+    fn set_breakpoints(&mut self, args: &Json) -> Result<Json, String> {
+        let path = args.get("source").and_then(|source| source.get("path")).and_then(Json::as_str)
+            .ok_or("setBreakpoints needs the path of the source")?;
+        let path = PathBuf::from(path);
+        let lines: Vec<u64> = args.get("breakpoints").and_then(Json::as_array).unwrap_or_default().iter()
+            .filter_map(|breakpoint| breakpoint.get("line").and_then(Json::as_u64))
+            .collect();
+
+        let source_breakpoints = lines.into_iter().map(|line| {
+            let id = self.next_breakpoint_id;
+            self.next_breakpoint_id += 1;
+            SourceBreakpoint { id, line }
+        }).collect::<Vec<_>>();
+        let breakpoints = source_breakpoints.iter()
+            .map(|breakpoint| self.source_breakpoint(&path, breakpoint))
+            .collect::<Vec<_>>();
+
+        self.source_breakpoints.insert(path, source_breakpoints);
+        self.update_breakpoints();
+        Ok(object(vec![("breakpoints", breakpoints.into())]))
+    }
+
+    /// Describes a source breakpoint, which is verified once its line is found in the listing
+    fn source_breakpoint(&self, path: &Path, breakpoint: &SourceBreakpoint) -> Json {
+        match self.session.as_ref().and_then(|session| session.find_line(path, breakpoint.line)) {
+            Some(listed) => object(vec![
+                ("id", breakpoint.id.into()),
+                ("verified", true.into()),
+                ("line", (listed.line as u64).into()),
+                ("instructionReference", reference(listed.addr)),
+            ]),
+            None => object(vec![
+                ("id", breakpoint.id.into()),
+                ("verified", false.into()),
+                ("line", breakpoint.line.into()),
+                ("message", "no code was assembled from this line".into()),
+            ]),
+        }
+    }
+
+    /// Replaces the breakpoints set on addresses
+    fn set_instruction_breakpoints(&mut self, args: &Json) -> Result<Json, String> {
+        let mut breakpoints = Vec::new();
+        self.instruction_breakpoints.clear();
+        for breakpoint in args.get("breakpoints").and_then(Json::as_array).unwrap_or_default() {
+            let addr = parse_address(breakpoint.get("instructionReference").ok_or("breakpoint needs an instructionReference")?)?;
+            let offset = breakpoint.get("offset").and_then(Json::as_f64).unwrap_or(0.0) as i64;
+            let addr = (i64::from(addr) + offset) as u16;
+            self.instruction_breakpoints.insert(addr);
+            breakpoints.push(object(vec![("verified", true.into()), ("instructionReference", reference(addr))]));
+        }
+        self.update_breakpoints();
+        Ok(object(vec![("breakpoints", breakpoints.into())]))
+    }
+
+    /// Gives the debugger every breakpoint, from both source lines and addresses
+    fn update_breakpoints(&mut self) {
+        self.debugger.clear_breakpoints();
+        let Some(session) = &self.session else {
+            return;
+        };
+        let source_addrs = self.source_breakpoints.iter().flat_map(|(path, breakpoints)| {
+            breakpoints.iter().filter_map(|breakpoint| session.find_line(path, breakpoint.line))
+        }).map(|listed| listed.addr);
+        for addr in source_addrs.chain(self.instruction_breakpoints.iter().copied()) {
+            self.debugger.add_breakpoint(addr);
+        }
+    }
+
+    /// Runs the program with the debugger, returning the event describing why it stopped
+    fn run(&mut self, run: impl FnOnce(&mut Debugger, &mut ComputerState) -> Stop) -> Result<Json, String> {
+        let Some(session) = &mut self.session else {
+            return Err("no program has been launched".to_string());
+        };
+        let stop = run(&mut self.debugger, &mut session.state);
+        let reason = match stop {
+            Stop::Done => "step",
+            Stop::Breakpoint(_) | Stop::ConditionFailed(..) => "breakpoint",
+            Stop::Watchpoint(_) | Stop::ExpressionChanged { .. } => "data breakpoint",
+            Stop::Error(_) => "exception",
+            Stop::Trapped(_) | Stop::Paused { .. } => "pause",
+        };
+        let description = (stop != Stop::Done).then(|| stop.to_string());
+        Ok(stopped_event(reason, description))
+    }
+
+    /// Returns the only frame, which is the instruction at the PC
+    fn stack_trace(&self) -> Result<Json, String> {
+        let session = self.session()?;
+        let state = &session.state;
+        let pc = state.pc.get() as u16;
+        let instruction = disassembler::disassemble_at(state.variant(), pc, |addr| state.bus.peek(addr));
+
+        let mut frame = vec![
+            ("id", 1u64.into()),
+            ("name", format!("${pc:04X} {}", instruction.text).into()),
+            ("instructionPointerReference", reference(pc)),
+        ];
+        let listed = session.listing.iter().find(|listed| listed.addr == pc && !listed.bytes.is_empty());
+        match listed {
+            Some(listed) => {
+                if let Some(file) = &listed.file {
+                    let name = file.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+                    frame.push(("source", object(vec![
+                        ("name", name.into()),
+                        ("path", file.to_string_lossy().into_owned().into()),
+                    ])));
+                }
+                frame.push(("line", (listed.line as u64).into()));
+            }
+            None => frame.push(("line", 0u64.into())),
+        }
+        frame.push(("column", 0u64.into()));
+        Ok(object(vec![("stackFrames", vec![object(frame)].into()), ("totalFrames", 1u64.into())]))
+    }
+
+    /// Returns the registers, followed by each flag
+    fn variables(&self, args: &Json) -> Result<Json, String> {
+        let state = &self.session()?.state;
+        if args.get("variablesReference").and_then(Json::as_u64) != Some(REGISTERS_REFERENCE) {
+            return Ok(object(vec![("variables", Vec::new().into())]));
+        }
+        let mut variables = vec![
+            ("A", format!("${:02X}", state.acc.get())),
+            ("X", format!("${:02X}", state.get_x())),
+            ("Y", format!("${:02X}", state.get_y())),
+            ("SP", format!("${:02X}", state.get_stk())),
+            ("PC", format!("${:04X}", state.pc.get())),
+            ("P", format!("${:02X}", state.sta.as_byte())),
+        ];
+        for (name, flag) in [
+            ("N", StatusRegister::N),
+            ("V", StatusRegister::V),
+            ("B", StatusRegister::B),
+            ("D", StatusRegister::D),
+            ("I", StatusRegister::I),
+            ("Z", StatusRegister::Z),
+            ("C", StatusRegister::C),
+        ] {
+            variables.push((name, u8::from(state.sta.contains(flag)).to_string()));
+        }
+        let variables = variables.into_iter().map(|(name, value)| object(vec![
+            ("name", name.into()),
+            ("value", value.into()),
+            ("variablesReference", 0u64.into()),
+        ])).collect::<Vec<_>>();
+        Ok(object(vec![("variables", variables.into())]))
+    }
+
+    fn read_memory(&self, args: &Json) -> Result<Json, String> {
+        let state = &self.session()?.state;
+        let addr = parse_address(args.get("memoryReference").ok_or("readMemory needs a memoryReference")?)?;
+        let offset = args.get("offset").and_then(Json::as_f64).unwrap_or(0.0) as i64;
+        let addr = (i64::from(addr) + offset) as u16;
+        let count = args.get("count").and_then(Json::as_u64).unwrap_or(0).min(MAX_READ_LEN);
+
+        let bytes: Vec<u8> = (0..count).map(|index| state.peek_byte_from_addr(usize::from(addr.wrapping_add(index as u16)))).collect();
+        Ok(object(vec![("address", reference(addr)), ("data", base64(&bytes).into())]))
+    }
+
+    /// Evaluates an expression of the debugger, such as for the watch panel or hovering
+    fn evaluate(&self, args: &Json) -> Result<Json, String> {
+        let state = &self.session()?.state;
+        let text = args.get("expression").and_then(Json::as_str).ok_or("evaluate needs an expression")?;
+        let expression = Expression::parse(text)?;
+        let value = self.debugger.evaluate(state, &expression).map_err(|error| error.to_string())?;
+        Ok(object(vec![("result", debugger::format_value(value).into()), ("variablesReference", 0u64.into())]))
+    }
+}
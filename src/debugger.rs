@@ -0,0 +1,628 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::{ExecutionError, StepOutcome};
+use crate::computer_state::operations;
+use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::watch::{WatchHit, WatchKind, Watchpoint};
+use crate::debugger::expression::{EvalError, Expression};
+use crate::disassembler;
+
+pub mod expression;
+
+// Command driven debugger
+// Commands are parsed from text and return the text to show, so the debugger can be driven by the
+// command line interface or by tests. Every command which runs the program is built on execute_next,
+// and stops at breakpoints, watchpoints, errors, traps, or after a limit on the number of steps, so that a
+// program which never reaches a breakpoint cannot hang the debugger
+
+/// Number of steps a command runs for before pausing, unless it stops for another reason
+pub const DEFAULT_RUN_LIMIT: u64 = 10_000_000;
+/// Number of instructions shown by disasm when no count is given
+const DEFAULT_DISASM_COUNT: usize = 10;
+/// Number of bytes shown by mem when no length is given
+const DEFAULT_MEM_LEN: usize = 64;
+/// Number of bytes on each line of a memory dump
+const MEM_BYTES_PER_LINE: usize = 16;
+
+pub const HELP: &str = "\
+break <addr> [if <expr>]
+                      stop before executing the instruction at the address, if the expression is
+                      not zero
+delete [addr]         remove the breakpoint at the address, or every breakpoint
+watch [range] [kind]  stop after an instruction accesses an address, or a range such as $20-$2F, or
+                      list the watchpoints. The kind is read, write (the default), access or change
+watch expr <expr>     stop when the value of the expression changes
+unwatch [number]      remove the watchpoint with the number in the list, or every watchpoint
+print <expr>          show the value of the expression
+continue              run until a breakpoint or watchpoint
+step [count]          execute one instruction, or the given number
+next                  step, running over subroutine calls
+finish                run until the current subroutine returns
+regs                  show the registers
+set <reg>=<value>     set a, x, y, sp, pc, p, or one of the flags n, v, b, d, i, z and c
+mem <addr> [len]      show memory
+disasm [addr] [count] disassemble from the address, or the PC
+help                  show this help
+quit                  exit the debugger
+Addresses and values are decimal, or hex with $, or binary with %. An empty line repeats the last command
+Expressions use registers a, x, y, sp, pc and p, flags n, v, b, d, i, z and c, cycles, the hit count of
+the breakpoint as hits, [addr] and w[addr] for a byte and word of memory, symbols, and the operators
+|| && | ^ & == != < <= > >= << >> + - * / % - ~ !";
+
+/// A register or flag which can be set
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Register {
+    A,
+    X,
+    Y,
+    Sp,
+    Pc,
+    P,
+    Flag(StatusRegister),
+}
+
+/// A debugger command
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Command {
+    /// Adds a breakpoint at the address, which only stops when the condition is not zero
+    Break(u16, Option<Expression>),
+    /// Deletes the breakpoint at the address, or every breakpoint
+    Delete(Option<u16>),
+    /// Adds the watchpoint, or lists the watchpoints
+    Watch(Option<Watchpoint>),
+    /// Watches the value of the expression
+    WatchExpression(Expression),
+    /// Removes the watchpoint with the number, counting from 1, or every watchpoint
+    Unwatch(Option<usize>),
+    Continue,
+    Step(u64),
+    Next,
+    Finish,
+    Regs,
+    Set(Register, u16),
+    Print(Expression),
+    Mem { addr: u16, len: usize },
+    /// Disassembles from the address, or the PC
+    Disasm { addr: Option<u16>, count: usize },
+    Help,
+    Quit,
+}
+
+impl Command {
+    /// Parses a command, which can be shortened to its first letter where that is not ambiguous
+    pub fn parse(line: &str) -> Result<Command, String> {
+        let mut words = line.split_whitespace();
+        let Some(name) = words.next() else {
+            return Err("no command given".to_string());
+        };
+        let args: Vec<&str> = words.collect();
+        let arg = |index: usize| args.get(index).copied();
+        let parse_arg = |index: usize| arg(index).map(parse_number).transpose();
+        // Expressions can contain spaces, so they are the rest of the line after the given number of words
+        let parse_expression = |words: usize| Expression::parse(remainder(line, words));
+
+        let command = match name.to_ascii_lowercase().as_str() {
+            "break" | "b" => {
+                let addr = parse_address(arg(0).ok_or("break needs an address")?)?;
+                let condition = match arg(1) {
+                    None => None,
+                    Some(word) if word.eq_ignore_ascii_case("if") => Some(parse_expression(3)?),
+                    Some(_) => return Err("break needs if before its condition".to_string()),
+                };
+                Command::Break(addr, condition)
+            }
+            "delete" | "d" => Command::Delete(arg(0).map(parse_address).transpose()?),
+            "watch" | "w" => match arg(0) {
+                None => Command::Watch(None),
+                Some(word) if word.eq_ignore_ascii_case("expr") => Command::WatchExpression(parse_expression(2)?),
+                Some(range) => Command::Watch(Some(Watchpoint {
+                    range: parse_range(range)?,
+                    kind: arg(1).map_or(Ok(WatchKind::Write), parse_watch_kind)?,
+                })),
+            },
+            "unwatch" => Command::Unwatch(parse_arg(0)?.map(|number| number as usize)),
+            "continue" | "c" => Command::Continue,
+            "step" | "s" => Command::Step(parse_arg(0)?.unwrap_or(1)),
+            "next" | "n" => Command::Next,
+            "finish" | "f" => Command::Finish,
+            "regs" | "r" => Command::Regs,
+            "set" => {
+                let assignment = args.concat();
+                let (register, value) = assignment.split_once('=').ok_or("set needs <reg>=<value>")?;
+                let register = parse_register(register)?;
+                let value = parse_number(value)?;
+                let max = match register {
+                    Register::Pc => u64::from(u16::MAX),
+                    Register::Flag(_) => 1,
+                    _ => u64::from(u8::MAX),
+                };
+                if value > max {
+                    return Err(format!("{value} is too large for the register"));
+                }
+                Command::Set(register, value as u16)
+            }
+            "print" | "p" => Command::Print(parse_expression(1)?),
+            "mem" | "m" => Command::Mem {
+                addr: parse_address(arg(0).ok_or("mem needs an address")?)?,
+                len: parse_arg(1)?.map_or(DEFAULT_MEM_LEN, |len| len as usize),
+            },
+            "disasm" | "u" => Command::Disasm {
+                addr: arg(0).map(parse_address).transpose()?,
+                count: parse_arg(1)?.map_or(DEFAULT_DISASM_COUNT, |count| count as usize),
+            },
+            "help" | "h" => Command::Help,
+            "quit" | "q" => Command::Quit,
+            _ => return Err(format!("unknown command {name}, type help for a list of commands")),
+        };
+        Ok(command)
+    }
+}
+
+/// Returns the rest of the line after the given number of words
+fn remainder(line: &str, words: usize) -> &str {
+    let mut rest = line.trim_start();
+    for _ in 0..words {
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        rest = rest[end..].trim_start();
+    }
+    rest
+}
+
+/// Parses a decimal number, or a hex number starting with $, or a binary number starting with %
+pub fn parse_number(text: &str) -> Result<u64, String> {
+    let parsed = if let Some(hex) = text.strip_prefix('$') {
+        u64::from_str_radix(hex, 16)
+    } else if let Some(binary) = text.strip_prefix('%') {
+        u64::from_str_radix(binary, 2)
+    } else {
+        text.parse()
+    };
+    parsed.map_err(|_| format!("invalid number {text}"))
+}
+
+fn parse_address(text: &str) -> Result<u16, String> {
+    let value = parse_number(text)?;
+    u16::try_from(value).map_err(|_| format!("{text} is not an address"))
+}
+
+/// Parses an address, or a range of addresses written as start-end
+fn parse_range(text: &str) -> Result<RangeInclusive<u16>, String> {
+    let (start, end) = match text.split_once('-') {
+        Some((start, end)) => (parse_address(start)?, parse_address(end)?),
+        None => (parse_address(text)?, parse_address(text)?),
+    };
+    if start > end {
+        return Err(format!("{text} ends before it starts"));
+    }
+    Ok(start..=end)
+}
+
+fn parse_watch_kind(name: &str) -> Result<WatchKind, String> {
+    Ok(match name.to_ascii_lowercase().as_str() {
+        "read" | "r" => WatchKind::Read,
+        "write" | "w" => WatchKind::Write,
+        "access" | "rw" => WatchKind::Access,
+        "change" => WatchKind::Change,
+        _ => return Err(format!("unknown kind of watchpoint {name}")),
+    })
+}
+
+fn parse_register(name: &str) -> Result<Register, String> {
+    Ok(match name.trim().to_ascii_lowercase().as_str() {
+        "a" => Register::A,
+        "x" => Register::X,
+        "y" => Register::Y,
+        "sp" | "s" => Register::Sp,
+        "pc" => Register::Pc,
+        "p" => Register::P,
+        "n" => Register::Flag(StatusRegister::N),
+        "v" => Register::Flag(StatusRegister::V),
+        "b" => Register::Flag(StatusRegister::B),
+        "d" => Register::Flag(StatusRegister::D),
+        "i" => Register::Flag(StatusRegister::I),
+        "z" => Register::Flag(StatusRegister::Z),
+        "c" => Register::Flag(StatusRegister::C),
+        _ => return Err(format!("unknown register {name}")),
+    })
+}
+
+/// Why a command which runs the program stopped
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Stop {
+    /// The command finished, such as after stepping the requested number of instructions
+    Done,
+    /// A breakpoint was reached, before executing the instruction at its address
+    Breakpoint(u16),
+    /// The last step made accesses which triggered watchpoints
+    Watchpoint(Vec<WatchHit>),
+    /// The value of a watched expression changed
+    ExpressionChanged { expression: Expression, old: Result<i64, EvalError>, new: Result<i64, EvalError> },
+    /// The condition of the breakpoint at the address could not be evaluated
+    ConditionFailed(u16, EvalError),
+    /// An instruction jumped or branched to itself, so the program can never continue
+    Trapped(u16),
+    /// The processor could not execute a step
+    Error(ExecutionError),
+    /// The run limit was reached
+    Paused { steps: u64 },
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stop::Done => Ok(()),
+            Stop::Breakpoint(addr) => write!(f, "Breakpoint at ${addr:04X}"),
+            Stop::Watchpoint(hits) => {
+                let hits: Vec<String> = hits.iter().map(|hit| format!("Watchpoint: {hit}")).collect();
+                f.write_str(&hits.join("\n"))
+            }
+            Stop::ExpressionChanged { expression, old, new } =>
+                write!(f, "Watch {expression}: {} -> {}", format_result(old), format_result(new)),
+            Stop::ConditionFailed(addr, error) => write!(f, "Breakpoint at ${addr:04X}, whose condition failed: {error}"),
+            Stop::Trapped(addr) => write!(f, "Trapped at ${addr:04X}"),
+            Stop::Error(error) => write!(f, "Execution stopped: {error}"),
+            Stop::Paused { steps } => write!(f, "Paused after {steps} steps"),
+        }
+    }
+}
+
+/// A breakpoint, and the number of times execution has reached it, whether or not its condition
+/// stopped it
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Breakpoint {
+    pub addr: u16,
+    pub hits: u64,
+    /// Expression which must not be zero for the breakpoint to stop execution
+    pub condition: Option<Expression>,
+}
+
+/// An expression being watched, and its value after the last step
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct ExpressionWatch {
+    expression: Expression,
+    value: Result<i64, EvalError>,
+}
+
+/// Breakpoints and settings of a debugging session
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Debugger {
+    breakpoints: BTreeMap<u16, Breakpoint>,
+    /// Watched expressions, which are numbered after the processor's watchpoints
+    watches: Vec<ExpressionWatch>,
+    /// Symbols which expressions can use
+    symbols: HashMap<String, i64>,
+    run_limit: u64,
+}
+
+impl Default for Debugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger { breakpoints: BTreeMap::new(), watches: Vec::new(), symbols: HashMap::new(), run_limit: DEFAULT_RUN_LIMIT }
+    }
+
+    /// Sets the number of steps a command runs for before pausing
+    pub fn set_run_limit(&mut self, steps: u64) {
+        self.run_limit = steps;
+    }
+
+    /// Sets the symbols which expressions can use, such as the symbols of an assembled program
+    pub fn set_symbols(&mut self, symbols: HashMap<String, i64>) {
+        self.symbols = symbols;
+    }
+
+    /// Adds a breakpoint at the address
+    /// Returns false if there already was one
+    pub fn add_breakpoint(&mut self, addr: u16) -> bool {
+        self.add_conditional_breakpoint(addr, None)
+    }
+
+    /// Adds a breakpoint at the address, which only stops when the condition is not zero
+    /// Returns false if there already was one
+    pub fn add_conditional_breakpoint(&mut self, addr: u16, condition: Option<Expression>) -> bool {
+        if self.breakpoints.contains_key(&addr) {
+            return false;
+        }
+        self.breakpoints.insert(addr, Breakpoint { addr, hits: 0, condition });
+        true
+    }
+
+    /// Removes the breakpoint at the address
+    /// Returns false if there was none
+    pub fn remove_breakpoint(&mut self, addr: u16) -> bool {
+        self.breakpoints.remove(&addr).is_some()
+    }
+
+    pub fn clear_breakpoints(&mut self) {
+        self.breakpoints.clear();
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        self.breakpoints.values()
+    }
+
+    /// Watches the value of the expression, which must be valid now
+    pub fn add_watch<B: Bus>(&mut self, state: &ComputerState<B>, expression: Expression) -> Result<i64, EvalError> {
+        let value = expression.eval(state, &self.symbols, None)?;
+        self.watches.push(ExpressionWatch { expression, value: Ok(value) });
+        Ok(value)
+    }
+
+    /// Evaluates the expression, without a hit count
+    pub fn evaluate<B: Bus>(&self, state: &ComputerState<B>, expression: &Expression) -> Result<i64, EvalError> {
+        expression.eval(state, &self.symbols, None)
+    }
+
+    /// Runs the command, returning the text to show
+    pub fn execute<B: Bus>(&mut self, state: &mut ComputerState<B>, command: &Command) -> String {
+        match *command {
+            Command::Break(addr, ref condition) => if self.add_conditional_breakpoint(addr, condition.clone()) {
+                match condition {
+                    Some(condition) => format!("Breakpoint set at ${addr:04X} if {condition}"),
+                    None => format!("Breakpoint set at ${addr:04X}"),
+                }
+            } else {
+                format!("There is already a breakpoint at ${addr:04X}")
+            },
+            Command::Delete(Some(addr)) => if self.remove_breakpoint(addr) {
+                format!("Breakpoint at ${addr:04X} deleted")
+            } else {
+                format!("There is no breakpoint at ${addr:04X}")
+            },
+            Command::Delete(None) => {
+                self.clear_breakpoints();
+                "Every breakpoint deleted".to_string()
+            }
+            Command::Watch(Some(ref watchpoint)) => {
+                state.add_watchpoint(watchpoint.range.clone(), watchpoint.kind);
+                format!("Watchpoint {} set on {watchpoint}", state.watchpoints().len())
+            }
+            Command::WatchExpression(ref expression) => match self.add_watch(state, expression.clone()) {
+                Ok(value) => format!(
+                    "Watchpoint {} set on {expression}, which is {}",
+                    state.watchpoints().len() + self.watches.len(),
+                    format_value(value),
+                ),
+                Err(error) => format!("Cannot watch {expression}: {error}"),
+            },
+            Command::Watch(None) if state.watchpoints().is_empty() && self.watches.is_empty() =>
+                "There are no watchpoints".to_string(),
+            Command::Watch(None) => {
+                let watchpoints = state.watchpoints().iter().map(ToString::to_string);
+                let watches = self.watches.iter().map(|watch| format!("expr {}", watch.expression));
+                watchpoints.chain(watches).enumerate()
+                    .map(|(index, watchpoint)| format!("{}: {watchpoint}", index + 1))
+                    .collect::<Vec<_>>().join("\n")
+            }
+            Command::Unwatch(Some(number)) => match self.remove_watch(state, number) {
+                Some(watchpoint) => format!("Watchpoint {number} on {watchpoint} deleted"),
+                None => format!("There is no watchpoint {number}"),
+            },
+            Command::Unwatch(None) => {
+                state.clear_watchpoints();
+                self.watches.clear();
+                "Every watchpoint deleted".to_string()
+            }
+            Command::Print(ref expression) => match self.evaluate(state, expression) {
+                Ok(value) => format!("{expression} = {}", format_value(value)),
+                Err(error) => format!("Cannot evaluate {expression}: {error}"),
+            },
+            Command::Continue => self.report(state, |debugger, state| debugger.run_continue(state)),
+            Command::Step(count) => self.report(state, |debugger, state| debugger.step(state, count)),
+            Command::Next => self.report(state, Debugger::next),
+            Command::Finish => self.report(state, Debugger::finish),
+            Command::Regs => registers(state),
+            Command::Set(register, value) => {
+                set_register(state, register, value);
+                registers(state)
+            }
+            Command::Mem { addr, len } => memory_dump(state, addr, len),
+            Command::Disasm { addr, count } => self.disassembly(state, addr.unwrap_or(state.pc.get() as u16), count),
+            Command::Help => HELP.to_string(),
+            Command::Quit => String::new(),
+        }
+    }
+
+    /// Removes the watchpoint with the number, counting from 1 through the processor's watchpoints and
+    /// then the watched expressions, returning a description of it
+    fn remove_watch<B: Bus>(&mut self, state: &mut ComputerState<B>, number: usize) -> Option<String> {
+        let index = number.checked_sub(1)?;
+        let watchpoints = state.watchpoints().len();
+        if index < watchpoints {
+            return state.remove_watchpoint(index).map(|watchpoint| watchpoint.to_string());
+        }
+        let index = index - watchpoints;
+        (index < self.watches.len()).then(|| format!("expr {}", self.watches.remove(index).expression))
+    }
+
+    /// Runs until a breakpoint or watchpoint
+    pub fn run_continue<B: Bus>(&mut self, state: &mut ComputerState<B>) -> Stop {
+        self.run(state, |_, _| false)
+    }
+
+    /// Executes the given number of steps, stopping early at breakpoints and watchpoints
+    pub fn step<B: Bus>(&mut self, state: &mut ComputerState<B>, count: u64) -> Stop {
+        let mut steps = 0;
+        self.run(state, |_, _| {
+            steps += 1;
+            steps >= count
+        })
+    }
+
+    /// Executes the next instruction, running the whole subroutine if it is a JSR
+    pub fn next<B: Bus>(&mut self, state: &mut ComputerState<B>) -> Stop {
+        let pc = state.pc.get() as u16;
+        if operations::decode_mnemonic(state.variant(), state.bus.peek(pc)) != "jsr" {
+            return self.step(state, 1);
+        }
+        // The subroutine has returned once the PC is after the JSR, with the return address popped
+        let return_addr = pc.wrapping_add(3);
+        let stack = state.get_stk();
+        self.run(state, |state, _| state.pc.get() as u16 == return_addr && state.get_stk() == stack)
+    }
+
+    /// Runs until an RTS returns from the current subroutine
+    pub fn finish<B: Bus>(&mut self, state: &mut ComputerState<B>) -> Stop {
+        let stack = state.get_stk();
+        self.run(state, |state, executed| {
+            // The return address of the current subroutine is just above the stack pointer
+            executed.is_some_and(|opcode| operations::decode_mnemonic(state.variant(), opcode) == "rts") &&
+                state.get_stk() > stack
+        })
+    }
+
+    /// Executes steps until the function returns true, given the opcode of the instruction executed
+    /// by the step if it executed one, or until the program stops for another reason
+    fn run<B: Bus>(&mut self, state: &mut ComputerState<B>, mut done: impl FnMut(&ComputerState<B>, Option<u8>) -> bool)
+        -> Stop {
+        // Only changes made by this run are reported, and not changes such as setting a register
+        state.take_watch_hits();
+        for watch in &mut self.watches {
+            watch.value = watch.expression.eval(state, &self.symbols, None);
+        }
+        for steps in 0..self.run_limit {
+            let pc = state.pc.get() as u16;
+            // The breakpoint the program is stopped at does not stop it again
+            if steps > 0 {
+                if let Some(breakpoint) = self.breakpoints.get_mut(&pc) {
+                    breakpoint.hits += 1;
+                    let condition = breakpoint.condition.as_ref()
+                        .map(|condition| condition.eval(state, &self.symbols, Some(breakpoint.hits)));
+                    match condition {
+                        None => return Stop::Breakpoint(pc),
+                        Some(Ok(0)) => {}
+                        Some(Ok(_)) => return Stop::Breakpoint(pc),
+                        Some(Err(error)) => return Stop::ConditionFailed(pc, error),
+                    }
+                }
+            }
+
+            let opcode = state.bus.peek(pc);
+            let stack = state.get_stk();
+            let executed = match state.execute_next() {
+                Ok(StepOutcome::Executed { .. } | StepOutcome::Trapped { .. }) => Some(opcode),
+                Ok(StepOutcome::Interrupted { .. } | StepOutcome::Waiting) => None,
+                Err(error) => return Stop::Error(error),
+            };
+            let hits = state.take_watch_hits();
+            if !hits.is_empty() {
+                return Stop::Watchpoint(hits);
+            }
+            for watch in &mut self.watches {
+                let value = watch.expression.eval(state, &self.symbols, None);
+                if value != watch.value {
+                    let old = std::mem::replace(&mut watch.value, value.clone());
+                    return Stop::ExpressionChanged { expression: watch.expression.clone(), old, new: value };
+                }
+            }
+            if done(state, executed) {
+                return Stop::Done;
+            }
+            // An RTS can return to itself, but it moves the stack pointer, unlike a trap
+            if executed.is_some() && state.pc.get() as u16 == pc && state.get_stk() == stack {
+                return Stop::Trapped(pc);
+            }
+        }
+        Stop::Paused { steps: self.run_limit }
+    }
+
+    /// Runs the program with the function, then describes why it stopped and where
+    fn report<B: Bus>(&mut self, state: &mut ComputerState<B>, run: impl FnOnce(&mut Self, &mut ComputerState<B>) -> Stop)
+        -> String {
+        let stop = run(self, state);
+        let pc = state.pc.get() as u16;
+        let current = self.disassembly(state, pc, 1);
+        match stop {
+            Stop::Done => current,
+            stop => format!("{stop}\n{current}"),
+        }
+    }
+
+    /// Disassembles the instructions from the address, marking the PC and breakpoints
+    fn disassembly<B: Bus>(&self, state: &ComputerState<B>, addr: u16, count: usize) -> String {
+        let mut addr = addr;
+        let mut lines = Vec::with_capacity(count);
+        for _ in 0..count {
+            let instruction = disassembler::disassemble_at(state.variant(), addr, |addr| state.bus.peek(addr));
+            let pc_marker = if addr == state.pc.get() as u16 { '>' } else { ' ' };
+            let breakpoint_marker = if self.breakpoints.contains_key(&addr) { '*' } else { ' ' };
+            lines.push(format!("{breakpoint_marker}{pc_marker}{instruction}"));
+            addr = addr.wrapping_add(instruction.bytes.len() as u16);
+        }
+        lines.join("\n")
+    }
+}
+
+/// Returns the registers, with the flags as letters which are upper case when set
+pub fn registers<B: Bus>(state: &ComputerState<B>) -> String {
+    let flags: String = [
+        ('n', StatusRegister::N),
+        ('v', StatusRegister::V),
+        ('-', StatusRegister::U),
+        ('b', StatusRegister::B),
+        ('d', StatusRegister::D),
+        ('i', StatusRegister::I),
+        ('z', StatusRegister::Z),
+        ('c', StatusRegister::C),
+    ].iter().map(|&(letter, flag)| if state.sta.contains(flag) { letter.to_ascii_uppercase() } else { letter }).collect();
+
+    format!(
+        "PC=${:04X} A=${:02X} X=${:02X} Y=${:02X} SP=${:02X} P=${:02X} [{flags}] CYC={}",
+        state.pc.get(),
+        state.acc.get(),
+        state.get_x(),
+        state.get_y(),
+        state.get_stk(),
+        state.sta.as_byte(),
+        state.cycles,
+    )
+}
+
+/// Returns the value in decimal, and in hex if it fits in a word
+pub(crate) fn format_value(value: i64) -> String {
+    match value {
+        0..=0xFF => format!("{value} (${value:02X})"),
+        0x100..=0xFFFF => format!("{value} (${value:04X})"),
+        _ => value.to_string(),
+    }
+}
+
+fn format_result(result: &Result<i64, EvalError>) -> String {
+    match result {
+        Ok(value) => format_value(*value),
+        Err(error) => format!("<{error}>"),
+    }
+}
+
+fn set_register<B: Bus>(state: &mut ComputerState<B>, register: Register, value: u16) {
+    match register {
+        Register::A => state.acc.set(value as u8),
+        Register::X => state.set_x(usize::from(value)),
+        Register::Y => state.set_y(usize::from(value)),
+        Register::Sp => state.set_stk(usize::from(value)),
+        Register::Pc => state.pc.set(usize::from(value)),
+        Register::P => state.sta = StatusRegister::from_byte(value as u8),
+        Register::Flag(flag) => state.sta = state.sta.replace(flag, flag.get_cond(value != 0)),
+    }
+}
+
+/// Returns a hex dump of the memory, with the printable bytes as ASCII
+pub fn memory_dump<B: Bus>(state: &ComputerState<B>, addr: u16, len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|offset| state.bus.peek(addr.wrapping_add(offset as u16))).collect();
+    bytes.chunks(MEM_BYTES_PER_LINE).enumerate().map(|(index, row)| {
+        let hex = row.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<_>>().join(" ");
+        let ascii: String = row.iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { char::from(byte) } else { '.' })
+            .collect();
+        let line_addr = addr.wrapping_add((index * MEM_BYTES_PER_LINE) as u16);
+        format!("{line_addr:04X}  {hex:<width$}  {ascii}", width = MEM_BYTES_PER_LINE * 3 - 1)
+    }).collect::<Vec<_>>().join("\n")
+}
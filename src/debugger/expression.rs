@@ -0,0 +1,257 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::Bus;
+use crate::debugger::Register;
+use crate::expression::{Grammar, Parser};
+
+// Expressions for breakpoint conditions and watches
+// Operators follow C precedence, from lowest to highest: || && | ^ & == != < <= > >= << >> + - * / %
+// Unary operators are - ~ and !. Comparisons and boolean operators give 1 for true and 0 for false
+// Values are registers (a, x, y, sp, pc, p), flags (n, v, b, d, i, z, c), the cycle counter (cycles),
+// the hit count of the breakpoint (hits), bytes and words of memory ([addr] and w[addr]), numbers and
+// symbols. The names of registers, flags, cycles and hits can be in any case, and hide symbols with
+// the same name
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+enum Expr {
+    Number(i64),
+    Register(Register),
+    Cycles,
+    Hits,
+    Symbol(String),
+    /// The byte of memory at the address
+    Byte(Box<Expr>),
+    /// The little endian word of memory at the address
+    Word(Box<Expr>),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum UnaryOp {
+    Negate,
+    Not,
+    LogicalNot,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum BinaryOp {
+    LogicalOr,
+    LogicalAnd,
+    Or,
+    Xor,
+    And,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    ShiftLeft,
+    ShiftRight,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
+
+/// Binary operators at each level of precedence, from lowest to highest
+const PRECEDENCE: [&[(&str, BinaryOp)]; 10] = [
+    &[("||", BinaryOp::LogicalOr)],
+    &[("&&", BinaryOp::LogicalAnd)],
+    &[("|", BinaryOp::Or)],
+    &[("^", BinaryOp::Xor)],
+    &[("&", BinaryOp::And)],
+    &[("==", BinaryOp::Equal), ("!=", BinaryOp::NotEqual)],
+    &[("<", BinaryOp::Less), ("<=", BinaryOp::LessEqual), (">", BinaryOp::Greater), (">=", BinaryOp::GreaterEqual)],
+    &[("<<", BinaryOp::ShiftLeft), (">>", BinaryOp::ShiftRight)],
+    &[("+", BinaryOp::Add), ("-", BinaryOp::Subtract)],
+    &[("*", BinaryOp::Multiply), ("/", BinaryOp::Divide), ("%", BinaryOp::Remainder)],
+];
+
+/// Every operator, so that the longest one at a position can be found
+const OPERATORS: [&str; 21] = [
+    "||", "&&", "|", "^", "&", "==", "!=", "<=", ">=", "<<", ">>", "<", ">", "+", "-", "*", "/", "%", "~", "!", "=",
+];
+
+/// Reason an expression has no value
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum EvalError {
+    Undefined(String),
+    /// The hit count was used outside of a breakpoint condition
+    NoHitCount,
+    DivisionByZero,
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::Undefined(name) => write!(f, "{name} is not defined"),
+            EvalError::NoHitCount => f.write_str("hits can only be used in breakpoint conditions"),
+            EvalError::DivisionByZero => f.write_str("division by zero"),
+        }
+    }
+}
+
+/// A parsed expression, along with the text it was parsed from
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Expression {
+    text: String,
+    expr: Expr,
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl Expression {
+    pub fn parse(text: &str) -> Result<Expression, String> {
+        let text = text.trim();
+        let expr = Parser::parse(text, DebuggerGrammar)?;
+        Ok(Expression { text: text.to_string(), expr })
+    }
+
+    /// Evaluates the expression against the processor, reading memory without side effects
+    /// The hit count is only given for breakpoint conditions
+    pub fn eval<B: Bus>(&self, state: &ComputerState<B>, symbols: &HashMap<String, i64>, hits: Option<u64>)
+        -> Result<i64, EvalError> {
+        self.expr.eval(&Context { state, symbols, hits })
+    }
+}
+
+struct Context<'a, B: Bus> {
+    state: &'a ComputerState<B>,
+    symbols: &'a HashMap<String, i64>,
+    hits: Option<u64>,
+}
+
+impl Expr {
+    fn eval<B: Bus>(&self, context: &Context<B>) -> Result<i64, EvalError> {
+        let state = context.state;
+        Ok(match self {
+            Expr::Number(value) => *value,
+            Expr::Register(register) => match *register {
+                Register::A => i64::from(state.acc.get()),
+                Register::X => state.get_x() as i64,
+                Register::Y => state.get_y() as i64,
+                Register::Sp => state.get_stk() as i64,
+                Register::Pc => state.pc.get() as i64,
+                Register::P => i64::from(state.sta.as_byte()),
+                Register::Flag(flag) => i64::from(state.sta.contains(flag)),
+            },
+            Expr::Cycles => state.cycles as i64,
+            Expr::Hits => context.hits.ok_or(EvalError::NoHitCount)? as i64,
+            Expr::Symbol(name) => *context.symbols.get(name).ok_or_else(|| EvalError::Undefined(name.clone()))?,
+            // Addresses wrap around at the end of memory
+            Expr::Byte(addr) => i64::from(state.peek_byte_from_addr(addr.eval(context)? as u16 as usize)),
+            Expr::Word(addr) => {
+                let addr = addr.eval(context)? as u16;
+                let lo_byte = i64::from(state.peek_byte_from_addr(usize::from(addr)));
+                let hi_byte = i64::from(state.peek_byte_from_addr(usize::from(addr.wrapping_add(1))));
+                (hi_byte << 8) | lo_byte
+            }
+            Expr::Unary(op, expr) => {
+                let value = expr.eval(context)?;
+                match op {
+                    UnaryOp::Negate => value.wrapping_neg(),
+                    UnaryOp::Not => !value,
+                    UnaryOp::LogicalNot => i64::from(value == 0),
+                }
+            }
+            // The right hand side of a boolean operator is only evaluated when it is needed
+            Expr::Binary(BinaryOp::LogicalOr, lhs, rhs) =>
+                i64::from(lhs.eval(context)? != 0 || rhs.eval(context)? != 0),
+            Expr::Binary(BinaryOp::LogicalAnd, lhs, rhs) =>
+                i64::from(lhs.eval(context)? != 0 && rhs.eval(context)? != 0),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.eval(context)?, rhs.eval(context)?);
+                match op {
+                    BinaryOp::LogicalOr | BinaryOp::LogicalAnd => unreachable!("boolean operators are evaluated above"),
+                    BinaryOp::Or => lhs | rhs,
+                    BinaryOp::Xor => lhs ^ rhs,
+                    BinaryOp::And => lhs & rhs,
+                    BinaryOp::Equal => i64::from(lhs == rhs),
+                    BinaryOp::NotEqual => i64::from(lhs != rhs),
+                    BinaryOp::Less => i64::from(lhs < rhs),
+                    BinaryOp::LessEqual => i64::from(lhs <= rhs),
+                    BinaryOp::Greater => i64::from(lhs > rhs),
+                    BinaryOp::GreaterEqual => i64::from(lhs >= rhs),
+                    BinaryOp::ShiftLeft => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)).unwrap_or(0),
+                    BinaryOp::ShiftRight => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)).unwrap_or(0),
+                    BinaryOp::Add => lhs.wrapping_add(rhs),
+                    BinaryOp::Subtract => lhs.wrapping_sub(rhs),
+                    BinaryOp::Multiply => lhs.wrapping_mul(rhs),
+                    BinaryOp::Divide => lhs.checked_div(rhs).ok_or(EvalError::DivisionByZero)?,
+                    BinaryOp::Remainder => lhs.checked_rem(rhs).ok_or(EvalError::DivisionByZero)?,
+                }
+            }
+        })
+    }
+}
+
+/// Returns whether the character can be part of a name, which includes the @ of qualified local labels
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '@'
+}
+
+/// Syntax of breakpoint conditions and watches
+struct DebuggerGrammar;
+
+impl Grammar for DebuggerGrammar {
+    type Expr = Expr;
+    type UnaryOp = UnaryOp;
+    type BinaryOp = BinaryOp;
+
+    const PRECEDENCE: &'static [&'static [(&'static str, BinaryOp)]] = &PRECEDENCE;
+    const UNARY: &'static [(&'static str, UnaryOp)] = &[
+        ("-", UnaryOp::Negate),
+        ("~", UnaryOp::Not),
+        ("!", UnaryOp::LogicalNot),
+    ];
+    const OPERATORS: &'static [&'static str] = &OPERATORS;
+
+    fn unary(op: UnaryOp, expr: Expr) -> Expr {
+        Expr::Unary(op, Box::new(expr))
+    }
+
+    fn binary(op: BinaryOp, lhs: Expr, rhs: Expr) -> Expr {
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    }
+
+    fn primary(parser: &mut Parser<'_, Self>, first: char) -> Result<Expr, String> {
+        if parser.rest().get(..2).is_some_and(|start| start.eq_ignore_ascii_case("w[")) {
+            parser.skip(2);
+            return Ok(Expr::Word(Box::new(parser.bracketed("]")?)));
+        }
+        match first {
+            '(' => {
+                parser.skip(1);
+                parser.bracketed(")")
+            }
+            '[' => {
+                parser.skip(1);
+                Ok(Expr::Byte(Box::new(parser.bracketed("]")?)))
+            }
+            '$' => parser.number(1, 16).map(Expr::Number),
+            '%' => parser.number(1, 2).map(Expr::Number),
+            '0'..='9' => parser.number(0, 10).map(Expr::Number),
+            '@' | '_' | 'a'..='z' | 'A'..='Z' => {
+                let name = parser.take_while(is_name_char);
+                Ok(match name.to_ascii_lowercase().as_str() {
+                    "cycles" => Expr::Cycles,
+                    "hits" => Expr::Hits,
+                    lower => match super::parse_register(lower) {
+                        Ok(register) => Expr::Register(register),
+                        Err(_) => Expr::Symbol(name.to_string()),
+                    },
+                })
+            }
+            _ => Err(format!("unexpected '{first}' in expression")),
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::computer_state::CpuVariant;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::operations::{self, AddressingMode};
+
+// Disassembly into standard 6502 assembly syntax
+// The length and operand of each instruction come from its addressing mode. Illegal opcodes, and
+// instructions cut off by the end of the range, are written as .byte directives, so the output
+// always reassembles to the same bytes
+
+/// A disassembled instruction, or a byte which is not one
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Instruction {
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    /// Assembly text, such as "LDA ($20),Y"
+    pub text: String,
+}
+
+/// Writes the address, the bytes and the assembly text
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = self.bytes.iter().map(|byte| format!("{byte:02X}")).collect::<Vec<_>>().join(" ");
+        write!(f, "{:04X}  {bytes:<8}  {}", self.addr, self.text)
+    }
+}
+
+/// Disassembles the instruction at the address, reading memory with the function
+/// The instruction may run past the end of memory, in which case its operand wraps around to $0000
+pub fn disassemble_at(variant: CpuVariant, addr: u16, read: impl Fn(u16) -> u8) -> Instruction {
+    let opcode = read(addr);
+    if operations::is_illegal(variant, opcode) {
+        return data_byte(addr, opcode);
+    }
+    let len = operations::instruction_len(variant, opcode);
+    let bytes: Vec<u8> = (0..len as u16).map(|offset| read(addr.wrapping_add(offset))).collect();
+    let mnemonic = operations::decode_mnemonic(variant, opcode).to_uppercase();
+    let operand = format_operand(operations::decode_addressing_mode(variant, opcode), addr, &bytes[1..]);
+    let text = if operand.is_empty() { mnemonic } else { format!("{mnemonic} {operand}") };
+    Instruction { addr, bytes, text }
+}
+
+/// Disassembles the bytes, which start at the origin
+pub fn disassemble_bytes(variant: CpuVariant, origin: u16, bytes: &[u8]) -> Vec<Instruction> {
+    // Instructions cut off by the end read zeros, and are then written as bytes
+    let read = |addr: u16| bytes.get(usize::from(addr.wrapping_sub(origin))).copied().unwrap_or_default();
+    disassemble_with(variant, origin, bytes.len(), read)
+}
+
+/// Disassembles the instructions starting in the range of addresses on the bus
+/// Memory is peeked, so disassembling does not disturb devices
+pub fn disassemble<B: Bus>(bus: &B, variant: CpuVariant, range: RangeInclusive<u16>) -> Vec<Instruction> {
+    let len = usize::from(range.end().saturating_sub(*range.start())) + 1;
+    disassemble_with(variant, *range.start(), len, |addr| bus.peek(addr))
+}
+
+/// Disassembles the given number of bytes starting at the address
+fn disassemble_with(variant: CpuVariant, start: u16, len: usize, read: impl Fn(u16) -> u8) -> Vec<Instruction> {
+    let mut instructions = Vec::new();
+    let mut offset = 0;
+    while offset < len {
+        let addr = start.wrapping_add(offset as u16);
+        let mut instruction = disassemble_at(variant, addr, &read);
+        // An instruction cut off by the end is not decoded, as its operand is not there
+        if offset + instruction.bytes.len() > len {
+            instruction = data_byte(addr, read(addr));
+        }
+        offset += instruction.bytes.len();
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+fn data_byte(addr: u16, byte: u8) -> Instruction {
+    Instruction { addr, bytes: vec![byte], text: format!(".byte ${byte:02X}") }
+}
+
+/// Returns the operand in assembly syntax, given the bytes after the opcode
+pub fn format_operand(mode: AddressingMode, addr: u16, operand: &[u8]) -> String {
+    let byte = operand.first().copied().unwrap_or_default();
+    let word = u16::from_le_bytes([byte, operand.get(1).copied().unwrap_or_default()]);
+    match mode {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${byte:02X}"),
+        AddressingMode::ZeroPage => format!("${byte:02X}"),
+        AddressingMode::ZeroPageX => format!("${byte:02X},X"),
+        AddressingMode::ZeroPageY => format!("${byte:02X},Y"),
+        AddressingMode::Absolute => format!("${word:04X}"),
+        AddressingMode::AbsoluteX => format!("${word:04X},X"),
+        AddressingMode::AbsoluteY => format!("${word:04X},Y"),
+        AddressingMode::Indirect => format!("(${word:04X})"),
+        AddressingMode::IndirectX => format!("(${byte:02X},X)"),
+        AddressingMode::IndirectY => format!("(${byte:02X}),Y"),
+        AddressingMode::ZeroPageIndirect => format!("(${byte:02X})"),
+        AddressingMode::AbsoluteIndirectX => format!("(${word:04X},X)"),
+        AddressingMode::Relative => format!("${:04X}", branch_target(addr, 2, byte)),
+        AddressingMode::ZeroPageRelative => {
+            let offset = operand.get(1).copied().unwrap_or_default();
+            format!("${byte:02X},${:04X}", branch_target(addr, 3, offset))
+        }
+    }
+}
+
+/// Returns the address a branch of the given length at the address jumps to
+pub const fn branch_target(addr: u16, len: u16, offset: u8) -> u16 {
+    addr.wrapping_add(len).wrapping_add(offset as i8 as u16)
+}
@@ -0,0 +1,143 @@
+// Parsing shared by the expression languages of the assembler and the debugger
+// A grammar supplies its operators and how to parse its values, and the parser handles the lexing and
+// precedence climbing. Unary operators bind more tightly than every binary operator, and binary
+// operators at the same level of precedence are left associative
+
+/// Operators and values of an expression language
+pub(crate) trait Grammar: Sized {
+    type Expr;
+    type UnaryOp: Copy + 'static;
+    type BinaryOp: Copy + 'static;
+
+    /// Binary operators at each level of precedence, from lowest to highest
+    const PRECEDENCE: &'static [&'static [(&'static str, Self::BinaryOp)]];
+    const UNARY: &'static [(&'static str, Self::UnaryOp)];
+    /// Every operator, so that an operator is only taken if it is the longest one at its position
+    /// If this is empty, an operator is taken whenever the input continues with it
+    const OPERATORS: &'static [&'static str] = &[];
+
+    fn unary(op: Self::UnaryOp, expr: Self::Expr) -> Self::Expr;
+
+    fn binary(op: Self::BinaryOp, lhs: Self::Expr, rhs: Self::Expr) -> Self::Expr;
+
+    /// Parses a value, such as a number, a name or a bracketed expression, which starts with the
+    /// given character
+    fn primary(parser: &mut Parser<'_, Self>, first: char) -> Result<Self::Expr, String>;
+}
+
+pub(crate) struct Parser<'a, G> {
+    text: &'a str,
+    pos: usize,
+    pub(crate) grammar: G,
+}
+
+impl<'a, G: Grammar> Parser<'a, G> {
+    /// Parses the whole of the text as an expression
+    pub(crate) fn parse(text: &'a str, grammar: G) -> Result<G::Expr, String> {
+        let mut parser = Parser { text, pos: 0, grammar };
+        let expr = parser.expression()?;
+        parser.skip_whitespace();
+        if parser.pos != text.len() {
+            return Err(format!("unexpected '{}' in expression", parser.rest()));
+        }
+        Ok(expr)
+    }
+
+    pub(crate) fn expression(&mut self) -> Result<G::Expr, String> {
+        self.binary(0)
+    }
+
+    pub(crate) fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// Moves past the given number of bytes, which must end on a character boundary
+    pub(crate) fn skip(&mut self, len: usize) {
+        self.pos += len;
+    }
+
+    pub(crate) fn skip_whitespace(&mut self) {
+        self.pos = self.text.len() - self.rest().trim_start().len();
+    }
+
+    /// Consumes the token if the input continues with it
+    pub(crate) fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        let matches = self.rest().starts_with(token);
+        if matches {
+            self.pos += token.len();
+        }
+        matches
+    }
+
+    /// Consumes the operator if the input continues with it, and if it is the longest operator there,
+    /// so that < does not match the start of <=
+    fn eat_operator(&mut self, operator: &str) -> bool {
+        if G::OPERATORS.is_empty() {
+            return self.eat(operator);
+        }
+        self.skip_whitespace();
+        let rest = self.rest();
+        let longest = G::OPERATORS.iter().filter(|&&candidate| rest.starts_with(candidate)).max_by_key(|op| op.len());
+        let matches = longest == Some(&operator);
+        if matches {
+            self.pos += operator.len();
+        }
+        matches
+    }
+
+    /// Consumes characters while they match, returning them
+    pub(crate) fn take_while(&mut self, matches: impl Fn(char) -> bool) -> &'a str {
+        let start = self.pos;
+        let len = self.rest().find(|c| !matches(c)).unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.text[start..self.pos]
+    }
+
+    /// Parses an expression up to the closing bracket
+    pub(crate) fn bracketed(&mut self, close: &str) -> Result<G::Expr, String> {
+        let expr = self.expression()?;
+        if !self.eat(close) {
+            return Err(format!("missing '{close}' in expression"));
+        }
+        Ok(expr)
+    }
+
+    /// Parses a number after a prefix of the given length
+    pub(crate) fn number(&mut self, prefix_len: usize, radix: u32) -> Result<i64, String> {
+        self.pos += prefix_len;
+        let digits = self.take_while(|c| c.is_ascii_alphanumeric());
+        i64::from_str_radix(digits, radix).map_err(|_| format!("invalid number '{digits}'"))
+    }
+
+    fn binary(&mut self, level: usize) -> Result<G::Expr, String> {
+        let Some(operators) = G::PRECEDENCE.get(level) else {
+            return self.unary();
+        };
+        let mut lhs = self.binary(level + 1)?;
+        'operators: loop {
+            for &(token, op) in operators.iter() {
+                if self.eat_operator(token) {
+                    let rhs = self.binary(level + 1)?;
+                    lhs = G::binary(op, lhs, rhs);
+                    continue 'operators;
+                }
+            }
+            return Ok(lhs);
+        }
+    }
+
+    fn unary(&mut self) -> Result<G::Expr, String> {
+        for &(token, op) in G::UNARY {
+            if self.eat_operator(token) {
+                let expr = self.unary()?;
+                return Ok(G::unary(op, expr));
+            }
+        }
+        self.skip_whitespace();
+        match self.rest().chars().next() {
+            Some(first) => G::primary(self, first),
+            None => Err("missing value in expression".to_string()),
+        }
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::BTreeSet;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::{AccessKind, Bus};
+use crate::computer_state::execution::StepOutcome;
+use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::watch::{WatchHit, WatchKind};
+
+// GDB remote serial protocol stub
+// Serves one debugger over any connection, such as a TCP stream or stdin and stdout. Packets are
+// framed as $data#checksum, and acknowledged with + until the debugger turns acknowledgements off
+// Registers are numbered a, x, y, sp, pc and p, as in the target description, and memory is read and
+// written without side effects on the bus. Breakpoints are kept by the stub, so memory is never
+// patched, and watchpoints are the processor's own, which stop after the instruction which made the
+// access. Interrupting a running program is only possible over connections which can be polled
+
+/// Number of steps between checks for an interrupt from the debugger while the program runs
+const INTERRUPT_POLL_STEPS: u64 = 10_000;
+/// Largest packet the debugger can send
+const PACKET_SIZE: usize = 0x4000;
+
+/// Signals reported when the program stops
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+/// Description of the registers, which is sent to the debugger on request
+pub const TARGET_XML: &str = r#"<?xml version="1.0"?>
+<!DOCTYPE target SYSTEM "gdb-target.dtd">
+<target version="1.0">
+  <feature name="org.mos6502.core">
+    <flags id="status_flags" size="1">
+      <field name="C" start="0" end="0"/>
+      <field name="Z" start="1" end="1"/>
+      <field name="I" start="2" end="2"/>
+      <field name="D" start="3" end="3"/>
+      <field name="B" start="4" end="4"/>
+      <field name="U" start="5" end="5"/>
+      <field name="V" start="6" end="6"/>
+      <field name="N" start="7" end="7"/>
+    </flags>
+    <reg name="a" bitsize="8" type="uint8" regnum="0"/>
+    <reg name="x" bitsize="8" type="uint8" regnum="1"/>
+    <reg name="y" bitsize="8" type="uint8" regnum="2"/>
+    <reg name="sp" bitsize="8" type="uint8" regnum="3"/>
+    <reg name="pc" bitsize="16" type="code_ptr" regnum="4"/>
+    <reg name="p" bitsize="8" type="status_flags" regnum="5"/>
+  </feature>
+</target>
+"#;
+
+/// A connection to the debugger
+pub trait Connection: Read + Write {
+    /// Returns whether the debugger has sent an interrupt, without waiting for one, and consumes it
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        Ok(false)
+    }
+}
+
+impl Connection for TcpStream {
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        self.set_nonblocking(true)?;
+        let mut byte = [0];
+        let peeked = self.peek(&mut byte);
+        self.set_nonblocking(false)?;
+        match peeked {
+            Ok(1) if byte[0] == 0x03 => {
+                self.read_exact(&mut byte)?;
+                Ok(true)
+            }
+            Ok(_) => Ok(false),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Stdin and stdout as a connection, for debuggers which start the stub as a pipe
+pub struct Stdio;
+
+impl Read for Stdio {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::stdin().read(buf)
+    }
+}
+
+impl Write for Stdio {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::stdout().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::stdout().flush()
+    }
+}
+
+impl Connection for Stdio {}
+
+/// What the debugger sent
+enum Incoming {
+    Packet(String),
+    /// A request to stop the program, which is sent outside of a packet
+    Interrupt,
+}
+
+/// What to do after handling a packet
+enum Reply {
+    Send(String),
+    /// Sends the reply, then ends the session
+    SendAndClose(String),
+    Close,
+}
+
+/// State of a session with a debugger
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct GdbStub {
+    breakpoints: BTreeSet<u16>,
+    /// Whether packets are acknowledged, which the debugger can turn off
+    acknowledge: bool,
+    /// Reply describing why the program last stopped
+    last_stop: String,
+}
+
+impl Default for GdbStub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GdbStub {
+    pub fn new() -> GdbStub {
+        GdbStub { breakpoints: BTreeSet::new(), acknowledge: true, last_stop: format!("S{SIGTRAP:02x}") }
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = &u16> {
+        self.breakpoints.iter()
+    }
+
+    /// Serves packets until the debugger detaches, kills the program or closes the connection
+    pub fn serve<B: Bus, C: Connection>(&mut self, state: &mut ComputerState<B>, connection: &mut C) -> io::Result<()> {
+        while let Some(packet) = self.read_packet(connection)? {
+            let reply = match packet {
+                Incoming::Packet(packet) => self.handle(state, &packet, connection)?,
+                // An interrupt while the program is not running stops it where it is
+                Incoming::Interrupt => {
+                    self.last_stop = format!("S{SIGINT:02x}");
+                    Reply::Send(self.last_stop.clone())
+                }
+            };
+            match reply {
+                Reply::Send(data) => write_packet(connection, &data)?,
+                Reply::SendAndClose(data) => {
+                    write_packet(connection, &data)?;
+                    return Ok(());
+                }
+                Reply::Close => return Ok(()),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the next packet, acknowledging it if acknowledgements are on
+    /// Returns None once the connection is closed
+    fn read_packet<C: Connection>(&self, connection: &mut C) -> io::Result<Option<Incoming>> {
+        loop {
+            match read_byte(connection)? {
+                None => return Ok(None),
+                Some(b'$') => {}
+                Some(0x03) => return Ok(Some(Incoming::Interrupt)),
+                // Acknowledgements of our replies, and anything between packets, are ignored
+                Some(_) => continue,
+            }
+
+            let mut data = Vec::new();
+            let mut sum = 0u8;
+            loop {
+                let Some(byte) = read_byte(connection)? else {
+                    return Ok(None);
+                };
+                if byte == b'#' {
+                    break;
+                }
+                sum = sum.wrapping_add(byte);
+                if data.len() == PACKET_SIZE {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "packet is too long"));
+                }
+                data.push(byte);
+            }
+            let mut checksum = [0; 2];
+            connection.read_exact(&mut checksum)?;
+            let valid = std::str::from_utf8(&checksum).ok()
+                .and_then(|checksum| u8::from_str_radix(checksum, 16).ok()) == Some(sum);
+
+            if self.acknowledge {
+                connection.write_all(if valid { b"+" } else { b"-" })?;
+                connection.flush()?;
+            }
+            if valid {
+                return Ok(Some(Incoming::Packet(String::from_utf8_lossy(&unescape(&data)).into_owned())));
+            }
+        }
+    }
+
+    /// Handles a packet, returning what to reply
+    fn handle<B: Bus, C: Connection>(&mut self, state: &mut ComputerState<B>, packet: &str, connection: &mut C)
+        -> io::Result<Reply> {
+        let reply = |result: Result<String, String>| Reply::Send(result.unwrap_or_else(|error| error));
+        let Some(command) = packet.chars().next() else {
+            return Ok(Reply::Send(String::new()));
+        };
+        let args = &packet[command.len_utf8()..];
+
+        Ok(match command {
+            '?' => Reply::Send(self.last_stop.clone()),
+            'g' => Reply::Send(read_registers(state)),
+            'G' => reply(write_registers(state, args).map(|()| "OK".to_string())),
+            'p' => reply(parse_hex(args).and_then(|number| read_register(state, number))),
+            'P' => reply(
+                args.split_once('=')
+                    .ok_or_else(error)
+                    .and_then(|(number, value)| write_register(state, parse_hex(number)?, value))
+                    .map(|()| "OK".to_string()),
+            ),
+            'm' => reply(parse_memory_range(args).map(|(addr, len)| read_memory(state, addr, len))),
+            'M' => reply(write_memory(state, args).map(|()| "OK".to_string())),
+            'Z' => reply(self.set_point(state, args, true).map(|()| "OK".to_string())),
+            'z' => reply(self.set_point(state, args, false).map(|()| "OK".to_string())),
+            'c' | 's' => {
+                if !args.is_empty() {
+                    match parse_hex(args).and_then(|addr| u16::try_from(addr).map_err(|_| error())) {
+                        Ok(addr) => state.pc.set(usize::from(addr)),
+                        Err(error) => return Ok(Reply::Send(error)),
+                    }
+                }
+                self.last_stop = self.resume(state, connection, command == 's')?;
+                Reply::Send(self.last_stop.clone())
+            }
+            'q' => Reply::Send(self.query(args)),
+            'Q' if args == "StartNoAckMode" => {
+                // The debugger stops acknowledging after this reply, which is still acknowledged
+                self.acknowledge = false;
+                Reply::Send("OK".to_string())
+            }
+            // There is a single thread, so every thread operation applies to it
+            'H' => Reply::Send("OK".to_string()),
+            'T' => Reply::Send("OK".to_string()),
+            'D' => Reply::SendAndClose("OK".to_string()),
+            'k' => Reply::Close,
+            // Replying with nothing tells the debugger the packet is not supported
+            _ => Reply::Send(String::new()),
+        })
+    }
+
+    /// Handles the query packets
+    fn query(&self, args: &str) -> String {
+        if args.starts_with("Supported") {
+            return format!("PacketSize={PACKET_SIZE:x};qXfer:features:read+;QStartNoAckMode+");
+        }
+        if let Some(annex) = args.strip_prefix("Xfer:features:read:") {
+            let Some((name, range)) = annex.split_once(':') else {
+                return error();
+            };
+            if name != "target.xml" {
+                return "E00".to_string();
+            }
+            let Ok((offset, len)) = parse_memory_range(range).map(|(offset, len)| (offset as usize, len)) else {
+                return error();
+            };
+            let xml = TARGET_XML.as_bytes();
+            // The length comes from the packet, so it can be large enough to overflow
+            let end = offset.saturating_add(len).min(xml.len());
+            let chunk = &xml[offset.min(end)..end];
+            let more = end < xml.len();
+            return format!("{}{}", if more { 'm' } else { 'l' }, String::from_utf8_lossy(&escape(chunk)));
+        }
+        match args {
+            "Attached" => "1".to_string(),
+            "C" => "QC1".to_string(),
+            "fThreadInfo" => "m1".to_string(),
+            "sThreadInfo" => "l".to_string(),
+            _ => String::new(),
+        }
+    }
+
+    /// Inserts or removes a breakpoint or watchpoint, given as type,addr,kind
+    fn set_point<B: Bus>(&mut self, state: &mut ComputerState<B>, args: &str, insert: bool) -> Result<(), String> {
+        let mut fields = args.split(',');
+        let (Some(kind), Some(addr), Some(len)) = (fields.next(), fields.next(), fields.next()) else {
+            return Err(error());
+        };
+        let addr = u16::try_from(parse_hex(addr)?).map_err(|_| error())?;
+        let watch_kind = match kind {
+            "0" => {
+                if insert {
+                    self.breakpoints.insert(addr);
+                } else {
+                    self.breakpoints.remove(&addr);
+                }
+                return Ok(());
+            }
+            "2" => WatchKind::Write,
+            "3" => WatchKind::Read,
+            "4" => WatchKind::Access,
+            // Other kinds are not supported, which is shown by replying with nothing
+            _ => return Err(String::new()),
+        };
+
+        // The length of a watchpoint is in bytes
+        let len = u16::try_from(parse_hex(len)?).ok().filter(|&len| len > 0).ok_or_else(error)?;
+        let range = addr..=addr.checked_add(len - 1).ok_or_else(error)?;
+        if insert {
+            state.add_watchpoint(range, watch_kind);
+        } else if let Some(index) = state.watchpoints().iter()
+            .position(|watchpoint| watchpoint.range == range && watchpoint.kind == watch_kind) {
+            state.remove_watchpoint(index);
+        }
+        Ok(())
+    }
+
+    /// Executes a step, or runs until the program stops, returning the stop reply
+    fn resume<B: Bus, C: Connection>(&mut self, state: &mut ComputerState<B>, connection: &mut C, single_step: bool)
+        -> io::Result<String> {
+        state.take_watch_hits();
+        let mut steps = 0u64;
+        loop {
+            let pc = state.pc.get() as u16;
+            // The breakpoint the program is stopped at does not stop it again
+            if steps > 0 && self.breakpoints.contains(&pc) {
+                return Ok(format!("S{SIGTRAP:02x}"));
+            }
+            if steps > 0 && steps.is_multiple_of(INTERRUPT_POLL_STEPS) && connection.poll_interrupt()? {
+                return Ok(format!("S{SIGINT:02x}"));
+            }
+
+            let stack = state.get_stk();
+            let outcome = state.execute_next();
+            steps += 1;
+            let hits = state.take_watch_hits();
+            if let Some(hit) = hits.first() {
+                return Ok(watch_stop(state, hit));
+            }
+            match outcome {
+                Err(_) => return Ok(format!("S{SIGILL:02x}")),
+                // A program which jumps or branches to itself can never continue
+                Ok(StepOutcome::Executed { .. } | StepOutcome::Trapped { .. })
+                    if state.pc.get() as u16 == pc && state.get_stk() == stack => {
+                    return Ok(format!("S{SIGTRAP:02x}"));
+                }
+                Ok(_) => {}
+            }
+            if single_step {
+                return Ok(format!("S{SIGTRAP:02x}"));
+            }
+        }
+    }
+}
+
+/// Returns the stop reply for an access which triggered a watchpoint
+fn watch_stop<B: Bus>(state: &ComputerState<B>, hit: &WatchHit) -> String {
+    let access_watched = state.watchpoints().iter()
+        .any(|watchpoint| watchpoint.kind == WatchKind::Access && watchpoint.range.contains(&hit.addr));
+    let reason = match hit.access {
+        _ if access_watched => "awatch",
+        AccessKind::Read => "rwatch",
+        AccessKind::Write => "watch",
+    };
+    format!("T{SIGTRAP:02x}{reason}:{:04x};", hit.addr)
+}
+
+/// Returns the generic error reply
+fn error() -> String {
+    "E01".to_string()
+}
+
+fn read_byte<C: Connection>(connection: &mut C) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+    match connection.read(&mut byte)? {
+        0 => Ok(None),
+        _ => Ok(Some(byte[0])),
+    }
+}
+
+/// Sends the packet, escaping characters which have a meaning in the framing
+fn write_packet<C: Connection>(connection: &mut C, data: &str) -> io::Result<()> {
+    connection.write_all(frame(data).as_bytes())?;
+    connection.flush()
+}
+
+/// Frames the data as a packet, with its checksum
+pub fn frame(data: &str) -> String {
+    let sum = data.bytes().fold(0u8, u8::wrapping_add);
+    format!("${data}#{sum:02x}")
+}
+
+/// Escapes the bytes of binary data, which are sent as } followed by the byte xored with $20
+fn escape(data: &[u8]) -> Vec<u8> {
+    let mut escaped = Vec::with_capacity(data.len());
+    for &byte in data {
+        if matches!(byte, b'$' | b'#' | b'}' | b'*') {
+            escaped.extend([b'}', byte ^ 0x20]);
+        } else {
+            escaped.push(byte);
+        }
+    }
+    escaped
+}
+
+fn unescape(data: &[u8]) -> Vec<u8> {
+    let mut unescaped = Vec::with_capacity(data.len());
+    let mut bytes = data.iter();
+    while let Some(&byte) = bytes.next() {
+        match byte {
+            b'}' => unescaped.extend(bytes.next().map(|byte| byte ^ 0x20)),
+            byte => unescaped.push(byte),
+        }
+    }
+    unescaped
+}
+
+fn parse_hex(text: &str) -> Result<u64, String> {
+    u64::from_str_radix(text, 16).map_err(|_| error())
+}
+
+/// Parses hex as bytes, two digits at a time
+fn parse_bytes(text: &str) -> Result<Vec<u8>, String> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return Err(error());
+    }
+    (0..text.len()).step_by(2).map(|index| u8::from_str_radix(&text[index..index + 2], 16).map_err(|_| error())).collect()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Parses an address and length written as addr,len
+fn parse_memory_range(args: &str) -> Result<(u16, usize), String> {
+    let (addr, len) = args.split_once(',').ok_or_else(error)?;
+    let addr = u16::try_from(parse_hex(addr)?).map_err(|_| error())?;
+    Ok((addr, usize::try_from(parse_hex(len)?).map_err(|_| error())?))
+}
+
+/// Returns the bytes of the register, which are little endian
+fn register_bytes<B: Bus>(state: &ComputerState<B>, number: u64) -> Option<Vec<u8>> {
+    Some(match number {
+        0 => vec![state.acc.get()],
+        1 => vec![state.get_x() as u8],
+        2 => vec![state.get_y() as u8],
+        3 => vec![state.get_stk() as u8],
+        4 => (state.pc.get() as u16).to_le_bytes().to_vec(),
+        5 => vec![state.sta.as_byte()],
+        _ => return None,
+    })
+}
+
+/// Returns the size of the register in bytes
+const fn register_len(number: u64) -> usize {
+    if number == 4 { 2 } else { 1 }
+}
+
+const REGISTER_COUNT: u64 = 6;
+
+fn read_registers<B: Bus>(state: &ComputerState<B>) -> String {
+    (0..REGISTER_COUNT).filter_map(|number| register_bytes(state, number)).map(|bytes| hex(&bytes)).collect()
+}
+
+fn read_register<B: Bus>(state: &ComputerState<B>, number: u64) -> Result<String, String> {
+    register_bytes(state, number).map(|bytes| hex(&bytes)).ok_or_else(error)
+}
+
+fn write_registers<B: Bus>(state: &mut ComputerState<B>, args: &str) -> Result<(), String> {
+    let bytes = parse_bytes(args)?;
+    let total: usize = (0..REGISTER_COUNT).map(register_len).sum();
+    if bytes.len() < total {
+        return Err(error());
+    }
+    let mut offset = 0;
+    for number in 0..REGISTER_COUNT {
+        let len = register_len(number);
+        set_register(state, number, &bytes[offset..offset + len]);
+        offset += len;
+    }
+    Ok(())
+}
+
+fn write_register<B: Bus>(state: &mut ComputerState<B>, number: u64, value: &str) -> Result<(), String> {
+    let bytes = parse_bytes(value)?;
+    if number >= REGISTER_COUNT || bytes.len() != register_len(number) {
+        return Err(error());
+    }
+    set_register(state, number, &bytes);
+    Ok(())
+}
+
+/// Sets the register from its little endian bytes, which must be the size of the register
+fn set_register<B: Bus>(state: &mut ComputerState<B>, number: u64, bytes: &[u8]) {
+    match number {
+        0 => state.acc.set(bytes[0]),
+        1 => state.set_x(usize::from(bytes[0])),
+        2 => state.set_y(usize::from(bytes[0])),
+        3 => state.set_stk(usize::from(bytes[0])),
+        4 => state.pc.set(usize::from(u16::from_le_bytes([bytes[0], bytes[1]]))),
+        _ => state.sta = StatusRegister::from_byte(bytes[0]),
+    }
+}
+
+/// Reads memory without side effects, wrapping around at the end of memory
+fn read_memory<B: Bus>(state: &ComputerState<B>, addr: u16, len: usize) -> String {
+    let bytes: Vec<u8> = (0..len.min(PACKET_SIZE / 2)).map(|offset| state.bus.peek(addr.wrapping_add(offset as u16))).collect();
+    hex(&bytes)
+}
+
+/// Writes memory given as addr,len:bytes, loading it so that ROM can be changed too
+fn write_memory<B: Bus>(state: &mut ComputerState<B>, args: &str) -> Result<(), String> {
+    let (range, bytes) = args.split_once(':').ok_or_else(error)?;
+    let (addr, len) = parse_memory_range(range)?;
+    let bytes = parse_bytes(bytes)?;
+    if bytes.len() != len {
+        return Err(error());
+    }
+    state.load_at(addr, &bytes);
+    Ok(())
+}
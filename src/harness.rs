@@ -0,0 +1,56 @@
+use std::fmt;
+
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::{ExecutionError, StepOutcome};
+
+pub mod decimal;
+pub mod klaus;
+pub mod single_step;
+
+// Headless runners for test programs which signal their result by trapping
+// A trap is an instruction which jumps or branches to itself, so the PC never moves again
+
+/// Where a program trapped, and how long it ran for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TrapReport {
+    /// Address of the instruction which jumps or branches to itself
+    pub pc: u16,
+    /// Number of steps executed, including serviced interrupts
+    pub steps: u64,
+}
+
+/// Reason a program stopped before trapping
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunError {
+    /// The processor could not execute a step
+    Execution(ExecutionError),
+    /// The program ran for the maximum number of steps without trapping
+    Timeout { steps: u64 },
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Execution(error) => write!(f, "{error}"),
+            RunError::Timeout { steps } => write!(f, "no trap was reached after {steps} steps"),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+/// Runs the processor until an instruction leaves the PC where it was, or until the maximum number of
+/// steps have been executed
+/// Idling after WAI does not count as a trap, as the PC is waiting for an interrupt to move it
+pub fn run_until_trap<B: Bus>(state: &mut ComputerState<B>, max_steps: u64) -> Result<TrapReport, RunError> {
+    for steps in 1..=max_steps {
+        let pc = state.pc.get() as u16;
+        match state.execute_next().map_err(RunError::Execution)? {
+            StepOutcome::Executed { .. } | StepOutcome::Trapped { .. } if state.pc.get() as u16 == pc =>
+                return Ok(TrapReport { pc, steps }),
+            _ => {}
+        }
+    }
+    Err(RunError::Timeout { steps: max_steps })
+}
@@ -0,0 +1,64 @@
+use std::fmt;
+
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::ExecutionError;
+use crate::harness::{run_until_trap, RunError};
+
+// Bruce Clark's exhaustive decimal mode test, from http://www.6502.org/tutorials/decimal_mode.html
+// The addresses are those of Klaus Dormann's assembly of it, 6502_decimal_test.a65, which puts the code
+// at $0200 and the variables in the zero page
+// The test sets ERROR to 0 once every result matched its prediction, then ends by trapping, or by
+// executing STP on the 65C02
+
+/// Address the code of the decimal test is assembled at
+pub const DECIMAL_TEST_START: u16 = 0x0200;
+/// Address of the ERROR byte, which is 0 if the test passed and 1 if it failed
+pub const DECIMAL_TEST_ERROR: u16 = 0x000B;
+/// Upper bound on the steps the decimal test takes, which is about 20 million
+pub const DECIMAL_TEST_MAX_STEPS: u64 = 100_000_000;
+
+/// Why the decimal test failed
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DecimalTestFailure {
+    /// The test ended with ERROR set
+    Failed {
+        error: u8,
+        /// Registers and current instruction when the test ended
+        registers: String,
+    },
+    /// The test stopped before it ended
+    Stopped(RunError),
+}
+
+impl fmt::Display for DecimalTestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecimalTestFailure::Failed { error, registers } =>
+                write!(f, "decimal test failed with ERROR ${error:02X}\n{registers}"),
+            DecimalTestFailure::Stopped(error) => write!(f, "decimal test did not end: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for DecimalTestFailure {}
+
+/// Constructs a processor with the code of the decimal test loaded at its start, with the PC there
+pub fn load_decimal_test(variant: CpuVariant, program: &[u8]) -> ComputerState {
+    let mut state = ComputerState::with_variant(variant);
+    state.pc.set(usize::from(DECIMAL_TEST_START));
+    state.set_up_state(program);
+    state
+}
+
+/// Runs the loaded decimal test until it ends, which succeeds only if ERROR is 0
+pub fn run_decimal_test<B: Bus>(state: &mut ComputerState<B>) -> Result<(), DecimalTestFailure> {
+    match run_until_trap(state, DECIMAL_TEST_MAX_STEPS) {
+        Ok(_) | Err(RunError::Execution(ExecutionError::Halted)) => {}
+        Err(error) => return Err(DecimalTestFailure::Stopped(error)),
+    }
+    match state.peek_byte_from_addr(usize::from(DECIMAL_TEST_ERROR)) {
+        0 => Ok(()),
+        error => Err(DecimalTestFailure::Failed { error, registers: state.get_state_str() }),
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt;
+
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam, ADDRESS_SPACE_SIZE};
+use crate::computer_state::snapshot::{SnapshotError, StateReader, StateWriter};
+use crate::harness::{run_until_trap, RunError, TrapReport};
+
+// Klaus Dormann's 6502 test suites, from https://github.com/Klaus2m5/6502_65C02_functional_tests
+// The addresses are those of the prebuilt images in bin_files, which are assembled with the default
+// configuration
+
+/// Address of the first instruction of the functional test
+pub const FUNCTIONAL_TEST_START: u16 = 0x0400;
+/// Address of the trap reached when every part of the functional test has passed
+pub const FUNCTIONAL_TEST_SUCCESS: u16 = 0x3469;
+/// Address of the number of the test case being run by the functional test
+pub const FUNCTIONAL_TEST_CASE: u16 = 0x0200;
+/// Upper bound on the steps the functional test takes, which is about 30 million
+pub const FUNCTIONAL_TEST_MAX_STEPS: u64 = 100_000_000;
+
+/// Address of the first instruction of the interrupt test
+pub const INTERRUPT_TEST_START: u16 = 0x0400;
+/// Address of the trap reached when every part of the interrupt test has passed
+pub const INTERRUPT_TEST_SUCCESS: u16 = 0x06F5;
+/// Address of the number of the test case being run by the interrupt test
+pub const INTERRUPT_TEST_CASE: u16 = 0x0200;
+/// Upper bound on the steps the interrupt test takes
+pub const INTERRUPT_TEST_MAX_STEPS: u64 = 1_000_000;
+/// Address of the feedback register the interrupt test uses to raise interrupts
+pub const FEEDBACK_PORT: u16 = 0xBFFC;
+/// Bit of the feedback register connected to the IRQ line
+pub const FEEDBACK_IRQ_BIT: u8 = 0;
+/// Bit of the feedback register connected to the NMI line
+pub const FEEDBACK_NMI_BIT: u8 = 1;
+
+/// Why a test program failed, along with where it had got to
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TestFailure {
+    pub reason: FailureReason,
+    /// Number of the test case which was running
+    pub test_case: u8,
+    /// Registers and current instruction when the test stopped
+    pub registers: String,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FailureReason {
+    /// The test trapped somewhere other than the success address
+    Trapped { pc: u16 },
+    /// The test stopped before reaching a trap
+    Stopped(RunError),
+}
+
+impl fmt::Display for TestFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.reason {
+            FailureReason::Trapped { pc } =>
+                write!(f, "test case ${:02X} failed, trapped at ${pc:04X}", self.test_case)?,
+            FailureReason::Stopped(error) =>
+                write!(f, "test case ${:02X} failed: {error}", self.test_case)?,
+        }
+        write!(f, "\n{}", self.registers)
+    }
+}
+
+impl std::error::Error for TestFailure {}
+
+/// How the bits of the feedback register drive the interrupt lines
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FeedbackDrive {
+    /// Writing a 1 asserts the line
+    TotemPole,
+    /// Writing a 0 asserts the line, which is how the prebuilt interrupt test is configured
+    OpenCollector,
+}
+
+/// Register on the bus which the interrupt test writes to raise and release the interrupt lines
+/// Every other address is passed on to the bus behind it
+pub struct FeedbackPort<B: Bus = FlatRam> {
+    pub bus: B,
+    addr: u16,
+    drive: FeedbackDrive,
+    value: u8,
+}
+
+impl<B: Bus> FeedbackPort<B> {
+    /// Constructs the register at the address, with both interrupt lines released
+    pub fn new(bus: B, addr: u16, drive: FeedbackDrive) -> Self {
+        let value = match drive {
+            FeedbackDrive::TotemPole => 0x00,
+            FeedbackDrive::OpenCollector => 0xFF,
+        };
+        Self { bus, addr, drive, value }
+    }
+
+    /// Returns whether the bit of the register asserts its interrupt line
+    const fn asserts(&self, bit: u8) -> bool {
+        let set = self.value & (1 << bit) != 0;
+        match self.drive {
+            FeedbackDrive::TotemPole => set,
+            FeedbackDrive::OpenCollector => !set,
+        }
+    }
+}
+
+impl<B: Bus> Bus for FeedbackPort<B> {
+    fn write(&mut self, addr: u16, value: u8) {
+        if addr == self.addr {
+            self.value = value;
+        } else {
+            self.bus.write(addr, value);
+        }
+    }
+
+    fn load(&mut self, addr: u16, value: u8) {
+        if addr == self.addr {
+            self.value = value;
+        } else {
+            self.bus.load(addr, value);
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        if addr == self.addr { self.value } else { self.bus.peek(addr) }
+    }
+
+    fn is_plain_memory(&self, addr: u16) -> bool {
+        addr != self.addr && self.bus.is_plain_memory(addr)
+    }
+
+    fn irq(&self) -> bool {
+        self.asserts(FEEDBACK_IRQ_BIT)
+    }
+
+    fn nmi(&self) -> bool {
+        self.asserts(FEEDBACK_NMI_BIT)
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_u8(self.value);
+        snapshot.write_nested(|bus| self.bus.save_state(bus));
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        self.value = snapshot.read_u8()?;
+        self.bus.load_state(&mut snapshot.read_nested()?)
+    }
+}
+
+/// Constructs a processor with the 64K functional test image loaded, with the PC at its start
+pub fn load_functional_test(variant: CpuVariant, image: &[u8]) -> ComputerState {
+    let mut state = ComputerState::with_variant(variant);
+    state.load_at(0x0000, image);
+    state.pc.set(usize::from(FUNCTIONAL_TEST_START));
+    state
+}
+
+/// Constructs a processor with the 64K interrupt test image loaded behind the feedback register, with
+/// the PC at its start
+pub fn load_interrupt_test(variant: CpuVariant, image: &[u8], drive: FeedbackDrive) -> ComputerState<FeedbackPort> {
+    // The image is loaded behind the feedback register, which starts with the interrupt lines released
+    let mut ram = FlatRam::new();
+    for (addr, byte) in image.iter().take(ADDRESS_SPACE_SIZE).enumerate() {
+        ram.load(addr as u16, *byte);
+    }
+    let mut state = ComputerState::with_bus(variant, FeedbackPort::new(ram, FEEDBACK_PORT, drive));
+    state.pc.set(usize::from(INTERRUPT_TEST_START));
+    state
+}
+
+/// Runs a loaded test program until it traps
+/// Succeeds only if the trap is at the success address, and otherwise reports the test case number
+/// stored at the given address
+pub fn run_test<B: Bus>(state: &mut ComputerState<B>, success: u16, test_case: u16, max_steps: u64) -> Result<TrapReport, TestFailure> {
+    let reason = match run_until_trap(state, max_steps) {
+        Ok(report) if report.pc == success => return Ok(report),
+        Ok(report) => FailureReason::Trapped { pc: report.pc },
+        Err(error) => FailureReason::Stopped(error),
+    };
+    Err(TestFailure {
+        reason,
+        test_case: state.peek_byte_from_addr(usize::from(test_case)),
+        registers: state.get_state_str(),
+    })
+}
+
+/// Runs the loaded functional test, which succeeds only if it traps at the success address
+pub fn run_functional_test<B: Bus>(state: &mut ComputerState<B>) -> Result<TrapReport, TestFailure> {
+    run_test(state, FUNCTIONAL_TEST_SUCCESS, FUNCTIONAL_TEST_CASE, FUNCTIONAL_TEST_MAX_STEPS)
+}
+
+/// Runs the loaded interrupt test, which succeeds only if it traps at the success address
+pub fn run_interrupt_test<B: Bus>(state: &mut ComputerState<B>) -> Result<TrapReport, TestFailure> {
+    run_test(state, INTERRUPT_TEST_SUCCESS, INTERRUPT_TEST_CASE, INTERRUPT_TEST_MAX_STEPS)
+}
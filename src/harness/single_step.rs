@@ -0,0 +1,387 @@
+use std::fmt;
+use std::path::Path;
+
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam};
+pub use crate::computer_state::bus::AccessKind;
+use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::snapshot::{SnapshotError, StateReader, StateWriter};
+use crate::json::{Json, JsonError};
+
+// Runner for the single step tests of https://github.com/SingleStepTests/ProcessorTests
+// Each opcode has a JSON file of tests, which give the registers and RAM before and after executing
+// one instruction, along with every access the instruction makes to the bus
+
+/// Bits of the status register which only exist when it is pushed onto the stack
+const PUSHED_ONLY_FLAGS: u8 = StatusRegister::B.union(StatusRegister::U).as_byte();
+
+/// A single access made to the bus
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct BusAccess {
+    pub addr: u16,
+    pub value: u8,
+    pub kind: AccessKind,
+}
+
+impl fmt::Display for BusAccess {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            AccessKind::Read => "read",
+            AccessKind::Write => "write",
+        };
+        write!(f, "{kind} ${:02X} at ${:04X}", self.value, self.addr)
+    }
+}
+
+/// 64KB of RAM which records every read and write the processor makes
+pub struct RecordingRam {
+    ram: FlatRam,
+    pub activity: Vec<BusAccess>,
+}
+
+impl RecordingRam {
+    /// Constructs RAM with every byte set to 0, and nothing recorded
+    pub fn new() -> Self {
+        Self { ram: FlatRam::new(), activity: Vec::new() }
+    }
+}
+
+impl Default for RecordingRam {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Bus for RecordingRam {
+    fn read(&mut self, addr: u16) -> u8 {
+        let value = self.ram.peek(addr);
+        self.activity.push(BusAccess { addr, value, kind: AccessKind::Read });
+        value
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        self.activity.push(BusAccess { addr, value, kind: AccessKind::Write });
+        self.ram.write(addr, value)
+    }
+
+    fn load(&mut self, addr: u16, value: u8) {
+        self.ram.load(addr, value)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.ram.peek(addr)
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        self.ram.save_state(snapshot)
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        self.ram.load_state(snapshot)
+    }
+}
+
+/// Registers and RAM of the processor, before or after a test
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProcessorState {
+    pub pc: u16,
+    pub s: u8,
+    pub a: u8,
+    pub x: u8,
+    pub y: u8,
+    pub p: u8,
+    /// Address and value of every byte of RAM the test uses
+    pub ram: Vec<(u16, u8)>,
+}
+
+/// A single test of one instruction
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SingleStepTest {
+    pub name: String,
+    pub initial: ProcessorState,
+    pub expected: ProcessorState,
+    /// Every access the instruction makes to the bus, one per cycle
+    pub cycles: Vec<BusAccess>,
+}
+
+/// Reason a file of tests could not be read
+#[derive(Debug)]
+pub enum SingleStepError {
+    Io(std::io::Error),
+    Json(JsonError),
+    /// The JSON is valid, but is not in the format of the tests
+    Format { test: usize },
+}
+
+impl fmt::Display for SingleStepError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SingleStepError::Io(error) => write!(f, "{error}"),
+            SingleStepError::Json(error) => write!(f, "invalid JSON: {error}"),
+            SingleStepError::Format { test } => write!(f, "test {test} is not in the single step test format"),
+        }
+    }
+}
+
+impl std::error::Error for SingleStepError {}
+
+/// What is compared after a test, besides the registers and RAM
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct CheckOptions {
+    /// Compare the number of cycles taken with the number of bus accesses in the test
+    pub cycle_count: bool,
+    /// Compare every access to the bus with the test, which also checks the cycle count
+    pub bus_activity: bool,
+}
+
+/// A test which did not produce the expected state, with a description of every difference
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Mismatch {
+    pub test: String,
+    pub differences: Vec<String>,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.test, self.differences.join(", "))
+    }
+}
+
+fn parse_byte(value: Option<&Json>) -> Option<u8> {
+    value?.as_u64()?.try_into().ok()
+}
+
+fn parse_addr(value: Option<&Json>) -> Option<u16> {
+    value?.as_u64()?.try_into().ok()
+}
+
+impl ProcessorState {
+    fn from_json(json: &Json) -> Option<ProcessorState> {
+        let ram = json.get("ram")?.as_array()?.iter()
+            .map(|entry| {
+                let entry = entry.as_array()?;
+                Some((parse_addr(entry.first())?, parse_byte(entry.get(1))?))
+            })
+            .collect::<Option<_>>()?;
+        Some(ProcessorState {
+            pc: parse_addr(json.get("pc"))?,
+            s: parse_byte(json.get("s"))?,
+            a: parse_byte(json.get("a"))?,
+            x: parse_byte(json.get("x"))?,
+            y: parse_byte(json.get("y"))?,
+            p: parse_byte(json.get("p"))?,
+            ram,
+        })
+    }
+}
+
+impl SingleStepTest {
+    /// Reads a test from its JSON object, or returns None if it is not in the expected format
+    pub fn from_json(json: &Json) -> Option<SingleStepTest> {
+        let cycles = json.get("cycles")?.as_array()?.iter()
+            .map(|cycle| {
+                let cycle = cycle.as_array()?;
+                let kind = match cycle.get(2)?.as_str()? {
+                    "read" => AccessKind::Read,
+                    "write" => AccessKind::Write,
+                    _ => return None,
+                };
+                Some(BusAccess { addr: parse_addr(cycle.first())?, value: parse_byte(cycle.get(1))?, kind })
+            })
+            .collect::<Option<_>>()?;
+        Some(SingleStepTest {
+            name: json.get("name")?.as_str()?.to_string(),
+            initial: ProcessorState::from_json(json.get("initial")?)?,
+            expected: ProcessorState::from_json(json.get("final")?)?,
+            cycles,
+        })
+    }
+
+    /// Executes the instruction of the test, and returns every way the result differs from what
+    /// is expected
+    pub fn run(&self, variant: CpuVariant, options: CheckOptions) -> Result<(), Mismatch> {
+        self.run_on(&mut ComputerState::with_bus(variant, RecordingRam::new()), options)
+    }
+
+    /// Runs the test on a processor which may have run other tests, so that the RAM is reused
+    /// Every byte the test touches is cleared afterwards
+    fn run_on(&self, state: &mut ComputerState<RecordingRam>, options: CheckOptions) -> Result<(), Mismatch> {
+        let result = self.compare_step(state, options);
+
+        let touched = self.initial.ram.iter().chain(&self.expected.ram).map(|&(addr, _)| addr);
+        let accessed = state.bus.activity.iter().map(|access| access.addr);
+        for addr in touched.chain(accessed).collect::<Vec<_>>() {
+            state.bus.load(addr, 0);
+        }
+        result
+    }
+
+    fn compare_step(&self, state: &mut ComputerState<RecordingRam>, options: CheckOptions) -> Result<(), Mismatch> {
+        let initial = &self.initial;
+        // Resetting takes the processor out of any state left by the last test, such as a JAM
+        state.reset();
+        state.pc.set(usize::from(initial.pc));
+        state.set_stk(usize::from(initial.s));
+        state.acc.set(initial.a);
+        state.set_x(usize::from(initial.x));
+        state.set_y(usize::from(initial.y));
+        state.sta = StatusRegister::from_pulled_byte(initial.p);
+        for &(addr, value) in &initial.ram {
+            state.bus.load(addr, value);
+        }
+        state.bus.activity.clear();
+
+        let mut differences = Vec::new();
+        let cycles = match state.execute_next() {
+            Ok(outcome) => usize::from(outcome.cycles()),
+            Err(error) => {
+                differences.push(error.to_string());
+                0
+            }
+        };
+
+        let expected = &self.expected;
+        let mut compare = |name: &str, actual: usize, expected: usize| {
+            if actual != expected {
+                differences.push(format!("{name} was ${actual:02X}, expected ${expected:02X}"));
+            }
+        };
+        compare("PC", state.pc.get(), usize::from(expected.pc));
+        compare("S", state.get_stk(), usize::from(expected.s));
+        compare("A", usize::from(state.acc.get()), usize::from(expected.a));
+        compare("X", state.get_x(), usize::from(expected.x));
+        compare("Y", state.get_y(), usize::from(expected.y));
+        // B and U are not stored in the register, so they are not compared
+        compare(
+            "P",
+            usize::from(state.sta.as_byte() & !PUSHED_ONLY_FLAGS),
+            usize::from(expected.p & !PUSHED_ONLY_FLAGS),
+        );
+        for &(addr, value) in &expected.ram {
+            compare(&format!("${addr:04X}"), usize::from(state.bus.peek(addr)), usize::from(value));
+        }
+
+        if options.bus_activity {
+            if let Some(cycle) = (0..self.cycles.len().max(state.bus.activity.len()))
+                .find(|&cycle| self.cycles.get(cycle) != state.bus.activity.get(cycle))
+            {
+                let describe = |access: Option<&BusAccess>| access.map_or("nothing".to_string(), BusAccess::to_string);
+                differences.push(format!(
+                    "cycle {cycle} was {}, expected {}",
+                    describe(state.bus.activity.get(cycle)),
+                    describe(self.cycles.get(cycle))
+                ));
+            }
+        }
+        if (options.cycle_count || options.bus_activity) && cycles != self.cycles.len() {
+            differences.push(format!("took {cycles} cycles, expected {}", self.cycles.len()));
+        }
+
+        if differences.is_empty() {
+            Ok(())
+        } else {
+            Err(Mismatch { test: self.name.clone(), differences })
+        }
+    }
+}
+
+/// Parses a file of tests, which is a JSON array of test objects
+pub fn parse_tests(text: &str) -> Result<Vec<SingleStepTest>, SingleStepError> {
+    let json = Json::parse(text).map_err(SingleStepError::Json)?;
+    let tests = json.as_array().ok_or(SingleStepError::Format { test: 0 })?;
+    tests.iter()
+        .enumerate()
+        .map(|(index, test)| SingleStepTest::from_json(test).ok_or(SingleStepError::Format { test: index }))
+        .collect()
+}
+
+/// Results of the tests of one opcode
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct OpcodeResult {
+    pub passed: usize,
+    pub failed: usize,
+    /// The first test which failed, to show what went wrong
+    pub first_failure: Option<Mismatch>,
+}
+
+impl OpcodeResult {
+    pub const fn is_pass(&self) -> bool {
+        self.failed == 0
+    }
+}
+
+/// Results of the tests of every opcode, or None for opcodes which have no tests
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct OpcodeMatrix {
+    pub results: Vec<Option<OpcodeResult>>,
+}
+
+impl OpcodeMatrix {
+    /// Returns the opcodes which have failed at least one test
+    pub fn failed_opcodes(&self) -> impl Iterator<Item = u8> + '_ {
+        (0..=u8::MAX).filter(|&opcode| self.results[usize::from(opcode)].as_ref().is_some_and(|result| !result.is_pass()))
+    }
+}
+
+/// Writes a 16 by 16 grid of opcodes, where '.' passed, 'X' failed and '-' was not tested, followed by
+/// the first failure of every opcode which failed
+impl fmt::Display for OpcodeMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  ")?;
+        for low in 0..16 {
+            write!(f, " {low:X}")?;
+        }
+        for high in 0..16 {
+            write!(f, "\n{high:X}_")?;
+            for low in 0..16 {
+                let symbol = match &self.results[high * 16 + low] {
+                    Some(result) if result.is_pass() => '.',
+                    Some(_) => 'X',
+                    None => '-',
+                };
+                write!(f, " {symbol}")?;
+            }
+        }
+        for opcode in self.failed_opcodes() {
+            let Some(result) = &self.results[usize::from(opcode)] else { continue };
+            write!(f, "\n${opcode:02X} failed {} of {} tests", result.failed, result.failed + result.passed)?;
+            if let Some(mismatch) = &result.first_failure {
+                write!(f, ", first {mismatch}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Runs every test in the file, and returns how many passed
+pub fn run_tests(tests: &[SingleStepTest], variant: CpuVariant, options: CheckOptions) -> OpcodeResult {
+    let mut result = OpcodeResult::default();
+    let mut state = ComputerState::with_bus(variant, RecordingRam::new());
+    for test in tests {
+        match test.run_on(&mut state, options) {
+            Ok(()) => result.passed += 1,
+            Err(mismatch) => {
+                result.failed += 1;
+                result.first_failure.get_or_insert(mismatch);
+            }
+        }
+    }
+    result
+}
+
+/// Runs the tests of every opcode in the directory, which are in files named after the opcode in
+/// lower case hexadecimal, such as a9.json
+/// Opcodes without a file are left out of the matrix
+pub fn run_directory(dir: &Path, variant: CpuVariant, options: CheckOptions) -> Result<OpcodeMatrix, SingleStepError> {
+    let mut results = vec![None; 0x100];
+    for opcode in 0..=u8::MAX {
+        let path = dir.join(format!("{opcode:02x}.json"));
+        if !path.exists() {
+            continue;
+        }
+        let text = std::fs::read_to_string(&path).map_err(SingleStepError::Io)?;
+        let tests = parse_tests(&text)?;
+        results[usize::from(opcode)] = Some(run_tests(&tests, variant, options));
+    }
+    Ok(OpcodeMatrix { results })
+}
@@ -0,0 +1,20 @@
+//! Emulator of the MOS 6502 and WDC 65C02 processors
+//! The processor is `ComputerState`, which executes instructions against any `Bus`
+
+pub mod assembler;
+pub mod computer_state;
+pub mod dap;
+pub mod debugger;
+pub mod disassembler;
+mod expression;
+pub mod gdb;
+pub mod harness;
+pub mod json;
+pub mod trace;
+#[cfg(test)]
+mod test;
+
+pub use computer_state::{ComputerState, CpuVariant};
+pub use computer_state::bus::{Bus, FlatRam};
+pub use computer_state::execution::{ExecutionError, IllegalOpcodePolicy, StepOutcome};
+pub use computer_state::status_register::StatusRegister;
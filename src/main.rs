@@ -1,43 +1,396 @@
-use std::io::Read;
+use std::io::{Read, Write};
 use std::io;
 
-mod computer_state;
-mod test;
+use mos6502emulator::{ComputerState, CpuVariant};
+use mos6502emulator::assembler;
+use mos6502emulator::dap::DapServer;
+use mos6502emulator::debugger::{self, Command, Debugger};
+use mos6502emulator::disassembler;
+use mos6502emulator::gdb::{GdbStub, Stdio};
+use mos6502emulator::harness::single_step::{self, CheckOptions};
+use mos6502emulator::trace::{TraceFilter, Tracer};
+
+/// Number of steps which can be undone in the interactive interface
+const REWIND_STEPS: usize = 10_000;
 
 fn main() {
     let args: Vec<_> = std::env::args().collect();
 
-    // Get input file from command line arguments
-    match args.iter().position(|arg| arg.starts_with("-f")) {
-        Some(path_index) => {
-            let path = &args[path_index + 1];
+    // Emulate the 65C02 instead of the NMOS 6502 if requested
+    let variant = if args.iter().any(|arg| arg == "--65c02") {
+        CpuVariant::Wdc65C02
+    } else {
+        CpuVariant::Nmos6502
+    };
+
+    // Run the single step tests in a directory instead of a program
+    if args.get(1).is_some_and(|arg| arg == "single-step") {
+        run_single_step(&args, variant);
+        return;
+    }
+
+    // Assemble a source file instead of running a program
+    if args.get(1).is_some_and(|arg| arg == "asm") {
+        run_assembler(&args, variant);
+        return;
+    }
+
+    // Disassemble a binary file instead of running it
+    if args.get(1).is_some_and(|arg| arg == "disasm") {
+        run_disassembler(&args, variant);
+        return;
+    }
+
+    // Serve the Debug Adapter Protocol on stdio, where the editor's launch request gives the program
+    if args.get(1).is_some_and(|arg| arg == "dap") {
+        if let Err(error) = DapServer::new(variant).serve(&mut io::stdin().lock(), &mut io::stdout()) {
+            eprintln!("Error while serving the debug adapter. Error: {error}");
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    let mut computer = ComputerState::with_variant(variant);
+    if !load_machine(&mut computer, &args) {
+        return;
+    }
+    let save_state_path = flag_value(&args, "--save-state");
 
+    // Debug the program with commands instead of stepping one instruction at a time
+    if args.get(1).is_some_and(|arg| arg == "debug") {
+        run_debugger(&mut computer, &args, variant);
+        return;
+    }
+
+    // Let GDB debug the program over TCP or stdio
+    if args.get(1).is_some_and(|arg| arg == "gdb") {
+        run_gdb_stub(&mut computer, &args);
+        return;
+    }
+
+    // Run the program without the interactive interface, tracing each instruction
+    if args.get(1).is_some_and(|arg| arg == "trace") {
+        run_trace(&mut computer, &args);
+        return;
+    }
+
+    computer.enable_rewind(REWIND_STEPS);
+
+    loop {
+        // Clears screen and moves cursor to the top of the terminal
+        print!("\x1B[2J\x1B[1;1H");
+        // Print the computer's current state, including registers and current instruction
+        println!("{}", computer.get_state_str());
+
+        // Read a single byte
+        // Waits for user before stepping the program
+        let mut input = [0u8];
+        let _ = io::stdin().read(&mut input).unwrap();
+
+        // Typing s saves the state instead of stepping
+        if input[0] == b's' {
+            if let Some(path) = save_state_path {
+                save_state(&computer, path);
+            }
+            continue;
+        }
+
+        // Typing b steps back to before the last instruction
+        if input[0] == b'b' {
+            if !computer.step_back() {
+                eprintln!("No more steps to undo");
+            }
+            continue;
+        }
+
+        if let Err(error) = computer.execute_next() {
+            println!("{}", computer.get_state_str());
+            eprintln!("Execution stopped: {error}");
+            // Keep the failing state so that it can be shared and reproduced
+            if let Some(path) = save_state_path {
+                save_state(&computer, path);
+            }
+            break;
+        }
+    }
+}
+
+/// Loads the program, or the save state, given on the command line
+/// Returns false if neither could be loaded
+fn load_machine(computer: &mut ComputerState, args: &[String]) -> bool {
+    let load_state_path = flag_value(args, "--load-state");
+
+    // Get input file from command line arguments
+    // It is not needed when a save state is loaded, as that holds all of memory
+    match flag_value(args, "-f") {
+        Some(path) => {
             // Read the program from file
             match std::fs::read(path) {
                 Ok(input) => {
-                    let mut computer = computer_state::ComputerState::new();
                     computer.set_up_state(&input);
-
-                    loop {
-                        // Clears screen and moves cursor to the top of the terminal
-                        print!("\x1B[2J\x1B[1;1H");
-                        // Print the computer's current state, including registers and current instruction
-                        println!("{}", computer.get_state_str());
-
-                        // Read a single byte and discard
-                        // Waits for user before stepping the program
-                        let _ = io::stdin().read(&mut [0u8]).unwrap();
-
-                        computer.execute_next();
-                    }
+                    computer.reset();
                 }
                 Err(error) => {
                     eprintln!("Error while trying to read from stdin. Error: {error}");
+                    return false;
                 }
             }
         }
-        None => {
+        None if load_state_path.is_none() => {
             eprintln!("Error! Input file not provided");
+            return false;
+        }
+        None => {}
+    }
+
+    // Restore the whole machine from a save state
+    if let Some(path) = load_state_path {
+        let loaded = std::fs::read(path)
+            .map_err(|error| error.to_string())
+            .and_then(|snapshot| computer.load_state(&snapshot).map_err(|error| error.to_string()));
+        if let Err(error) = loaded {
+            eprintln!("Error while trying to load the save state {path}. Error: {error}");
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns the argument after the flag, if the flag was given
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a String> {
+    args.iter().position(|arg| arg == flag).and_then(|index| args.get(index + 1))
+}
+
+/// Writes a save state of the whole machine to the path
+fn save_state(computer: &ComputerState, path: &str) {
+    match std::fs::write(path, computer.save_state()) {
+        Ok(()) => eprintln!("Saved state to {path}"),
+        Err(error) => eprintln!("Error while trying to save the state to {path}. Error: {error}"),
+    }
+}
+
+/// Runs the single step tests of every opcode in the directory, and prints which opcodes passed
+/// Exits with an error if any test failed
+fn run_single_step(args: &[String], variant: CpuVariant) {
+    let Some(dir) = args.get(2) else {
+        eprintln!("Error! Test directory not provided");
+        std::process::exit(2);
+    };
+    let options = CheckOptions {
+        cycle_count: args.iter().any(|arg| arg == "--cycles"),
+        bus_activity: args.iter().any(|arg| arg == "--bus"),
+    };
+
+    match single_step::run_directory(std::path::Path::new(dir), variant, options) {
+        Ok(matrix) => {
+            println!("{matrix}");
+            if matrix.failed_opcodes().next().is_some() {
+                std::process::exit(1);
+            }
+        }
+        Err(error) => {
+            eprintln!("Error while running the tests in {dir}. Error: {error}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Runs the program until it stops or the trace limit is reached, writing a nestest style trace
+/// to the file given with --output, or to stdout
+fn run_trace(computer: &mut ComputerState, args: &[String]) {
+    let filter = match parse_trace_filter(args) {
+        Ok(filter) => filter,
+        Err(error) => {
+            eprintln!("Error! {error}");
+            std::process::exit(2);
+        }
+    };
+    let output: Box<dyn Write> = match flag_value(args, "--output") {
+        Some(path) if path != "-" => match std::fs::File::create(path) {
+            Ok(file) => Box::new(io::BufWriter::new(file)),
+            Err(error) => {
+                eprintln!("Error while trying to create the trace file {path}. Error: {error}");
+                std::process::exit(2);
+            }
+        },
+        _ => Box::new(io::BufWriter::new(io::stdout().lock())),
+    };
+
+    let mut tracer = Tracer::new(output, filter);
+    let result = loop {
+        if tracer.is_finished() {
+            break Ok(());
+        }
+        if let Err(error) = tracer.step(computer) {
+            break Err(error);
+        }
+    };
+    if let Some(addr) = tracer.trapped() {
+        eprintln!("Trapped at ${addr:04X}");
+    }
+    let flushed = tracer.into_inner().map(drop);
+    if let Err(error) = result {
+        eprintln!("Execution stopped: {error}");
+    }
+    if let Err(error) = flushed {
+        eprintln!("Error while trying to write the trace. Error: {error}");
+    }
+}
+
+/// Reads the trace filter from the --range, --skip and --limit flags
+/// Ranges are inclusive pairs of hex addresses, separated by commas, such as C000-C0FF,E000-FFFF
+fn parse_trace_filter(args: &[String]) -> Result<TraceFilter, String> {
+    let mut filter = TraceFilter::default();
+    if let Some(ranges) = flag_value(args, "--range") {
+        for range in ranges.split(',') {
+            let parsed = range.split_once('-').and_then(|(start, end)| {
+                let start = u16::from_str_radix(start.trim_start_matches('$'), 16).ok()?;
+                let end = u16::from_str_radix(end.trim_start_matches('$'), 16).ok()?;
+                Some(start..=end)
+            });
+            filter.ranges.push(parsed.ok_or_else(|| format!("Invalid address range {range}"))?);
+        }
+    }
+    if let Some(skip) = flag_value(args, "--skip") {
+        filter.skip = skip.parse().map_err(|_| format!("Invalid instruction count {skip}"))?;
+    }
+    if let Some(limit) = flag_value(args, "--limit") {
+        filter.limit = Some(limit.parse().map_err(|_| format!("Invalid instruction count {limit}"))?);
+    }
+    Ok(filter)
+}
+
+/// Prints the disassembly of the binary file, as if it were loaded at the address given with --org
+fn run_disassembler(args: &[String], variant: CpuVariant) {
+    let Some(path) = args.get(2) else {
+        eprintln!("Error! Input file not provided");
+        std::process::exit(2);
+    };
+    let origin = match flag_value(args, "--org").map(|org| u16::from_str_radix(org.trim_start_matches('$'), 16)) {
+        None => 0,
+        Some(Ok(origin)) => origin,
+        Some(Err(_)) => {
+            eprintln!("Error! Invalid load address");
+            std::process::exit(2);
+        }
+    };
+
+    match std::fs::read(path) {
+        Ok(bytes) => {
+            for instruction in disassembler::disassemble_bytes(variant, origin, &bytes) {
+                println!("{instruction}");
+            }
+        }
+        Err(error) => {
+            eprintln!("Error while trying to read {path}. Error: {error}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Assembles the source file into a binary, written to the path given with -o or next to the source
+/// A listing is written to the path given with --listing, or to stdout if it is -
+fn run_assembler(args: &[String], variant: CpuVariant) {
+    let Some(path) = args.get(2).map(std::path::Path::new) else {
+        eprintln!("Error! Source file not provided");
+        std::process::exit(2);
+    };
+    let assembly = match assembler::assemble_file(path, variant) {
+        Ok(assembly) => assembly,
+        Err(error) => {
+            eprintln!("Error! {error}");
+            std::process::exit(1);
+        }
+    };
+
+    let output = flag_value(args, "-o").map_or_else(|| path.with_extension("bin"), std::path::PathBuf::from);
+    if let Err(error) = std::fs::write(&output, &assembly.binary) {
+        eprintln!("Error while trying to write {}. Error: {error}", output.display());
+        std::process::exit(2);
+    }
+    eprintln!("Assembled {} bytes at ${:04X} into {}", assembly.binary.len(), assembly.origin, output.display());
+
+    if let Some(listing_path) = flag_value(args, "--listing") {
+        let listing: String = assembly.listing.iter().map(|line| format!("{line}\n")).collect();
+        if listing_path == "-" {
+            print!("{listing}");
+        } else if let Err(error) = std::fs::write(listing_path, listing) {
+            eprintln!("Error while trying to write {listing_path}. Error: {error}");
+            std::process::exit(2);
+        }
+    }
+}
+
+/// Serves the GDB remote protocol on a local TCP port, or on stdin and stdout if no port is given
+fn run_gdb_stub(computer: &mut ComputerState, args: &[String]) {
+    let mut stub = GdbStub::new();
+    let result = match flag_value(args, "--port") {
+        Some(port) => {
+            let Ok(port) = port.parse::<u16>() else {
+                eprintln!("Error! Invalid port {port}");
+                std::process::exit(2);
+            };
+            std::net::TcpListener::bind(("127.0.0.1", port)).and_then(|listener| {
+                eprintln!("Waiting for GDB on 127.0.0.1:{port}");
+                let (mut stream, _) = listener.accept()?;
+                stream.set_nodelay(true)?;
+                stub.serve(computer, &mut stream)
+            })
+        }
+        None => stub.serve(computer, &mut Stdio),
+    };
+    if let Err(error) = result {
+        eprintln!("Error while serving GDB. Error: {error}");
+        std::process::exit(2);
+    }
+}
+
+/// Reads debugger commands from stdin until quit is typed or stdin is closed
+fn run_debugger(computer: &mut ComputerState, args: &[String], variant: CpuVariant) {
+    let mut debugger = Debugger::new();
+    // Symbols for expressions come from assembling the program's source
+    if let Some(source) = flag_value(args, "--symbols") {
+        match assembler::assemble_file(std::path::Path::new(source), variant) {
+            Ok(assembly) => debugger.set_symbols(assembly.symbols.into_iter().collect()),
+            Err(error) => {
+                eprintln!("Error! {error}");
+                std::process::exit(1);
+            }
+        }
+    }
+    let mut last_command = None;
+    println!("{}", debugger::registers(computer));
+    println!("{}", debugger.execute(computer, &Command::Disasm { addr: None, count: 1 }));
+
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        match io::stdin().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(error) => {
+                eprintln!("Error while trying to read from stdin. Error: {error}");
+                break;
+            }
+        }
+
+        // An empty line repeats the last command
+        let command = if line.trim().is_empty() {
+            match &last_command {
+                Some(command) => Ok(Command::clone(command)),
+                None => continue,
+            }
+        } else {
+            Command::parse(&line)
+        };
+        match command {
+            Ok(Command::Quit) => break,
+            Ok(command) => {
+                println!("{}", debugger.execute(computer, &command));
+                last_command = Some(command);
+            }
+            Err(error) => eprintln!("{error}"),
         }
     }
 }
@@ -1,2 +1,16 @@
 
-mod test_computer_state;
\ No newline at end of file
+mod test_computer_state;
+#[cfg(test)]
+mod test_json;
+#[cfg(test)]
+mod test_trace;
+#[cfg(test)]
+mod test_disassembler;
+#[cfg(test)]
+mod test_assembler;
+#[cfg(test)]
+mod test_debugger;
+#[cfg(test)]
+mod test_gdb;
+#[cfg(test)]
+mod test_dap;
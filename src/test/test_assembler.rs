@@ -0,0 +1,211 @@
+use crate::assembler::{assemble, assemble_file, AssemblyError};
+use crate::computer_state::{ComputerState, CpuVariant};
+
+fn binary(source: &str) -> Vec<u8> {
+    assemble(source, CpuVariant::Nmos6502).unwrap().binary
+}
+
+fn error(source: &str) -> AssemblyError {
+    assemble(source, CpuVariant::Nmos6502).unwrap_err()
+}
+
+#[test]
+fn test_addressing_modes() {
+    let source = "
+        lda #$01
+        lda $20
+        lda $20,x
+        ldx $20,y
+        lda $1234
+        lda $1234,x
+        lda $1234,y
+        lda ($20,x)
+        lda ($20),y
+        jmp ($FFFC)
+        asl
+        asl a
+        inx
+    ";
+
+    assert_eq!(
+        vec![0xA9, 0x01, 0xA5, 0x20, 0xB5, 0x20, 0xB6, 0x20, 0xAD, 0x34, 0x12, 0xBD, 0x34, 0x12,
+             0xB9, 0x34, 0x12, 0xA1, 0x20, 0xB1, 0x20, 0x6C, 0xFC, 0xFF, 0x0A, 0x0A, 0xE8],
+        binary(source),
+    );
+}
+
+#[test]
+fn test_zero_page_selection() {
+    // Known zero page values use zero page addressing, and forward references use absolute
+    let source = "
+        ptr = $80
+        lda ptr
+        lda later
+        sta $0080
+        later = $10
+    ";
+
+    assert_eq!(vec![0xA5, 0x80, 0xAD, 0x10, 0x00, 0x85, 0x80], binary(source));
+}
+
+#[test]
+fn test_absolute_only_modes() {
+    // STA has no zero page,Y mode, and JMP has no zero page mode
+    assert_eq!(vec![0x99, 0x20, 0x00, 0x4C, 0x20, 0x00], binary("sta $20,y\njmp $20"));
+}
+
+#[test]
+fn test_labels_and_branches() {
+    let source = "
+        .org $0600
+start:  ldx #3
+@loop:  dex
+        bne @loop
+        beq done
+        nop
+done:   jsr start
+    ";
+    let assembly = assemble(source, CpuVariant::Nmos6502).unwrap();
+
+    assert_eq!(0x0600, assembly.origin);
+    assert_eq!(vec![0xA2, 0x03, 0xCA, 0xD0, 0xFD, 0xF0, 0x01, 0xEA, 0x20, 0x00, 0x06], assembly.binary);
+    assert_eq!(Some(&0x0602), assembly.symbols.get("start@loop"));
+    assert_eq!(Some(&0x0608), assembly.symbols.get("done"));
+}
+
+#[test]
+fn test_local_labels_are_scoped() {
+    let source = "
+first:  nop
+@loop:  bne @loop
+second: nop
+@loop:  bne @loop
+    ";
+
+    assert_eq!(vec![0xEA, 0xD0, 0xFE, 0xEA, 0xD0, 0xFE], binary(source));
+}
+
+#[test]
+fn test_expressions() {
+    let source = "
+        base = $1234
+        .byte <base, >base, base & $F0, 1 + 2 * 3, (1 + 2) * 3, 1 << 4 | 1, -1, %101, 'A', 7 / 2, 7 % 2, ~0 & $FF
+        .word base + 1, *
+    ";
+
+    assert_eq!(
+        vec![0x34, 0x12, 0x30, 7, 9, 0x11, 0xFF, 5, 0x41, 3, 1, 0xFF, 0x35, 0x12, 0x0C, 0x00],
+        binary(source),
+    );
+}
+
+#[test]
+fn test_byte_strings_and_comments() {
+    assert_eq!(vec![b'a', b';', b',', 1, 2], binary(".byte \"a;,\", 1 ; comment, 3\n.byte 2"));
+}
+
+#[test]
+fn test_org_gaps_are_filled() {
+    let assembly = assemble(".org $10\n.byte 1\n.org $13\n.byte 2", CpuVariant::Nmos6502).unwrap();
+
+    assert_eq!(0x10, assembly.origin);
+    assert_eq!(vec![1, 0, 0, 2], assembly.binary);
+}
+
+#[test]
+fn test_undocumented_opcodes() {
+    // Documented opcodes are preferred, so SBC # is never the undocumented $EB
+    assert_eq!(vec![0xA7, 0x10, 0xE9, 0x01, 0xEA], binary("lax $10\nsbc #1\nnop"));
+}
+
+#[test]
+fn test_65c02_instructions() {
+    let source = "
+        .org $0200
+start:  lda ($20)
+        bra start
+        bbr0 $20,start
+        smb1 $20
+        stz $1234,x
+        jmp ($1000,x)
+    ";
+    let assembly = assemble(source, CpuVariant::Wdc65C02).unwrap();
+
+    assert_eq!(
+        vec![0xB2, 0x20, 0x80, 0xFC, 0x0F, 0x20, 0xF9, 0x97, 0x20, 0x9E, 0x34, 0x12, 0x7C, 0x00, 0x10],
+        assembly.binary,
+    );
+    assert_eq!("line 1: unknown instruction BRA for the 6502", error("bra *").to_string());
+}
+
+#[test]
+fn test_errors() {
+    assert_eq!(
+        AssemblyError { file: None, line: Some(1), message: "nowhere is not defined".to_string() },
+        error("jmp nowhere"),
+    );
+    assert_eq!(Some(2), error("a: nop\na: nop").line);
+    assert_eq!("a is already defined", error("a: nop\na: nop").message);
+    assert_eq!("invalid addressing mode for INX", error("inx #1").message);
+    assert_eq!("value 256 does not fit in a byte", error("lda #256").message);
+    assert_eq!("address 256 is not in the zero page", error("lda ($100),y").message);
+    assert_eq!("branch target is 198 bytes away, which is out of range", error("bne $C8").message);
+    assert_eq!("unknown directive .foo", error(".foo").message);
+    assert_eq!("division by zero", error(".byte 1/0").message);
+    assert_eq!("program runs past $FFFF", error(".org $FFFF\nnop\nnop").message);
+}
+
+#[test]
+fn test_listing() {
+    let assembly = assemble("start: lda #1\n  .byte 1, 2, 3, 4", CpuVariant::Nmos6502).unwrap();
+
+    assert_eq!("    1  0000  A9 01     start: lda #1", assembly.listing[0].to_string());
+    assert_eq!("    2  0002  01 02 03    .byte 1, 2, 3, 4\n       0005  04", assembly.listing[1].to_string());
+}
+
+#[test]
+fn test_include_and_incbin() {
+    let dir = std::env::temp_dir().join(format!("mos6502_assembler_{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    std::fs::write(dir.join("main.s"), ".include \"lib/sub.s\"\njsr sub\n").unwrap();
+    std::fs::write(dir.join("lib/sub.s"), "sub: rts\n.incbin \"data.bin\"\n").unwrap();
+    std::fs::write(dir.join("lib/data.bin"), [0xAA, 0xBB]).unwrap();
+    std::fs::write(dir.join("loop.s"), ".include \"loop.s\"\n").unwrap();
+
+    let assembly = assemble_file(&dir.join("main.s"), CpuVariant::Nmos6502);
+    let looping = assemble_file(&dir.join("loop.s"), CpuVariant::Nmos6502);
+    let missing = assemble_file(&dir.join("missing.s"), CpuVariant::Nmos6502);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let assembly = assembly.unwrap();
+    assert_eq!(vec![0x60, 0xAA, 0xBB, 0x20, 0x00, 0x00], assembly.binary);
+    assert_eq!(Some(dir.join("lib/sub.s")), assembly.listing[1].file);
+    assert_eq!("files are included too deeply", looping.unwrap_err().message);
+    assert_eq!(None, missing.unwrap_err().line);
+}
+
+#[test]
+fn test_assembled_program_runs() {
+    let source = "
+        .org $0200
+        ldx #0
+@copy:  lda text,x
+        beq @done
+        sta $0300,x
+        inx
+        bne @copy
+@done:  jmp @done
+text:   .byte \"HI\", 0
+    ";
+    let assembly = assemble(source, CpuVariant::Nmos6502).unwrap();
+    let mut state = ComputerState::new();
+    state.load_at(assembly.origin, &assembly.binary);
+    state.pc.set(usize::from(assembly.origin));
+
+    for _ in 0..20 {
+        state.execute_next().unwrap();
+    }
+
+    assert_eq!(b'H', state.peek_byte_from_addr(0x0300));
+    assert_eq!(b'I', state.peek_byte_from_addr(0x0301));
+}
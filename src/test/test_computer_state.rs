@@ -1,2 +1,19 @@
-
-mod test_operations;
\ No newline at end of file
+mod test_operations;
+#[cfg(test)]
+mod test_cycles;
+#[cfg(test)]
+mod test_interrupts;
+#[cfg(test)]
+mod test_execution;
+#[cfg(test)]
+mod test_bus;
+#[cfg(test)]
+mod test_address_decoder;
+#[cfg(test)]
+mod test_mapper;
+#[cfg(test)]
+mod test_snapshot;
+#[cfg(test)]
+mod test_rewind;
+#[cfg(test)]
+mod test_watch;
@@ -0,0 +1,159 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::bus::address_decoder::{AddressDecoder, Region};
+use crate::computer_state::execution::ConfigurationError;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::variant_opcode_from_operation;
+
+/// Device which returns the offset it was read at, and remembers the last write
+struct EchoDevice {
+    last_write: u8,
+}
+
+impl Bus for EchoDevice {
+    fn write(&mut self, _: u16, value: u8) {
+        self.last_write = value;
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        if addr == 0x0F { self.last_write } else { addr as u8 }
+    }
+}
+
+/// 2K of RAM mirrored up to $1FFF, an echo device at $6000 and a 16K ROM mirrored up to $FFFF
+fn machine() -> AddressDecoder {
+    AddressDecoder::new(vec![
+        Region::rom(0x8000, 0xFFFF, vec![0xEA; 0x4000]),
+        Region::ram(0x0000, 0x1FFF, 0x0800),
+        Region::device(0x6000, 0x600F, Box::new(EchoDevice { last_write: 0 })),
+    ]).unwrap()
+}
+
+fn opcode(op: fn(&mut ComputerState<AddressDecoder>)) -> u8 {
+    variant_opcode_from_operation(CpuVariant::Nmos6502, op)
+}
+
+#[test]
+fn test_ram_mirrored() {
+    let mut bus = machine();
+    bus.write(0x0012, 0x42);
+
+    assert_eq!(0x42, bus.read(0x0812));
+    assert_eq!(0x42, bus.read(0x1812));
+}
+
+#[test]
+fn test_rom_ignores_writes() {
+    let mut bus = machine();
+    bus.write(0x8000, 0x00);
+
+    assert_eq!(0xEA, bus.read(0x8000));
+    // The 16K image repeats across the 32K range
+    bus.load(0x8001, 0x42);
+    assert_eq!(0x42, bus.read(0xC001));
+}
+
+#[test]
+fn test_device_region() {
+    let mut bus = machine();
+    bus.write(0x6003, 0x42);
+
+    assert_eq!(0x05, bus.read(0x6005));
+    assert_eq!(0x42, bus.peek(0x600F));
+}
+
+#[test]
+fn test_unmapped_reads_open_bus() {
+    let mut bus = machine();
+    bus.read(0x6007);
+
+    assert_eq!(0x07, bus.read(0x4000));
+}
+
+#[test]
+fn test_overlapping_regions() {
+    let result = AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x7FFF, 0x8000),
+        Region::rom(0x8000, 0xFFFF, vec![0; 0x8000]),
+        Region::device(0x7000, 0x700F, Box::new(EchoDevice { last_write: 0 })),
+    ]);
+
+    assert_eq!(
+        Some(ConfigurationError::OverlappingRegions { first: (0x0000, 0x7FFF), second: (0x7000, 0x700F) }),
+        result.err()
+    );
+}
+
+#[test]
+fn test_invalid_region() {
+    let result = AddressDecoder::new(vec![
+        Region::ram(0x8000, 0x7FFF, 0x100),
+    ]);
+
+    assert_eq!(Some(ConfigurationError::InvalidRegion { start: 0x8000, end: 0x7FFF }), result.err());
+}
+
+#[test]
+fn test_invalid_mirror_mask() {
+    let result = AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x1FFF, 0x0800).with_mirror_mask(0x0FFF),
+    ]);
+
+    assert_eq!(
+        Some(ConfigurationError::InvalidMirrorMask { start: 0x0000, end: 0x1FFF, mask: 0x0FFF }),
+        result.err()
+    );
+}
+
+#[test]
+fn test_rom_not_power_of_two_size() {
+    // A 24K image mirrored across 32K would need a mask of $5FFF, which scrambles the mirrors
+    let result = AddressDecoder::new(vec![
+        Region::rom(0x8000, 0xFFFF, vec![0xEA; 0x6000]),
+    ]);
+
+    assert_eq!(
+        Some(ConfigurationError::InvalidMirrorMask { start: 0x8000, end: 0xFFFF, mask: 0x5FFF }),
+        result.err()
+    );
+}
+
+#[test]
+fn test_rom_not_power_of_two_size_filling_range() {
+    let mut bus = AddressDecoder::new(vec![
+        Region::rom(0xA000, 0xFFFF, (0..0x6000).map(|i| (i / 0x1000) as u8).collect()),
+    ]).unwrap();
+
+    assert_eq!(0x00, bus.read(0xA000));
+    assert_eq!(0x02, bus.read(0xC000));
+    assert_eq!(0x05, bus.read(0xFFFF));
+}
+
+#[test]
+fn test_read_only_ram() {
+    let mut bus = AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x00FF, 0x100).read_only(),
+    ]).unwrap();
+    bus.write(0x0010, 0x42);
+
+    assert_eq!(0, bus.read(0x0010));
+}
+
+#[test]
+fn test_set_up_state_loads_into_rom() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, machine());
+    state.pc.set(0x8000);
+    state.set_up_state(&[
+        opcode(lda_im),
+        0x42,
+        opcode(sta_ab),
+        0x00,
+        0x80,
+    ]);
+
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    assert_eq!(0x42, state.acc.get());
+    // The program was loaded into ROM, but it cannot modify itself
+    assert_eq!(opcode(lda_im), state.peek_byte_from_addr(0x8000));
+}
@@ -0,0 +1,90 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::variant_opcode_from_operation;
+
+/// Address of a device which counts how many times it has been read
+const COUNTER_ADDR: u16 = 0xD000;
+/// Address of a device which records the last value written to it
+const LATCH_ADDR: u16 = 0xD001;
+
+/// RAM with two memory-mapped devices
+struct DeviceBus {
+    ram: FlatRam,
+    reads: u8,
+    latch: Option<u8>,
+}
+
+impl DeviceBus {
+    fn new() -> Self {
+        Self { ram: FlatRam::new(), reads: 0, latch: None }
+    }
+}
+
+impl Bus for DeviceBus {
+    fn read(&mut self, addr: u16) -> u8 {
+        if addr == COUNTER_ADDR {
+            self.reads += 1;
+        }
+        self.peek(addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        match addr {
+            COUNTER_ADDR => {}
+            LATCH_ADDR => self.latch = Some(value),
+            _ => self.ram.write(addr, value),
+        }
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        match addr {
+            COUNTER_ADDR => self.reads,
+            _ => self.ram.peek(addr),
+        }
+    }
+}
+
+fn opcode(op: fn(&mut ComputerState<DeviceBus>)) -> u8 {
+    variant_opcode_from_operation(CpuVariant::Nmos6502, op)
+}
+
+#[test]
+fn test_indirect_y_read_goes_through_bus() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, DeviceBus::new());
+    state.set_y(0x01);
+    state.set_up_state(&[
+        opcode(lda_iny),
+        0x10
+    ]);
+    state.set_nibble_at_addr(0x10, COUNTER_ADDR - 1);
+    state.execute_next().unwrap();
+
+    // The device saw the read and returned its count after it
+    assert_eq!(1, state.acc.get());
+    assert_eq!(1, state.bus.reads);
+}
+
+#[test]
+fn test_write_goes_through_bus() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, DeviceBus::new());
+    state.acc.set(0x42);
+    state.set_up_state(&[
+        opcode(sta_ab),
+        0x01,
+        0xD0
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(Some(0x42), state.bus.latch);
+}
+
+#[test]
+fn test_peek_has_no_side_effects() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, DeviceBus::new());
+    state.pc.set(usize::from(COUNTER_ADDR));
+
+    let _ = state.get_state_str();
+    assert_eq!(0, state.peek_byte_from_addr(usize::from(COUNTER_ADDR)));
+    assert_eq!(0, state.bus.reads);
+}
@@ -0,0 +1,178 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::operations::bitwise::*;
+use crate::computer_state::operations::branch::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::{opcode_from_operation, variant_opcode_from_operation, MosOp};
+use crate::computer_state::status_register::StatusRegister;
+
+#[test]
+fn test_cycles_accumulate() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x10,
+        opcode_from_operation(sta_ab),
+        0x00,
+        0x02
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    assert_eq!(6, state.cycles);
+}
+
+#[test]
+fn test_lda_abx_same_page() {
+    let mut state = ComputerState::new();
+    state.set_x(0x4);
+    state.set_up_state(&[
+        opcode_from_operation(lda_abx),
+        0x55,
+        0x66
+    ]);
+
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_lda_abx_page_crossed() {
+    let mut state = ComputerState::new();
+    state.set_x(0xB0);
+    state.set_up_state(&[
+        opcode_from_operation(lda_abx),
+        0x55,
+        0x66
+    ]);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_lda_aby_page_crossed() {
+    let mut state = ComputerState::new();
+    state.set_y(0xB0);
+    state.set_up_state(&[
+        opcode_from_operation(lda_aby),
+        0x55,
+        0x66
+    ]);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_lda_iny_page_crossed() {
+    let mut state = ComputerState::new();
+    state.set_y(0xF0);
+    state.set_up_state(&[
+        opcode_from_operation(lda_iny),
+        0x55
+    ]);
+    state.set_nibble_at_addr(0x55, 0x3412);
+
+    assert_eq!(6, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_sta_abx_page_crossed() {
+    // Stores always take the extra cycle, so there is no penalty
+    let mut state = ComputerState::new();
+    state.set_x(0xB0);
+    state.set_up_state(&[
+        opcode_from_operation(sta_abx),
+        0x55,
+        0x66
+    ]);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+}
+
+/// Runs a shift or rotate at $6655 plus the X index on the 65C02, returning its cycle count
+fn cmos_shift_abx_cycles(op: MosOp, x: usize) -> u8 {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_x(x);
+    state.set_up_state(&[
+        variant_opcode_from_operation(CpuVariant::Wdc65C02, op),
+        0x55,
+        0x66
+    ]);
+
+    state.execute_next().unwrap().cycles()
+}
+
+#[test]
+fn test_shift_abx_nmos() {
+    // The NMOS 6502 always takes the extra cycle
+    for op in [asl_abx, lsr_abx, rol_abx, ror_abx] {
+        let mut state = ComputerState::new();
+        state.set_x(0x4);
+        state.set_up_state(&[
+            opcode_from_operation(op),
+            0x55,
+            0x66
+        ]);
+
+        assert_eq!(7, state.execute_next().unwrap().cycles());
+    }
+}
+
+#[test]
+fn test_asl_abx_cmos() {
+    assert_eq!(6, cmos_shift_abx_cycles(asl_abx, 0x4));
+    assert_eq!(7, cmos_shift_abx_cycles(asl_abx, 0xB0));
+}
+
+#[test]
+fn test_lsr_abx_cmos() {
+    assert_eq!(6, cmos_shift_abx_cycles(lsr_abx, 0x4));
+    assert_eq!(7, cmos_shift_abx_cycles(lsr_abx, 0xB0));
+}
+
+#[test]
+fn test_rol_abx_cmos() {
+    assert_eq!(6, cmos_shift_abx_cycles(rol_abx, 0x4));
+    assert_eq!(7, cmos_shift_abx_cycles(rol_abx, 0xB0));
+}
+
+#[test]
+fn test_ror_abx_cmos() {
+    assert_eq!(6, cmos_shift_abx_cycles(ror_abx, 0x4));
+    assert_eq!(7, cmos_shift_abx_cycles(ror_abx, 0xB0));
+}
+
+#[test]
+fn test_branch_not_taken() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(bcs),
+        0x10
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_branch_taken() {
+    let mut state = ComputerState::new();
+    state.sta |= StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(bcs),
+        0x10
+    ]);
+
+    assert_eq!(3, state.execute_next().unwrap().cycles());
+}
+
+#[test]
+fn test_branch_taken_page_crossed() {
+    let mut state = ComputerState::new();
+    state.sta |= StatusRegister::C;
+    state.pc.set(0x02F0);
+    state.set_up_state(&[
+        opcode_from_operation(bcs),
+        0x20
+    ]);
+
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    assert_eq!(0x0312, state.pc.get());
+}
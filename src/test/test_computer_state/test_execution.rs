@@ -0,0 +1,168 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::execution::*;
+use crate::computer_state::operations::illegal::*;
+use crate::computer_state::operations::interrupt::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::opcode_from_operation;
+
+#[test]
+fn test_illegal_opcode_emulated() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(lax_zp),
+        0x10
+    ]);
+    state.set_byte_at_addr(0x10, 0x42);
+
+    assert_eq!(Ok(StepOutcome::Executed { cycles: 3 }), state.execute_next());
+    assert_eq!(0x42, state.get_x());
+}
+
+#[test]
+fn test_illegal_opcode_halt() {
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    state.set_up_state(&[
+        opcode_from_operation(lax_zp),
+        0x10
+    ]);
+
+    assert_eq!(Err(ExecutionError::IllegalOpcode { opcode: 0xA7, addr: 0 }), state.execute_next());
+    // The opcode is not executed, and the PC still points at it
+    assert_eq!(0, state.pc.get());
+    assert_eq!(0, state.cycles);
+}
+
+#[test]
+fn test_illegal_opcode_nop() {
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Nop);
+    state.set_up_state(&[
+        opcode_from_operation(jam),
+        opcode_from_operation(lda_im),
+        0x42
+    ]);
+
+    assert_eq!(Ok(StepOutcome::Executed { cycles: 2 }), state.execute_next());
+    state.execute_next().unwrap();
+    assert_eq!(0x42, state.acc.get());
+}
+
+#[test]
+fn test_illegal_opcode_nop_skips_operand() {
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Nop);
+    state.set_up_state(&[
+        opcode_from_operation(nop_ab),
+        opcode_from_operation(lda_im),
+        0x42,
+        opcode_from_operation(lda_im),
+        0x24
+    ]);
+
+    assert_eq!(Ok(StepOutcome::Executed { cycles: 4 }), state.execute_next());
+    assert_eq!(3, state.pc.get());
+    state.execute_next().unwrap();
+    assert_eq!(0x24, state.acc.get());
+}
+
+#[test]
+fn test_illegal_opcode_trap() {
+    fn handler(state: &mut ComputerState, opcode: u8) -> u8 {
+        state.acc.set(opcode);
+        4
+    }
+
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Trap);
+    state.set_illegal_opcode_handler(handler);
+    state.set_up_state(&[
+        opcode_from_operation(jam)
+    ]);
+
+    assert_eq!(Ok(StepOutcome::Trapped { opcode: 0x02, cycles: 4 }), state.execute_next());
+    assert_eq!(0x02, state.acc.get());
+    assert_eq!(1, state.pc.get());
+    assert_eq!(4, state.cycles);
+}
+
+#[test]
+fn test_illegal_opcode_trap_without_handler() {
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Trap);
+    state.set_up_state(&[
+        opcode_from_operation(jam)
+    ]);
+
+    assert_eq!(
+        Err(ExecutionError::Configuration(ConfigurationError::MissingTrapHandler)),
+        state.execute_next()
+    );
+}
+
+#[test]
+fn test_documented_opcode_ignores_policy() {
+    let mut state = ComputerState::new();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x42
+    ]);
+
+    assert_eq!(Ok(StepOutcome::Executed { cycles: 2 }), state.execute_next());
+}
+
+#[test]
+fn test_cmos_undefined_opcode_halt() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    state.set_up_state(&[0x03]);
+
+    assert_eq!(Err(ExecutionError::IllegalOpcode { opcode: 0x03, addr: 0 }), state.execute_next());
+}
+
+#[test]
+fn test_jam_reset() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(jam)
+    ]);
+    state.set_nibble_at_addr(RESET_VECTOR, 0x8000);
+    state.set_byte_at_addr(0x8000, opcode_from_operation(nop));
+
+    assert!(state.execute_next().is_err());
+    state.reset();
+    assert_eq!(Ok(StepOutcome::Executed { cycles: 2 }), state.execute_next());
+}
+
+#[test]
+fn test_fetch_wraps_at_end_of_memory() {
+    let mut state = ComputerState::new();
+    state.pc.set(0xFFFE);
+    state.set_up_state(&[
+        opcode_from_operation(lda_ab),
+        0x34,
+    ]);
+    state.set_byte_at_addr(0x0000, 0x12);
+    state.set_byte_at_addr(0x1234, 0x42);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x42, state.acc.get());
+    assert_eq!(0x0001, state.pc.get());
+}
+
+#[test]
+fn test_indexed_address_wraps() {
+    let mut state = ComputerState::new();
+    state.set_x(0x02);
+    state.pc.set(0x0200);
+    state.set_up_state(&[
+        opcode_from_operation(lda_abx),
+        0xFF,
+        0xFF
+    ]);
+    state.set_byte_at_addr(0x0001, 0x42);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x42, state.acc.get());
+}
@@ -0,0 +1,225 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::operations::flags::*;
+use crate::computer_state::operations::interrupt::*;
+use crate::computer_state::operations::{opcode_from_operation, variant_opcode_from_operation};
+use crate::computer_state::status_register::StatusRegister;
+
+#[test]
+fn test_reset() {
+    let mut state = ComputerState::new();
+    state.set_nibble_at_addr(RESET_VECTOR, 0x8000);
+    state.reset();
+
+    assert_eq!(0x8000, state.pc.get());
+    assert_eq!(0xFC, state.get_stk());
+    assert!(state.sta.contains_only(StatusRegister::I));
+    assert_eq!(7, state.cycles);
+}
+
+#[test]
+fn test_brk() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(brk)
+    ]);
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+
+    assert_eq!(7, state.execute_next().unwrap().cycles());
+    assert_eq!(0x9000, state.pc.get());
+    assert!(state.sta.contains_only(StatusRegister::I));
+    // BRK pushes the status with the break flag and unused bit set
+    assert_eq!(0x30, state.pop_from_stack());
+    // The return address skips the padding byte after BRK
+    assert_eq!(2, state.pop_nibble_from_stack());
+    assert_eq!(0xFF, state.get_stk());
+}
+
+#[test]
+fn test_irq() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(nop)
+    ]);
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+    state.set_irq(true);
+
+    assert_eq!(7, state.execute_next().unwrap().cycles());
+    assert_eq!(0x9000, state.pc.get());
+    assert!(state.sta.contains_only(StatusRegister::I));
+    // Hardware interrupts push the status without the break flag
+    assert_eq!(0x20, state.pop_from_stack());
+    assert_eq!(0, state.pop_nibble_from_stack());
+}
+
+#[test]
+fn test_irq_masked() {
+    let mut state = ComputerState::new();
+    state.sta |= StatusRegister::I;
+    state.set_up_state(&[
+        opcode_from_operation(nop),
+        opcode_from_operation(cli),
+    ]);
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+    state.set_irq(true);
+
+    state.execute_next().unwrap();
+    assert_eq!(1, state.pc.get());
+
+    // Once interrupts are enabled, the IRQ is serviced
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    assert_eq!(0x9000, state.pc.get());
+}
+
+#[test]
+fn test_irq_level_triggered() {
+    let mut state = ComputerState::new();
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+    state.set_byte_at_addr(0x9000, opcode_from_operation(cli));
+    state.set_irq(true);
+
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    // The line is still held, so the IRQ is serviced again
+    state.execute_next().unwrap();
+    assert_eq!(0x9000, state.pc.get());
+
+    state.set_irq(false);
+    state.execute_next().unwrap();
+    assert_eq!(0x9001, state.pc.get());
+}
+
+#[test]
+fn test_nmi() {
+    let mut state = ComputerState::new();
+    state.sta |= StatusRegister::I;
+    state.set_up_state(&[
+        opcode_from_operation(nop)
+    ]);
+    state.set_nibble_at_addr(NMI_VECTOR, 0xA000);
+    state.set_nmi(true);
+
+    assert_eq!(7, state.execute_next().unwrap().cycles());
+    assert_eq!(0xA000, state.pc.get());
+    assert!(!StatusRegister::from_byte(state.pop_from_stack()).contains(StatusRegister::B));
+}
+
+#[test]
+fn test_nmi_edge_triggered() {
+    let mut state = ComputerState::new();
+    state.set_nibble_at_addr(NMI_VECTOR, 0xA000);
+    state.set_byte_at_addr(0xA000, opcode_from_operation(nop));
+    state.set_nmi(true);
+
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    // The line is still held, but the NMI is only serviced once
+    assert_eq!(0xA001, state.pc.get());
+
+    state.set_nmi(false);
+    state.set_nmi(true);
+    state.execute_next().unwrap();
+    assert_eq!(0xA000, state.pc.get());
+}
+
+#[test]
+fn test_brk_rti() {
+    let mut state = ComputerState::new();
+    state.sta = StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(brk),
+        0xEA,
+        opcode_from_operation(nop),
+    ]);
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+    state.set_byte_at_addr(0x9000, opcode_from_operation(rti));
+
+    state.execute_next().unwrap();
+    // Return address high byte, low byte, then status
+    assert_eq!(0x00, state.fetch_byte_from_addr(0x01FF));
+    assert_eq!(0x02, state.fetch_byte_from_addr(0x01FE));
+    assert_eq!(0x31, state.fetch_byte_from_addr(0x01FD));
+
+    state.execute_next().unwrap();
+    assert_eq!(2, state.pc.get());
+    assert_eq!(0xFF, state.get_stk());
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
+/// RAM with interrupt lines which the test drives directly
+struct InterruptingBus {
+    ram: FlatRam,
+    irq: bool,
+    nmi: bool,
+}
+
+impl Bus for InterruptingBus {
+    fn write(&mut self, addr: u16, value: u8) {
+        self.ram.write(addr, value)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.ram.peek(addr)
+    }
+
+    fn irq(&self) -> bool {
+        self.irq
+    }
+
+    fn nmi(&self) -> bool {
+        self.nmi
+    }
+}
+
+fn interrupting_state() -> ComputerState<InterruptingBus> {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, InterruptingBus { ram: FlatRam::new(), irq: false, nmi: false });
+    let nop = variant_opcode_from_operation(CpuVariant::Nmos6502, nop::<InterruptingBus>);
+    state.set_up_state(&[nop, nop]);
+    state.set_byte_at_addr(0x9000, nop);
+    state.set_byte_at_addr(0xA000, nop);
+    state.set_nibble_at_addr(IRQ_VECTOR, 0x9000);
+    state.set_nibble_at_addr(NMI_VECTOR, 0xA000);
+    state
+}
+
+#[test]
+fn test_bus_irq() {
+    let mut state = interrupting_state();
+    state.bus.irq = true;
+
+    assert_eq!(7, state.execute_next().unwrap().cycles());
+    assert_eq!(0x9000, state.pc.get());
+    // Interrupts are now disabled, so the held line is ignored
+    state.execute_next().unwrap();
+    assert_eq!(0x9001, state.pc.get());
+}
+
+#[test]
+fn test_bus_nmi_edge_triggered() {
+    let mut state = interrupting_state();
+    state.bus.nmi = true;
+
+    state.execute_next().unwrap();
+    assert_eq!(0xA000, state.pc.get());
+    state.execute_next().unwrap();
+    assert_eq!(0xA001, state.pc.get());
+
+    state.bus.nmi = false;
+    state.execute_next().unwrap();
+    state.bus.nmi = true;
+    state.execute_next().unwrap();
+    assert_eq!(0xA000, state.pc.get());
+}
+
+#[test]
+fn test_bus_nmi_shares_line() {
+    let mut state = interrupting_state();
+    state.set_nmi(true);
+    state.execute_next().unwrap();
+
+    // The line is already asserted by the processor, so the bus asserting it is not an edge
+    state.bus.nmi = true;
+    state.execute_next().unwrap();
+    assert_eq!(0xA001, state.pc.get());
+}
@@ -0,0 +1,133 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::bus::mapper::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::variant_opcode_from_operation;
+
+/// Image where every byte holds the number of the 16K bank it is in
+fn banked_image(banks: u8) -> Vec<u8> {
+    (0..banks).flat_map(|bank| vec![bank; BANK_SIZE_16K]).collect()
+}
+
+/// Writes the value to an MMC1 register one bit at a time, as a program would
+fn write_mmc1<B: Bus>(bus: &mut B, addr: u16, value: u8) {
+    for bit in 0..5 {
+        bus.write(addr, (value >> bit) & 1);
+    }
+}
+
+#[test]
+fn test_latch_selects_window() {
+    let mut bus = Cartridge::new(LatchMapper::new(banked_image(4), 0x4000, 0x0200), FlatRam::new());
+    assert_eq!(0, bus.read(0x4000));
+
+    bus.write(0x0200, 3);
+    assert_eq!(3, bus.read(0x4000));
+    assert_eq!(3, bus.read(0x7FFF));
+    // Addresses outside the window reach the RAM behind the mapper
+    bus.write(0x8000, 0x42);
+    assert_eq!(0x42, bus.read(0x8000));
+    // Bank numbers past the end of the ROM wrap around
+    bus.write(0x0200, 5);
+    assert_eq!(1, bus.read(0x4000));
+}
+
+#[test]
+fn test_nrom_mirrors_16k() {
+    let mut bus = Cartridge::new(Nrom::new(banked_image(1)), FlatRam::new());
+    bus.load(0x8010, 0x42);
+    bus.write(0x8011, 0x42);
+
+    assert_eq!(0x42, bus.read(0xC010));
+    assert_eq!(0, bus.read(0xC011));
+}
+
+#[test]
+fn test_uxrom_switches_low_bank() {
+    let mut bus = Cartridge::new(UxRom::new(banked_image(8)), FlatRam::new());
+    bus.write(0x8000, 5);
+
+    assert_eq!(5, bus.read(0x8000));
+    assert_eq!(7, bus.read(0xC000));
+    assert_eq!(5, bus.mapper.bank());
+}
+
+#[test]
+fn test_mmc1_serial_writes() {
+    let mut bus = Cartridge::new(Mmc1::new(banked_image(8)), FlatRam::new());
+    // Power-on state switches $8000 and fixes the last bank at $C000
+    write_mmc1(&mut bus, 0xE000, 3);
+
+    assert_eq!(3, bus.read(0x8000));
+    assert_eq!(7, bus.read(0xC000));
+}
+
+#[test]
+fn test_mmc1_reset_bit() {
+    let mut bus = Cartridge::new(Mmc1::new(banked_image(8)), FlatRam::new());
+    bus.write(0x8000, 1);
+    bus.write(0x8000, 1);
+    bus.write(0x8000, 0x80);
+    // The partial write was discarded, so this is a complete write
+    write_mmc1(&mut bus, 0xE000, 2);
+
+    assert_eq!(2, bus.mapper.prg_bank());
+}
+
+#[test]
+fn test_mmc1_prg_modes() {
+    let mut bus = Cartridge::new(Mmc1::new(banked_image(8)), FlatRam::new());
+    write_mmc1(&mut bus, 0xE000, 5);
+
+    // 32K mode ignores the lowest bit of the bank
+    write_mmc1(&mut bus, 0x8000, 0b00000);
+    assert_eq!(4, bus.read(0x8000));
+    assert_eq!(5, bus.read(0xC000));
+
+    // First bank fixed at $8000
+    write_mmc1(&mut bus, 0x8000, 0b01000);
+    assert_eq!(0, bus.read(0x8000));
+    assert_eq!(5, bus.read(0xC000));
+}
+
+#[test]
+fn test_mmc1_chr_banks() {
+    let mut bus = Cartridge::new(Mmc1::new(banked_image(8)), FlatRam::new());
+    write_mmc1(&mut bus, 0xA000, 0x11);
+    write_mmc1(&mut bus, 0xC000, 0x07);
+
+    assert_eq!([0x11, 0x07], bus.mapper.chr_banks());
+}
+
+#[test]
+fn test_mmc1_prg_ram() {
+    let mut bus = Cartridge::new(Mmc1::new(banked_image(8)), FlatRam::new());
+    bus.write(0x6000, 0x42);
+    assert_eq!(0x42, bus.read(0x6000));
+
+    // Disabling the PRG RAM leaves the address to the bus behind the mapper
+    write_mmc1(&mut bus, 0xE000, 0x10);
+    assert_eq!(0, bus.read(0x6000));
+}
+
+#[test]
+fn test_program_switches_bank() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, Cartridge::new(UxRom::new(banked_image(4)), FlatRam::new()));
+    let opcode = |op: fn(&mut ComputerState<Cartridge<UxRom>>)| variant_opcode_from_operation(CpuVariant::Nmos6502, op);
+    state.pc.set(0xC000);
+    state.set_up_state(&[
+        opcode(lda_im),
+        0x02,
+        opcode(sta_ab),
+        0x00,
+        0x80,
+        opcode(lda_ab),
+        0x00,
+        0x80,
+    ]);
+
+    for _ in 0..3 {
+        state.execute_next().unwrap();
+    }
+    assert_eq!(2, state.acc.get());
+}
@@ -12,4 +12,8 @@ mod test_load_store;
 #[cfg(test)]
 mod test_stack;
 #[cfg(test)]
-mod test_comparison;
\ No newline at end of file
+mod test_comparison;
+#[cfg(test)]
+mod test_illegal;
+#[cfg(test)]
+mod test_cmos;
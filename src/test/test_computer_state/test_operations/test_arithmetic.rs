@@ -11,11 +11,11 @@ use crate::computer_state::status_register::StatusRegister;
 fn test_adc_im() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         36
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(72, state.acc.get());
     assert!(state.sta.is_empty());
@@ -25,11 +25,11 @@ fn test_adc_im() {
 fn test_adc_im_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -39,11 +39,11 @@ fn test_adc_im_zero_flag() {
 fn test_adc_im_carry_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(72);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         200
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -53,11 +53,11 @@ fn test_adc_im_carry_flag() {
 fn test_adc_im_zero_and_carry_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         0xFF
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
@@ -67,11 +67,11 @@ fn test_adc_im_zero_and_carry_flag() {
 fn test_adc_im_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(32);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         200
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(232, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -81,11 +81,11 @@ fn test_adc_im_negative_flag() {
 fn test_adc_im_overflow_positive() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(32);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         120
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(152, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::V));
@@ -95,11 +95,11 @@ fn test_adc_im_overflow_positive() {
 fn test_adc_im_overflow_negative() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(144);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_im),
         208
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(96, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::V));
@@ -109,12 +109,12 @@ fn test_adc_im_overflow_negative() {
 fn test_adc_zp() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -125,12 +125,12 @@ fn test_adc_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
     state.set_x(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_zpx),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF6, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF6, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -140,13 +140,13 @@ fn test_adc_zpx() {
 fn test_adc_ab() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_ab),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F1, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -157,13 +157,13 @@ fn test_adc_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
     state.set_x(8);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_abx),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F9, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F9, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -174,13 +174,13 @@ fn test_adc_aby() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
     state.set_y(8);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_aby),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F9, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F9, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -191,13 +191,13 @@ fn test_adc_inx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_inx),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x63, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 20);
-    state.execute_next();
+    state.set_nibble_at_addr(0x63, 0x1234);
+    state.set_byte_at_addr(0x1234, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -208,13 +208,13 @@ fn test_adc_iny() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(36);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(adc_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 20);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(56, state.acc.get());
     assert!(state.sta.is_empty());
@@ -225,12 +225,14 @@ fn test_adc_iny() {
 #[test]
 fn test_sbc_im() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(0x50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_im),
         0x20
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x30, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -239,12 +241,14 @@ fn test_sbc_im() {
 #[test]
 fn test_sbc_im_borrow() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(0xD0);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_im),
         0xF0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xE0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -253,12 +257,14 @@ fn test_sbc_im_borrow() {
 #[test]
 fn test_sbc_im_overflow() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(0x50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_im),
         0xB0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xA0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::V));
@@ -267,12 +273,14 @@ fn test_sbc_im_overflow() {
 #[test]
 fn test_sbc_im_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_im),
         50
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
@@ -281,13 +289,15 @@ fn test_sbc_im_zero_flag() {
 #[test]
 fn test_sbc_zp() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -296,14 +306,16 @@ fn test_sbc_zp() {
 #[test]
 fn test_sbc_zpx() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
     state.set_x(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_zpx),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF6, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF6, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -312,14 +324,16 @@ fn test_sbc_zpx() {
 #[test]
 fn test_sbc_ab() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_ab),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F1, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -328,15 +342,17 @@ fn test_sbc_ab() {
 #[test]
 fn test_sbc_abx() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
     state.set_x(8);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_abx),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F9, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F9, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -345,15 +361,17 @@ fn test_sbc_abx() {
 #[test]
 fn test_sbc_aby() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
     state.set_y(8);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_aby),
         0xF1,
         0x36
     ]);
-    state.mem.set_byte_at_addr(0x36F9, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x36F9, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -362,15 +380,17 @@ fn test_sbc_aby() {
 #[test]
 fn test_sbc_inx() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_inx),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x63, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 20);
-    state.execute_next();
+    state.set_nibble_at_addr(0x63, 0x1234);
+    state.set_byte_at_addr(0x1234, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -379,61 +399,174 @@ fn test_sbc_inx() {
 #[test]
 fn test_sbc_iny() {
     let mut state: ComputerState = ComputerState::new();
+    // Carry set means there is no borrow
+    state.sta |= StatusRegister::C;
     state.acc.set(36);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sbc_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 20);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 20);
+    state.execute_next().unwrap();
 
     assert_eq!(16, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
 }
 
 
+// DECIMAL MODE TESTS
+#[test]
+fn test_adc_decimal() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D;
+    state.acc.set(0x12);
+    state.set_up_state(&[
+        opcode_from_operation(adc_im),
+        0x34
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x46, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D));
+}
+
+#[test]
+fn test_adc_decimal_digit_carry() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D | StatusRegister::C;
+    state.acc.set(0x58);
+    state.set_up_state(&[
+        opcode_from_operation(adc_im),
+        0x46
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x05, state.acc.get());
+    // N and V come from the unadjusted sum 0xA5
+    assert!(state.sta.contains_only(
+        StatusRegister::D | StatusRegister::N | StatusRegister::V | StatusRegister::C
+    ));
+}
+
+#[test]
+fn test_adc_decimal_overflow() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D;
+    state.acc.set(0x81);
+    state.set_up_state(&[
+        opcode_from_operation(adc_im),
+        0x92
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x73, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::V | StatusRegister::C));
+}
+
+#[test]
+fn test_adc_decimal_zero_from_binary_sum() {
+    // The NMOS 6502 takes Z from the binary sum (0x9A) rather than the decimal result
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D;
+    state.acc.set(0x99);
+    state.set_up_state(&[
+        opcode_from_operation(adc_im),
+        0x01
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x00, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_sbc_decimal() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D | StatusRegister::C;
+    state.acc.set(0x46);
+    state.set_up_state(&[
+        opcode_from_operation(sbc_im),
+        0x12
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x34, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::C));
+}
+
+#[test]
+fn test_sbc_decimal_digit_borrow() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D;
+    state.acc.set(0x32);
+    state.set_up_state(&[
+        opcode_from_operation(sbc_im),
+        0x02
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x29, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::C));
+}
+
+#[test]
+fn test_sbc_decimal_borrow() {
+    let mut state: ComputerState = ComputerState::new();
+    state.sta |= StatusRegister::D | StatusRegister::C;
+    state.acc.set(0x21);
+    state.set_up_state(&[
+        opcode_from_operation(sbc_im),
+        0x34
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x87, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::N));
+}
+
+
 // DEC TESTS
 #[test]
 fn test_dec_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(19, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(19, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_dec_zp_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 1);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 1);
+    state.execute_next().unwrap();
 
-    assert_eq!(0, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(0, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.contains_only(StatusRegister::Z));
 }
 
 #[test]
 fn test_dec_zp_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 0);
+    state.execute_next().unwrap();
 
-    assert_eq!(0xFF, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(0xFF, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.contains_only(StatusRegister::N));
 }
 
@@ -441,29 +574,29 @@ fn test_dec_zp_negative_flag() {
 fn test_dec_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_zpx),
         0x45
     ]);
-    state.mem.set_byte_at_addr(0x55, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x55, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(19, state.mem.fetch_byte_from_addr(0x55));
+    assert_eq!(19, state.fetch_byte_from_addr(0x55));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_dec_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_ab),
         0x45,
         0x1A
     ]);
-    state.mem.set_byte_at_addr(0x1A45, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x1A45, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(19, state.mem.fetch_byte_from_addr(0x1A45));
+    assert_eq!(19, state.fetch_byte_from_addr(0x1A45));
     assert!(state.sta.is_empty());
 }
 
@@ -471,15 +604,15 @@ fn test_dec_ab() {
 fn test_dec_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dec_abx),
         0x45,
         0x1A
     ]);
-    state.mem.set_byte_at_addr(0x1A55, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x1A55, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(19, state.mem.fetch_byte_from_addr(0x1A55));
+    assert_eq!(19, state.fetch_byte_from_addr(0x1A55));
     assert!(state.sta.is_empty());
 }
 
@@ -488,42 +621,42 @@ fn test_dec_abx() {
 #[test]
 fn test_inc_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(21, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(21, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_inc_zp_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 0xFF);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 0xFF);
+    state.execute_next().unwrap();
 
-    assert_eq!(0, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(0, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.contains_only(StatusRegister::Z));
 }
 
 #[test]
 fn test_inc_zp_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_zp),
         0xF1
     ]);
-    state.mem.set_byte_at_addr(0xF1, 0x7F);
-    state.execute_next();
+    state.set_byte_at_addr(0xF1, 0x7F);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x80, state.mem.fetch_byte_from_addr(0xF1));
+    assert_eq!(0x80, state.fetch_byte_from_addr(0xF1));
     assert!(state.sta.contains_only(StatusRegister::N));
 }
 
@@ -531,29 +664,29 @@ fn test_inc_zp_negative_flag() {
 fn test_inc_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_zpx),
         0x45
     ]);
-    state.mem.set_byte_at_addr(0x55, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x55, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(21, state.mem.fetch_byte_from_addr(0x55));
+    assert_eq!(21, state.fetch_byte_from_addr(0x55));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_inc_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_ab),
         0x45,
         0x1A
     ]);
-    state.mem.set_byte_at_addr(0x1A45, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x1A45, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(21, state.mem.fetch_byte_from_addr(0x1A45));
+    assert_eq!(21, state.fetch_byte_from_addr(0x1A45));
     assert!(state.sta.is_empty());
 }
 
@@ -561,15 +694,15 @@ fn test_inc_ab() {
 fn test_inc_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inc_abx),
         0x45,
         0x1A
     ]);
-    state.mem.set_byte_at_addr(0x1A55, 20);
-    state.execute_next();
+    state.set_byte_at_addr(0x1A55, 20);
+    state.execute_next().unwrap();
 
-    assert_eq!(21, state.mem.fetch_byte_from_addr(0x1A55));
+    assert_eq!(21, state.fetch_byte_from_addr(0x1A55));
     assert!(state.sta.is_empty());
 }
 
@@ -579,10 +712,10 @@ fn test_inc_abx() {
 fn test_dex() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dex),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(4, state.get_x());
     assert!(state.sta.is_empty());
@@ -592,10 +725,10 @@ fn test_dex() {
 fn test_dex_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dex),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -605,10 +738,10 @@ fn test_dex_zero_flag() {
 fn test_dex_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dex),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_x());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -620,10 +753,10 @@ fn test_dex_negative_flag() {
 fn test_dey() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dey),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(4, state.get_y());
     assert!(state.sta.is_empty());
@@ -633,10 +766,10 @@ fn test_dey() {
 fn test_dey_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dey),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -646,10 +779,10 @@ fn test_dey_zero_flag() {
 fn test_dey_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(0);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(dey),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_y());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -661,10 +794,10 @@ fn test_dey_negative_flag() {
 fn test_inx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inx),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(6, state.get_x());
     assert!(state.sta.is_empty());
@@ -674,10 +807,10 @@ fn test_inx() {
 fn test_inx_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0xFF);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inx),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -687,10 +820,10 @@ fn test_inx_zero_flag() {
 fn test_inx_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x7F);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(inx),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x80, state.get_x());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -702,10 +835,10 @@ fn test_inx_negative_flag() {
 fn test_iny() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(iny),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(6, state.get_y());
     assert!(state.sta.is_empty());
@@ -715,10 +848,10 @@ fn test_iny() {
 fn test_iny_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(0xFF);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(iny),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -728,10 +861,10 @@ fn test_iny_zero_flag() {
 fn test_iny_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.set_y(0xFF);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(iny),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains_only(StatusRegister::Z));
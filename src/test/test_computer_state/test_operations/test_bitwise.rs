@@ -10,11 +10,11 @@ use crate::computer_state::status_register::StatusRegister;
 fn test_and_im() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x13);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_im),
         0x23
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x03, state.acc.get());
     assert!(state.sta.is_empty());
@@ -24,11 +24,11 @@ fn test_and_im() {
 fn test_and_im_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_im),
         0x11
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -38,11 +38,11 @@ fn test_and_im_zero_flag() {
 fn test_and_im_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xA1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_im),
         0xF0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xA0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -52,12 +52,12 @@ fn test_and_im_negative_flag() {
 fn test_and_zp() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_zp),
         0xF5
     ]);
-    state.mem.set_byte_at_addr(0xF5, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0xF5, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -68,12 +68,12 @@ fn test_and_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
     state.set_x(0x50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_zpx),
         0x30
     ]);
-    state.mem.set_byte_at_addr(0x80, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x80, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -83,13 +83,13 @@ fn test_and_zpx() {
 fn test_and_ab() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_ab),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x0530, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x0530, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -100,13 +100,13 @@ fn test_and_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
     state.set_x(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_abx),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -117,13 +117,13 @@ fn test_and_aby() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
     state.set_y(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_aby),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -134,13 +134,13 @@ fn test_and_inx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_inx),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x77, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 0x43);
-    state.execute_next();
+    state.set_nibble_at_addr(0x77, 0x1234);
+    state.set_byte_at_addr(0x1234, 0x43);
+    state.execute_next().unwrap();
 
     assert_eq!(0x41, state.acc.get());
     assert!(state.sta.is_empty());
@@ -151,13 +151,13 @@ fn test_and_iny() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xFF);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(and_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 0x29);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 0x29);
+    state.execute_next().unwrap();
 
     assert_eq!(0x29, state.acc.get());
     assert!(state.sta.is_empty());
@@ -169,11 +169,11 @@ fn test_and_iny() {
 fn test_or_im() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x13);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_im),
         0x30
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x33, state.acc.get());
     assert!(state.sta.is_empty());
@@ -183,11 +183,11 @@ fn test_or_im() {
 fn test_or_im_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x00);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_im),
         0x00
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x00, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -197,11 +197,11 @@ fn test_or_im_zero_flag() {
 fn test_or_im_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xA1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_im),
         0xF0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xF1, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -211,12 +211,12 @@ fn test_or_im_negative_flag() {
 fn test_or_zp() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x25);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_zp),
         0xF5
     ]);
-    state.mem.set_byte_at_addr(0xF5, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0xF5, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x35, state.acc.get());
     assert!(state.sta.is_empty());
@@ -227,12 +227,12 @@ fn test_or_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x42);
     state.set_x(0x50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_zpx),
         0x30
     ]);
-    state.mem.set_byte_at_addr(0x80, 0x81);
-    state.execute_next();
+    state.set_byte_at_addr(0x80, 0x81);
+    state.execute_next().unwrap();
 
     assert_eq!(0xC3, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -242,13 +242,13 @@ fn test_or_zpx() {
 fn test_or_ab() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_ab),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x0530, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x0530, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x55, state.acc.get());
     assert!(state.sta.is_empty());
@@ -259,13 +259,13 @@ fn test_or_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x53);
     state.set_x(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_abx),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x40);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x40);
+    state.execute_next().unwrap();
 
     assert_eq!(0x53, state.acc.get());
     assert!(state.sta.is_empty());
@@ -276,13 +276,13 @@ fn test_or_aby() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x02);
     state.set_y(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_aby),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x20);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x20);
+    state.execute_next().unwrap();
 
     assert_eq!(0x22, state.acc.get());
     assert!(state.sta.is_empty());
@@ -293,13 +293,13 @@ fn test_or_inx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(1);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_inx),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x77, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 3);
-    state.execute_next();
+    state.set_nibble_at_addr(0x77, 0x1234);
+    state.set_byte_at_addr(0x1234, 3);
+    state.execute_next().unwrap();
 
     assert_eq!(3, state.acc.get());
     assert!(state.sta.is_empty());
@@ -310,13 +310,13 @@ fn test_or_iny() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xFF);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(or_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 0x23);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 0x23);
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -328,11 +328,11 @@ fn test_or_iny() {
 fn test_eor_im() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x13);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_im),
         0x30
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x23, state.acc.get());
     assert!(state.sta.is_empty());
@@ -342,11 +342,11 @@ fn test_eor_im() {
 fn test_eor_im_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x11);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_im),
         0x11
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x00, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -356,11 +356,11 @@ fn test_eor_im_zero_flag() {
 fn test_eor_im_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_im),
         0xF0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xF5, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -370,12 +370,12 @@ fn test_eor_im_negative_flag() {
 fn test_eor_zp() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x25);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_zp),
         0xF5
     ]);
-    state.mem.set_byte_at_addr(0xF5, 0x31);
-    state.execute_next();
+    state.set_byte_at_addr(0xF5, 0x31);
+    state.execute_next().unwrap();
 
     assert_eq!(0x14, state.acc.get());
     assert!(state.sta.is_empty());
@@ -386,12 +386,12 @@ fn test_eor_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x23);
     state.set_x(0x50);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_zpx),
         0x30
     ]);
-    state.mem.set_byte_at_addr(0x80, 0x31);
-    state.execute_next();
+    state.set_byte_at_addr(0x80, 0x31);
+    state.execute_next().unwrap();
 
     assert_eq!(0x12, state.acc.get());
     assert!(state.sta.is_empty());
@@ -401,13 +401,13 @@ fn test_eor_zpx() {
 fn test_eor_ab() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x41);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_ab),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x0530, 0x55);
-    state.execute_next();
+    state.set_byte_at_addr(0x0530, 0x55);
+    state.execute_next().unwrap();
 
     assert_eq!(0x14, state.acc.get());
     assert!(state.sta.is_empty());
@@ -418,13 +418,13 @@ fn test_eor_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x53);
     state.set_x(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_abx),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x40);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x40);
+    state.execute_next().unwrap();
 
     assert_eq!(0x13, state.acc.get());
     assert!(state.sta.is_empty());
@@ -435,13 +435,13 @@ fn test_eor_aby() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x02);
     state.set_y(0x0A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_aby),
         0x30,
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x053A, 0x20);
-    state.execute_next();
+    state.set_byte_at_addr(0x053A, 0x20);
+    state.execute_next().unwrap();
 
     assert_eq!(0x22, state.acc.get());
     assert!(state.sta.is_empty());
@@ -452,13 +452,13 @@ fn test_eor_inx() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x19);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_inx),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x77, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 0x28);
-    state.execute_next();
+    state.set_nibble_at_addr(0x77, 0x1234);
+    state.set_byte_at_addr(0x1234, 0x28);
+    state.execute_next().unwrap();
 
     assert_eq!(0x31, state.acc.get());
     assert!(state.sta.is_empty());
@@ -469,13 +469,13 @@ fn test_eor_iny() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x35);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(eor_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 0x23);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 0x23);
+    state.execute_next().unwrap();
 
     assert_eq!(0x16, state.acc.get());
     assert!(state.sta.is_empty());
@@ -487,15 +487,15 @@ fn test_eor_iny() {
 fn test_bit_zp() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x23);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_zp),
         0x41
     ]);
-    state.mem.set_byte_at_addr(0x41, 0x35);
-    state.execute_next();
+    state.set_byte_at_addr(0x41, 0x35);
+    state.execute_next().unwrap();
 
     assert_eq!(0x23, state.acc.get());
-    assert_eq!(0x35, state.mem.fetch_byte_from_addr(0x41));
+    assert_eq!(0x35, state.fetch_byte_from_addr(0x41));
     assert!(state.sta.is_empty());
 }
 
@@ -503,12 +503,12 @@ fn test_bit_zp() {
 fn test_bit_zp_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x11);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_zp),
         0x41
     ]);
-    state.mem.set_byte_at_addr(0x41, 0x22);
-    state.execute_next();
+    state.set_byte_at_addr(0x41, 0x22);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z));
 }
@@ -517,12 +517,12 @@ fn test_bit_zp_zero_flag() {
 fn test_bit_zp_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x23);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_zp),
         0x41
     ]);
-    state.mem.set_byte_at_addr(0x41, 0xA5);
-    state.execute_next();
+    state.set_byte_at_addr(0x41, 0xA5);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::N));
 }
@@ -531,12 +531,12 @@ fn test_bit_zp_negative_flag() {
 fn test_bit_zp_overflow_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x23);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_zp),
         0x41
     ]);
-    state.mem.set_byte_at_addr(0x41, 0x45);
-    state.execute_next();
+    state.set_byte_at_addr(0x41, 0x45);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::V));
 }
@@ -545,12 +545,12 @@ fn test_bit_zp_overflow_flag() {
 fn test_bit_zp_zero_negative_overflow_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x02);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_zp),
         0x41
     ]);
-    state.mem.set_byte_at_addr(0x41, 0xC5);
-    state.execute_next();
+    state.set_byte_at_addr(0x41, 0xC5);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::N | StatusRegister::V));
 }
@@ -559,13 +559,13 @@ fn test_bit_zp_zero_negative_overflow_flag() {
 fn test_bit_ab() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x23);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(bit_ab),
         0x41,
         0x90
     ]);
-    state.mem.set_byte_at_addr(0x9041, 0x35);
-    state.execute_next();
+    state.set_byte_at_addr(0x9041, 0x35);
+    state.execute_next().unwrap();
 
     assert!(state.sta.is_empty());
 }
@@ -576,10 +576,10 @@ fn test_bit_ab() {
 fn test_asl_acc() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x09);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x12, state.acc.get());
     assert!(state.sta.is_empty());
@@ -589,10 +589,10 @@ fn test_asl_acc() {
 fn test_asl_acc_carry_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x99);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x32, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -602,10 +602,10 @@ fn test_asl_acc_carry_flag() {
 fn test_asl_acc_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::Z));
@@ -615,10 +615,10 @@ fn test_asl_acc_zero_flag() {
 fn test_asl_acc_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xC0);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x80, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::N));
@@ -627,14 +627,14 @@ fn test_asl_acc_negative_flag() {
 #[test]
 fn test_asl_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 0x09);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 0x09);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x10));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x10));
     assert!(state.sta.is_empty());
 }
 
@@ -642,29 +642,29 @@ fn test_asl_zp() {
 fn test_asl_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 0x09);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 0x09);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x15));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x15));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_asl_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_ab),
         0x10,
         0x85
     ]);
-    state.mem.set_byte_at_addr(0x8510, 0x09);
-    state.execute_next();
+    state.set_byte_at_addr(0x8510, 0x09);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x8510));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x8510));
     assert!(state.sta.is_empty());
 }
 
@@ -672,15 +672,15 @@ fn test_asl_ab() {
 fn test_asl_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(asl_abx),
         0x10,
         0x85
     ]);
-    state.mem.set_byte_at_addr(0x8515, 0x09);
-    state.execute_next();
+    state.set_byte_at_addr(0x8515, 0x09);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x8515));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x8515));
     assert!(state.sta.is_empty());
 }
 
@@ -690,10 +690,10 @@ fn test_asl_abx() {
 fn test_lsr_acc() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(8);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(4, state.acc.get());
     assert!(state.sta.is_empty());
@@ -703,10 +703,10 @@ fn test_lsr_acc() {
 fn test_lsr_acc_carry_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(9);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(4, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -716,10 +716,10 @@ fn test_lsr_acc_carry_flag() {
 fn test_lsr_acc_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(1);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::Z));
@@ -728,14 +728,14 @@ fn test_lsr_acc_zero_flag() {
 #[test]
 fn test_lsr_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_zp),
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x05, 8);
-    state.execute_next();
+    state.set_byte_at_addr(0x05, 8);
+    state.execute_next().unwrap();
 
-    assert_eq!(4, state.mem.fetch_byte_from_addr(0x05));
+    assert_eq!(4, state.fetch_byte_from_addr(0x05));
     assert!(state.sta.is_empty());
 }
 
@@ -743,29 +743,29 @@ fn test_lsr_zp() {
 fn test_lsr_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_zpx),
         0x05
     ]);
-    state.mem.set_byte_at_addr(0x0A, 8);
-    state.execute_next();
+    state.set_byte_at_addr(0x0A, 8);
+    state.execute_next().unwrap();
 
-    assert_eq!(4, state.mem.fetch_byte_from_addr(0x0A));
+    assert_eq!(4, state.fetch_byte_from_addr(0x0A));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_lsr_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_ab),
         0x05,
         0x08
     ]);
-    state.mem.set_byte_at_addr(0x0805, 8);
-    state.execute_next();
+    state.set_byte_at_addr(0x0805, 8);
+    state.execute_next().unwrap();
 
-    assert_eq!(4, state.mem.fetch_byte_from_addr(0x0805));
+    assert_eq!(4, state.fetch_byte_from_addr(0x0805));
     assert!(state.sta.is_empty());
 }
 
@@ -773,15 +773,15 @@ fn test_lsr_ab() {
 fn test_lsr_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lsr_abx),
         0x05,
         0x08
     ]);
-    state.mem.set_byte_at_addr(0x080A, 8);
-    state.execute_next();
+    state.set_byte_at_addr(0x080A, 8);
+    state.execute_next().unwrap();
 
-    assert_eq!(4, state.mem.fetch_byte_from_addr(0x080A));
+    assert_eq!(4, state.fetch_byte_from_addr(0x080A));
     assert!(state.sta.is_empty());
 }
 
@@ -791,10 +791,10 @@ fn test_lsr_abx() {
 fn test_rol_acc() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x09);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x12, state.acc.get());
     assert!(state.sta.is_empty());
@@ -805,10 +805,10 @@ fn test_rol_acc_old_carry() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x09);
     state.sta |= StatusRegister::C;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x13, state.acc.get());
     assert!(state.sta.is_empty());
@@ -819,10 +819,10 @@ fn test_rol_acc_carry_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x89);
     state.sta |= StatusRegister::C;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x13, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C));
@@ -833,10 +833,10 @@ fn test_rol_acc_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0xC9);
     state.sta |= StatusRegister::C;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x93, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::N));
@@ -846,10 +846,10 @@ fn test_rol_acc_negative_flag() {
 fn test_rol_acc_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::Z));
@@ -858,14 +858,14 @@ fn test_rol_acc_zero_flag() {
 #[test]
 fn test_rol_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 0x9);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 0x9);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x10));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x10));
     assert!(state.sta.is_empty());
 }
 
@@ -873,29 +873,29 @@ fn test_rol_zp() {
 fn test_rol_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 0x9);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 0x9);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x15));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x15));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_rol_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_ab),
         0x10,
         0x11
     ]);
-    state.mem.set_byte_at_addr(0x1110, 0x9);
-    state.execute_next();
+    state.set_byte_at_addr(0x1110, 0x9);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x1110));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x1110));
     assert!(state.sta.is_empty());
 }
 
@@ -903,15 +903,15 @@ fn test_rol_ab() {
 fn test_rol_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(rol_abx),
         0x10,
         0x11
     ]);
-    state.mem.set_byte_at_addr(0x1115, 0x9);
-    state.execute_next();
+    state.set_byte_at_addr(0x1115, 0x9);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x12, state.mem.fetch_byte_from_addr(0x1115));
+    assert_eq!(0x12, state.fetch_byte_from_addr(0x1115));
     assert!(state.sta.is_empty());
 }
 
@@ -921,10 +921,10 @@ fn test_rol_abx() {
 fn test_ror_acc() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x08);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x04, state.acc.get());
     assert!(state.sta.is_empty());
@@ -935,10 +935,10 @@ fn test_ror_acc_old_carry() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x08);
     state.sta |= StatusRegister::C;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x84, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -949,10 +949,10 @@ fn test_ror_acc_carry_negative_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x09);
     state.sta |= StatusRegister::C;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x84, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::N));
@@ -962,10 +962,10 @@ fn test_ror_acc_carry_negative_flag() {
 fn test_ror_acc_zero_flag() {
     let mut state: ComputerState = ComputerState::new();
     state.acc.set(0x01);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_acc),
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::C | StatusRegister::Z));
@@ -974,14 +974,14 @@ fn test_ror_acc_zero_flag() {
 #[test]
 fn test_ror_zp() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 0x08);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 0x08);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x04, state.mem.fetch_byte_from_addr(0x10));
+    assert_eq!(0x04, state.fetch_byte_from_addr(0x10));
     assert!(state.sta.is_empty());
 }
 
@@ -989,29 +989,29 @@ fn test_ror_zp() {
 fn test_ror_zpx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 0x08);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 0x08);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x04, state.mem.fetch_byte_from_addr(0x15));
+    assert_eq!(0x04, state.fetch_byte_from_addr(0x15));
     assert!(state.sta.is_empty());
 }
 
 #[test]
 fn test_ror_ab() {
     let mut state: ComputerState = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_ab),
         0x10,
         0x75
     ]);
-    state.mem.set_byte_at_addr(0x7510, 0x08);
-    state.execute_next();
+    state.set_byte_at_addr(0x7510, 0x08);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x04, state.mem.fetch_byte_from_addr(0x7510));
+    assert_eq!(0x04, state.fetch_byte_from_addr(0x7510));
     assert!(state.sta.is_empty());
 }
 
@@ -1019,14 +1019,14 @@ fn test_ror_ab() {
 fn test_ror_abx() {
     let mut state: ComputerState = ComputerState::new();
     state.set_x(0x05);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ror_abx),
         0x10,
         0x75
     ]);
-    state.mem.set_byte_at_addr(0x7515, 0x08);
-    state.execute_next();
+    state.set_byte_at_addr(0x7515, 0x08);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x04, state.mem.fetch_byte_from_addr(0x7515));
+    assert_eq!(0x04, state.fetch_byte_from_addr(0x7515));
     assert!(state.sta.is_empty());
 }
@@ -0,0 +1,319 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::execution::ExecutionError;
+use crate::computer_state::operations::arithmetic::*;
+use crate::computer_state::operations::bitwise::*;
+use crate::computer_state::operations::branch::*;
+use crate::computer_state::operations::interrupt::*;
+use crate::computer_state::operations::jumps_calls::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::stack::*;
+use crate::computer_state::operations::{decode_operation_name, variant_opcode_from_operation, MosOp};
+use crate::computer_state::status_register::StatusRegister;
+
+fn cmos_opcode(op: MosOp) -> u8 {
+    variant_opcode_from_operation(CpuVariant::Wdc65C02, op)
+}
+
+#[test]
+fn test_every_opcode_decodes() {
+    for variant in [CpuVariant::Nmos6502, CpuVariant::Wdc65C02] {
+        for opcode in 0..=255 {
+            assert_ne!("INVALID", decode_operation_name(variant, opcode));
+        }
+    }
+}
+
+#[test]
+fn test_bra() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(bra),
+        0x23
+    ]);
+
+    assert_eq!(3, state.execute_next().unwrap().cycles());
+    assert_eq!(2 + 0x23, state.pc.get());
+}
+
+#[test]
+fn test_phx_ply() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_x(0x85);
+    state.set_up_state(&[
+        cmos_opcode(phx),
+        cmos_opcode(ply)
+    ]);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+
+    assert_eq!(0x85, state.get_y());
+    assert!(state.sta.contains_only(StatusRegister::N));
+}
+
+#[test]
+fn test_stz_ab() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(stz_ab),
+        0x34,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1234, 0xFF);
+
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    assert_eq!(0, state.fetch_byte_from_addr(0x1234));
+}
+
+#[test]
+fn test_trb_zp() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.acc.set(0x0F);
+    state.set_up_state(&[
+        cmos_opcode(trb_zp),
+        0x20
+    ]);
+    state.set_byte_at_addr(0x20, 0xFF);
+    state.execute_next().unwrap();
+
+    assert_eq!(0xF0, state.fetch_byte_from_addr(0x20));
+    assert!(state.sta.is_empty());
+}
+
+#[test]
+fn test_tsb_ab_zero_flag() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.acc.set(0x0F);
+    state.set_up_state(&[
+        cmos_opcode(tsb_ab),
+        0x34,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1234, 0xF0);
+    state.execute_next().unwrap();
+
+    assert_eq!(0xFF, state.fetch_byte_from_addr(0x1234));
+    assert!(state.sta.contains_only(StatusRegister::Z));
+}
+
+#[test]
+fn test_inc_acc() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.acc.set(0xFF);
+    state.set_up_state(&[
+        cmos_opcode(inc_acc)
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::Z));
+}
+
+#[test]
+fn test_dec_acc() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.acc.set(0x00);
+    state.set_up_state(&[
+        cmos_opcode(dec_acc)
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0xFF, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::N));
+}
+
+#[test]
+fn test_lda_izp() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(lda_izp),
+        0xFF
+    ]);
+    // The pointer wraps around the zero-page
+    state.set_byte_at_addr(0xFF, 0x34);
+    state.set_byte_at_addr(0x00, 0x12);
+    state.set_byte_at_addr(0x1234, 0x42);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+    assert_eq!(0x42, state.acc.get());
+}
+
+#[test]
+fn test_bit_im() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.acc.set(0x01);
+    state.set_up_state(&[
+        cmos_opcode(bit_im),
+        0xC0
+    ]);
+    state.execute_next().unwrap();
+
+    // N and V are not taken from the operand
+    assert!(state.sta.contains_only(StatusRegister::Z));
+}
+
+#[test]
+fn test_rmb_smb() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(rmb0),
+        0x20,
+        cmos_opcode(smb7),
+        0x20
+    ]);
+    state.set_byte_at_addr(0x20, 0x01);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+
+    assert_eq!(0x80, state.fetch_byte_from_addr(0x20));
+}
+
+#[test]
+fn test_bbr_bbs() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(bbs3),
+        0x20,
+        0x10,
+        cmos_opcode(bbr3),
+        0x20,
+        0x10
+    ]);
+    state.set_byte_at_addr(0x20, 0x08);
+
+    // Bit 3 is set, so BBS3 branches
+    state.execute_next().unwrap();
+    assert_eq!(3 + 0x10, state.pc.get());
+
+    state.pc.set(3);
+    state.execute_next().unwrap();
+    assert_eq!(6, state.pc.get());
+}
+
+#[test]
+fn test_jmp_in_page_bug() {
+    let mut nmos = ComputerState::new();
+    let mut cmos = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    for state in [&mut nmos, &mut cmos] {
+        state.set_up_state(&[
+            0x6C,
+            0xFF,
+            0x12
+        ]);
+        state.set_byte_at_addr(0x12FF, 0x34);
+        state.set_byte_at_addr(0x1300, 0x56);
+        state.set_byte_at_addr(0x1200, 0x78);
+        state.execute_next().unwrap();
+    }
+
+    assert_eq!(0x7834, nmos.pc.get());
+    assert_eq!(0x5634, cmos.pc.get());
+}
+
+#[test]
+fn test_jmp_inx() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_x(0x04);
+    state.set_up_state(&[
+        cmos_opcode(jmp_inx),
+        0x30,
+        0x12
+    ]);
+    state.set_nibble_at_addr(0x1234, 0xABCD);
+
+    assert_eq!(6, state.execute_next().unwrap().cycles());
+    assert_eq!(0xABCD, state.pc.get());
+}
+
+#[test]
+fn test_adc_decimal_cmos_flags() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.sta |= StatusRegister::D;
+    state.acc.set(0x99);
+    state.set_up_state(&[
+        cmos_opcode(adc_im),
+        0x01
+    ]);
+
+    // The extra cycle is taken in decimal mode
+    assert_eq!(3, state.execute_next().unwrap().cycles());
+    assert_eq!(0x00, state.acc.get());
+    // Unlike the NMOS 6502, N and Z reflect the decimal result
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::Z | StatusRegister::C));
+}
+
+#[test]
+fn test_sbc_decimal_cmos() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.sta |= StatusRegister::D | StatusRegister::C;
+    state.acc.set(0x21);
+    state.set_up_state(&[
+        cmos_opcode(sbc_im),
+        0x34
+    ]);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x87, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::N));
+}
+
+#[test]
+fn test_brk_clears_decimal() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.sta |= StatusRegister::D;
+    state.set_up_state(&[
+        cmos_opcode(brk)
+    ]);
+    state.execute_next().unwrap();
+
+    assert!(state.sta.contains_only(StatusRegister::I));
+}
+
+#[test]
+fn test_wai() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.sta |= StatusRegister::I;
+    state.set_up_state(&[
+        cmos_opcode(wai),
+        cmos_opcode(inc_acc)
+    ]);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    assert_eq!(1, state.pc.get());
+
+    // A masked IRQ resumes execution without being serviced
+    state.set_irq(true);
+    state.execute_next().unwrap();
+    assert_eq!(2, state.pc.get());
+    assert_eq!(1, state.acc.get());
+}
+
+#[test]
+fn test_stp() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        cmos_opcode(stp)
+    ]);
+    state.set_nibble_at_addr(RESET_VECTOR, 0x8000);
+    state.execute_next().unwrap();
+    state.set_irq(true);
+    assert_eq!(Err(ExecutionError::Halted), state.execute_next());
+    assert_eq!(1, state.pc.get());
+
+    state.reset();
+    assert_eq!(0x8000, state.pc.get());
+}
+
+#[test]
+fn test_cmos_nops() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    state.set_up_state(&[
+        0x03,
+        0x02, 0x00,
+        0x5C, 0x00, 0x00,
+    ]);
+
+    assert_eq!(1, state.execute_next().unwrap().cycles());
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(8, state.execute_next().unwrap().cycles());
+    assert_eq!(6, state.pc.get());
+}
@@ -7,11 +7,11 @@ use crate::computer_state::status_register::StatusRegister;
 fn test_cmp_im_equal() {
     let mut state = ComputerState::new();
     state.acc.set(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_im),
         100
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -20,24 +20,52 @@ fn test_cmp_im_equal() {
 fn test_cmp_im_less_than() {
     let mut state = ComputerState::new();
     state.acc.set(99);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_im),
         100
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::N));
 }
 
+#[test]
+fn test_cmp_im_negative_difference() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xF0);
+    state.set_up_state(&[
+        opcode_from_operation(cmp_im),
+        0x10
+    ]);
+    state.execute_next().unwrap();
+
+    // Negative flag is bit 7 of the difference, not whether the register is less than memory
+    assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_cmp_im_less_than_positive_difference() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x01);
+    state.sta = StatusRegister::Z | StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(cmp_im),
+        0xFF
+    ]);
+    state.execute_next().unwrap();
+
+    assert!(state.sta.is_empty());
+}
+
 #[test]
 fn test_cmp_im_greater_than() {
     let mut state = ComputerState::new();
     state.acc.set(101);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_im),
         100
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::C));
 }
@@ -46,12 +74,12 @@ fn test_cmp_im_greater_than() {
 fn test_cmp_zp() {
     let mut state = ComputerState::new();
     state.acc.set(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -61,12 +89,12 @@ fn test_cmp_zpx() {
     let mut state = ComputerState::new();
     state.acc.set(100);
     state.set_x(0x5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -75,12 +103,12 @@ fn test_cmp_zpx() {
 fn test_cmp_ab() {
     let mut state = ComputerState::new();
     state.acc.set(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_ab),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -90,12 +118,12 @@ fn test_cmp_abx() {
     let mut state = ComputerState::new();
     state.acc.set(100);
     state.set_x(0x5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_abx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -105,12 +133,12 @@ fn test_cmp_aby() {
     let mut state = ComputerState::new();
     state.acc.set(100);
     state.set_y(0x5);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_aby),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x15, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x15, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -120,13 +148,13 @@ fn test_cmp_inx() {
     let mut state = ComputerState::new();
     state.acc.set(100);
     state.set_x(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_inx),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x77, 0x1234);
-    state.mem.set_byte_at_addr(0x1234, 100);
-    state.execute_next();
+    state.set_nibble_at_addr(0x77, 0x1234);
+    state.set_byte_at_addr(0x1234, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -136,13 +164,13 @@ fn test_cmp_iny() {
     let mut state = ComputerState::new();
     state.acc.set(100);
     state.set_y(0x22);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cmp_iny),
         0x41
     ]);
-    state.mem.set_nibble_at_addr(0x41, 0x1234);
-    state.mem.set_byte_at_addr(0x1256, 100);
-    state.execute_next();
+    state.set_nibble_at_addr(0x41, 0x1234);
+    state.set_byte_at_addr(0x1256, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -151,11 +179,11 @@ fn test_cmp_iny() {
 fn test_cpx_im() {
     let mut state = ComputerState::new();
     state.set_x(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpx_im),
         100
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -164,12 +192,12 @@ fn test_cpx_im() {
 fn test_cpx_zp() {
     let mut state = ComputerState::new();
     state.set_x(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpx_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -178,13 +206,13 @@ fn test_cpx_zp() {
 fn test_cpx_ab() {
     let mut state = ComputerState::new();
     state.set_x(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpx_ab),
         0x10,
         0x2A
     ]);
-    state.mem.set_byte_at_addr(0x2A10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x2A10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -193,11 +221,11 @@ fn test_cpx_ab() {
 fn test_cpy_im() {
     let mut state = ComputerState::new();
     state.set_y(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpy_im),
         100
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -206,12 +234,12 @@ fn test_cpy_im() {
 fn test_cpy_zp() {
     let mut state = ComputerState::new();
     state.set_y(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpy_zp),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
@@ -220,13 +248,13 @@ fn test_cpy_zp() {
 fn test_cpy_ab() {
     let mut state = ComputerState::new();
     state.set_y(100);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(cpy_ab),
         0x10,
         0x2A
     ]);
-    state.mem.set_byte_at_addr(0x2A10, 100);
-    state.execute_next();
+    state.set_byte_at_addr(0x2A10, 100);
+    state.execute_next().unwrap();
 
     assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
 }
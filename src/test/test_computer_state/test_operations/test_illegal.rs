@@ -0,0 +1,405 @@
+use crate::computer_state::ComputerState;
+use crate::computer_state::execution::ExecutionError;
+use crate::computer_state::operations::bitwise::{asl_abx, lsr_abx};
+use crate::computer_state::operations::illegal::*;
+use crate::computer_state::operations::opcode_from_operation;
+use crate::computer_state::status_register::StatusRegister;
+
+#[test]
+fn test_shift_opcodes() {
+    assert_eq!(0x1E, opcode_from_operation(asl_abx));
+    assert_eq!(0x5E, opcode_from_operation(lsr_abx));
+}
+
+#[test]
+fn test_slo_zp() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x01);
+    state.set_up_state(&[
+        opcode_from_operation(slo_zp),
+        0x20
+    ]);
+    state.set_byte_at_addr(0x20, 0xC0);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+    assert_eq!(0x80, state.fetch_byte_from_addr(0x20));
+    assert_eq!(0x81, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_rla_ab() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x0F);
+    state.sta |= StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(rla_ab),
+        0x34,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1234, 0x05);
+
+    assert_eq!(6, state.execute_next().unwrap().cycles());
+    assert_eq!(0x0B, state.fetch_byte_from_addr(0x1234));
+    assert_eq!(0x0B, state.acc.get());
+    assert!(state.sta.is_empty());
+}
+
+#[test]
+fn test_sre_zpx() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x03);
+    state.set_x(0x02);
+    state.set_up_state(&[
+        opcode_from_operation(sre_zpx),
+        0x20
+    ]);
+    state.set_byte_at_addr(0x22, 0x07);
+
+    assert_eq!(6, state.execute_next().unwrap().cycles());
+    assert_eq!(0x03, state.fetch_byte_from_addr(0x22));
+    assert_eq!(0x00, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
+}
+
+#[test]
+fn test_rra_abx() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x10);
+    state.set_x(0x01);
+    state.set_up_state(&[
+        opcode_from_operation(rra_abx),
+        0x33,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1234, 0x03);
+
+    // No page crossing penalty for read-modify-write operations
+    assert_eq!(7, state.execute_next().unwrap().cycles());
+    assert_eq!(0x01, state.fetch_byte_from_addr(0x1234));
+    // The carry out of the rotation is added
+    assert_eq!(0x12, state.acc.get());
+    assert!(state.sta.is_empty());
+}
+
+#[test]
+fn test_dcp_inx() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x40);
+    state.set_x(0x04);
+    state.set_up_state(&[
+        opcode_from_operation(dcp_inx),
+        0x20
+    ]);
+    state.set_nibble_at_addr(0x24, 0x1234);
+    state.set_byte_at_addr(0x1234, 0x41);
+
+    assert_eq!(8, state.execute_next().unwrap().cycles());
+    assert_eq!(0x40, state.fetch_byte_from_addr(0x1234));
+    assert!(state.sta.contains_only(StatusRegister::Z | StatusRegister::C));
+}
+
+#[test]
+fn test_isc_iny() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x40);
+    state.set_y(0x04);
+    state.sta |= StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(isc_iny),
+        0x20
+    ]);
+    state.set_nibble_at_addr(0x20, 0x1230);
+    state.set_byte_at_addr(0x1234, 0x0F);
+
+    assert_eq!(8, state.execute_next().unwrap().cycles());
+    assert_eq!(0x10, state.fetch_byte_from_addr(0x1234));
+    assert_eq!(0x30, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
+#[test]
+fn test_lax_aby_page_crossed() {
+    let mut state = ComputerState::new();
+    state.set_y(0x10);
+    state.set_up_state(&[
+        opcode_from_operation(lax_aby),
+        0xF8,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1308, 0x85);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+    assert_eq!(0x85, state.acc.get());
+    assert_eq!(0x85, state.get_x());
+    assert!(state.sta.contains_only(StatusRegister::N));
+}
+
+#[test]
+fn test_sax_zp() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xF0);
+    state.set_x(0x3C);
+    state.set_up_state(&[
+        opcode_from_operation(sax_zp),
+        0x20
+    ]);
+
+    assert_eq!(3, state.execute_next().unwrap().cycles());
+    assert_eq!(0x30, state.fetch_byte_from_addr(0x20));
+    assert!(state.sta.is_empty());
+}
+
+#[test]
+fn test_anc_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xF0);
+    state.set_up_state(&[
+        opcode_from_operation(anc_im),
+        0x80
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(0x80, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_alr_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xFF);
+    state.set_up_state(&[
+        opcode_from_operation(alr_im),
+        0x03
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(0x01, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
+#[test]
+fn test_arr_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xFF);
+    state.sta |= StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(arr_im),
+        0xC0
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(0xE0, state.acc.get());
+    // Carry from bit 6, overflow from bit 6 xor bit 5
+    assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_arr_im_overflow() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xFF);
+    state.set_up_state(&[
+        opcode_from_operation(arr_im),
+        0x80
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x40, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::V | StatusRegister::C));
+}
+
+#[test]
+fn test_arr_im_decimal() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xFF);
+    state.sta |= StatusRegister::D;
+    state.set_up_state(&[
+        opcode_from_operation(arr_im),
+        0x66
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x99, state.acc.get());
+    // Overflow from bit 6 changing during the rotation
+    assert!(state.sta.contains_only(StatusRegister::D | StatusRegister::V | StatusRegister::C));
+}
+
+#[test]
+fn test_sbx_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x0F);
+    state.set_x(0xFC);
+    state.set_up_state(&[
+        opcode_from_operation(sbx_im),
+        0x02
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(0x0A, state.get_x());
+    assert_eq!(0x0F, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
+#[test]
+fn test_usbc_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x50);
+    state.sta |= StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(usbc_im),
+        0x20
+    ]);
+
+    assert_eq!(0xEB, opcode_from_operation(usbc_im));
+    state.execute_next().unwrap();
+    assert_eq!(0x30, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
+#[test]
+fn test_las_aby() {
+    let mut state = ComputerState::new();
+    state.set_stk(0xF3);
+    state.set_up_state(&[
+        opcode_from_operation(las_aby),
+        0x34,
+        0x12
+    ]);
+    state.set_byte_at_addr(0x1234, 0x3F);
+
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    assert_eq!(0x33, state.acc.get());
+    assert_eq!(0x33, state.get_x());
+    assert_eq!(0x33, state.get_stk());
+}
+
+#[test]
+fn test_ane_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x00);
+    state.set_x(0xFF);
+    state.set_up_state(&[
+        opcode_from_operation(ane_im),
+        0xFF
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0xEE, state.acc.get());
+    assert!(state.sta.contains_only(StatusRegister::N));
+}
+
+#[test]
+fn test_lxa_im() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x01);
+    state.set_up_state(&[
+        opcode_from_operation(lxa_im),
+        0x0F
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x0F, state.acc.get());
+    assert_eq!(0x0F, state.get_x());
+}
+
+#[test]
+fn test_sha_aby() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xFF);
+    state.set_x(0xF3);
+    state.set_y(0x01);
+    state.set_up_state(&[
+        opcode_from_operation(sha_aby),
+        0x33,
+        0x12
+    ]);
+
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+    assert_eq!(0x13, state.fetch_byte_from_addr(0x1234));
+}
+
+#[test]
+fn test_shx_aby_page_crossed() {
+    let mut state = ComputerState::new();
+    state.set_x(0x05);
+    state.set_y(0x10);
+    state.set_up_state(&[
+        opcode_from_operation(shx_aby),
+        0xF8,
+        0x12
+    ]);
+
+    state.execute_next().unwrap();
+    // The high byte of the address is replaced by the stored value
+    assert_eq!(0x01, state.fetch_byte_from_addr(0x0108));
+    assert_eq!(0x00, state.fetch_byte_from_addr(0x1308));
+}
+
+#[test]
+fn test_shy_abx() {
+    let mut state = ComputerState::new();
+    state.set_x(0x01);
+    state.set_y(0xFF);
+    state.set_up_state(&[
+        opcode_from_operation(shy_abx),
+        0x33,
+        0x12
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x13, state.fetch_byte_from_addr(0x1234));
+}
+
+#[test]
+fn test_tas_aby() {
+    let mut state = ComputerState::new();
+    state.acc.set(0xF0);
+    state.set_x(0x3F);
+    state.set_y(0x01);
+    state.set_up_state(&[
+        opcode_from_operation(tas_aby),
+        0x33,
+        0x12
+    ]);
+
+    state.execute_next().unwrap();
+    assert_eq!(0x30, state.get_stk());
+    assert_eq!(0x10, state.fetch_byte_from_addr(0x1234));
+}
+
+#[test]
+fn test_nops() {
+    let mut state = ComputerState::new();
+    state.set_x(0x10);
+    state.set_up_state(&[
+        0x1A,
+        0x80, 0x12,
+        0x04, 0x12,
+        0x14, 0x12,
+        0x0C, 0x34, 0x12,
+        0x1C, 0xF8, 0x12,
+    ]);
+
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(2, state.execute_next().unwrap().cycles());
+    assert_eq!(3, state.execute_next().unwrap().cycles());
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    assert_eq!(4, state.execute_next().unwrap().cycles());
+    // Page crossing penalty
+    assert_eq!(5, state.execute_next().unwrap().cycles());
+    assert_eq!(13, state.pc.get());
+    assert_eq!(0, state.acc.get());
+    assert!(state.sta.is_empty());
+}
+
+#[test]
+fn test_jam() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(jam)
+    ]);
+
+    assert_eq!(Err(ExecutionError::Jammed { opcode: 0x02, addr: 0 }), state.execute_next());
+    assert_eq!(Err(ExecutionError::Halted), state.execute_next());
+    assert_eq!(0, state.pc.get());
+}
@@ -6,60 +6,78 @@ use crate::computer_state::operations::opcode_from_operation;
 #[test]
 fn test_jmp_ab() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(jmp_ab),
         0x34,
         0x12
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x1234, state.mem.pc.get());
+    assert_eq!(0x1234, state.pc.get());
 }
 
 #[test]
 fn test_jmp_in() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(jmp_in),
         0x34,
         0x12
     ]);
-    state.mem.set_nibble_at_addr(0x1234, 0xABF6);
-    state.execute_next();
+    state.set_nibble_at_addr(0x1234, 0xABF6);
+    state.execute_next().unwrap();
 
-    assert_eq!(0xABF6, state.mem.pc.get());
+    assert_eq!(0xABF6, state.pc.get());
 }
 
 #[test]
 fn test_jsr() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(jsr),
         0x34,
         0x12
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x1234, state.mem.pc.get());
-    assert_eq!(3, state.mem.pop_nibble_from_stack());
+    assert_eq!(0x1234, state.pc.get());
+    // The return address is the last byte of the JSR, pushed high byte first
+    assert_eq!(0xFD, state.get_stk());
+    assert_eq!(0x00, state.fetch_byte_from_addr(0x01FF));
+    assert_eq!(0x02, state.fetch_byte_from_addr(0x01FE));
+    assert_eq!(2, state.pop_nibble_from_stack());
 }
 
 #[test]
 fn test_rts() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(jsr),
         0x07,
         0x06,
     ]);
-    state.mem.set_byte_at_addr(0x0607, opcode_from_operation(lda_im));
-    state.mem.set_byte_at_addr(0x0608, 0x99);
-    state.mem.set_byte_at_addr(0x0609, opcode_from_operation(rts));
+    state.set_byte_at_addr(0x0607, opcode_from_operation(lda_im));
+    state.set_byte_at_addr(0x0608, 0x99);
+    state.set_byte_at_addr(0x0609, opcode_from_operation(rts));
 
     for _ in 1..4 {
-        state.execute_next()
+        state.execute_next().unwrap();
     }
 
     assert_eq!(0x99, state.acc.get());
-    assert_eq!(3, state.mem.pc.get());
+    assert_eq!(3, state.pc.get());
+    assert_eq!(0xFF, state.get_stk());
+}
+
+#[test]
+fn test_rts_pushed_address() {
+    let mut state = ComputerState::new();
+    // Push the address minus one of the target, then return to it
+    state.set_up_state(&[
+        opcode_from_operation(rts)
+    ]);
+    state.push_nibble_on_stack(0x1233);
+    state.execute_next().unwrap();
+
+    assert_eq!(0x1234, state.pc.get());
 }
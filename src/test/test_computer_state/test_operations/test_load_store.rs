@@ -6,24 +6,38 @@ use crate::computer_state::status_register::StatusRegister;
 #[test]
 fn test_lda_im() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
        opcode_from_operation(lda_im),
        36
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(36, state.acc.get());
     assert!(state.sta.is_empty())
 }
 
+#[test]
+fn test_lda_im_replaces_flags() {
+    let mut state = ComputerState::new();
+    state.sta = StatusRegister::Z | StatusRegister::N | StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        36
+    ]);
+    state.execute_next().unwrap();
+
+    // Zero and negative are cleared, while carry is left unchanged
+    assert!(state.sta.contains_only(StatusRegister::C));
+}
+
 #[test]
 fn test_lda_im_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_im),
         0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -32,11 +46,11 @@ fn test_lda_im_zero_flag() {
 #[test]
 fn test_lda_im_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_im),
         128
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(128, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N));
@@ -45,12 +59,12 @@ fn test_lda_im_negative_flag() {
 #[test]
 fn test_lda_zp() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 78);
-    state.execute_next();
+    state.set_byte_at_addr(123, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.acc.get());
     assert!(state.sta.is_empty())
@@ -59,12 +73,12 @@ fn test_lda_zp() {
 #[test]
 fn test_lda_zp_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 0);
-    state.execute_next();
+    state.set_byte_at_addr(123, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -73,12 +87,12 @@ fn test_lda_zp_zero_flag() {
 #[test]
 fn test_lda_zp_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 150);
-    state.execute_next();
+    state.set_byte_at_addr(123, 150);
+    state.execute_next().unwrap();
 
     assert_eq!(150, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -88,12 +102,12 @@ fn test_lda_zp_negative_flag() {
 fn test_lda_zpx() {
     let mut state = ComputerState::new();
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.acc.get());
     assert!(state.sta.is_empty())
@@ -103,12 +117,12 @@ fn test_lda_zpx() {
 fn test_lda_zpx_wrap() {
     let mut state = ComputerState::new();
     state.set_x(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zpx),
         0xFF
     ]);
-    state.mem.set_byte_at_addr(0x7F, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x7F, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.acc.get());
     assert!(state.sta.is_empty())
@@ -118,12 +132,12 @@ fn test_lda_zpx_wrap() {
 fn test_lda_zpx_zero_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zpx),
         0x15
     ]);
-    state.mem.set_byte_at_addr(0x25, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -133,12 +147,12 @@ fn test_lda_zpx_zero_flag() {
 fn test_lda_zpx_negative_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_zpx),
         0x15
     ]);
-    state.mem.set_byte_at_addr(0x25, 0xF5);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0xF5);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF5, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -147,13 +161,13 @@ fn test_lda_zpx_negative_flag() {
 #[test]
 fn test_lda_ab() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.acc.get());
     assert!(state.sta.is_empty())
@@ -162,13 +176,13 @@ fn test_lda_ab() {
 #[test]
 fn test_lda_ab_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -177,13 +191,13 @@ fn test_lda_ab_zero_flag() {
 #[test]
 fn test_lda_ab_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -193,13 +207,13 @@ fn test_lda_ab_negative_flag() {
 fn test_lda_abx() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.acc.get());
     assert!(state.sta.is_empty())
@@ -209,13 +223,13 @@ fn test_lda_abx() {
 fn test_lda_abx_zero_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -225,13 +239,13 @@ fn test_lda_abx_zero_flag() {
 fn test_lda_abx_negative_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -241,13 +255,13 @@ fn test_lda_abx_negative_flag() {
 fn test_lda_aby() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.acc.get());
     assert!(state.sta.is_empty())
@@ -257,13 +271,13 @@ fn test_lda_aby() {
 fn test_lda_aby_zero_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -273,13 +287,13 @@ fn test_lda_aby_zero_flag() {
 fn test_lda_aby_negative_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -289,15 +303,15 @@ fn test_lda_aby_negative_flag() {
 fn test_lda_inx() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_inx),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x59, 0x4D3C);
+    state.set_nibble_at_addr(0x59, 0x4D3C);
     // Value
-    state.mem.set_byte_at_addr(0x4D3C, 0x33);
-    state.execute_next();
+    state.set_byte_at_addr(0x4D3C, 0x33);
+    state.execute_next().unwrap();
 
     assert_eq!(0x33, state.acc.get());
     assert!(state.sta.is_empty())
@@ -307,15 +321,15 @@ fn test_lda_inx() {
 fn test_lda_inx_wrap() {
     let mut state = ComputerState::new();
     state.set_x(0x6B);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_inx),
         0xFF
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x6A, 0x4D3C);
+    state.set_nibble_at_addr(0x6A, 0x4D3C);
     // Value
-    state.mem.set_byte_at_addr(0x4D3C, 0x33);
-    state.execute_next();
+    state.set_byte_at_addr(0x4D3C, 0x33);
+    state.execute_next().unwrap();
 
     assert_eq!(0x33, state.acc.get());
     assert!(state.sta.is_empty())
@@ -325,15 +339,15 @@ fn test_lda_inx_wrap() {
 fn test_lda_inx_zero_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_inx),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x59, 0x4D3C);
+    state.set_nibble_at_addr(0x59, 0x4D3C);
     // Value
-    state.mem.set_byte_at_addr(0x4D3C, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x4D3C, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -343,15 +357,15 @@ fn test_lda_inx_zero_flag() {
 fn test_lda_inx_negative_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_inx),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x59, 0x4D3C);
+    state.set_nibble_at_addr(0x59, 0x4D3C);
     // Value
-    state.mem.set_byte_at_addr(0x4D3C, 0xE3);
-    state.execute_next();
+    state.set_byte_at_addr(0x4D3C, 0xE3);
+    state.execute_next().unwrap();
 
     assert_eq!(0xE3, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -361,15 +375,15 @@ fn test_lda_inx_negative_flag() {
 fn test_lda_iny() {
     let mut state = ComputerState::new();
     state.set_y(0x1A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_iny),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x55, 0x3412);
+    state.set_nibble_at_addr(0x55, 0x3412);
     // Value
-    state.mem.set_byte_at_addr(0x342C, 0x33);
-    state.execute_next();
+    state.set_byte_at_addr(0x342C, 0x33);
+    state.execute_next().unwrap();
 
     assert_eq!(0x33, state.acc.get());
     assert!(state.sta.is_empty())
@@ -379,15 +393,15 @@ fn test_lda_iny() {
 fn test_lda_iny_zero_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x1A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_iny),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x55, 0x3412);
+    state.set_nibble_at_addr(0x55, 0x3412);
     // Value
-    state.mem.set_byte_at_addr(0x342C, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x342C, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains(StatusRegister::Z))
@@ -397,15 +411,15 @@ fn test_lda_iny_zero_flag() {
 fn test_lda_iny_negative_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x1A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(lda_iny),
         0x55
     ]);
     // Pointer
-    state.mem.set_nibble_at_addr(0x55, 0x3412);
+    state.set_nibble_at_addr(0x55, 0x3412);
     // Value
-    state.mem.set_byte_at_addr(0x342C, 0xF3);
-    state.execute_next();
+    state.set_byte_at_addr(0x342C, 0xF3);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF3, state.acc.get());
     assert!(state.sta.contains(StatusRegister::N))
@@ -414,11 +428,11 @@ fn test_lda_iny_negative_flag() {
 #[test]
 fn test_ldx_imm() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_im),
         36
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(36, state.get_x());
     assert!(state.sta.is_empty());
@@ -427,11 +441,11 @@ fn test_ldx_imm() {
 #[test]
 fn test_ldx_imm_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_im),
         0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -440,11 +454,11 @@ fn test_ldx_imm_zero_flag() {
 #[test]
 fn test_ldx_imm_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_im),
         0xFF
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_x());
     assert!(state.sta.contains(StatusRegister::N));
@@ -453,12 +467,12 @@ fn test_ldx_imm_negative_flag() {
 #[test]
 fn test_ldx_zp() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 78);
-    state.execute_next();
+    state.set_byte_at_addr(123, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_x());
     assert!(state.sta.is_empty());
@@ -467,12 +481,12 @@ fn test_ldx_zp() {
 #[test]
 fn test_ldx_zp_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 0);
-    state.execute_next();
+    state.set_byte_at_addr(123, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -481,12 +495,12 @@ fn test_ldx_zp_zero_flag() {
 #[test]
 fn test_ldx_zp_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 150);
-    state.execute_next();
+    state.set_byte_at_addr(123, 150);
+    state.execute_next().unwrap();
 
     assert_eq!(150, state.get_x());
     assert!(state.sta.contains(StatusRegister::N));
@@ -496,12 +510,12 @@ fn test_ldx_zp_negative_flag() {
 fn test_ldx_zpy() {
     let mut state = ComputerState::new();
     state.set_y(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zpy),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_x());
     assert!(state.sta.is_empty());
@@ -511,12 +525,12 @@ fn test_ldx_zpy() {
 fn test_ldx_zpy_wrap() {
     let mut state = ComputerState::new();
     state.set_y(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zpy),
         0xFF
     ]);
-    state.mem.set_byte_at_addr(0x7F, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x7F, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_x());
     assert!(state.sta.is_empty());
@@ -526,12 +540,12 @@ fn test_ldx_zpy_wrap() {
 fn test_ldx_zpy_zero_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zpy),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -541,12 +555,12 @@ fn test_ldx_zpy_zero_flag() {
 fn test_ldx_zpy_negative_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_zpy),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 0xF5);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0xF5);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF5, state.get_x());
     assert!(state.sta.contains(StatusRegister::N));
@@ -555,13 +569,13 @@ fn test_ldx_zpy_negative_flag() {
 #[test]
 fn test_ldx_ab() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.get_x());
     assert!(state.sta.is_empty())
@@ -570,13 +584,13 @@ fn test_ldx_ab() {
 #[test]
 fn test_ldx_ab_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -585,13 +599,13 @@ fn test_ldx_ab_zero_flag() {
 #[test]
 fn test_ldx_ab_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.get_x());
     assert!(state.sta.contains(StatusRegister::N));
@@ -601,13 +615,13 @@ fn test_ldx_ab_negative_flag() {
 fn test_ldx_aby() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.get_x());
     assert!(state.sta.is_empty())
@@ -617,13 +631,13 @@ fn test_ldx_aby() {
 fn test_ldx_aby_zero_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_x());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -633,13 +647,13 @@ fn test_ldx_aby_zero_flag() {
 fn test_ldx_aby_negative_flag() {
     let mut state = ComputerState::new();
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldx_aby),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.get_x());
     assert!(state.sta.contains(StatusRegister::N));
@@ -648,11 +662,11 @@ fn test_ldx_aby_negative_flag() {
 #[test]
 fn test_ldy_im() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_im),
         36
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(36, state.get_y());
     assert!(state.sta.is_empty())
@@ -661,11 +675,11 @@ fn test_ldy_im() {
 #[test]
 fn test_ldy_im_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_im),
         0
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -674,11 +688,11 @@ fn test_ldy_im_zero_flag() {
 #[test]
 fn test_ldy_im_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_im),
         0xFF
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_y());
     assert!(state.sta.contains(StatusRegister::N));
@@ -687,12 +701,12 @@ fn test_ldy_im_negative_flag() {
 #[test]
 fn test_ldy_zp() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 78);
-    state.execute_next();
+    state.set_byte_at_addr(123, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_y());
     assert!(state.sta.is_empty())
@@ -701,12 +715,12 @@ fn test_ldy_zp() {
 #[test]
 fn test_ldy_zp_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 0);
-    state.execute_next();
+    state.set_byte_at_addr(123, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -715,12 +729,12 @@ fn test_ldy_zp_zero_flag() {
 #[test]
 fn test_ldy_zp_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zp),
         123
     ]);
-    state.mem.set_byte_at_addr(123, 0xFF);
-    state.execute_next();
+    state.set_byte_at_addr(123, 0xFF);
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_y());
     assert!(state.sta.contains(StatusRegister::N));
@@ -730,12 +744,12 @@ fn test_ldy_zp_negative_flag() {
 fn test_ldy_zpx() {
     let mut state = ComputerState::new();
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_y());
     assert!(state.sta.is_empty())
@@ -745,12 +759,12 @@ fn test_ldy_zpx() {
 fn test_ldy_zpx_wrap() {
     let mut state = ComputerState::new();
     state.set_x(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zpx),
         0xFF
     ]);
-    state.mem.set_byte_at_addr(0x7F, 78);
-    state.execute_next();
+    state.set_byte_at_addr(0x7F, 78);
+    state.execute_next().unwrap();
 
     assert_eq!(78, state.get_y());
     assert!(state.sta.is_empty())
@@ -760,12 +774,12 @@ fn test_ldy_zpx_wrap() {
 fn test_ldy_zpx_zero_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -775,12 +789,12 @@ fn test_ldy_zpx_zero_flag() {
 fn test_ldy_zpx_negative_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_zpx),
         0x10
     ]);
-    state.mem.set_byte_at_addr(0x25, 0xFF);
-    state.execute_next();
+    state.set_byte_at_addr(0x25, 0xFF);
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.get_y());
     assert!(state.sta.contains(StatusRegister::N));
@@ -789,13 +803,13 @@ fn test_ldy_zpx_negative_flag() {
 #[test]
 fn test_ldy_ab() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.get_y());
     assert!(state.sta.is_empty())
@@ -804,13 +818,13 @@ fn test_ldy_ab() {
 #[test]
 fn test_ldy_ab_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -819,13 +833,13 @@ fn test_ldy_ab_zero_flag() {
 #[test]
 fn test_ldy_ab_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_ab),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6655, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6655, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.get_y());
     assert!(state.sta.contains(StatusRegister::N));
@@ -835,13 +849,13 @@ fn test_ldy_ab_negative_flag() {
 fn test_ldy_abx() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0x10);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0x10);
+    state.execute_next().unwrap();
 
     assert_eq!(0x10, state.get_y());
     assert!(state.sta.is_empty())
@@ -851,13 +865,13 @@ fn test_ldy_abx() {
 fn test_ldy_abx_zero_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.get_y());
     assert!(state.sta.contains(StatusRegister::Z));
@@ -867,13 +881,13 @@ fn test_ldy_abx_zero_flag() {
 fn test_ldy_abx_negative_flag() {
     let mut state = ComputerState::new();
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(ldy_abx),
         0x55,
         0x66
     ]);
-    state.mem.set_byte_at_addr(0x6659, 0xF6);
-    state.execute_next();
+    state.set_byte_at_addr(0x6659, 0xF6);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF6, state.get_y());
     assert!(state.sta.contains(StatusRegister::N));
@@ -884,13 +898,13 @@ fn test_ldy_abx_negative_flag() {
 fn test_sta_zp() {
     let mut state = ComputerState::new();
     state.acc.set(0x9D);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_zp),
         0xE5
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x9D, state.mem.fetch_byte_from_addr(0xE5));
+    assert_eq!(0x9D, state.fetch_byte_from_addr(0xE5));
     assert!(state.sta.is_empty());
 }
 
@@ -906,13 +920,13 @@ fn test_sta_zp_flags_unaffected() {
                  StatusRegister::B  |
                  StatusRegister::D  |
                  StatusRegister::I;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_zp),
         0xE5
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x9D, state.mem.fetch_byte_from_addr(0xE5));
+    assert_eq!(0x9D, state.fetch_byte_from_addr(0xE5));
     assert!(state.sta.contains_only(
         StatusRegister::V |
             StatusRegister::Z  |
@@ -929,13 +943,13 @@ fn test_sta_zpx() {
     let mut state = ComputerState::new();
     state.acc.set(0x4F);
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_zpx),
         0x10
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x4F, state.mem.fetch_byte_from_addr(0x25));
+    assert_eq!(0x4F, state.fetch_byte_from_addr(0x25));
     assert!(state.sta.is_empty());
 }
 
@@ -943,14 +957,14 @@ fn test_sta_zpx() {
 fn test_sta_ab() {
     let mut state = ComputerState::new();
     state.acc.set(0x10);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_ab),
         0x55,
         0x66
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x10, state.mem.fetch_byte_from_addr(0x6655));
+    assert_eq!(0x10, state.fetch_byte_from_addr(0x6655));
     assert!(state.sta.is_empty());
 }
 
@@ -959,14 +973,14 @@ fn test_sta_abx() {
     let mut state = ComputerState::new();
     state.acc.set(0x10);
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_abx),
         0x55,
         0x66
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x10, state.mem.fetch_byte_from_addr(0x6659));
+    assert_eq!(0x10, state.fetch_byte_from_addr(0x6659));
     assert!(state.sta.is_empty());
 }
 
@@ -975,14 +989,14 @@ fn test_sta_aby() {
     let mut state = ComputerState::new();
     state.acc.set(0x10);
     state.set_y(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_aby),
         0x55,
         0x66
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x10, state.mem.fetch_byte_from_addr(0x6659));
+    assert_eq!(0x10, state.fetch_byte_from_addr(0x6659));
     assert!(state.sta.is_empty());
 }
 
@@ -991,14 +1005,14 @@ fn test_sta_inx() {
     let mut state = ComputerState::new();
     state.acc.set(0x33);
     state.set_x(0x4);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_inx),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x59, 0x4D3C);
-    state.execute_next();
+    state.set_nibble_at_addr(0x59, 0x4D3C);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x33, state.mem.fetch_byte_from_addr(0x4D3C));
+    assert_eq!(0x33, state.fetch_byte_from_addr(0x4D3C));
     assert!(state.sta.is_empty());
 }
 
@@ -1007,14 +1021,14 @@ fn test_sta_inx_wrap() {
     let mut state = ComputerState::new();
     state.acc.set(0x33);
     state.set_x(0x6B);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_inx),
         0xFF
     ]);
-    state.mem.set_nibble_at_addr(0x6A, 0x4D3C);
-    state.execute_next();
+    state.set_nibble_at_addr(0x6A, 0x4D3C);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x33, state.mem.fetch_byte_from_addr(0x4D3C));
+    assert_eq!(0x33, state.fetch_byte_from_addr(0x4D3C));
     assert!(state.sta.is_empty());
 }
 
@@ -1023,14 +1037,14 @@ fn test_sta_iny() {
     let mut state = ComputerState::new();
     state.acc.set(0x33);
     state.set_y(0x1A);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sta_iny),
         0x55
     ]);
-    state.mem.set_nibble_at_addr(0x55, 0x3412);
-    state.execute_next();
+    state.set_nibble_at_addr(0x55, 0x3412);
+    state.execute_next().unwrap();
 
-    assert_eq!(0x33, state.mem.fetch_byte_from_addr(0x342C));
+    assert_eq!(0x33, state.fetch_byte_from_addr(0x342C));
     assert!(state.sta.is_empty());
 }
 
@@ -1038,13 +1052,13 @@ fn test_sta_iny() {
 fn test_stx_zp() {
     let mut state = ComputerState::new();
     state.set_x(0x78);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(stx_zp),
         123
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(123));
+    assert_eq!(0x78, state.fetch_byte_from_addr(123));
     assert!(state.sta.is_empty());
 }
 
@@ -1053,13 +1067,13 @@ fn test_stx_zpy() {
     let mut state = ComputerState::new();
     state.set_x(0x78);
     state.set_y(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(stx_zpy),
         0x10
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x25));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x25));
     assert!(state.sta.is_empty());
 }
 
@@ -1068,13 +1082,13 @@ fn test_stx_zpy_wrap() {
     let mut state = ComputerState::new();
     state.set_x(0x78);
     state.set_y(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(stx_zpy),
         0xFF
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x7F));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x7F));
     assert!(state.sta.is_empty());
 }
 
@@ -1082,14 +1096,14 @@ fn test_stx_zpy_wrap() {
 fn test_stx_ab() {
     let mut state = ComputerState::new();
     state.set_x(0x78);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(stx_ab),
         0x55,
         0x66
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x6655));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x6655));
     assert!(state.sta.is_empty());
 }
 
@@ -1097,13 +1111,13 @@ fn test_stx_ab() {
 fn test_sty_zp() {
     let mut state = ComputerState::new();
     state.set_y(0x78);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sty_zp),
         123
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(123));
+    assert_eq!(0x78, state.fetch_byte_from_addr(123));
     assert!(state.sta.is_empty());
 }
 
@@ -1112,13 +1126,13 @@ fn test_sty_zpx() {
     let mut state = ComputerState::new();
     state.set_y(0x78);
     state.set_x(0x15);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sty_zpx),
         0x10
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x25));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x25));
     assert!(state.sta.is_empty());
 }
 
@@ -1127,13 +1141,13 @@ fn test_sty_zpx_wrap() {
     let mut state = ComputerState::new();
     state.set_y(0x78);
     state.set_x(0x80);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sty_zpx),
         0xFF
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x7F));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x7F));
     assert!(state.sta.is_empty());
 }
 
@@ -1141,13 +1155,13 @@ fn test_sty_zpx_wrap() {
 fn test_sty_ab() {
     let mut state = ComputerState::new();
     state.set_y(0x78);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(sty_ab),
         0x55,
         0x66
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
-    assert_eq!(0x78, state.mem.fetch_byte_from_addr(0x6655));
+    assert_eq!(0x78, state.fetch_byte_from_addr(0x6655));
     assert!(state.sta.is_empty());
 }
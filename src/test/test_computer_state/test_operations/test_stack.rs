@@ -8,13 +8,13 @@ use crate::computer_state::status_register::StatusRegister;
 fn test_pha() {
     let mut state = ComputerState::new();
     state.acc.set(0x13);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(pha)
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0x13, state.acc.get());
-    assert_eq!(0x13, state.mem.pop_from_stack());
+    assert_eq!(0x13, state.pop_from_stack());
     assert!(state.sta.is_empty());
 }
 
@@ -22,23 +22,23 @@ fn test_pha() {
 fn test_php() {
     let mut state = ComputerState::new();
     state.sta = StatusRegister::from_byte(0xFF);
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(php)
     ]);
-    state.execute_next();
+    state.execute_next().unwrap();
 
     assert_eq!(0xFF, state.sta.as_byte());
-    assert_eq!(0xFF,state.mem.pop_from_stack());
+    assert_eq!(0xFF,state.pop_from_stack());
 }
 
 #[test]
 fn test_pla() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(pla)
     ]);
-    state.mem.push_on_stack(0x05);
-    state.execute_next();
+    state.push_on_stack(0x05);
+    state.execute_next().unwrap();
 
     assert_eq!(0x05, state.acc.get());
     assert!(state.sta.is_empty());
@@ -47,11 +47,11 @@ fn test_pla() {
 #[test]
 fn test_pla_zero_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(pla)
     ]);
-    state.mem.push_on_stack(0);
-    state.execute_next();
+    state.push_on_stack(0);
+    state.execute_next().unwrap();
 
     assert_eq!(0, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::Z));
@@ -60,11 +60,11 @@ fn test_pla_zero_flag() {
 #[test]
 fn test_pla_negative_flag() {
     let mut state = ComputerState::new();
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(pla)
     ]);
-    state.mem.push_on_stack(0xF5);
-    state.execute_next();
+    state.push_on_stack(0xF5);
+    state.execute_next().unwrap();
 
     assert_eq!(0xF5, state.acc.get());
     assert!(state.sta.contains_only(StatusRegister::N));
@@ -81,7 +81,7 @@ fn test_plp() {
                  StatusRegister::B |
                  StatusRegister::D |
                  StatusRegister::I;
-    state.set_up_state(&vec![
+    state.set_up_state(&[
         opcode_from_operation(php),
         opcode_from_operation(clc),
         opcode_from_operation(cld),
@@ -90,16 +90,62 @@ fn test_plp() {
         opcode_from_operation(plp),
     ]);
     for _ in 0..6 {
-        state.execute_next();
+        state.execute_next().unwrap();
     }
 
+    // The break flag only exists on the stack, so it is not pulled back into the register
     assert!(state.sta.contains_only(
         StatusRegister::V |
         StatusRegister::Z |
         StatusRegister::N |
         StatusRegister::C |
-        StatusRegister::B |
         StatusRegister::D |
         StatusRegister::I
     ));
 }
+
+#[test]
+fn test_php_layout() {
+    let mut state = ComputerState::new();
+    state.sta = StatusRegister::N | StatusRegister::C;
+    state.set_up_state(&[
+        opcode_from_operation(php)
+    ]);
+    state.execute_next().unwrap();
+
+    // NV-BDIZC, with the break flag and unused bit set
+    assert_eq!(0b1011_0001, state.pop_from_stack());
+    assert!(state.sta.contains_only(StatusRegister::N | StatusRegister::C));
+}
+
+#[test]
+fn test_plp_ignores_break_and_unused() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(plp)
+    ]);
+    state.push_on_stack(0b0011_0010);
+    state.execute_next().unwrap();
+
+    assert!(state.sta.contains_only(StatusRegister::Z));
+}
+
+#[test]
+fn test_pha_stack_wraps() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x42);
+    state.set_stk(0x00);
+    state.set_up_state(&[
+        opcode_from_operation(pha),
+        opcode_from_operation(pla),
+    ]);
+    state.execute_next().unwrap();
+
+    // The stack pointer wraps around within page 1
+    assert_eq!(0x42, state.fetch_byte_from_addr(0x0100));
+    assert_eq!(0xFF, state.get_stk());
+
+    state.execute_next().unwrap();
+    assert_eq!(0x00, state.get_stk());
+    assert_eq!(0x42, state.acc.get());
+}
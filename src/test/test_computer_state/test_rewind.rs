@@ -0,0 +1,144 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::computer_state::bus::mapper::{Cartridge, UxRom, BANK_SIZE_16K};
+use crate::computer_state::execution::ExecutionError;
+use crate::computer_state::operations::illegal::jam;
+use crate::computer_state::operations::jumps_calls::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::opcode_from_operation;
+use crate::computer_state::status_register::StatusRegister;
+
+/// LDA #$42; STA $10; JSR $0200
+fn program() -> ComputerState {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x42,
+        opcode_from_operation(sta_zp),
+        0x10,
+        opcode_from_operation(jsr),
+        0x00,
+        0x02,
+    ]);
+    state.set_byte_at_addr(0x10, 0x07);
+    state
+}
+
+#[test]
+fn test_step_back() {
+    let mut state = program();
+    state.enable_rewind(10);
+    for _ in 0..3 {
+        state.execute_next().unwrap();
+    }
+    assert_eq!(0x0200, state.pc.get());
+
+    assert!(state.step_back());
+    assert_eq!(4, state.pc.get());
+    assert_eq!(0xFF, state.get_stk());
+    assert_eq!(0x00, state.peek_byte_from_addr(0x01FF));
+    assert_eq!(5, state.cycles);
+
+    assert!(state.step_back());
+    assert_eq!(0x07, state.peek_byte_from_addr(0x10));
+    assert!(state.step_back());
+    assert_eq!(0, state.pc.get());
+    assert_eq!(0, state.acc.get());
+    assert!(!state.sta.contains(StatusRegister::Z));
+    assert!(!state.step_back());
+}
+
+#[test]
+fn test_step_back_disabled() {
+    let mut state = program();
+    state.execute_next().unwrap();
+
+    assert!(!state.step_back());
+    assert_eq!(2, state.pc.get());
+}
+
+#[test]
+fn test_rewind_capacity() {
+    let mut state = program();
+    state.enable_rewind(2);
+    for _ in 0..3 {
+        state.execute_next().unwrap();
+    }
+
+    assert_eq!(2, state.rewind_history().unwrap().len());
+    assert!(state.step_back());
+    assert!(state.step_back());
+    assert!(!state.step_back());
+    // The first step fell out of the history
+    assert_eq!(2, state.pc.get());
+}
+
+#[test]
+fn test_rewind_to_cycle() {
+    let mut state = program();
+    state.enable_rewind(10);
+    for _ in 0..3 {
+        state.execute_next().unwrap();
+    }
+    assert_eq!(11, state.cycles);
+
+    // Cycle 3 is in the middle of STA, so it rewinds to the start of it
+    assert!(state.rewind_to_cycle(3));
+    assert_eq!(2, state.cycles);
+    assert_eq!(2, state.pc.get());
+    assert_eq!(0x07, state.peek_byte_from_addr(0x10));
+}
+
+#[test]
+fn test_rewind_to_unreachable_cycle() {
+    let mut state = program();
+    state.execute_next().unwrap();
+    state.enable_rewind(10);
+    state.execute_next().unwrap();
+
+    assert!(!state.rewind_to_cycle(0));
+    assert_eq!(5, state.cycles);
+}
+
+#[test]
+fn test_step_back_from_jam() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(jam)
+    ]);
+    state.enable_rewind(10);
+
+    assert_eq!(Err(ExecutionError::Jammed { opcode: opcode_from_operation(jam), addr: 0 }), state.execute_next());
+    assert_eq!(Err(ExecutionError::Halted), state.execute_next());
+    assert!(state.step_back());
+    assert!(state.step_back());
+    assert_eq!(0, state.pc.get());
+    assert!(state.execute_next().is_err());
+}
+
+#[test]
+fn test_step_back_over_bank_switch() {
+    // Every byte of bank n is $A0 + n
+    let image = (0..4).flat_map(|bank| vec![0xA0 + bank; BANK_SIZE_16K]).collect();
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, Cartridge::new(UxRom::new(image), FlatRam::new()));
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x02,
+        opcode_from_operation(sta_ab),
+        0x00,
+        0x80,
+    ]);
+    state.enable_rewind(10);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    assert_eq!(2, state.bus.mapper.bank());
+    assert_eq!(0xA2, state.bus.peek(0x8000));
+
+    assert!(state.step_back());
+    assert_eq!(0, state.bus.mapper.bank());
+    assert_eq!(0xA0, state.bus.peek(0x8000));
+    assert_eq!(2, state.pc.get());
+    // The ROM of the bank which was switched in is untouched
+    state.bus.write(0x8000, 2);
+    assert_eq!(0xA2, state.bus.peek(0x8000));
+}
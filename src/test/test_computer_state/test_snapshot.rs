@@ -0,0 +1,190 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::bus::address_decoder::{AddressDecoder, Region};
+use crate::computer_state::bus::mapper::{Cartridge, Mmc1, BANK_SIZE_16K};
+use crate::computer_state::execution::IllegalOpcodePolicy;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::opcode_from_operation;
+use crate::computer_state::snapshot::*;
+use crate::computer_state::status_register::StatusRegister;
+
+/// Device with a single register
+struct Latch {
+    value: u8,
+}
+
+impl Bus for Latch {
+    fn write(&mut self, _: u16, value: u8) {
+        self.value = value;
+    }
+
+    fn peek(&self, _: u16) -> u8 {
+        self.value
+    }
+
+    fn save_state(&self, snapshot: &mut StateWriter) {
+        snapshot.write_u8(self.value);
+    }
+
+    fn load_state(&mut self, snapshot: &mut StateReader) -> Result<(), SnapshotError> {
+        self.value = snapshot.read_u8()?;
+        Ok(())
+    }
+}
+
+fn decoder() -> AddressDecoder {
+    AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x07FF, 0x0800),
+        Region::device(0x4000, 0x4000, Box::new(Latch { value: 0 })),
+        Region::rom(0xF000, 0xFFFF, vec![0; 0x1000]),
+    ]).unwrap()
+}
+
+#[test]
+fn test_round_trip() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x42,
+        opcode_from_operation(ldx_im),
+        0x07,
+    ]);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    state.set_illegal_opcode_policy(IllegalOpcodePolicy::Halt);
+    let snapshot = state.save_state();
+
+    let mut restored = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    restored.load_state(&snapshot).unwrap();
+    assert_eq!(4, restored.pc.get());
+    assert_eq!(0x42, restored.acc.get());
+    assert_eq!(0x07, restored.get_x());
+    assert_eq!(4, restored.cycles);
+    assert_eq!(CpuVariant::Nmos6502, restored.variant());
+    assert_eq!(opcode_from_operation(ldx_im), restored.peek_byte_from_addr(2));
+    assert_eq!(snapshot, restored.save_state());
+}
+
+#[test]
+fn test_round_trip_devices() {
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, decoder());
+    state.set_byte_at_addr(0x0010, 0x42);
+    state.set_byte_at_addr(0x4000, 0x07);
+    state.sta = StatusRegister::C;
+    let snapshot = state.save_state();
+
+    let mut restored = ComputerState::with_bus(CpuVariant::Nmos6502, decoder());
+    restored.load_state(&snapshot).unwrap();
+    assert_eq!(0x42, restored.peek_byte_from_addr(0x0010));
+    assert_eq!(0x07, restored.peek_byte_from_addr(0x4000));
+    assert!(restored.sta.contains_only(StatusRegister::C));
+}
+
+#[test]
+fn test_round_trip_mapper() {
+    let image: Vec<u8> = (0..8).flat_map(|bank| vec![bank; BANK_SIZE_16K]).collect();
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, Cartridge::new(Mmc1::new(image.clone()), decoder()));
+    for bit in 0..5 {
+        state.set_byte_at_addr(0xE000, (3 >> bit) & 1);
+    }
+    state.set_byte_at_addr(0x6000, 0x42);
+    let snapshot = state.save_state();
+
+    let mut restored = ComputerState::with_bus(CpuVariant::Nmos6502, Cartridge::new(Mmc1::new(image), decoder()));
+    restored.load_state(&snapshot).unwrap();
+    assert_eq!(3, restored.peek_byte_from_addr(0x8000));
+    assert_eq!(0x42, restored.peek_byte_from_addr(0x6000));
+}
+
+#[test]
+fn test_not_a_snapshot() {
+    assert_eq!(Err(SnapshotError::NotASnapshot), ComputerState::new().load_state(b"M65"));
+    assert_eq!(Err(SnapshotError::NotASnapshot), ComputerState::new().load_state(b"NES\x1A\x01\x00"));
+}
+
+#[test]
+fn test_newer_version() {
+    let mut snapshot = ComputerState::new().save_state();
+    snapshot[4..6].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+
+    assert_eq!(Err(SnapshotError::UnsupportedVersion(FORMAT_VERSION + 1)), ComputerState::new().load_state(&snapshot));
+}
+
+#[test]
+fn test_truncated() {
+    let snapshot = ComputerState::new().save_state();
+
+    assert_eq!(Err(SnapshotError::Truncated), ComputerState::new().load_state(&snapshot[..snapshot.len() - 1]));
+}
+
+#[test]
+fn test_memory_size_mismatch() {
+    let snapshot = ComputerState::with_bus(CpuVariant::Nmos6502, decoder()).save_state();
+    let larger_ram = AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x0FFF, 0x1000),
+    ]).unwrap();
+
+    assert_eq!(
+        Err(SnapshotError::SizeMismatch { expected: 0x1000, found: 0x0800 }),
+        ComputerState::with_bus(CpuVariant::Nmos6502, larger_ram).load_state(&snapshot)
+    );
+}
+
+#[test]
+fn test_later_chunks_and_fields_skipped() {
+    let mut state = ComputerState::new();
+    state.acc.set(0x42);
+    let snapshot = state.save_state();
+
+    // Append a field to the CPU chunk, and a chunk from a later version
+    let cpu_len = u32::from_le_bytes(snapshot[10..14].try_into().unwrap()) as usize;
+    let mut later = snapshot[..6].to_vec();
+    later.extend_from_slice(b"CPU ");
+    later.extend_from_slice(&(cpu_len as u32 + 1).to_le_bytes());
+    later.extend_from_slice(&snapshot[14..14 + cpu_len]);
+    later.push(0xFF);
+    later.extend_from_slice(b"NEW \x02\x00\x00\x00\x01\x02");
+    later.extend_from_slice(&snapshot[14 + cpu_len..]);
+
+    let mut restored = ComputerState::new();
+    restored.load_state(&later).unwrap();
+    assert_eq!(0x42, restored.acc.get());
+}
+
+#[test]
+fn test_missing_chunk() {
+    let snapshot = ComputerState::new().save_state();
+    let cpu_len = u32::from_le_bytes(snapshot[10..14].try_into().unwrap()) as usize;
+
+    assert_eq!(Err(SnapshotError::MissingChunk(*b"BUS ")), ComputerState::new().load_state(&snapshot[..14 + cpu_len]));
+}
+
+#[test]
+fn test_failed_load_leaves_state() {
+    let mut saved = ComputerState::with_bus(CpuVariant::Nmos6502, decoder());
+    saved.acc.set(0x42);
+    saved.set_byte_at_addr(0x0010, 0x42);
+    saved.set_byte_at_addr(0x4000, 0x42);
+    let snapshot = saved.save_state();
+
+    // The RAM and the device load before the ROM is found to be the wrong size
+    let mut state = ComputerState::with_bus(CpuVariant::Nmos6502, AddressDecoder::new(vec![
+        Region::ram(0x0000, 0x07FF, 0x0800),
+        Region::device(0x4000, 0x4000, Box::new(Latch { value: 0x07 })),
+        Region::rom(0xE000, 0xFFFF, vec![0; 0x2000]),
+    ]).unwrap());
+    state.acc.set(0x07);
+    state.set_byte_at_addr(0x0010, 0x07);
+    let before = state.save_state();
+
+    assert_eq!(Err(SnapshotError::SizeMismatch { expected: 0x2000, found: 0x1000 }), state.load_state(&snapshot));
+    assert_eq!(0x07, state.acc.get());
+    assert_eq!(0x07, state.peek_byte_from_addr(0x0010));
+    assert_eq!(0x07, state.peek_byte_from_addr(0x4000));
+    assert_eq!(before, state.save_state());
+
+    // The CPU chunk is not applied when the bus chunk is missing
+    let cpu_len = u32::from_le_bytes(snapshot[10..14].try_into().unwrap()) as usize;
+    assert_eq!(Err(SnapshotError::MissingChunk(*b"BUS ")), state.load_state(&snapshot[..14 + cpu_len]));
+    assert_eq!(before, state.save_state());
+}
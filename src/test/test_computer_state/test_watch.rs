@@ -0,0 +1,100 @@
+use crate::computer_state::ComputerState;
+use crate::computer_state::bus::{AccessKind, Bus};
+use crate::computer_state::operations::jumps_calls::*;
+use crate::computer_state::operations::load_store::*;
+use crate::computer_state::operations::opcode_from_operation;
+use crate::computer_state::watch::{WatchHit, WatchKind, Watchpoint};
+
+/// LDA #$42; STA $10; JSR $0200
+fn program() -> ComputerState {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[
+        opcode_from_operation(lda_im),
+        0x42,
+        opcode_from_operation(sta_zp),
+        0x10,
+        opcode_from_operation(jsr),
+        0x00,
+        0x02,
+    ]);
+    state.bus.load(0x10, 0x07);
+    state
+}
+
+#[test]
+fn test_write_watchpoint() {
+    let mut state = program();
+    state.add_watchpoint(0x10..=0x10, WatchKind::Write);
+
+    state.execute_next().unwrap();
+    assert!(state.take_watch_hits().is_empty());
+    state.execute_next().unwrap();
+    assert_eq!(
+        vec![WatchHit { pc: 0x0002, addr: 0x10, access: AccessKind::Write, old: 0x07, new: 0x42 }],
+        state.take_watch_hits(),
+    );
+    assert!(state.take_watch_hits().is_empty());
+}
+
+#[test]
+fn test_read_watchpoint_includes_operand_fetches() {
+    let mut state = program();
+    state.add_watchpoint(0x01..=0x01, WatchKind::Read);
+
+    state.execute_next().unwrap();
+    assert_eq!(
+        vec![WatchHit { pc: 0x0000, addr: 0x01, access: AccessKind::Read, old: 0x42, new: 0x42 }],
+        state.take_watch_hits(),
+    );
+}
+
+#[test]
+fn test_stack_pushes_are_watched() {
+    let mut state = program();
+    state.add_watchpoint(0x0100..=0x01FF, WatchKind::Access);
+    for _ in 0..3 {
+        state.execute_next().unwrap();
+    }
+
+    // JSR pushes the address of its last byte, high byte first
+    let hits = state.take_watch_hits();
+    assert_eq!(vec![(0x01FF, 0x00), (0x01FE, 0x06)], hits.iter().map(|hit| (hit.addr, hit.new)).collect::<Vec<_>>());
+    assert!(hits.iter().all(|hit| hit.pc == 0x0004 && hit.access == AccessKind::Write));
+}
+
+#[test]
+fn test_change_watchpoint() {
+    let mut state = program();
+    state.bus.load(0x10, 0x42);
+    state.add_watchpoint(0x00..=0xFF, WatchKind::Change);
+    state.execute_next().unwrap();
+    state.execute_next().unwrap();
+    assert!(state.take_watch_hits().is_empty());
+
+    state.set_byte_at_addr(0x10, 0x43);
+    assert_eq!(1, state.take_watch_hits().len());
+}
+
+#[test]
+fn test_peek_and_load_are_not_watched() {
+    let mut state = program();
+    state.add_watchpoint(0x0000..=0xFFFF, WatchKind::Access);
+    state.peek_byte_from_addr(0x10);
+    state.bus.load(0x10, 0x01);
+    state.load_at(0x0300, &[1, 2, 3]);
+
+    assert!(state.take_watch_hits().is_empty());
+}
+
+#[test]
+fn test_remove_watchpoints() {
+    let mut state = program();
+    state.add_watchpoint(0x10..=0x10, WatchKind::Write);
+    state.add_watchpoint(0x20..=0x2F, WatchKind::Read);
+
+    assert_eq!(None, state.remove_watchpoint(2));
+    assert_eq!(Some(Watchpoint { range: 0x10..=0x10, kind: WatchKind::Write }), state.remove_watchpoint(0));
+    assert_eq!("read $0020-$002F", state.watchpoints()[0].to_string());
+    state.clear_watchpoints();
+    assert!(state.watchpoints().is_empty());
+}
@@ -0,0 +1,221 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use crate::computer_state::CpuVariant;
+use crate::dap::{read_message, write_message, DapServer};
+use crate::json::Json;
+
+/// Counts X up to 3 in a subroutine, then traps
+const SOURCE: &str = "        .org $0200
+start:  jsr count       ; line 2, $0200
+        ldy #1          ; line 3, $0203
+done:   jmp done        ; line 4, $0205
+
+count:  inx             ; line 6, $0208
+        cpx #3          ; line 7, $0209
+        bne count       ; line 8, $020B
+        rts             ; line 9, $020D
+";
+
+/// Writes the source to a file of its own, returning its path
+fn source_file(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mos6502_dap_{}_{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("count.s");
+    std::fs::write(&path, SOURCE).unwrap();
+    path
+}
+
+/// Sends the request, returning the response and the events after it
+fn request(server: &mut DapServer, command: &str, arguments: &str) -> Vec<Json> {
+    let request = Json::parse(&format!(r#"{{"seq": 1, "type": "request", "command": "{command}", "arguments": {arguments}}}"#)).unwrap();
+    let messages = server.handle(&request);
+    assert_eq!(Some(command), messages[0].get("command").and_then(Json::as_str));
+    messages
+}
+
+/// Sends the request, returning the body of its response, which must have succeeded
+fn body(server: &mut DapServer, command: &str, arguments: &str) -> Json {
+    let messages = request(server, command, arguments);
+    assert_eq!(Some(true), messages[0].get("success").and_then(Json::as_bool), "{}", messages[0]);
+    messages[0].get("body").unwrap().clone()
+}
+
+/// Sends the request, returning the reason of the stopped event which follows the response
+fn stop_reason(server: &mut DapServer, command: &str) -> String {
+    let messages = request(server, command, "{}");
+    let event = messages.last().unwrap();
+    assert_eq!(Some("stopped"), event.get("event").and_then(Json::as_str));
+    event.get("body").and_then(|body| body.get("reason")).and_then(Json::as_str).unwrap().to_string()
+}
+
+/// Returns the line of the only stack frame
+fn current_line(server: &mut DapServer) -> u64 {
+    let frames = body(server, "stackTrace", r#"{"threadId": 1}"#);
+    frames.get("stackFrames").and_then(Json::as_array).unwrap()[0].get("line").and_then(Json::as_u64).unwrap()
+}
+
+/// Launches the source, stopped on entry
+fn launched(path: &Path) -> DapServer {
+    let mut server = DapServer::new(CpuVariant::Nmos6502);
+    body(&mut server, "initialize", r#"{"adapterID": "mos6502"}"#);
+    body(&mut server, "launch", &format!(r#"{{"source": "{}", "stopOnEntry": true}}"#, path.display()));
+    server
+}
+
+#[test]
+fn test_message_framing() {
+    let mut input = Vec::new();
+    write_message(&mut input, &Json::parse(r#"{"seq": 1, "type": "request", "command": "initialize"}"#).unwrap()).unwrap();
+    write_message(&mut input, &Json::parse(r#"{"seq": 2, "type": "request", "command": "disconnect"}"#).unwrap()).unwrap();
+    let mut output = Vec::new();
+    DapServer::new(CpuVariant::Nmos6502).serve(&mut Cursor::new(input), &mut output).unwrap();
+
+    let mut output = Cursor::new(output);
+    let mut messages = Vec::new();
+    while let Some(message) = read_message(&mut output).unwrap() {
+        messages.push(message);
+    }
+    let describe = |message: &Json| (
+        message.get("seq").and_then(Json::as_u64).unwrap(),
+        message.get("command").or_else(|| message.get("event")).and_then(Json::as_str).unwrap().to_string(),
+    );
+    assert_eq!(
+        vec![(1, "initialize".to_string()), (2, "initialized".to_string()), (3, "disconnect".to_string())],
+        messages.iter().map(describe).collect::<Vec<_>>(),
+    );
+    assert_eq!(Some(1), messages[0].get("request_seq").and_then(Json::as_u64));
+}
+
+#[test]
+fn test_breakpoints_on_lines_and_addresses() {
+    let path = source_file("breakpoints");
+    let mut server = launched(&path);
+
+    // Line 5 is empty, so its breakpoint moves to the next line with code, and nothing is after line 9
+    let breakpoints = body(&mut server, "setBreakpoints", &format!(
+        r#"{{"source": {{"path": "{}"}}, "breakpoints": [{{"line": 5}}, {{"line": 20}}]}}"#, path.display(),
+    ));
+    assert_eq!(
+        r#"{"breakpoints":[{"id":1,"verified":true,"line":6,"instructionReference":"0x0208"},{"id":2,"verified":false,"line":20,"message":"no code was assembled from this line"}]}"#,
+        breakpoints.to_string(),
+    );
+    body(&mut server, "setInstructionBreakpoints", r#"{"breakpoints": [{"instructionReference": "0x020D"}]}"#);
+
+    assert_eq!("entry", stop_reason(&mut server, "configurationDone"));
+    assert_eq!(2, current_line(&mut server));
+    assert_eq!("breakpoint", stop_reason(&mut server, "continue"));
+    assert_eq!(6, current_line(&mut server));
+
+    // Setting the breakpoints of the file again replaces them
+    body(&mut server, "setBreakpoints", &format!(r#"{{"source": {{"path": "{}"}}, "breakpoints": []}}"#, path.display()));
+    assert_eq!("breakpoint", stop_reason(&mut server, "continue"));
+    assert_eq!(9, current_line(&mut server));
+    assert_eq!("pause", stop_reason(&mut server, "continue"));
+    assert_eq!(4, current_line(&mut server));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_breakpoints_set_before_launch() {
+    let path = source_file("early_breakpoints");
+    let mut server = DapServer::new(CpuVariant::Nmos6502);
+    body(&mut server, "initialize", r#"{"adapterID": "mos6502"}"#);
+
+    // There is no listing yet, so the line cannot be found until the program is launched
+    let breakpoints = body(&mut server, "setBreakpoints", &format!(
+        r#"{{"source": {{"path": "{}"}}, "breakpoints": [{{"line": 7}}]}}"#, path.display(),
+    ));
+    assert_eq!(
+        r#"{"breakpoints":[{"id":1,"verified":false,"line":7,"message":"no code was assembled from this line"}]}"#,
+        breakpoints.to_string(),
+    );
+
+    let messages = request(&mut server, "launch", &format!(r#"{{"source": "{}", "stopOnEntry": true}}"#, path.display()));
+    assert_eq!(
+        r#"{"reason":"changed","breakpoint":{"id":1,"verified":true,"line":7,"instructionReference":"0x0209"}}"#,
+        messages[1].get("body").unwrap().to_string(),
+    );
+
+    assert_eq!("entry", stop_reason(&mut server, "configurationDone"));
+    assert_eq!("breakpoint", stop_reason(&mut server, "continue"));
+    assert_eq!(7, current_line(&mut server));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_stepping() {
+    let path = source_file("stepping");
+    let mut server = launched(&path);
+    stop_reason(&mut server, "configurationDone");
+
+    assert_eq!("step", stop_reason(&mut server, "stepIn"));
+    assert_eq!(6, current_line(&mut server));
+    assert_eq!("step", stop_reason(&mut server, "next"));
+    assert_eq!(7, current_line(&mut server));
+    assert_eq!("step", stop_reason(&mut server, "stepOut"));
+    assert_eq!(3, current_line(&mut server));
+
+    // Stepping over the subroutine from the start runs all of it
+    let mut server = launched(&path);
+    stop_reason(&mut server, "configurationDone");
+    stop_reason(&mut server, "next");
+    assert_eq!(3, current_line(&mut server));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_registers_memory_and_evaluate() {
+    let path = source_file("registers");
+    let mut server = launched(&path);
+    stop_reason(&mut server, "configurationDone");
+
+    let scopes = body(&mut server, "scopes", r#"{"frameId": 1}"#);
+    let reference = scopes.get("scopes").and_then(Json::as_array).unwrap()[0].get("variablesReference").unwrap().to_string();
+    let variables = body(&mut server, "variables", &format!(r#"{{"variablesReference": {reference}}}"#));
+    let variables: Vec<String> = variables.get("variables").and_then(Json::as_array).unwrap().iter()
+        .map(|variable| format!("{}={}", variable.get("name").and_then(Json::as_str).unwrap(), variable.get("value").and_then(Json::as_str).unwrap()))
+        .collect();
+    assert_eq!(
+        vec!["A=$00", "X=$00", "Y=$00", "SP=$FF", "PC=$0200", "P=$00", "N=0", "V=0", "B=0", "D=0", "I=0", "Z=0", "C=0"],
+        variables,
+    );
+
+    // JSR $0208, which is 20 08 02, and the first byte of LDY #1
+    let memory = body(&mut server, "readMemory", r#"{"memoryReference": "0x01FF", "offset": 1, "count": 4}"#);
+    assert_eq!(r#"{"address":"0x0200","data":"IAgCoA=="}"#, memory.to_string());
+
+    let result = body(&mut server, "evaluate", r#"{"expression": "w[start + 1] == count"}"#);
+    assert_eq!(Some("1 ($01)"), result.get("result").and_then(Json::as_str));
+    let messages = request(&mut server, "evaluate", r#"{"expression": "nowhere"}"#);
+    assert_eq!(Some("nowhere is not defined"), messages[0].get("message").and_then(Json::as_str));
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
+
+#[test]
+fn test_launch_binary_at_address() {
+    let dir = std::env::temp_dir().join(format!("mos6502_dap_{}_binary", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("program.bin");
+    // INX; JMP $0301
+    std::fs::write(&path, [0xE8, 0x4C, 0x01, 0x03]).unwrap();
+
+    let mut server = DapServer::new(CpuVariant::Nmos6502);
+    body(&mut server, "launch", &format!(r#"{{"program": "{}", "loadAddress": "$0300"}}"#, path.display()));
+    let messages = request(&mut server, "configurationDone", "{}");
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(Some("Trapped at $0301"), messages[1].get("body").and_then(|body| body.get("description")).and_then(Json::as_str));
+    let frames = body(&mut server, "stackTrace", "{}");
+    assert_eq!(
+        r#"{"stackFrames":[{"id":1,"name":"$0301 JMP $0301","instructionPointerReference":"0x0301","line":0,"column":0}],"totalFrames":1}"#,
+        frames.to_string(),
+    );
+
+    let messages = request(&mut DapServer::new(CpuVariant::Nmos6502), "stepIn", "{}");
+    assert_eq!(Some("no program has been launched"), messages[0].get("message").and_then(Json::as_str));
+}
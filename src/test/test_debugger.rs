@@ -0,0 +1,323 @@
+use crate::assembler::assemble;
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::execution::ExecutionError;
+use std::collections::HashMap;
+
+use crate::computer_state::bus::AccessKind;
+use crate::computer_state::status_register::StatusRegister;
+use crate::computer_state::watch::{WatchHit, WatchKind, Watchpoint};
+use crate::debugger::{Command, Debugger, Register, Stop};
+use crate::debugger::expression::{EvalError, Expression};
+
+/// Processor with the program assembled at $0200, and the PC at its start
+fn program(source: &str) -> ComputerState {
+    let assembly = assemble(&format!(".org $0200\n{source}"), CpuVariant::Nmos6502).unwrap();
+    let mut state = ComputerState::new();
+    state.load_at(assembly.origin, &assembly.binary);
+    state.pc.set(0x0200);
+    state
+}
+
+/// Calls a subroutine which counts X up to 3, then traps
+const SUBROUTINE: &str = "
+start:  jsr count       ; $0200
+        ldy #1          ; $0203
+done:   jmp done        ; $0205
+count:  inx             ; $0208
+        cpx #3          ; $0209
+        bne count       ; $020B
+        rts             ; $020D
+";
+
+#[test]
+fn test_parse_commands() {
+    assert_eq!(Ok(Command::Break(0x0200, None)), Command::parse("break $0200"));
+    assert_eq!(Ok(Command::Delete(None)), Command::parse("d"));
+    assert_eq!(Ok(Command::Step(1)), Command::parse("step"));
+    assert_eq!(Ok(Command::Step(10)), Command::parse("s 10"));
+    assert_eq!(Ok(Command::Set(Register::A, 0x10)), Command::parse("set a=$10"));
+    assert_eq!(Ok(Command::Set(Register::Flag(StatusRegister::C), 1)), Command::parse("set c = 1"));
+    assert_eq!(Ok(Command::Mem { addr: 0x0200, len: 64 }), Command::parse("mem $0200 64"));
+    assert_eq!(Ok(Command::Mem { addr: 5, len: 2 }), Command::parse("m %101 $2"));
+    assert_eq!(Ok(Command::Disasm { addr: None, count: 10 }), Command::parse("disasm"));
+    assert_eq!(Ok(Command::Quit), Command::parse("QUIT"));
+
+    assert_eq!(Err("invalid number $GG".to_string()), Command::parse("break $GG"));
+    assert_eq!(Err("$10000 is not an address".to_string()), Command::parse("break $10000"));
+    assert_eq!(Err("256 is too large for the register".to_string()), Command::parse("set x=256"));
+    assert!(Command::parse("jump").is_err());
+}
+
+#[test]
+fn test_parse_watch_commands() {
+    assert_eq!(Ok(Command::Watch(None)), Command::parse("watch"));
+    assert_eq!(
+        Ok(Command::Watch(Some(Watchpoint { range: 0x20..=0x20, kind: WatchKind::Write }))),
+        Command::parse("w $20"),
+    );
+    assert_eq!(
+        Ok(Command::Watch(Some(Watchpoint { range: 0x0100..=0x01FF, kind: WatchKind::Change }))),
+        Command::parse("watch $0100-$01FF change"),
+    );
+    assert_eq!(Ok(Command::Unwatch(Some(2))), Command::parse("unwatch 2"));
+
+    assert_eq!(Err("$20-$10 ends before it starts".to_string()), Command::parse("watch $20-$10"));
+    assert_eq!(Err("unknown kind of watchpoint peek".to_string()), Command::parse("watch $20 peek"));
+}
+
+#[test]
+fn test_continue_to_watchpoint() {
+    let mut state = program("
+        ldx #0          ; $0200
+loop:   inx             ; $0202
+        stx $20         ; $0203
+        cpx #3          ; $0205
+        bne loop        ; $0207
+done:   jmp done        ; $0209
+    ");
+    let mut debugger = Debugger::new();
+    state.add_watchpoint(0x20..=0x20, WatchKind::Write);
+
+    // The program stops after the instruction which made the access
+    assert_eq!(
+        Stop::Watchpoint(vec![WatchHit { pc: 0x0203, addr: 0x20, access: AccessKind::Write, old: 0, new: 1 }]),
+        debugger.run_continue(&mut state),
+    );
+    assert_eq!(0x0205, state.pc.get());
+    assert_eq!(
+        "Watchpoint: write to $0020 by instruction at $0203, $01 -> $02\n >0205  E0 03     CPX #$03",
+        debugger.execute(&mut state, &Command::Continue),
+    );
+
+    assert_eq!("1: write $0020", debugger.execute(&mut state, &Command::Watch(None)));
+    assert_eq!("Watchpoint 1 on write $0020 deleted", debugger.execute(&mut state, &Command::Unwatch(Some(1))));
+    assert_eq!("There is no watchpoint 1", debugger.execute(&mut state, &Command::Unwatch(Some(1))));
+    assert_eq!(Stop::Trapped(0x0209), debugger.run_continue(&mut state));
+}
+
+#[test]
+fn test_continue_to_breakpoint() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    debugger.add_breakpoint(0x020D);
+
+    assert_eq!(Stop::Breakpoint(0x020D), debugger.run_continue(&mut state));
+    assert_eq!(3, state.get_x());
+    assert_eq!(1, debugger.breakpoints().next().unwrap().hits);
+
+    // Continuing from a breakpoint does not stop at it again straight away
+    assert_eq!(Stop::Trapped(0x0205), debugger.run_continue(&mut state));
+    assert_eq!(1, state.get_y());
+}
+
+#[test]
+fn test_step() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+
+    assert_eq!(Stop::Done, debugger.step(&mut state, 1));
+    assert_eq!(0x0208, state.pc.get());
+    assert_eq!(Stop::Done, debugger.step(&mut state, 3));
+    assert_eq!(0x0208, state.pc.get());
+
+    debugger.add_breakpoint(0x020D);
+    assert_eq!(Stop::Breakpoint(0x020D), debugger.step(&mut state, 100));
+}
+
+#[test]
+fn test_next_steps_over_subroutines() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+
+    assert_eq!(Stop::Done, debugger.next(&mut state));
+    assert_eq!(0x0203, state.pc.get());
+    assert_eq!(3, state.get_x());
+
+    assert_eq!(Stop::Done, debugger.next(&mut state));
+    assert_eq!(0x0205, state.pc.get());
+}
+
+#[test]
+fn test_next_handles_recursion() {
+    // The subroutine calls itself until X reaches 2, so the first return is from the inner call
+    let mut state = program("
+        jsr sub         ; $0200
+        brk             ; $0203
+sub:    inx
+        cpx #2
+        beq @out
+        jsr sub
+@out:   rts
+    ");
+    let mut debugger = Debugger::new();
+
+    assert_eq!(Stop::Done, debugger.next(&mut state));
+    assert_eq!(0x0203, state.pc.get());
+    assert_eq!(0xFF, state.get_stk());
+}
+
+#[test]
+fn test_finish() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    debugger.step(&mut state, 2);
+
+    assert_eq!(Stop::Done, debugger.finish(&mut state));
+    assert_eq!(0x0203, state.pc.get());
+    assert_eq!(3, state.get_x());
+}
+
+#[test]
+fn test_stops_on_errors_and_limit() {
+    let mut state = program("nop\n.byte $02");
+    let mut debugger = Debugger::new();
+    assert_eq!(Stop::Error(ExecutionError::Jammed { opcode: 0x02, addr: 0x0201 }), debugger.run_continue(&mut state));
+
+    // Never traps, as the loop is two instructions long
+    let mut state = program("loop: nop\njmp loop");
+    debugger.set_run_limit(50);
+    assert_eq!(Stop::Paused { steps: 50 }, debugger.run_continue(&mut state));
+}
+
+#[test]
+fn test_execute_commands() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+
+    assert_eq!("Breakpoint set at $0209", debugger.execute(&mut state, &Command::Break(0x0209, None)));
+    assert_eq!(
+        "Breakpoint at $0209\n*>0209  E0 03     CPX #$03",
+        debugger.execute(&mut state, &Command::Continue),
+    );
+    assert_eq!(" >020B  D0 FB     BNE $0208", debugger.execute(&mut state, &Command::Step(1)));
+
+    assert_eq!(
+        "PC=$020B A=$10 X=$01 Y=$00 SP=$FD P=$80 [Nv-bdizc] CYC=10",
+        debugger.execute(&mut state, &Command::Set(Register::A, 0x10)),
+    );
+    debugger.execute(&mut state, &Command::Set(Register::Flag(StatusRegister::C), 1));
+    assert_eq!(StatusRegister::N | StatusRegister::C, state.sta);
+
+    assert_eq!(
+        "  0208  E8        INX\n* 0209  E0 03     CPX #$03",
+        debugger.execute(&mut state, &Command::Disasm { addr: Some(0x0208), count: 2 }),
+    );
+    assert_eq!("Breakpoint at $0209 deleted", debugger.execute(&mut state, &Command::Delete(Some(0x0209))));
+    assert_eq!(0, debugger.breakpoints().count());
+}
+
+#[test]
+fn test_memory_dump() {
+    let mut state = program(".byte \"Hello\", 0, $FF");
+    let mut debugger = Debugger::new();
+
+    assert_eq!(
+        format!("0200  48 65 6C 6C 6F 00 FF{}  Hello..", " ".repeat(27)),
+        debugger.execute(&mut state, &Command::Mem { addr: 0x0200, len: 7 }),
+    );
+    assert_eq!(2, debugger.execute(&mut state, &Command::Mem { addr: 0x0200, len: 17 }).lines().count());
+}
+
+/// Evaluates the expression against the processor, with a symbol named table at $1234 and 5 hits
+fn eval(state: &ComputerState, text: &str) -> Result<i64, EvalError> {
+    let symbols = HashMap::from([("table".to_string(), 0x1234)]);
+    Expression::parse(text).unwrap().eval(state, &symbols, Some(5))
+}
+
+#[test]
+fn test_expressions() {
+    let mut state = program("");
+    state.acc.set(0x10);
+    state.set_x(3);
+    state.sta = StatusRegister::C | StatusRegister::Z;
+    state.cycles = 1000;
+    state.load_at(0x0020, &[0x34, 0x12]);
+    state.load_at(0xFFFF, &[0x99]);
+    state.load_at(0x0000, &[0x12]);
+
+    assert_eq!(Ok(0x13), eval(&state, "a + X"));
+    assert_eq!(Ok(0x0200), eval(&state, "pc"));
+    assert_eq!(Ok(0xFF), eval(&state, "sp"));
+    assert_eq!(Ok(1), eval(&state, "c && z && !n"));
+    assert_eq!(Ok(0x34), eval(&state, "[$20]"));
+    assert_eq!(Ok(0x1234), eval(&state, "w[$1F + 1]"));
+    assert_eq!(Ok(0x1299), eval(&state, "W[$FFFF]"));
+    assert_eq!(Ok(1), eval(&state, "w[$20] == table"));
+    assert_eq!(Ok(1), eval(&state, "cycles >= 1000 && hits == 5"));
+    assert_eq!(Ok(7), eval(&state, "1 + 2 * 3"));
+    assert_eq!(Ok(1), eval(&state, "1 << 2 == 4 || 1 / 0"));
+    assert_eq!(Ok(0), eval(&state, "x <= 2"));
+    assert_eq!(Ok(1), eval(&state, "x < 4 & x > 2"));
+    assert_eq!(Ok(-1), eval(&state, "-(x != 3) | ~0"));
+
+    assert_eq!(Err(EvalError::Undefined("missing".to_string())), eval(&state, "missing"));
+    assert_eq!(Err(EvalError::DivisionByZero), eval(&state, "a % (x - 3)"));
+    assert_eq!(Err(EvalError::NoHitCount), Expression::parse("hits").unwrap().eval(&state, &HashMap::new(), None));
+}
+
+#[test]
+fn test_expression_errors() {
+    assert_eq!(Err("unexpected '= 1' in expression".to_string()), Expression::parse("a = 1"));
+    assert_eq!(Err("missing ']' in expression".to_string()), Expression::parse("[$20"));
+    assert_eq!(Err("missing value in expression".to_string()), Expression::parse("x +"));
+    assert_eq!(Err("invalid number 'G'".to_string()), Expression::parse("$G"));
+    assert_eq!(Err("unexpected '€' in expression".to_string()), Expression::parse("a€"));
+    assert_eq!(Err("unexpected 'é' in expression".to_string()), Expression::parse("é"));
+}
+
+#[test]
+fn test_conditional_breakpoint() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    let command = Command::parse("break $0209 if x == 2").unwrap();
+
+    assert_eq!("Breakpoint set at $0209 if x == 2", debugger.execute(&mut state, &command));
+    assert_eq!(Stop::Breakpoint(0x0209), debugger.run_continue(&mut state));
+    assert_eq!(2, state.get_x());
+    assert_eq!(2, debugger.breakpoints().next().unwrap().hits);
+
+    // The hit count includes the times the condition did not stop execution
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    debugger.add_conditional_breakpoint(0x0209, Some(Expression::parse("hits == 3").unwrap()));
+    assert_eq!(Stop::Breakpoint(0x0209), debugger.run_continue(&mut state));
+    assert_eq!(3, state.get_x());
+
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    debugger.add_conditional_breakpoint(0x0209, Some(Expression::parse("nowhere").unwrap()));
+    assert_eq!(
+        "Breakpoint at $0209, whose condition failed: nowhere is not defined",
+        debugger.run_continue(&mut state).to_string(),
+    );
+
+    assert_eq!(Err("break needs if before its condition".to_string()), Command::parse("break $0209 x == 2"));
+}
+
+#[test]
+fn test_watch_expression() {
+    let mut state = program(SUBROUTINE);
+    let mut debugger = Debugger::new();
+    let mut symbols = HashMap::new();
+    symbols.insert("limit".to_string(), 2);
+    debugger.set_symbols(symbols);
+    state.add_watchpoint(0x20..=0x20, WatchKind::Write);
+
+    assert_eq!(
+        "Watchpoint 2 set on x >= limit, which is 0 ($00)",
+        debugger.execute(&mut state, &Command::parse("watch expr x >= limit").unwrap()),
+    );
+    assert_eq!(
+        "Watch x >= limit: 0 ($00) -> 1 ($01)\n >0209  E0 03     CPX #$03",
+        debugger.execute(&mut state, &Command::Continue),
+    );
+    assert_eq!(2, state.get_x());
+
+    assert_eq!("1: write $0020\n2: expr x >= limit", debugger.execute(&mut state, &Command::Watch(None)));
+    assert_eq!("Watchpoint 2 on expr x >= limit deleted", debugger.execute(&mut state, &Command::Unwatch(Some(2))));
+    assert_eq!(
+        "Cannot watch nowhere: nowhere is not defined",
+        debugger.execute(&mut state, &Command::parse("watch expr nowhere").unwrap()),
+    );
+    assert_eq!("x * 2 = 4 ($04)", debugger.execute(&mut state, &Command::parse("print x * 2").unwrap()));
+}
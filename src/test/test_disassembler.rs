@@ -0,0 +1,102 @@
+use crate::computer_state::CpuVariant;
+use crate::computer_state::bus::{Bus, FlatRam};
+use crate::disassembler::{disassemble, disassemble_at, disassemble_bytes, Instruction};
+
+fn texts(instructions: &[Instruction]) -> Vec<&str> {
+    instructions.iter().map(|instruction| instruction.text.as_str()).collect()
+}
+
+#[test]
+fn test_addressing_modes() {
+    let program = [
+        0xA9, 0x01, // LDA #$01
+        0xA5, 0x20, // LDA $20
+        0xB5, 0x20, // LDA $20,X
+        0xB6, 0x20, // LDX $20,Y
+        0xAD, 0x34, 0x12, // LDA $1234
+        0xBD, 0x34, 0x12, // LDA $1234,X
+        0xB9, 0x34, 0x12, // LDA $1234,Y
+        0xA1, 0x20, // LDA ($20,X)
+        0xB1, 0x20, // LDA ($20),Y
+        0x6C, 0xFC, 0xFF, // JMP ($FFFC)
+        0x0A, // ASL A
+        0xE8, // INX
+        0x20, 0x00, 0x80, // JSR $8000
+    ];
+
+    assert_eq!(
+        vec!["LDA #$01", "LDA $20", "LDA $20,X", "LDX $20,Y", "LDA $1234", "LDA $1234,X", "LDA $1234,Y",
+             "LDA ($20,X)", "LDA ($20),Y", "JMP ($FFFC)", "ASL A", "INX", "JSR $8000"],
+        texts(&disassemble_bytes(CpuVariant::Nmos6502, 0x0200, &program)),
+    );
+}
+
+#[test]
+fn test_branch_targets() {
+    // BNE back to itself, then BEQ forward over the next instruction
+    let program = [0xD0, 0xFE, 0xF0, 0x01, 0xEA, 0xEA];
+    let instructions = disassemble_bytes(CpuVariant::Nmos6502, 0xC000, &program);
+
+    assert_eq!(vec!["BNE $C000", "BEQ $C005", "NOP", "NOP"], texts(&instructions));
+    assert_eq!(0xC004, instructions[2].addr);
+}
+
+#[test]
+fn test_illegal_opcodes_are_bytes() {
+    let instructions = disassemble_bytes(CpuVariant::Nmos6502, 0, &[0xA7, 0x02, 0xEA]);
+
+    assert_eq!(vec![".byte $A7", ".byte $02", "NOP"], texts(&instructions));
+}
+
+#[test]
+fn test_truncated_instruction() {
+    let instructions = disassemble_bytes(CpuVariant::Nmos6502, 0, &[0xEA, 0xAD, 0x34]);
+
+    assert_eq!(vec!["NOP", ".byte $AD", ".byte $34"], texts(&instructions));
+}
+
+#[test]
+fn test_65c02_instructions() {
+    let program = [
+        0xB2, 0x20, // LDA ($20)
+        0x7C, 0x00, 0x10, // JMP ($1000,X)
+        0x80, 0xFE, // BRA
+        0x0F, 0x20, 0xFD, // BBR0 $20
+        0x87, 0x20, // SMB0 $20
+        0x1A, // INC A
+        0x02, 0x00, // undefined, a two byte NOP
+    ];
+
+    assert_eq!(
+        vec!["LDA ($20)", "JMP ($1000,X)", "BRA $0005", "BBR0 $20,$0007", "SMB0 $20", "INC A", ".byte $02", "BRK"],
+        texts(&disassemble_bytes(CpuVariant::Wdc65C02, 0, &program)),
+    );
+}
+
+#[test]
+fn test_disassemble_bus_range() {
+    let mut bus = FlatRam::new();
+    for (offset, byte) in [0xA2, 0x05, 0xCA, 0xD0, 0xFD].into_iter().enumerate() {
+        bus.write(0x0600 + offset as u16, byte);
+    }
+
+    let instructions = disassemble(&bus, CpuVariant::Nmos6502, 0x0600..=0x0604);
+
+    assert_eq!(vec!["LDX #$05", "DEX", "BNE $0602"], texts(&instructions));
+    assert_eq!(vec![0xD0, 0xFD], instructions[2].bytes);
+    assert_eq!("0602  CA        DEX", instructions[1].to_string());
+}
+
+#[test]
+fn test_disassemble_at_wraps() {
+    let memory = |addr: u16| match addr {
+        0xFFFF => 0xAD,
+        0x0000 => 0x34,
+        0x0001 => 0x12,
+        _ => 0,
+    };
+
+    let instruction = disassemble_at(CpuVariant::Nmos6502, 0xFFFF, memory);
+
+    assert_eq!("LDA $1234", instruction.text);
+}
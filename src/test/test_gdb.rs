@@ -0,0 +1,157 @@
+use std::io::{self, Cursor, Read, Write};
+
+use crate::assembler::assemble;
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::gdb::{frame, Connection, GdbStub, TARGET_XML};
+
+/// Connection which reads the given input, records the output, and can be interrupted
+struct MockConnection {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+    interrupted: bool,
+}
+
+impl Read for MockConnection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.input.read(buf)
+    }
+}
+
+impl Write for MockConnection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Connection for MockConnection {
+    fn poll_interrupt(&mut self) -> io::Result<bool> {
+        Ok(self.interrupted)
+    }
+}
+
+/// Processor with the program assembled at $0200, and the PC at its start
+fn program(source: &str) -> ComputerState {
+    let assembly = assemble(&format!(".org $0200\n{source}"), CpuVariant::Nmos6502).unwrap();
+    let mut state = ComputerState::new();
+    state.load_at(assembly.origin, &assembly.binary);
+    state.pc.set(0x0200);
+    state
+}
+
+/// Sends the packets, without acknowledgements, and returns everything the stub sent back
+fn raw_session(state: &mut ComputerState, packets: &[&str], interrupted: bool) -> String {
+    let input: String = std::iter::once(frame("QStartNoAckMode")).chain(packets.iter().map(|packet| frame(packet))).collect();
+    let mut connection = MockConnection { input: Cursor::new(input.into_bytes()), output: Vec::new(), interrupted };
+    GdbStub::new().serve(state, &mut connection).unwrap();
+    String::from_utf8(connection.output).unwrap()
+}
+
+/// Sends the packets, and returns the data of each reply
+fn session(state: &mut ComputerState, packets: &[&str]) -> Vec<String> {
+    let output = raw_session(state, packets, false);
+    // The reply to turning off acknowledgements is still acknowledged
+    let output = output.strip_prefix("+").unwrap();
+    let mut replies: Vec<String> = output.split('$').skip(1).map(|reply| {
+        let (data, checksum) = reply.split_once('#').unwrap();
+        assert_eq!(frame(data), format!("${data}#{checksum}"));
+        data.to_string()
+    }).collect();
+    assert_eq!("OK", replies.remove(0));
+    replies
+}
+
+#[test]
+fn test_acknowledgements() {
+    let mut state = program("nop");
+    let input = format!("{}$g#00{}", frame("?"), frame("D"));
+    let mut connection = MockConnection { input: Cursor::new(input.into_bytes()), output: Vec::new(), interrupted: false };
+    GdbStub::new().serve(&mut state, &mut connection).unwrap();
+
+    // The packet with the wrong checksum is rejected, so the debugger sends it again
+    assert_eq!(format!("+{}-+{}", frame("S05"), frame("OK")), String::from_utf8(connection.output).unwrap());
+}
+
+#[test]
+fn test_registers() {
+    let mut state = program("nop");
+    state.acc.set(0x12);
+    state.set_x(0x34);
+    state.set_stk(0xFD);
+
+    let replies = session(&mut state, &["g", "p4", "P0=ab", "p0", "G0102030405060700", "p6", "P4=12"]);
+    assert_eq!(vec!["123400fd000200", "0002", "OK", "ab", "OK", "E01", "E01"], replies);
+    assert_eq!((1, 2, 3, 4), (state.acc.get(), state.get_x(), state.get_y(), state.get_stk()));
+    assert_eq!(0x0605, state.pc.get());
+    assert_eq!(0x07, state.sta.as_byte());
+}
+
+#[test]
+fn test_memory() {
+    let mut state = program(".byte 1, 2, 3");
+
+    let replies = session(&mut state, &["m200,3", "Mfffe,3:aabbcc", "mfffe,3", "M200,2:aa", "m200"]);
+    assert_eq!(vec!["010203", "OK", "aabbcc", "E01", "E01"], replies);
+    assert_eq!(0xCC, state.peek_byte_from_addr(0));
+}
+
+#[test]
+fn test_breakpoints_and_stepping() {
+    let mut state = program("
+start:  inx             ; $0200
+        cpx #3          ; $0201
+        bne start       ; $0203
+done:   jmp done        ; $0205
+    ");
+
+    let replies = session(&mut state, &["Z0,201,1", "c", "p1", "c", "p1", "z0,201,1", "s", "p4", "c", "p4"]);
+    assert_eq!(vec!["OK", "S05", "01", "S05", "02", "OK", "S05", "0302", "S05", "0502"], replies);
+}
+
+#[test]
+fn test_watchpoints() {
+    let mut state = program("
+        lda #5          ; $0200
+        sta $20         ; $0202
+        lda $21         ; $0204
+        pha             ; $0206
+done:   jmp done        ; $0207
+    ");
+
+    let replies = session(&mut state, &["Z2,20,1", "Z3,21,1", "Z4,1ff,1", "c", "p4", "c", "c", "z4,1ff,1", "c", "Z1,0,1"]);
+    // Watchpoints stop after the instruction which made the access, and Z1 is not supported
+    assert_eq!(
+        vec!["OK", "OK", "OK", "T05watch:0020;", "0402", "T05rwatch:0021;", "T05awatch:01ff;", "OK", "S05", ""],
+        replies,
+    );
+    assert_eq!(2, state.watchpoints().len());
+}
+
+#[test]
+fn test_interrupt_and_errors() {
+    // Never traps, as the loop is two instructions long
+    let mut state = program("loop: nop\njmp loop");
+    assert!(raw_session(&mut state, &["c"], true).contains(&frame("S02")));
+
+    let mut state = program("nop\n.byte $02");
+    assert_eq!(vec!["S04", "0102"], session(&mut state, &["c", "p4"]));
+}
+
+#[test]
+fn test_queries() {
+    let mut state = program("nop");
+    let first = format!("qXfer:features:read:target.xml:0,{:x}", 100);
+    let rest = format!("qXfer:features:read:target.xml:{:x},1000", 100);
+    let huge = format!("qXfer:features:read:target.xml:{:x},ffffffffffffffff", 100);
+
+    let replies = session(&mut state, &["qSupported:swbreak+", "qAttached", &first, &rest, "qXfer:features:read:other.xml:0,10", "vMustReplyEmpty", &huge]);
+    assert_eq!("PacketSize=4000;qXfer:features:read+;QStartNoAckMode+", replies[0]);
+    assert_eq!("1", replies[1]);
+    assert_eq!(format!("m{}", &TARGET_XML[..100]), replies[2]);
+    assert_eq!(format!("l{}", &TARGET_XML[100..]), replies[3]);
+    assert_eq!(vec!["E00", ""], replies[4..6]);
+    assert_eq!(replies[3], replies[6]);
+}
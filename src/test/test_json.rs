@@ -0,0 +1,44 @@
+use crate::json::{Json, JsonError};
+
+#[test]
+fn test_parse_values() {
+    let json = Json::parse(r#" { "a": [1, -2.5, 3e2], "b": true, "c": null, "d": "x" } "#).unwrap();
+
+    assert_eq!(Some(&[Json::Number(1.0), Json::Number(-2.5), Json::Number(300.0)][..]), json.get("a").and_then(Json::as_array));
+    assert_eq!(Some(true), json.get("b").and_then(Json::as_bool));
+    assert_eq!(Some(&Json::Null), json.get("c"));
+    assert_eq!(Some("x"), json.get("d").and_then(Json::as_str));
+    assert_eq!(None, json.get("e"));
+}
+
+#[test]
+fn test_parse_escapes() {
+    let json = Json::parse(r#""a\"b\\c\né😀""#).unwrap();
+
+    assert_eq!(Some("a\"b\\c\né😀"), json.as_str());
+}
+
+#[test]
+fn test_as_u64() {
+    assert_eq!(Some(42), Json::Number(42.0).as_u64());
+    assert_eq!(None, Json::Number(4.5).as_u64());
+    assert_eq!(None, Json::Number(-1.0).as_u64());
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(Err(JsonError { message: "expected ',' or ']'", offset: 3 }), Json::parse("[1 2]"));
+    assert_eq!(Err(JsonError { message: "trailing characters", offset: 3 }), Json::parse("{} x"));
+    assert_eq!(Err(JsonError { message: "unterminated string", offset: 4 }), Json::parse(r#""abc"#));
+}
+
+#[test]
+fn test_write() {
+    let json = Json::Object(vec![
+        ("a".to_string(), Json::from(vec![Json::from(1), Json::from(true)])),
+        ("b".to_string(), Json::from("x\"\n")),
+    ]);
+
+    assert_eq!(r#"{"a":[1,true],"b":"x\"\n"}"#, json.to_string());
+    assert_eq!(Ok(json.clone()), Json::parse(&json.to_string()));
+}
@@ -0,0 +1,177 @@
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::execution::ExecutionError;
+use crate::computer_state::status_register::StatusRegister;
+use crate::trace::{trace_line, TraceError, TraceFilter, Tracer};
+
+/// Processor with the bytes loaded at the PC, in the state nestest starts in
+fn state_at(pc: u16, bytes: &[u8]) -> ComputerState {
+    let mut state = ComputerState::new();
+    state.load_at(pc, bytes);
+    state.pc.set(usize::from(pc));
+    state.sta = StatusRegister::I;
+    state.set_stk(0xFD);
+    state.cycles = 7;
+    state
+}
+
+#[test]
+fn test_nestest_layout() {
+    let state = state_at(0xC000, &[0x4C, 0xF5, 0xC5]);
+
+    assert_eq!(
+        "C000  4C F5 C5  JMP $C5F5                       A:00 X:00 Y:00 P:24 SP:FD CYC:7",
+        trace_line(&state),
+    );
+}
+
+#[test]
+fn test_illegal_marker() {
+    let mut state = state_at(0xC6BD, &[0x04, 0xA9]);
+    state.acc.set(0xAA);
+
+    assert_eq!(
+        "C6BD  04 A9    *NOP $A9 = 00                    A:AA X:00 Y:00 P:24 SP:FD CYC:7",
+        trace_line(&state),
+    );
+
+    // ISC is written as ISB, like nestest.log
+    let state = state_at(0xC000, &[0xE7, 0x10]);
+    assert!(trace_line(&state).starts_with("C000  E7 10    *ISB $10 = 00 "));
+}
+
+#[test]
+fn test_operand_values() {
+    fn disassembly(state: &ComputerState) -> String {
+        trace_line(state)[16..48].trim_end().to_string()
+    }
+
+    let mut state = state_at(0x0600, &[0xB1, 0x80]);
+    state.load_at(0x80, &[0x00, 0x02]);
+    state.load_at(0x0234, &[0x5A]);
+    state.set_y(0x34);
+    assert_eq!("LDA ($80),Y = 0200 @ 0234 = 5A", disassembly(&state));
+
+    let mut state = state_at(0x0600, &[0xA1, 0xFF]);
+    state.load_at(0x00, &[0x03]);
+    state.load_at(0xFF, &[0x04]);
+    assert_eq!("LDA ($FF,X) @ FF = 0304 = 00", disassembly(&state));
+
+    let mut state = state_at(0x0600, &[0x9D, 0xFF, 0x02]);
+    state.set_x(0x01);
+    state.load_at(0x0300, &[0x11]);
+    assert_eq!("STA $02FF,X @ 0300 = 11", disassembly(&state));
+
+    let state = state_at(0x0600, &[0x0A]);
+    assert_eq!("ASL A", disassembly(&state));
+
+    let state = state_at(0x0600, &[0xD0, 0xFE]);
+    assert_eq!("BNE $0600", disassembly(&state));
+}
+
+#[test]
+fn test_indirect_jump_page_wrap() {
+    let mut state = state_at(0x0600, &[0x6C, 0xFF, 0x02]);
+    state.load_at(0x02FF, &[0x34, 0x56]);
+    state.load_at(0x0200, &[0x12]);
+
+    assert!(trace_line(&state).contains("JMP ($02FF) = 1234 "));
+}
+
+#[test]
+fn test_65c02_modes() {
+    let mut state = ComputerState::with_variant(CpuVariant::Wdc65C02);
+    // LDA ($10); BBR0 $10,$0600
+    state.load_at(0x0600, &[0xB2, 0x10, 0x0F, 0x10, 0xFB]);
+    state.load_at(0x10, &[0x00, 0x07]);
+    state.pc.set(0x0600);
+    assert!(trace_line(&state).contains("LDA ($10) = 0700 = 00 "));
+
+    state.pc.set(0x0602);
+    assert!(trace_line(&state).starts_with("0602  0F 10 FB  BBR0 $10,$0600 "));
+}
+
+/// INX; INX; INX; JMP $0000
+fn looping_program() -> ComputerState {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[0xE8, 0xE8, 0xE8, 0x4C, 0x00, 0x00]);
+    state
+}
+
+fn traced_lines(filter: TraceFilter, steps: usize) -> Vec<String> {
+    let mut state = looping_program();
+    let mut tracer = Tracer::new(Vec::new(), filter);
+    for _ in 0..steps {
+        tracer.step(&mut state).unwrap();
+    }
+    let output = String::from_utf8(tracer.into_inner().unwrap()).unwrap();
+    output.lines().map(|line| line[..4].to_string()).collect()
+}
+
+#[test]
+fn test_tracer_writes_every_instruction() {
+    assert_eq!(vec!["0000", "0001", "0002", "0003", "0000"], traced_lines(TraceFilter::default(), 5));
+}
+
+#[test]
+fn test_tracer_filters() {
+    let ranges = TraceFilter { ranges: vec![0x0001..=0x0002], ..TraceFilter::default() };
+    assert_eq!(vec!["0001", "0002", "0001", "0002"], traced_lines(ranges, 8));
+
+    let skip = TraceFilter { skip: 3, ..TraceFilter::default() };
+    assert_eq!(vec!["0003", "0000"], traced_lines(skip, 5));
+
+    let limit = TraceFilter { skip: 1, limit: Some(2), ..TraceFilter::default() };
+    assert_eq!(vec!["0001", "0002"], traced_lines(limit, 8));
+}
+
+#[test]
+fn test_tracer_finishes_at_limit() {
+    let mut state = looping_program();
+    let mut tracer = Tracer::new(Vec::new(), TraceFilter { limit: Some(2), ..TraceFilter::default() });
+
+    tracer.step(&mut state).unwrap();
+    assert!(!tracer.is_finished());
+    tracer.step(&mut state).unwrap();
+    assert!(tracer.is_finished());
+    assert_eq!(2, tracer.lines_written());
+}
+
+#[test]
+fn test_tracer_finishes_at_trap() {
+    // INX; JMP $0001
+    let mut state = ComputerState::new();
+    state.set_up_state(&[0xE8, 0x4C, 0x01, 0x00]);
+    let mut tracer = Tracer::new(Vec::new(), TraceFilter { ranges: vec![0x0000..=0x0000], limit: Some(5), ..TraceFilter::default() });
+
+    tracer.step(&mut state).unwrap();
+    assert!(!tracer.is_finished());
+    tracer.step(&mut state).unwrap();
+    assert!(tracer.is_finished());
+    assert_eq!(Some(0x0001), tracer.trapped());
+    assert_eq!(1, tracer.lines_written());
+}
+
+#[test]
+fn test_tracer_skips_interrupts() {
+    let mut state = looping_program();
+    state.load_at(0xFFFE, &[0x00, 0x02]);
+    state.set_irq(true);
+    let mut tracer = Tracer::new(Vec::new(), TraceFilter::default());
+
+    tracer.step(&mut state).unwrap();
+    assert_eq!(0x0200, state.pc.get());
+    assert_eq!(0, tracer.lines_written());
+}
+
+#[test]
+fn test_tracer_writes_jammed_instruction() {
+    let mut state = ComputerState::new();
+    state.set_up_state(&[0x02]);
+    let mut tracer = Tracer::new(Vec::new(), TraceFilter::default());
+
+    let result = tracer.step(&mut state);
+
+    assert!(matches!(result, Err(TraceError::Execution(ExecutionError::Jammed { opcode: 0x02, addr: 0 }))));
+    let output = String::from_utf8(tracer.into_inner().unwrap()).unwrap();
+    assert!(output.starts_with("0000  02       *JAM "));
+}
@@ -0,0 +1,226 @@
+use std::fmt;
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
+
+use crate::computer_state::{ComputerState, CpuVariant};
+use crate::computer_state::bus::Bus;
+use crate::computer_state::execution::{ExecutionError, StepOutcome};
+use crate::computer_state::operations::{self, AddressingMode};
+use crate::disassembler::branch_target;
+
+// Execution traces in the format of nestest.log, so that they can be compared line by line against
+// the log of a known good emulator
+// Each line is the state before an instruction executes: its address and bytes, the instruction with
+// the values it will read, the registers and the cycle count. There is no PPU, so the PPU column of
+// nestest.log is left out, and cycles are counted from when the processor was created
+// Operand values are peeked, so tracing does not disturb devices on the bus
+
+/// Returns the trace line for the instruction at the program counter
+pub fn trace_line<B: Bus>(state: &ComputerState<B>) -> String {
+    let pc = state.pc.get() as u16;
+    let opcode = state.bus.peek(pc);
+    let len = operations::instruction_len(state.variant(), opcode);
+    let bytes = (0..len as u16)
+        .map(|offset| format!("{:02X}", state.bus.peek(pc.wrapping_add(offset))))
+        .collect::<Vec<_>>()
+        .join(" ");
+    // Like nestest.log, undocumented instructions are marked with a star
+    let marker = if operations::is_illegal(state.variant(), opcode) { '*' } else { ' ' };
+
+    format!(
+        "{pc:04X}  {bytes:<8} {marker}{:<32}A:{:02X} X:{:02X} Y:{:02X} P:{:02X} SP:{:02X} CYC:{}",
+        disassemble_with_values(state, pc),
+        state.acc.get(),
+        state.get_x(),
+        state.get_y(),
+        state.sta.to_pushed_byte(false),
+        state.get_stk(),
+        state.cycles,
+    )
+}
+
+/// Returns the instruction at the address, with the addresses it uses and the values found there
+fn disassemble_with_values<B: Bus>(state: &ComputerState<B>, pc: u16) -> String {
+    let variant = state.variant();
+    let opcode = state.bus.peek(pc);
+    let mnemonic = match operations::decode_mnemonic(variant, opcode) {
+        // nestest.log uses the other common name for ISC
+        "isc" => "ISB".to_string(),
+        mnemonic => mnemonic.to_uppercase(),
+    };
+
+    let peek = |addr: u16| state.bus.peek(addr);
+    let peek_word = |addr: u16| u16::from_le_bytes([peek(addr), peek(addr.wrapping_add(1))]);
+    // Pointers in the zero page wrap around within it
+    let peek_zp_word = |addr: u8| u16::from_le_bytes([peek(u16::from(addr)), peek(u16::from(addr.wrapping_add(1)))]);
+    let byte = peek(pc.wrapping_add(1));
+    let word = peek_word(pc.wrapping_add(1));
+    let x = state.get_x() as u8;
+    let y = state.get_y() as u8;
+
+    let operand = match operations::decode_addressing_mode(variant, opcode) {
+        AddressingMode::Implied => String::new(),
+        AddressingMode::Accumulator => "A".to_string(),
+        AddressingMode::Immediate => format!("#${byte:02X}"),
+        AddressingMode::ZeroPage => format!("${byte:02X} = {:02X}", peek(u16::from(byte))),
+        AddressingMode::ZeroPageX => {
+            let addr = byte.wrapping_add(x);
+            format!("${byte:02X},X @ {addr:02X} = {:02X}", peek(u16::from(addr)))
+        }
+        AddressingMode::ZeroPageY => {
+            let addr = byte.wrapping_add(y);
+            format!("${byte:02X},Y @ {addr:02X} = {:02X}", peek(u16::from(addr)))
+        }
+        // Jumps do not read their target
+        AddressingMode::Absolute if matches!(mnemonic.as_str(), "JMP" | "JSR") => format!("${word:04X}"),
+        AddressingMode::Absolute => format!("${word:04X} = {:02X}", peek(word)),
+        AddressingMode::AbsoluteX => {
+            let addr = word.wrapping_add(u16::from(x));
+            format!("${word:04X},X @ {addr:04X} = {:02X}", peek(addr))
+        }
+        AddressingMode::AbsoluteY => {
+            let addr = word.wrapping_add(u16::from(y));
+            format!("${word:04X},Y @ {addr:04X} = {:02X}", peek(addr))
+        }
+        AddressingMode::Indirect => {
+            // The NMOS 6502 does not carry into the high byte of the pointer
+            let high_ptr = if variant == CpuVariant::Nmos6502 && word & 0xFF == 0xFF {
+                word & 0xFF00
+            } else {
+                word.wrapping_add(1)
+            };
+            let target = u16::from_le_bytes([peek(word), peek(high_ptr)]);
+            format!("(${word:04X}) = {target:04X}")
+        }
+        AddressingMode::IndirectX => {
+            let ptr = byte.wrapping_add(x);
+            let addr = peek_zp_word(ptr);
+            format!("(${byte:02X},X) @ {ptr:02X} = {addr:04X} = {:02X}", peek(addr))
+        }
+        AddressingMode::IndirectY => {
+            let base = peek_zp_word(byte);
+            let addr = base.wrapping_add(u16::from(y));
+            format!("(${byte:02X}),Y = {base:04X} @ {addr:04X} = {:02X}", peek(addr))
+        }
+        AddressingMode::ZeroPageIndirect => {
+            let addr = peek_zp_word(byte);
+            format!("(${byte:02X}) = {addr:04X} = {:02X}", peek(addr))
+        }
+        AddressingMode::AbsoluteIndirectX => {
+            let ptr = word.wrapping_add(u16::from(x));
+            format!("(${word:04X},X) @ {ptr:04X} = {:04X}", peek_word(ptr))
+        }
+        AddressingMode::Relative => format!("${:04X}", branch_target(pc, 2, byte)),
+        AddressingMode::ZeroPageRelative => {
+            let offset = peek(pc.wrapping_add(2));
+            format!("${byte:02X},${:04X}", branch_target(pc, 3, offset))
+        }
+    };
+
+    if operand.is_empty() { mnemonic } else { format!("{mnemonic} {operand}") }
+}
+
+/// Chooses which executed instructions are traced
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct TraceFilter {
+    /// Only instructions at addresses in one of these ranges are traced, or every address if empty
+    pub ranges: Vec<RangeInclusive<u16>>,
+    /// Number of instructions in range which are executed before tracing starts
+    pub skip: u64,
+    /// Maximum number of instructions to trace
+    pub limit: Option<u64>,
+}
+
+impl TraceFilter {
+    /// Returns whether the address is in one of the ranges
+    pub fn contains(&self, addr: u16) -> bool {
+        self.ranges.is_empty() || self.ranges.iter().any(|range| range.contains(&addr))
+    }
+}
+
+/// Reason a traced step failed
+#[derive(Debug)]
+pub enum TraceError {
+    /// The processor could not execute the instruction, which is still traced if it ran
+    Execution(ExecutionError),
+    /// The trace could not be written
+    Io(io::Error),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::Execution(error) => write!(f, "{error}"),
+            TraceError::Io(error) => write!(f, "could not write the trace: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+/// Executes instructions while writing a trace line for each one which passes the filter
+pub struct Tracer<W: Write> {
+    output: W,
+    filter: TraceFilter,
+    /// Number of instructions in range executed so far
+    matched: u64,
+    /// Number of lines written so far
+    written: u64,
+    /// Address of the instruction which jumped or branched to itself, once one has
+    trapped: Option<u16>,
+}
+
+impl<W: Write> Tracer<W> {
+    pub fn new(output: W, filter: TraceFilter) -> Tracer<W> {
+        Tracer { output, filter, matched: 0, written: 0, trapped: None }
+    }
+
+    /// Returns whether as many lines as the limit allows have been written, or the program has
+    /// trapped, after which it would only trace the same instruction forever
+    pub fn is_finished(&self) -> bool {
+        self.trapped.is_some() || self.filter.limit.is_some_and(|limit| self.written >= limit)
+    }
+
+    /// Returns the address of the instruction which jumped or branched to itself, if one has
+    pub const fn trapped(&self) -> Option<u16> {
+        self.trapped
+    }
+
+    /// Returns the number of lines written so far
+    pub const fn lines_written(&self) -> u64 {
+        self.written
+    }
+
+    /// Executes the next step of the processor, tracing it if it executed an instruction
+    /// Interrupts being serviced and the processor waiting are not instructions, so are not traced
+    pub fn step<B: Bus>(&mut self, state: &mut ComputerState<B>) -> Result<StepOutcome, TraceError> {
+        let pc = state.pc.get() as u16;
+        let stack = state.get_stk();
+        let line = trace_line(state);
+        let result = state.execute_next();
+
+        let executed = matches!(
+            result,
+            Ok(StepOutcome::Executed { .. } | StepOutcome::Trapped { .. }) | Err(ExecutionError::Jammed { .. })
+        );
+        if executed && self.filter.contains(pc) {
+            self.matched += 1;
+            if self.matched > self.filter.skip && !self.is_finished() {
+                writeln!(self.output, "{line}").map_err(TraceError::Io)?;
+                self.written += 1;
+            }
+        }
+        // An RTS can return to itself, but it moves the stack pointer, unlike a trap
+        let completed = matches!(result, Ok(StepOutcome::Executed { .. } | StepOutcome::Trapped { .. }));
+        if completed && state.pc.get() as u16 == pc && state.get_stk() == stack {
+            self.trapped = Some(pc);
+        }
+        result.map_err(TraceError::Execution)
+    }
+
+    /// Flushes and returns the output
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.output.flush()?;
+        Ok(self.output)
+    }
+}
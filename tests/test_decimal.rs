@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use mos6502emulator::CpuVariant;
+use mos6502emulator::harness::decimal::*;
+
+/// Reads the code of the decimal test from tests/roms, which is not distributed with the emulator
+fn read_rom(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("roms").join(name);
+    std::fs::read(&path).unwrap_or_else(|error| panic!(
+        "could not read {}: {error}\nAssemble it at $0200 from 6502_decimal_test.a65 of https://github.com/Klaus2m5/6502_65C02_functional_tests",
+        path.display()
+    ))
+}
+
+#[test]
+#[ignore = "needs 6502_decimal_test.bin in tests/roms"]
+fn test_decimal_test() {
+    let mut state = load_decimal_test(CpuVariant::Nmos6502, &read_rom("6502_decimal_test.bin"));
+
+    if let Err(failure) = run_decimal_test(&mut state) {
+        panic!("{failure}");
+    }
+}
+
+#[test]
+fn test_error_clear() {
+    // LDA #$00; STA ERROR; JMP $0204
+    let mut state = load_decimal_test(CpuVariant::Nmos6502, &[0xA9, 0x00, 0x85, 0x0B, 0x4C, 0x04, 0x02]);
+
+    assert_eq!(Ok(()), run_decimal_test(&mut state));
+}
+
+#[test]
+fn test_error_set() {
+    // LDY #$01; STY ERROR; JMP $0204
+    let mut state = load_decimal_test(CpuVariant::Nmos6502, &[0xA0, 0x01, 0x84, 0x0B, 0x4C, 0x04, 0x02]);
+
+    let failure = run_decimal_test(&mut state).unwrap_err();
+    assert!(matches!(failure, DecimalTestFailure::Failed { error: 0x01, .. }));
+}
+
+#[test]
+fn test_ends_on_stp() {
+    // LDA #$00; STA ERROR; STP
+    let mut state = load_decimal_test(CpuVariant::Wdc65C02, &[0xA9, 0x00, 0x85, 0x0B, 0xDB]);
+
+    assert_eq!(Ok(()), run_decimal_test(&mut state));
+}
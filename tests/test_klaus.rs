@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use mos6502emulator::{Bus, CpuVariant};
+use mos6502emulator::harness::RunError;
+use mos6502emulator::harness::klaus::*;
+
+/// Reads a test image from tests/roms, which is not distributed with the emulator
+fn read_rom(name: &str) -> Vec<u8> {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("roms").join(name);
+    std::fs::read(&path).unwrap_or_else(|error| panic!(
+        "could not read {}: {error}\nDownload it from the bin_files of https://github.com/Klaus2m5/6502_65C02_functional_tests",
+        path.display()
+    ))
+}
+
+/// Image which runs from the start address of the functional test
+fn image(program: &[u8]) -> Vec<u8> {
+    let mut image = vec![0; 0x10000];
+    let start = usize::from(FUNCTIONAL_TEST_START);
+    image[start..start + program.len()].copy_from_slice(program);
+    image
+}
+
+#[test]
+#[ignore = "needs 6502_functional_test.bin in tests/roms"]
+fn test_functional_test() {
+    let mut state = load_functional_test(CpuVariant::Nmos6502, &read_rom("6502_functional_test.bin"));
+
+    if let Err(failure) = run_functional_test(&mut state) {
+        panic!("{failure}");
+    }
+}
+
+#[test]
+#[ignore = "needs 6502_interrupt_test.bin in tests/roms"]
+fn test_interrupt_test() {
+    let mut state = load_interrupt_test(CpuVariant::Nmos6502, &read_rom("6502_interrupt_test.bin"), FeedbackDrive::OpenCollector);
+
+    if let Err(failure) = run_interrupt_test(&mut state) {
+        panic!("{failure}");
+    }
+}
+
+#[test]
+fn test_success_trap() {
+    // LDA #$01; JMP $0402
+    let mut state = load_functional_test(CpuVariant::Nmos6502, &image(&[0xA9, 0x01, 0x4C, 0x02, 0x04]));
+
+    let report = run_test(&mut state, 0x0402, FUNCTIONAL_TEST_CASE, 10).unwrap();
+    assert_eq!(0x0402, report.pc);
+    assert_eq!(2, report.steps);
+}
+
+#[test]
+fn test_failure_trap() {
+    // LDA #$07; STA $0200; BNE *
+    let mut state = load_functional_test(CpuVariant::Nmos6502, &image(&[0xA9, 0x07, 0x8D, 0x00, 0x02, 0xD0, 0xFE]));
+
+    let failure = run_test(&mut state, 0x3469, FUNCTIONAL_TEST_CASE, 10).unwrap_err();
+    assert_eq!(FailureReason::Trapped { pc: 0x0405 }, failure.reason);
+    assert_eq!(0x07, failure.test_case);
+    assert!(failure.to_string().starts_with("test case $07 failed, trapped at $0405"));
+}
+
+#[test]
+fn test_timeout() {
+    // NOP; NOP; NOP
+    let mut state = load_functional_test(CpuVariant::Nmos6502, &image(&[0xEA, 0xEA, 0xEA]));
+
+    let failure = run_test(&mut state, 0x3469, FUNCTIONAL_TEST_CASE, 2).unwrap_err();
+    assert_eq!(FailureReason::Stopped(RunError::Timeout { steps: 2 }), failure.reason);
+}
+
+/// Image which raises an IRQ through the feedback register, then traps once the handler has returned
+fn feedback_image(assert: u8, release: u8) -> Vec<u8> {
+    // CLI; LDA #assert; STA FEEDBACK_PORT; JMP $0406
+    let mut image = image(&[0x58, 0xA9, assert, 0x8D, 0xFC, 0xBF, 0x4C, 0x06, 0x04]);
+    // INX; LDA #release; STA FEEDBACK_PORT; RTI
+    image[0x0500..0x0507].copy_from_slice(&[0xE8, 0xA9, release, 0x8D, 0xFC, 0xBF, 0x40]);
+    image[0xFFFA..0xFFFC].copy_from_slice(&[0x00, 0x05]);
+    image[0xFFFE..].copy_from_slice(&[0x00, 0x05]);
+    image
+}
+
+#[test]
+fn test_feedback_open_collector_irq() {
+    let mut state = load_interrupt_test(CpuVariant::Nmos6502, &feedback_image(0xFE, 0xFF), FeedbackDrive::OpenCollector);
+
+    let report = run_test(&mut state, 0x0406, INTERRUPT_TEST_CASE, 20).unwrap();
+    assert_eq!(1, state.get_x());
+    assert_eq!(9, report.steps);
+}
+
+#[test]
+fn test_feedback_totem_pole_nmi() {
+    let mut state = load_interrupt_test(CpuVariant::Nmos6502, &feedback_image(0x02, 0x00), FeedbackDrive::TotemPole);
+
+    run_test(&mut state, 0x0406, INTERRUPT_TEST_CASE, 20).unwrap();
+    assert_eq!(1, state.get_x());
+    assert_eq!(0x00, state.bus.peek(FEEDBACK_PORT));
+}
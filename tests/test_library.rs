@@ -0,0 +1,29 @@
+use mos6502emulator::{Bus, ComputerState, CpuVariant, FlatRam, StatusRegister, StepOutcome};
+use mos6502emulator::computer_state::operations::decode_operation_name;
+
+#[test]
+fn test_run_program() {
+    let mut state = ComputerState::new();
+    // LDA #$42; STA $0300
+    state.load_at(0x0200, &[0xA9, 0x42, 0x8D, 0x00, 0x03]);
+    state.pc.set(0x0200);
+
+    assert_eq!(StepOutcome::Executed { cycles: 2 }, state.execute_next().unwrap());
+    assert_eq!(StepOutcome::Executed { cycles: 4 }, state.execute_next().unwrap());
+    assert_eq!(0x42, state.bus.peek(0x0300));
+    assert!(!state.sta.contains(StatusRegister::Z));
+}
+
+#[test]
+fn test_custom_bus() {
+    let mut bus = FlatRam::new();
+    bus.write(0x1234, 0x42);
+    let state = ComputerState::with_bus(CpuVariant::Wdc65C02, bus);
+
+    assert_eq!(0x42, state.peek_byte_from_addr(0x1234));
+}
+
+#[test]
+fn test_disassembly_metadata() {
+    assert_eq!("lda_im", decode_operation_name(CpuVariant::Nmos6502, 0xA9));
+}
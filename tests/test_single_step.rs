@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use mos6502emulator::CpuVariant;
+use mos6502emulator::computer_state::operations::is_illegal;
+use mos6502emulator::harness::single_step::*;
+
+/// LDA #$42 at $1000, with the given expected accumulator
+fn lda_test(name: &str, expected_a: u8) -> String {
+    format!(r#"{{
+        "name": "{name}",
+        "initial": {{ "pc": 4096, "s": 253, "a": 0, "x": 0, "y": 0, "p": 36, "ram": [[4096, 169], [4097, 66]] }},
+        "final": {{ "pc": 4098, "s": 253, "a": {expected_a}, "x": 0, "y": 0, "p": 36, "ram": [[4096, 169], [4097, 66]] }},
+        "cycles": [[4096, 169, "read"], [4097, 66, "read"]]
+    }}"#)
+}
+
+/// Directory for the files of a test, which is emptied first
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("mos6502emulator-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn test_passing_test() {
+    let tests = parse_tests(&format!("[{}]", lda_test("a9 pass", 0x42))).unwrap();
+    let options = CheckOptions { cycle_count: true, bus_activity: true };
+
+    assert_eq!(Ok(()), tests[0].run(CpuVariant::Nmos6502, options));
+}
+
+#[test]
+fn test_mismatched_registers() {
+    let tests = parse_tests(&format!("[{}]", lda_test("a9 fail", 0x43))).unwrap();
+
+    let mismatch = tests[0].run(CpuVariant::Nmos6502, CheckOptions::default()).unwrap_err();
+    assert_eq!(vec!["A was $42, expected $43".to_string()], mismatch.differences);
+}
+
+#[test]
+fn test_invalid_format() {
+    assert!(matches!(parse_tests(r#"[{"name": "a9 0"}]"#), Err(SingleStepError::Format { test: 0 })));
+    assert!(matches!(parse_tests("[{"), Err(SingleStepError::Json(_))));
+}
+
+#[test]
+fn test_opcode_matrix() {
+    let dir = test_dir("matrix");
+    std::fs::write(dir.join("a9.json"), format!("[{}, {}]", lda_test("a9 0", 0x42), lda_test("a9 1", 0x43))).unwrap();
+
+    let matrix = run_directory(&dir, CpuVariant::Nmos6502, CheckOptions::default()).unwrap();
+    assert_eq!(vec![0xA9], matrix.failed_opcodes().collect::<Vec<_>>());
+    assert_eq!(None, matrix.results[0x00]);
+    let output = matrix.to_string();
+    assert!(output.lines().nth(11).unwrap().starts_with("A_ - - - - - - - - - X"));
+    assert!(output.contains("$A9 failed 1 of 2 tests, first a9 1: A was $42, expected $43"));
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+#[ignore = "needs the ProcessorTests 6502 tests in tests/roms/ProcessorTests/6502/v1"]
+fn test_processor_tests() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/roms/ProcessorTests/6502/v1");
+    let options = CheckOptions { cycle_count: true, bus_activity: false };
+
+    let matrix = run_directory(&dir, CpuVariant::Nmos6502, options).unwrap();
+    println!("{matrix}");
+    let legal_failures: Vec<_> = matrix.failed_opcodes().filter(|&opcode| !is_illegal(CpuVariant::Nmos6502, opcode)).collect();
+    assert!(legal_failures.is_empty(), "legal opcodes failed: {legal_failures:02X?}");
+}